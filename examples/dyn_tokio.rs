@@ -1,7 +1,7 @@
 use minus::error::MinusError;
 use std::fmt::Write;
 use std::time::Duration;
-use tokio::{join, task::spawn_blocking, time::sleep};
+use tokio::{join, time::sleep};
 
 #[tokio::main]
 async fn main() -> Result<(), MinusError> {
@@ -16,11 +16,8 @@ async fn main() -> Result<(), MinusError> {
         Result::<_, MinusError>::Ok(())
     };
 
-    let (res1, res2) = join!(
-        spawn_blocking(move || minus::dynamic_paging(output2)),
-        increment
-    );
-    res1.unwrap()?;
+    let (res1, res2) = join!(minus::async_paging(output2), increment);
+    res1?;
     res2?;
     Ok(())
 }
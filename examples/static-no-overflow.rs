@@ -4,7 +4,7 @@ use std::fmt::Write;
 
 fn main() -> Result<(), MinusError> {
     let mut pager = Pager::new();
-    pager.set_run_no_overflow(true)?;
+    pager.force_pager(true)?;
     for i in 0..=10u32 {
         writeln!(pager, "{}", i)?;
     }
@@ -14,7 +14,7 @@
           inherit system overlays;
         };
 
-        rust-toolchain = pkgs.rust-bin.stable."1.67.0".default.override {
+        rust-toolchain = pkgs.rust-bin.stable."1.74.0".default.override {
           extensions = [ "rust-src" "rust-analyzer" ];
         };
 
@@ -0,0 +1,114 @@
+//! Provides the [`Capabilities`] type
+
+/// Features of the terminal that minus can make use of
+///
+/// These are detected once when the pager starts by calling [`Backend::capabilities`]. minus
+/// falls back to doing without a feature when the terminal lacks it:
+/// - Without `synchronized_output`, frames are drawn as they are written which may cause
+///   flickering on slow terminals
+/// - Without `scrolling`, the entire screen is redrawn when the view moves instead of only the
+///   rows that change
+/// - Without `kitty_keyboard`, the flags given to
+///   [`Pager::set_keyboard_enhancement`](crate::Pager::set_keyboard_enhancement) are ignored
+/// - Without `mouse`, mouse capture is never enabled
+///
+/// Applications can see what was detected through
+/// [`Pager::capabilities`](crate::Pager::capabilities), for example to choose the colors of a
+/// [`Theme`](crate::Theme) depending on `truecolor`.
+///
+/// [`Backend::capabilities`]: super::Backend::capabilities
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct Capabilities {
+    /// Whether the terminal can display a frame only once it is complete
+    pub synchronized_output: bool,
+    /// Whether the terminal can scroll its contents up or down
+    pub scrolling: bool,
+    /// Whether the terminal can display 24-bit colors
+    pub truecolor: bool,
+    /// Whether the terminal supports the [kitty keyboard protocol]
+    ///
+    /// [kitty keyboard protocol]: https://sw.kovidgoyal.net/kitty/keyboard-protocol/
+    pub kitty_keyboard: bool,
+    /// Whether the terminal can report mouse events
+    pub mouse: bool,
+}
+
+impl Capabilities {
+    /// Guess the capabilities of the terminal of the process from the environment
+    ///
+    /// This looks at the `TERM`, `TERM_PROGRAM` and `COLORTERM` environment variables. Nothing is
+    /// sent to the terminal.
+    #[must_use]
+    pub fn detect() -> Self {
+        Self::from_env(|name| std::env::var(name).ok())
+    }
+
+    fn from_env(var: impl Fn(&str) -> Option<String>) -> Self {
+        let term = var("TERM").unwrap_or_default();
+        let term_program = var("TERM_PROGRAM").unwrap_or_default();
+        if term == "dumb" {
+            return Self {
+                synchronized_output: false,
+                scrolling: false,
+                truecolor: false,
+                kitty_keyboard: false,
+                mouse: false,
+            };
+        }
+        let known_term = |names: &[&str]| names.iter().any(|name| term.contains(name));
+        let known_program = |names: &[&str]| names.contains(&term_program.as_str());
+
+        Self {
+            synchronized_output: known_term(&["kitty", "foot", "alacritty", "wezterm", "contour"])
+                || known_program(&["WezTerm", "iTerm.app", "ghostty", "vscode"]),
+            scrolling: true,
+            truecolor: matches!(var("COLORTERM").as_deref(), Some("truecolor" | "24bit")),
+            kitty_keyboard: known_term(&["kitty", "foot", "wezterm", "ghostty"])
+                || known_program(&["WezTerm", "ghostty"]),
+            mouse: true,
+        }
+    }
+}
+
+/// What minus assumed about every terminal before capabilities were detected
+///
+/// Scrolling, mouse events and the kitty keyboard protocol are taken to be supported while the
+/// others are not.
+impl Default for Capabilities {
+    fn default() -> Self {
+        Self {
+            synchronized_output: false,
+            scrolling: true,
+            truecolor: false,
+            kitty_keyboard: true,
+            mouse: true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Capabilities;
+
+    fn from_vars(vars: &[(&str, &str)]) -> Capabilities {
+        Capabilities::from_env(|name| {
+            vars.iter()
+                .find(|(var, _)| *var == name)
+                .map(|(_, value)| (*value).to_string())
+        })
+    }
+
+    #[test]
+    fn detects_from_environment() {
+        let caps = from_vars(&[("TERM", "xterm-kitty"), ("COLORTERM", "truecolor")]);
+        assert!(caps.synchronized_output && caps.kitty_keyboard && caps.truecolor);
+
+        let caps = from_vars(&[("TERM", "xterm-256color")]);
+        assert!(caps.scrolling && caps.mouse);
+        assert!(!caps.synchronized_output && !caps.kitty_keyboard && !caps.truecolor);
+
+        let caps = from_vars(&[("TERM", "dumb"), ("COLORTERM", "truecolor")]);
+        assert!(!caps.scrolling && !caps.mouse && !caps.truecolor);
+    }
+}
@@ -0,0 +1,328 @@
+//! Provides the [`Backend`] trait for plugging in the terminal layer used by minus
+//!
+//! minus draws everything by writing ANSI escape sequences to the backend, hence most backends
+//! only need to provide a way to write the output, get the terminal size and read the user
+//! input. The remaining terminal operations like switching to the alternate screen or hiding the
+//! cursor are written as escape sequences by default, although backends can override them.
+//!
+//! By default minus uses the [`CrosstermBackend`] which works over the standard output of the
+//! process. Use [`Pager::set_backend`](crate::Pager::set_backend) to use a different backend.
+//! To run minus over other streams like an SSH channel, see [`StreamBackend`]. For testing
+//! applications without a real terminal, see [`TestBackend`]. To record the input of a session
+//! and replay it later, see [`Recorder`] and [`Replay`].
+
+// All methods of a backend simply report the IO errors of the underlying terminal
+#![allow(clippy::missing_errors_doc)]
+
+use crate::OutputTarget;
+use crossterm::{
+    cursor,
+    event::{self, Event},
+    execute, terminal,
+    tty::IsTty,
+};
+use parking_lot::Mutex;
+use std::{
+    io::{self, Stderr, Stdout, Write},
+    time::Duration,
+};
+
+#[cfg(feature = "tokio")]
+use std::pin::Pin;
+
+mod capabilities;
+mod record;
+mod stream;
+mod test_backend;
+
+pub use capabilities::Capabilities;
+pub use record::{RecordedEvent, Recorder, Replay};
+pub use stream::StreamBackend;
+pub use test_backend::TestBackend;
+
+/// Stream of the user input returned by [`Backend::event_stream`]
+#[cfg(feature = "tokio")]
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+pub type EventStream = Pin<Box<dyn futures_core::Stream<Item = io::Result<Event>> + Send>>;
+
+/// A terminal that minus can run on
+///
+/// All methods take `&self` as the backend is shared between the thread drawing the output and the
+/// thread reading the user input. Implementors should use interior mutability where required.
+///
+/// # Example
+/// A backend that forwards everything to [`CrosstermBackend`] but never enables mouse capture.
+/// ```
+/// use minus::backend::{Backend, CrosstermBackend};
+/// use minus::input::crossterm_event::Event;
+/// use std::{io, time::Duration};
+///
+/// struct NoMouse(CrosstermBackend);
+///
+/// impl Backend for NoMouse {
+///     fn write(&self, buf: &[u8]) -> io::Result<usize> {
+///         self.0.write(buf)
+///     }
+///     fn flush(&self) -> io::Result<()> {
+///         self.0.flush()
+///     }
+///     fn is_tty(&self) -> bool {
+///         self.0.is_tty()
+///     }
+///     fn size(&self) -> io::Result<(u16, u16)> {
+///         self.0.size()
+///     }
+///     fn enable_raw_mode(&self) -> io::Result<()> {
+///         self.0.enable_raw_mode()
+///     }
+///     fn disable_raw_mode(&self) -> io::Result<()> {
+///         self.0.disable_raw_mode()
+///     }
+///     fn enable_mouse_capture(&self) -> io::Result<()> {
+///         Ok(())
+///     }
+///     fn disable_mouse_capture(&self) -> io::Result<()> {
+///         Ok(())
+///     }
+///     fn poll_event(&self, timeout: Duration) -> io::Result<bool> {
+///         self.0.poll_event(timeout)
+///     }
+///     fn read_event(&self) -> io::Result<Event> {
+///         self.0.read_event()
+///     }
+/// }
+/// ```
+pub trait Backend: Send + Sync {
+    /// Write `buf` to the terminal, returning how many bytes were written
+    fn write(&self, buf: &[u8]) -> io::Result<usize>;
+    /// Flush any buffered output to the terminal
+    fn flush(&self) -> io::Result<()>;
+    /// Whether the output is an interactive terminal
+    ///
+    /// When this is `false`, [`page_all`](crate::page_all) writes all the text at once and
+    /// quits instead of paging it.
+    fn is_tty(&self) -> bool;
+    /// Size of the terminal as `(columns, rows)`
+    fn size(&self) -> io::Result<(u16, u16)>;
+    /// Put the terminal in raw mode
+    fn enable_raw_mode(&self) -> io::Result<()>;
+    /// Take the terminal out of raw mode
+    fn disable_raw_mode(&self) -> io::Result<()>;
+    /// Wait for up to `timeout` for an event to be available
+    ///
+    /// Returns `true` if an event is available and can be read with [`Backend::read_event`].
+    fn poll_event(&self, timeout: Duration) -> io::Result<bool>;
+    /// Read the next event, blocking until one is available
+    fn read_event(&self) -> io::Result<Event>;
+
+    /// Features supported by the terminal
+    ///
+    /// This is called once when the pager starts, after it is known that the terminal is
+    /// interactive. By default this returns [`Capabilities::default`].
+    fn capabilities(&self) -> Capabilities {
+        Capabilities::default()
+    }
+    /// Ask the terminal whether it supports the [kitty keyboard protocol]
+    ///
+    /// This may need a round trip to the terminal, hence it is only called when the pager starts
+    /// with keyboard enhancement flags set. The answer replaces
+    /// [`Capabilities::kitty_keyboard`]. By default this returns `None`, which keeps what
+    /// [`Backend::capabilities`] returned.
+    ///
+    /// [kitty keyboard protocol]: https://sw.kovidgoyal.net/kitty/keyboard-protocol/
+    fn supports_keyboard_enhancement(&self) -> Option<bool> {
+        None
+    }
+    /// Stream of the events otherwise read with [`Backend::read_event`], for reading them from
+    /// async code
+    ///
+    /// [`async_paging`](crate::async_paging) reads the user input from this stream when one is
+    /// returned, rather than calling [`Backend::poll_event`] on a thread of its own. By default
+    /// this returns `None`.
+    #[cfg(feature = "tokio")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+    fn event_stream(&self) -> Option<EventStream> {
+        None
+    }
+
+    /// Switch to the alternate screen
+    fn enter_alternate_screen(&self) -> io::Result<()> {
+        execute!(BackendWriter(self), terminal::EnterAlternateScreen)
+    }
+    /// Switch back to the main screen
+    fn leave_alternate_screen(&self) -> io::Result<()> {
+        execute!(BackendWriter(self), terminal::LeaveAlternateScreen)
+    }
+    /// Start receiving mouse events
+    fn enable_mouse_capture(&self) -> io::Result<()> {
+        execute!(BackendWriter(self), event::EnableMouseCapture)
+    }
+    /// Stop receiving mouse events
+    fn disable_mouse_capture(&self) -> io::Result<()> {
+        execute!(BackendWriter(self), event::DisableMouseCapture)
+    }
+    /// Ask the terminal to report keys using the [kitty keyboard protocol] with `flags`
+    ///
+    /// Terminals which don't support the protocol ignore this.
+    ///
+    /// [kitty keyboard protocol]: https://sw.kovidgoyal.net/kitty/keyboard-protocol/
+    fn push_keyboard_enhancement_flags(
+        &self,
+        flags: event::KeyboardEnhancementFlags,
+    ) -> io::Result<()> {
+        execute!(
+            BackendWriter(self),
+            event::PushKeyboardEnhancementFlags(flags)
+        )
+    }
+    /// Restore the keyboard enhancement flags that were active before the last
+    /// [`Backend::push_keyboard_enhancement_flags`]
+    fn pop_keyboard_enhancement_flags(&self) -> io::Result<()> {
+        execute!(BackendWriter(self), event::PopKeyboardEnhancementFlags)
+    }
+    /// Show the cursor
+    fn show_cursor(&self) -> io::Result<()> {
+        execute!(BackendWriter(self), cursor::Show)
+    }
+    /// Hide the cursor
+    fn hide_cursor(&self) -> io::Result<()> {
+        execute!(BackendWriter(self), cursor::Hide)
+    }
+}
+
+/// The default backend which runs on the standard output of the process using [`crossterm`]
+///
+/// It can also write to the standard error instead, see [`CrosstermBackend::with_target`], or
+/// to any other stream, see [`CrosstermBackend::with_writer`].
+pub struct CrosstermBackend {
+    out: Output,
+}
+
+enum Output {
+    Stdout(Stdout),
+    Stderr(Stderr),
+    Writer {
+        out: Mutex<Box<dyn Write + Send>>,
+        is_tty: bool,
+    },
+}
+
+impl CrosstermBackend {
+    /// Create a backend over the standard output
+    #[must_use]
+    pub fn new() -> Self {
+        Self::with_target(OutputTarget::Stdout)
+    }
+
+    /// Create a backend that writes to the given output stream
+    ///
+    /// The terminal size and the user input are taken from the controlling terminal of the
+    /// process regardless of `target`.
+    #[must_use]
+    pub fn with_target(target: OutputTarget) -> Self {
+        let out = match target {
+            OutputTarget::Stdout => Output::Stdout(io::stdout()),
+            OutputTarget::Stderr => Output::Stderr(io::stderr()),
+        };
+        Self { out }
+    }
+
+    /// Create a backend that writes to `out`
+    ///
+    /// This lets the pager run on a stream opened by the application, like `/dev/tty` when the
+    /// standard output is redirected to a file. Like with [`CrosstermBackend::with_target`], the
+    /// terminal size and the user input are taken from the controlling terminal of the process.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use minus::{backend::CrosstermBackend, Pager};
+    /// use std::fs::OpenOptions;
+    ///
+    /// let tty = OpenOptions::new().write(true).open("/dev/tty").unwrap();
+    /// let pager = Pager::new();
+    /// pager.set_backend(Box::new(CrosstermBackend::with_writer(tty)))
+    ///     .expect("Failed to communicate with the pager");
+    /// ```
+    #[must_use]
+    pub fn with_writer(out: impl Write + IsTty + Send + 'static) -> Self {
+        let is_tty = out.is_tty();
+        Self {
+            out: Output::Writer {
+                out: Mutex::new(Box::new(out)),
+                is_tty,
+            },
+        }
+    }
+}
+
+impl Default for CrosstermBackend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Backend for CrosstermBackend {
+    fn write(&self, buf: &[u8]) -> io::Result<usize> {
+        match &self.out {
+            Output::Stdout(out) => out.lock().write(buf),
+            Output::Stderr(out) => out.lock().write(buf),
+            Output::Writer { out, .. } => out.lock().write(buf),
+        }
+    }
+    fn flush(&self) -> io::Result<()> {
+        match &self.out {
+            Output::Stdout(out) => out.lock().flush(),
+            Output::Stderr(out) => out.lock().flush(),
+            Output::Writer { out, .. } => out.lock().flush(),
+        }
+    }
+    fn is_tty(&self) -> bool {
+        match &self.out {
+            Output::Stdout(out) => out.is_tty(),
+            Output::Stderr(out) => out.is_tty(),
+            Output::Writer { is_tty, .. } => *is_tty,
+        }
+    }
+    fn size(&self) -> io::Result<(u16, u16)> {
+        terminal::size()
+    }
+    fn enable_raw_mode(&self) -> io::Result<()> {
+        terminal::enable_raw_mode()
+    }
+    fn disable_raw_mode(&self) -> io::Result<()> {
+        terminal::disable_raw_mode()
+    }
+    fn poll_event(&self, timeout: Duration) -> io::Result<bool> {
+        event::poll(timeout)
+    }
+    fn read_event(&self) -> io::Result<Event> {
+        event::read()
+    }
+    fn capabilities(&self) -> Capabilities {
+        Capabilities::detect()
+    }
+    // Support for the kitty keyboard protocol can be asked from the terminal itself, except for
+    // dumb terminals which wouldn't answer
+    fn supports_keyboard_enhancement(&self) -> Option<bool> {
+        if !Capabilities::detect().scrolling {
+            return Some(false);
+        }
+        Some(terminal::supports_keyboard_enhancement().unwrap_or(false))
+    }
+    #[cfg(feature = "tokio")]
+    fn event_stream(&self) -> Option<EventStream> {
+        Some(Box::pin(event::EventStream::new()))
+    }
+}
+
+/// Adapter to use a [`Backend`] where a [`Write`] is required
+pub(crate) struct BackendWriter<'a, B: Backend + ?Sized>(pub &'a B);
+
+impl<B: Backend + ?Sized> Write for BackendWriter<'_, B> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.write(buf)
+    }
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.flush()
+    }
+}
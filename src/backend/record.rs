@@ -0,0 +1,312 @@
+//! Provides the [`Recorder`] and [`Replay`] backends
+
+use super::{Backend, Capabilities};
+use crossterm::event::{Event, KeyboardEnhancementFlags};
+use once_cell::sync::OnceCell;
+use parking_lot::Mutex;
+use std::{
+    collections::VecDeque,
+    io,
+    sync::Arc,
+    thread,
+    time::{Duration, Instant},
+};
+
+/// An event read from the terminal along with when it was read
+///
+/// With the `serde` feature, recorded events can be saved to a file and loaded back for
+/// replaying them later.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RecordedEvent {
+    /// Time elapsed between the pager starting to read input and this event
+    pub at: Duration,
+    /// The event itself
+    pub event: Event,
+}
+
+/// A backend that records all the events read from another backend
+///
+/// Everything is forwarded to the wrapped backend as it is. The recorded events can be got with
+/// [`Recorder::events`] at any time, including while the pager is running, and fed back to the
+/// pager with [`Replay`] to reproduce the session. To turn a session into a regression test, give
+/// the events to [`TestBackend::push_events`](super::TestBackend::push_events).
+///
+/// This type can be cheaply cloned. All clones share the wrapped backend and the recorded events,
+/// hence a clone can be given to the pager while the other one is kept to get the events.
+///
+/// # Example
+/// ```
+/// use minus::backend::{CrosstermBackend, Recorder};
+/// use minus::Pager;
+///
+/// let recorder = Recorder::new(CrosstermBackend::new());
+///
+/// let pager = Pager::new();
+/// pager.set_backend(Box::new(recorder.clone())).unwrap();
+/// // Page some text with minus::page_all(pager)
+///
+/// for recorded in recorder.events() {
+///     eprintln!("{:?}: {:?}", recorded.at, recorded.event);
+/// }
+/// ```
+pub struct Recorder<B> {
+    backend: Arc<B>,
+    start: Arc<OnceCell<Instant>>,
+    events: Arc<Mutex<Vec<RecordedEvent>>>,
+}
+
+impl<B: Backend> Recorder<B> {
+    /// Record the events read from `backend`
+    #[must_use]
+    pub fn new(backend: B) -> Self {
+        Self {
+            backend: Arc::new(backend),
+            start: Arc::new(OnceCell::new()),
+            events: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    /// The events recorded so far
+    #[must_use]
+    pub fn events(&self) -> Vec<RecordedEvent> {
+        self.events.lock().clone()
+    }
+}
+
+impl<B> Clone for Recorder<B> {
+    fn clone(&self) -> Self {
+        Self {
+            backend: self.backend.clone(),
+            start: self.start.clone(),
+            events: self.events.clone(),
+        }
+    }
+}
+
+impl<B: Backend> Backend for Recorder<B> {
+    fn write(&self, buf: &[u8]) -> io::Result<usize> {
+        self.backend.write(buf)
+    }
+    fn flush(&self) -> io::Result<()> {
+        self.backend.flush()
+    }
+    fn is_tty(&self) -> bool {
+        self.backend.is_tty()
+    }
+    fn size(&self) -> io::Result<(u16, u16)> {
+        self.backend.size()
+    }
+    fn enable_raw_mode(&self) -> io::Result<()> {
+        self.backend.enable_raw_mode()
+    }
+    fn disable_raw_mode(&self) -> io::Result<()> {
+        self.backend.disable_raw_mode()
+    }
+    fn poll_event(&self, timeout: Duration) -> io::Result<bool> {
+        self.start.get_or_init(Instant::now);
+        self.backend.poll_event(timeout)
+    }
+    fn read_event(&self) -> io::Result<Event> {
+        let start = *self.start.get_or_init(Instant::now);
+        let event = self.backend.read_event()?;
+        self.events.lock().push(RecordedEvent {
+            at: start.elapsed(),
+            event: event.clone(),
+        });
+        Ok(event)
+    }
+    fn capabilities(&self) -> Capabilities {
+        self.backend.capabilities()
+    }
+    fn supports_keyboard_enhancement(&self) -> Option<bool> {
+        self.backend.supports_keyboard_enhancement()
+    }
+    fn enter_alternate_screen(&self) -> io::Result<()> {
+        self.backend.enter_alternate_screen()
+    }
+    fn leave_alternate_screen(&self) -> io::Result<()> {
+        self.backend.leave_alternate_screen()
+    }
+    fn enable_mouse_capture(&self) -> io::Result<()> {
+        self.backend.enable_mouse_capture()
+    }
+    fn disable_mouse_capture(&self) -> io::Result<()> {
+        self.backend.disable_mouse_capture()
+    }
+    fn push_keyboard_enhancement_flags(&self, flags: KeyboardEnhancementFlags) -> io::Result<()> {
+        self.backend.push_keyboard_enhancement_flags(flags)
+    }
+    fn pop_keyboard_enhancement_flags(&self) -> io::Result<()> {
+        self.backend.pop_keyboard_enhancement_flags()
+    }
+    fn show_cursor(&self) -> io::Result<()> {
+        self.backend.show_cursor()
+    }
+    fn hide_cursor(&self) -> io::Result<()> {
+        self.backend.hide_cursor()
+    }
+}
+
+/// A backend that feeds previously recorded events to the pager
+///
+/// The events are given to the pager at the same pace as they were recorded by a [`Recorder`],
+/// counting from when the pager starts reading input. This reproduces issues that depend on the
+/// timing of the input, like keys pressed while text is still being pushed. Once all the events
+/// have been replayed, the input is read from the wrapped backend again, which also receives all
+/// the output.
+///
+/// # Example
+/// ```
+/// use minus::backend::{CrosstermBackend, RecordedEvent, Replay};
+/// use minus::input::crossterm_event::{Event, KeyCode, KeyEvent, KeyModifiers};
+/// use minus::Pager;
+/// use std::time::Duration;
+///
+/// let events = vec![RecordedEvent {
+///     at: Duration::from_millis(500),
+///     event: Event::Key(KeyEvent::new(KeyCode::Char('G'), KeyModifiers::NONE)),
+/// }];
+///
+/// let pager = Pager::new();
+/// pager.set_backend(Box::new(Replay::new(CrosstermBackend::new(), events))).unwrap();
+/// ```
+pub struct Replay<B> {
+    backend: B,
+    start: OnceCell<Instant>,
+    events: Mutex<VecDeque<RecordedEvent>>,
+}
+
+impl<B: Backend> Replay<B> {
+    /// Replay `events` over `backend`
+    #[must_use]
+    pub fn new(backend: B, events: impl IntoIterator<Item = RecordedEvent>) -> Self {
+        Self {
+            backend,
+            start: OnceCell::new(),
+            events: Mutex::new(events.into_iter().collect()),
+        }
+    }
+
+    /// How long to wait until the next recorded event is due, if any is left
+    fn next_due(&self) -> Option<Duration> {
+        let start = *self.start.get_or_init(Instant::now);
+        let at = self.events.lock().front()?.at;
+        Some(at.saturating_sub(start.elapsed()))
+    }
+}
+
+impl<B: Backend> Backend for Replay<B> {
+    fn write(&self, buf: &[u8]) -> io::Result<usize> {
+        self.backend.write(buf)
+    }
+    fn flush(&self) -> io::Result<()> {
+        self.backend.flush()
+    }
+    fn is_tty(&self) -> bool {
+        self.backend.is_tty()
+    }
+    fn size(&self) -> io::Result<(u16, u16)> {
+        self.backend.size()
+    }
+    fn enable_raw_mode(&self) -> io::Result<()> {
+        self.backend.enable_raw_mode()
+    }
+    fn disable_raw_mode(&self) -> io::Result<()> {
+        self.backend.disable_raw_mode()
+    }
+    fn poll_event(&self, timeout: Duration) -> io::Result<bool> {
+        match self.next_due() {
+            Some(due) if due <= timeout => {
+                thread::sleep(due);
+                Ok(true)
+            }
+            Some(_) => {
+                thread::sleep(timeout);
+                Ok(false)
+            }
+            None => self.backend.poll_event(timeout),
+        }
+    }
+    fn read_event(&self) -> io::Result<Event> {
+        let Some(due) = self.next_due() else {
+            return self.backend.read_event();
+        };
+        thread::sleep(due);
+        // Only the input thread reads events, hence the event can't have been taken meanwhile
+        Ok(self.events.lock().pop_front().unwrap().event)
+    }
+    fn capabilities(&self) -> Capabilities {
+        self.backend.capabilities()
+    }
+    fn supports_keyboard_enhancement(&self) -> Option<bool> {
+        self.backend.supports_keyboard_enhancement()
+    }
+    fn enter_alternate_screen(&self) -> io::Result<()> {
+        self.backend.enter_alternate_screen()
+    }
+    fn leave_alternate_screen(&self) -> io::Result<()> {
+        self.backend.leave_alternate_screen()
+    }
+    fn enable_mouse_capture(&self) -> io::Result<()> {
+        self.backend.enable_mouse_capture()
+    }
+    fn disable_mouse_capture(&self) -> io::Result<()> {
+        self.backend.disable_mouse_capture()
+    }
+    fn push_keyboard_enhancement_flags(&self, flags: KeyboardEnhancementFlags) -> io::Result<()> {
+        self.backend.push_keyboard_enhancement_flags(flags)
+    }
+    fn pop_keyboard_enhancement_flags(&self) -> io::Result<()> {
+        self.backend.pop_keyboard_enhancement_flags()
+    }
+    fn show_cursor(&self) -> io::Result<()> {
+        self.backend.show_cursor()
+    }
+    fn hide_cursor(&self) -> io::Result<()> {
+        self.backend.hide_cursor()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RecordedEvent, Recorder, Replay};
+    use crate::backend::{Backend, TestBackend};
+    use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+    use std::time::{Duration, Instant};
+
+    fn key(c: char) -> Event {
+        Event::Key(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE))
+    }
+
+    #[test]
+    fn record_and_replay() {
+        let backend = TestBackend::new(20, 5);
+        backend.push_events([key('j'), key('q')]);
+        let recorder = Recorder::new(backend);
+        assert!(recorder.poll_event(Duration::ZERO).unwrap());
+        assert_eq!(recorder.read_event().unwrap(), key('j'));
+        assert_eq!(recorder.read_event().unwrap(), key('q'));
+
+        let events = recorder.events();
+        assert_eq!(
+            events.iter().map(|e| e.event.clone()).collect::<Vec<_>>(),
+            [key('j'), key('q')]
+        );
+        assert!(events[0].at <= events[1].at);
+
+        let delayed = RecordedEvent {
+            at: Duration::from_millis(50),
+            event: key('G'),
+        };
+        let fallback = TestBackend::new(20, 5);
+        fallback.push_events([key('g')]);
+        let replay = Replay::new(fallback, [delayed]);
+        let start = Instant::now();
+        assert!(!replay.poll_event(Duration::ZERO).unwrap());
+        assert_eq!(replay.read_event().unwrap(), key('G'));
+        assert!(start.elapsed() >= Duration::from_millis(50));
+        // The wrapped backend takes over once the recording is exhausted
+        assert_eq!(replay.read_event().unwrap(), key('g'));
+    }
+}
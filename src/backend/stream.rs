@@ -0,0 +1,427 @@
+//! Provides the [`StreamBackend`]
+
+use super::Backend;
+use crossbeam_channel::{Receiver, RecvTimeoutError, Sender};
+use crossterm::event::{
+    Event, KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+};
+use parking_lot::Mutex;
+use std::{
+    io::{self, Read, Write},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+/// A backend that runs over arbitrary input and output streams
+///
+/// This is useful for running minus somewhere other than the terminal of the process, like on the
+/// channel of an SSH session or a PTY owned by the application. The output of the pager is
+/// written to the given [`Write`]r. The user input can come from
+/// - a [`Read`]er passed to [`StreamBackend::with_input`], whose bytes are parsed as the input of
+///   a terminal
+/// - [`Event`]s sent by the application through [`StreamBackend::send_event`], like ones it
+///   parsed by itself
+///
+/// Since the backend can't know the size of the remote terminal, it must be given while creating
+/// the backend and updated with [`StreamBackend::set_size`] whenever the terminal is resized.
+/// Switching the remote terminal into raw mode is also left to the application.
+///
+/// This type can be cheaply cloned. All clones share the same streams, hence a clone can be given
+/// to the pager while the other one is kept to send events and resizes.
+///
+/// # Example
+/// ```
+/// use minus::{backend::StreamBackend, ExitStrategy, Pager};
+/// use minus::input::crossterm_event::{Event, KeyCode, KeyEvent, KeyModifiers};
+///
+/// // Throw away the output. A real application would write it to the remote terminal
+/// let backend = StreamBackend::new(std::io::sink(), 80, 24);
+/// // Quit the pager by pressing 'q'
+/// backend.send_event(Event::Key(KeyEvent::new(KeyCode::Char('q'), KeyModifiers::NONE)));
+///
+/// let pager = Pager::new();
+/// pager.set_backend(Box::new(backend)).unwrap();
+/// pager.set_exit_strategy(ExitStrategy::PagerQuit).unwrap();
+/// pager.push_str("Hello world").unwrap();
+/// # #[cfg(feature = "static_output")]
+/// minus::page_all(pager).unwrap();
+/// ```
+#[derive(Clone)]
+pub struct StreamBackend {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    output: Mutex<Box<dyn Write + Send>>,
+    size: Mutex<(u16, u16)>,
+    tx: Sender<Event>,
+    rx: Receiver<Event>,
+    /// An event received while polling which hasn't been read yet
+    peeked: Mutex<Option<Event>>,
+    /// Whether the input stream has reached its end
+    closed: AtomicBool,
+}
+
+impl StreamBackend {
+    /// Create a backend that writes to `output` with a terminal of `cols` columns and `rows` rows
+    #[must_use]
+    pub fn new(output: impl Write + Send + 'static, cols: u16, rows: u16) -> Self {
+        let (tx, rx) = crossbeam_channel::unbounded();
+        Self {
+            inner: Arc::new(Inner {
+                output: Mutex::new(Box::new(output)),
+                size: Mutex::new((cols, rows)),
+                tx,
+                rx,
+                peeked: Mutex::new(None),
+                closed: AtomicBool::new(false),
+            }),
+        }
+    }
+
+    /// Read the user input from `input`
+    ///
+    /// The bytes are read on a separate thread and parsed as the keys and mouse events sent by a
+    /// terminal in raw mode. Mouse events are only understood in the SGR format, which is what
+    /// minus asks for.
+    ///
+    /// Once `input` reaches its end or fails, reading events from the backend fails as well and
+    /// hence the pager quits with an error.
+    #[must_use]
+    pub fn with_input(self, mut input: impl Read + Send + 'static) -> Self {
+        let inner = self.inner.clone();
+        thread::spawn(move || {
+            let mut buf = [0; 1024];
+            let mut pending = Vec::new();
+            loop {
+                match input.read(&mut buf) {
+                    Ok(0) | Err(_) => break,
+                    Ok(n) => pending.extend_from_slice(&buf[..n]),
+                }
+                for ev in parse_events(&mut pending) {
+                    if inner.tx.send(ev).is_err() {
+                        return;
+                    }
+                }
+            }
+            inner.closed.store(true, Ordering::SeqCst);
+        });
+        self
+    }
+
+    /// Send `event` to the pager as if the user had done it
+    pub fn send_event(&self, event: Event) {
+        // The receiver lives as long as the backend so this can never fail
+        let _ = self.inner.tx.send(event);
+    }
+
+    /// Change the size of the terminal to `cols` columns and `rows` rows
+    ///
+    /// This also lets the pager know about the change so that it can redraw itself.
+    pub fn set_size(&self, cols: u16, rows: u16) {
+        *self.inner.size.lock() = (cols, rows);
+        self.send_event(Event::Resize(cols, rows));
+    }
+}
+
+impl Backend for StreamBackend {
+    fn write(&self, buf: &[u8]) -> io::Result<usize> {
+        self.inner.output.lock().write(buf)
+    }
+    fn flush(&self) -> io::Result<()> {
+        self.inner.output.lock().flush()
+    }
+    fn is_tty(&self) -> bool {
+        true
+    }
+    fn size(&self) -> io::Result<(u16, u16)> {
+        Ok(*self.inner.size.lock())
+    }
+    fn enable_raw_mode(&self) -> io::Result<()> {
+        Ok(())
+    }
+    fn disable_raw_mode(&self) -> io::Result<()> {
+        Ok(())
+    }
+    fn poll_event(&self, timeout: Duration) -> io::Result<bool> {
+        if self.inner.peeked.lock().is_some() {
+            return Ok(true);
+        }
+        match self.inner.rx.recv_timeout(timeout) {
+            Ok(ev) => {
+                *self.inner.peeked.lock() = Some(ev);
+                Ok(true)
+            }
+            // Report that an event is available so that the error is returned by read_event
+            Err(RecvTimeoutError::Timeout) => Ok(self.inner.closed.load(Ordering::SeqCst)),
+            Err(RecvTimeoutError::Disconnected) => Ok(true),
+        }
+    }
+    fn read_event(&self) -> io::Result<Event> {
+        let peeked = self.inner.peeked.lock().take();
+        if let Some(ev) = peeked {
+            return Ok(ev);
+        }
+        loop {
+            match self.inner.rx.recv_timeout(Duration::from_millis(100)) {
+                Ok(ev) => return Ok(ev),
+                Err(RecvTimeoutError::Timeout) if !self.inner.closed.load(Ordering::SeqCst) => {}
+                Err(_) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::UnexpectedEof,
+                        "input stream closed",
+                    ))
+                }
+            }
+        }
+    }
+}
+
+/// Parse all the complete events at the start of `bytes` and remove them from it
+///
+/// An incomplete escape sequence or character at the end is left in `bytes`, except for a lone
+/// `ESC` which is taken as the Escape key.
+fn parse_events(bytes: &mut Vec<u8>) -> Vec<Event> {
+    let mut events = Vec::new();
+    let mut start = 0;
+    while start < bytes.len() {
+        match parse_event(&bytes[start..]) {
+            Some((ev, len)) => {
+                events.extend(ev);
+                start += len;
+            }
+            None if bytes[start..] == [0x1b] => {
+                events.push(key(KeyCode::Esc, KeyModifiers::NONE));
+                start += 1;
+            }
+            None => break,
+        }
+    }
+    bytes.drain(..start);
+    events
+}
+
+const fn key(code: KeyCode, modifiers: KeyModifiers) -> Event {
+    Event::Key(KeyEvent::new(code, modifiers))
+}
+
+/// Parse the event at the start of `bytes`, returning it along with the number of bytes it took
+///
+/// Unknown sequences are consumed without producing an event. Returns `None` if `bytes` is
+/// incomplete.
+fn parse_event(bytes: &[u8]) -> Option<(Option<Event>, usize)> {
+    let none = KeyModifiers::NONE;
+    let ev = match bytes[0] {
+        0x1b => return parse_escape(bytes),
+        b'\r' | b'\n' => key(KeyCode::Enter, none),
+        b'\t' => key(KeyCode::Tab, none),
+        0x7f | 0x08 => key(KeyCode::Backspace, none),
+        c @ 0x01..=0x1a => key(KeyCode::Char((c - 1 + b'a') as char), KeyModifiers::CONTROL),
+        0x00 => key(KeyCode::Char(' '), KeyModifiers::CONTROL),
+        c if c < 0x20 => return Some((None, 1)),
+        _ => {
+            let len = utf8_len(bytes[0]);
+            if bytes.len() < len {
+                return None;
+            }
+            let c = std::str::from_utf8(&bytes[..len])
+                .ok()
+                .and_then(|s| s.chars().next());
+            return Some((c.map(|c| key(KeyCode::Char(c), none)), len));
+        }
+    };
+    Some((Some(ev), 1))
+}
+
+const fn utf8_len(first: u8) -> usize {
+    match first {
+        0xf0..=0xff => 4,
+        0xe0..=0xef => 3,
+        0xc0..=0xdf => 2,
+        _ => 1,
+    }
+}
+
+fn parse_escape(bytes: &[u8]) -> Option<(Option<Event>, usize)> {
+    match *bytes.get(1)? {
+        b'[' => parse_csi(bytes),
+        b'O' => {
+            let code = match *bytes.get(2)? {
+                b'A' => KeyCode::Up,
+                b'B' => KeyCode::Down,
+                b'C' => KeyCode::Right,
+                b'D' => KeyCode::Left,
+                b'H' => KeyCode::Home,
+                b'F' => KeyCode::End,
+                b'P' => KeyCode::F(1),
+                b'Q' => KeyCode::F(2),
+                b'R' => KeyCode::F(3),
+                b'S' => KeyCode::F(4),
+                _ => return Some((None, 3)),
+            };
+            Some((Some(key(code, KeyModifiers::NONE)), 3))
+        }
+        0x1b => Some((Some(key(KeyCode::Esc, KeyModifiers::NONE)), 1)),
+        _ => {
+            // Alt + key
+            let (ev, len) = parse_event(&bytes[1..])?;
+            let ev = ev.map(|ev| match ev {
+                Event::Key(mut k) => {
+                    k.modifiers |= KeyModifiers::ALT;
+                    Event::Key(k)
+                }
+                ev => ev,
+            });
+            Some((ev, len + 1))
+        }
+    }
+}
+
+fn parse_csi(bytes: &[u8]) -> Option<(Option<Event>, usize)> {
+    let end = bytes[2..].iter().position(|b| (0x40..=0x7e).contains(b))? + 2;
+    let len = end + 1;
+    let params = std::str::from_utf8(&bytes[2..end]).ok();
+    let Some(params) = params else {
+        return Some((None, len));
+    };
+
+    if let Some(params) = params.strip_prefix('<') {
+        return Some((parse_sgr_mouse(params, bytes[end] == b'M'), len));
+    }
+
+    let mut args = params.split(';').map(|arg| arg.parse::<u8>().ok());
+    let first = args.next().flatten();
+    let modifiers = args.next().flatten().map_or(KeyModifiers::NONE, modifiers);
+    let code = match bytes[end] {
+        b'A' => KeyCode::Up,
+        b'B' => KeyCode::Down,
+        b'C' => KeyCode::Right,
+        b'D' => KeyCode::Left,
+        b'H' => KeyCode::Home,
+        b'F' => KeyCode::End,
+        b'Z' => return Some((Some(key(KeyCode::BackTab, KeyModifiers::SHIFT)), len)),
+        b'~' => match first {
+            Some(1 | 7) => KeyCode::Home,
+            Some(2) => KeyCode::Insert,
+            Some(3) => KeyCode::Delete,
+            Some(4 | 8) => KeyCode::End,
+            Some(5) => KeyCode::PageUp,
+            Some(6) => KeyCode::PageDown,
+            Some(n @ 11..=15) => KeyCode::F(n - 10),
+            Some(n @ 17..=21) => KeyCode::F(n - 11),
+            Some(n @ 23..=24) => KeyCode::F(n - 12),
+            _ => return Some((None, len)),
+        },
+        _ => return Some((None, len)),
+    };
+    Some((Some(key(code, modifiers)), len))
+}
+
+/// Convert the modifier parameter of a CSI sequence to [`KeyModifiers`]
+fn modifiers(param: u8) -> KeyModifiers {
+    let bits = param.saturating_sub(1);
+    let mut modifiers = KeyModifiers::NONE;
+    if bits & 1 != 0 {
+        modifiers |= KeyModifiers::SHIFT;
+    }
+    if bits & 2 != 0 {
+        modifiers |= KeyModifiers::ALT;
+    }
+    if bits & 4 != 0 {
+        modifiers |= KeyModifiers::CONTROL;
+    }
+    modifiers
+}
+
+/// Parse a mouse event in the SGR format, i.e `ESC [ < button ; column ; row M/m`
+fn parse_sgr_mouse(params: &str, pressed: bool) -> Option<Event> {
+    let mut args = params.split(';').map(|arg| arg.parse::<u16>().ok());
+    let (button, column, row) = (args.next()??, args.next()??, args.next()??);
+
+    let mut modifiers = KeyModifiers::NONE;
+    if button & 4 != 0 {
+        modifiers |= KeyModifiers::SHIFT;
+    }
+    if button & 8 != 0 {
+        modifiers |= KeyModifiers::ALT;
+    }
+    if button & 16 != 0 {
+        modifiers |= KeyModifiers::CONTROL;
+    }
+
+    let mouse_button = match button & 3 {
+        0 => MouseButton::Left,
+        1 => MouseButton::Middle,
+        _ => MouseButton::Right,
+    };
+    let kind = if button & 64 != 0 {
+        match button & 3 {
+            0 => MouseEventKind::ScrollUp,
+            1 => MouseEventKind::ScrollDown,
+            2 => MouseEventKind::ScrollLeft,
+            _ => MouseEventKind::ScrollRight,
+        }
+    } else if button & 32 != 0 {
+        if button & 3 == 3 {
+            MouseEventKind::Moved
+        } else {
+            MouseEventKind::Drag(mouse_button)
+        }
+    } else if pressed {
+        MouseEventKind::Down(mouse_button)
+    } else {
+        MouseEventKind::Up(mouse_button)
+    };
+
+    Some(Event::Mouse(MouseEvent {
+        kind,
+        column: column.saturating_sub(1),
+        row: row.saturating_sub(1),
+        modifiers,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{key, parse_events};
+    use crossterm::event::{Event, KeyCode, KeyModifiers, MouseEvent, MouseEventKind};
+
+    #[test]
+    fn parses_input() {
+        let none = KeyModifiers::NONE;
+        let mut bytes = "q\u{e9}\r\x1b[A\x1b[6~\x1b[1;5C\x1bj\x03\x1b[<65;3;4M\x1b["
+            .as_bytes()
+            .to_vec();
+        assert_eq!(
+            parse_events(&mut bytes),
+            [
+                key(KeyCode::Char('q'), none),
+                key(KeyCode::Char('\u{e9}'), none),
+                key(KeyCode::Enter, none),
+                key(KeyCode::Up, none),
+                key(KeyCode::PageDown, none),
+                key(KeyCode::Right, KeyModifiers::CONTROL),
+                key(KeyCode::Char('j'), KeyModifiers::ALT),
+                key(KeyCode::Char('c'), KeyModifiers::CONTROL),
+                Event::Mouse(MouseEvent {
+                    kind: MouseEventKind::ScrollDown,
+                    column: 2,
+                    row: 3,
+                    modifiers: none,
+                }),
+            ]
+        );
+        // The incomplete sequence is kept until the rest arrives
+        assert_eq!(bytes, b"\x1b[");
+        bytes.extend_from_slice(b"B\x1b");
+        assert_eq!(
+            parse_events(&mut bytes),
+            [key(KeyCode::Down, none), key(KeyCode::Esc, none)]
+        );
+        assert!(bytes.is_empty());
+    }
+}
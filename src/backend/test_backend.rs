@@ -0,0 +1,348 @@
+//! Provides the [`TestBackend`]
+
+use super::Backend;
+use crossterm::event::Event;
+use parking_lot::{Condvar, Mutex};
+use std::{collections::VecDeque, io, sync::Arc, time::Duration};
+
+/// An in-memory backend for testing applications that use minus
+///
+/// Everything that minus draws is recorded into a virtual screen of a fixed size which can be
+/// inspected with [`TestBackend::screen`]. The user input is taken from a script of [`Event`]s
+/// given through [`TestBackend::push_events`], which is read one event at a time like a real
+/// terminal would.
+///
+/// This type can be cheaply cloned. All clones share the same screen and script hence a clone
+/// can be given to the pager while the other one is kept to inspect the screen.
+///
+/// Since only one pager can be running at a time, make sure that tests which start the pager
+/// are not run in parallel. Also set the [`ExitStrategy`](crate::ExitStrategy) to
+/// [`PagerQuit`](crate::ExitStrategy::PagerQuit), otherwise the test process exits as soon as
+/// the pager quits.
+///
+/// # Example
+/// ```
+/// use minus::{backend::TestBackend, ExitStrategy, Pager};
+/// use minus::input::crossterm_event::{Event, KeyCode, KeyEvent, KeyModifiers};
+///
+/// let key = |c| Event::Key(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE));
+///
+/// let backend = TestBackend::new(20, 5);
+/// // Go to the bottom and quit
+/// backend.push_events([key('G'), key('q')]);
+///
+/// let pager = Pager::new();
+/// pager.set_backend(Box::new(backend.clone())).unwrap();
+/// pager.set_exit_strategy(ExitStrategy::PagerQuit).unwrap();
+/// for i in 1..=10 {
+///     pager.push_str(format!("Line {i}\n")).unwrap();
+/// }
+/// # #[cfg(feature = "static_output")]
+/// # {
+/// minus::page_all(pager).unwrap();
+///
+/// assert_eq!(backend.screen()[..4], ["Line 7", "Line 8", "Line 9", "Line 10"]);
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct TestBackend {
+    size: (u16, u16),
+    screen: Arc<Mutex<VirtualScreen>>,
+    events: Arc<(Mutex<VecDeque<Event>>, Condvar)>,
+}
+
+impl TestBackend {
+    /// Create a backend with a screen of `cols` columns and `rows` rows
+    #[must_use]
+    pub fn new(cols: u16, rows: u16) -> Self {
+        Self {
+            size: (cols, rows),
+            screen: Arc::new(Mutex::new(VirtualScreen::new(cols.into(), rows.into()))),
+            events: Arc::new((Mutex::new(VecDeque::new()), Condvar::new())),
+        }
+    }
+
+    /// Add `events` at the end of the input script
+    ///
+    /// This can also be called while the pager is running.
+    pub fn push_events(&self, events: impl IntoIterator<Item = Event>) {
+        let (script, cvar) = &*self.events;
+        script.lock().extend(events);
+        cvar.notify_all();
+    }
+
+    /// Text shown on each row of the screen with trailing whitespace removed
+    ///
+    /// Colors and other styling are not recorded.
+    #[must_use]
+    pub fn screen(&self) -> Vec<String> {
+        self.screen
+            .lock()
+            .grid
+            .iter()
+            .map(|row| row.iter().collect::<String>().trim_end().to_string())
+            .collect()
+    }
+
+    /// Position of the cursor as `(column, row)`
+    #[must_use]
+    pub fn cursor(&self) -> (usize, usize) {
+        let screen = self.screen.lock();
+        (screen.x, screen.y)
+    }
+}
+
+impl Backend for TestBackend {
+    fn write(&self, buf: &[u8]) -> io::Result<usize> {
+        self.screen.lock().feed(buf);
+        Ok(buf.len())
+    }
+    fn flush(&self) -> io::Result<()> {
+        Ok(())
+    }
+    fn is_tty(&self) -> bool {
+        true
+    }
+    fn size(&self) -> io::Result<(u16, u16)> {
+        Ok(self.size)
+    }
+    fn enable_raw_mode(&self) -> io::Result<()> {
+        Ok(())
+    }
+    fn disable_raw_mode(&self) -> io::Result<()> {
+        Ok(())
+    }
+    // Keep the last frame visible after the pager quits so that it can be inspected
+    fn leave_alternate_screen(&self) -> io::Result<()> {
+        Ok(())
+    }
+    fn poll_event(&self, timeout: Duration) -> io::Result<bool> {
+        let (script, cvar) = &*self.events;
+        let mut script = script.lock();
+        if script.is_empty() {
+            cvar.wait_for(&mut script, timeout);
+        }
+        Ok(!script.is_empty())
+    }
+    fn read_event(&self) -> io::Result<Event> {
+        let (script, cvar) = &*self.events;
+        let mut script = script.lock();
+        cvar.wait_while(&mut script, |script| script.is_empty());
+        Ok(script.pop_front().unwrap())
+    }
+}
+
+/// A grid of characters that interprets the escape sequences written by minus
+struct VirtualScreen {
+    cols: usize,
+    rows: usize,
+    grid: Vec<Vec<char>>,
+    x: usize,
+    y: usize,
+    /// Cursor position saved with `ESC 7`
+    saved: (usize, usize),
+    /// Bytes of an incomplete escape sequence or character from the last write
+    pending: Vec<u8>,
+}
+
+impl VirtualScreen {
+    fn new(cols: usize, rows: usize) -> Self {
+        Self {
+            cols,
+            rows,
+            grid: vec![vec![' '; cols]; rows],
+            x: 0,
+            y: 0,
+            saved: (0, 0),
+            pending: Vec::new(),
+        }
+    }
+
+    fn feed(&mut self, buf: &[u8]) {
+        // There is no cell to write to or move the cursor to
+        if self.rows == 0 || self.cols == 0 {
+            return;
+        }
+        self.pending.extend_from_slice(buf);
+        let pending = std::mem::take(&mut self.pending);
+        let valid_len = match std::str::from_utf8(&pending) {
+            Ok(text) => text.len(),
+            Err(e) => e.valid_up_to(),
+        };
+        let text = std::str::from_utf8(&pending[..valid_len]).unwrap();
+        let consumed = self.interpret(text);
+        self.pending = pending[consumed..].to_vec();
+    }
+
+    /// Interpret `text` and return the number of bytes consumed
+    ///
+    /// Anything after an incomplete escape sequence at the end is left unconsumed.
+    fn interpret(&mut self, text: &str) -> usize {
+        let mut rest = text;
+        while let Some(c) = rest.chars().next() {
+            match c {
+                '\u{1b}' => {
+                    let Some(len) = self.escape_sequence(rest) else {
+                        break;
+                    };
+                    rest = &rest[len..];
+                    continue;
+                }
+                '\r' => self.x = 0,
+                '\n' => self.line_feed(),
+                c if c.is_control() => {}
+                c => self.put_char(c),
+            }
+            rest = &rest[c.len_utf8()..];
+        }
+        text.len() - rest.len()
+    }
+
+    /// Handle the escape sequence at the start of `text` and return its length
+    fn escape_sequence(&mut self, text: &str) -> Option<usize> {
+        match text[1..].chars().next()? {
+            '[' => {
+                let end = text[2..].find(|c| ('\u{40}'..='\u{7e}').contains(&c))? + 2;
+                self.csi(&text[2..end], text[end..].chars().next()?);
+                Some(end + 1)
+            }
+            ']' => {
+                let body = &text[2..];
+                match (body.find('\u{7}'), body.find("\u{1b}\\")) {
+                    (Some(bel), Some(st)) if st < bel => Some(st + 4),
+                    (Some(bel), _) => Some(bel + 3),
+                    (None, Some(st)) => Some(st + 4),
+                    (None, None) => None,
+                }
+            }
+            '7' => {
+                self.saved = (self.x, self.y);
+                Some(2)
+            }
+            '8' => {
+                (self.x, self.y) = self.saved;
+                Some(2)
+            }
+            c => Some(1 + c.len_utf8()),
+        }
+    }
+
+    fn csi(&mut self, params: &str, action: char) {
+        if params.starts_with('?') {
+            // Private modes like showing the cursor don't change the screen
+            return;
+        }
+        let args = params
+            .split(';')
+            .map(|arg| arg.parse::<usize>().ok())
+            .collect::<Vec<_>>();
+        let arg = |i: usize, default: usize| args.get(i).copied().flatten().unwrap_or(default);
+
+        match action {
+            'H' | 'f' => {
+                self.y = arg(0, 1).clamp(1, self.rows) - 1;
+                self.x = arg(1, 1).clamp(1, self.cols) - 1;
+            }
+            'A' => self.y = self.y.saturating_sub(arg(0, 1)),
+            'B' => self.y = (self.y + arg(0, 1)).min(self.rows.saturating_sub(1)),
+            'C' => self.x = (self.x + arg(0, 1)).min(self.cols.saturating_sub(1)),
+            'D' => self.x = self.x.saturating_sub(arg(0, 1)),
+            'G' => self.x = arg(0, 1).clamp(1, self.cols) - 1,
+            'J' => match arg(0, 0) {
+                0 => {
+                    self.clear_row(self.y, self.x..self.cols);
+                    (self.y + 1..self.rows).for_each(|y| self.clear_row(y, 0..self.cols));
+                }
+                1 => {
+                    (0..self.y).for_each(|y| self.clear_row(y, 0..self.cols));
+                    self.clear_row(self.y, 0..self.x + 1);
+                }
+                _ => (0..self.rows).for_each(|y| self.clear_row(y, 0..self.cols)),
+            },
+            'K' => match arg(0, 0) {
+                0 => self.clear_row(self.y, self.x..self.cols),
+                1 => self.clear_row(self.y, 0..self.x + 1),
+                _ => self.clear_row(self.y, 0..self.cols),
+            },
+            'X' => self.clear_row(self.y, self.x..self.x + arg(0, 1)),
+            'S' => self.scroll_up(arg(0, 1)),
+            'T' => {
+                for _ in 0..arg(0, 1).min(self.rows) {
+                    self.grid.pop();
+                    self.grid.insert(0, vec![' '; self.cols]);
+                }
+            }
+            // Styling and everything else is ignored
+            _ => {}
+        }
+    }
+
+    fn clear_row(&mut self, y: usize, cols: std::ops::Range<usize>) {
+        let end = cols.end.min(self.cols);
+        if let Some(row) = self.grid.get_mut(y) {
+            row[cols.start.min(end)..end].fill(' ');
+        }
+    }
+
+    fn scroll_up(&mut self, n: usize) {
+        for _ in 0..n.min(self.rows) {
+            self.grid.remove(0);
+            self.grid.push(vec![' '; self.cols]);
+        }
+    }
+
+    fn line_feed(&mut self) {
+        if self.y + 1 < self.rows {
+            self.y += 1;
+        } else {
+            self.scroll_up(1);
+        }
+    }
+
+    fn put_char(&mut self, c: char) {
+        // Like real terminals, wrap to the next row only when a character is written past the
+        // last column
+        if self.x >= self.cols {
+            self.x = 0;
+            self.line_feed();
+        }
+        self.grid[self.y][self.x] = c;
+        self.x += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TestBackend;
+    use crate::backend::Backend;
+
+    #[test]
+    fn interprets_escape_sequences() {
+        let backend = TestBackend::new(10, 3);
+        backend
+            .write(b"\x1b[2J\x1b[1;1Hfirst\r\n\x1b[1mbo")
+            .unwrap();
+        // Sequences and characters split across writes
+        backend.write(b"ld\x1b[").unwrap();
+        backend.write(b"0m\r\nthird row!").unwrap();
+        assert_eq!(backend.screen(), ["first", "bold", "third row!"]);
+
+        backend.write(b"\x1b[2;3H\x1b[K\x1b[1S").unwrap();
+        assert_eq!(backend.screen(), ["bo", "third row!", ""]);
+        assert_eq!(backend.cursor(), (2, 1));
+
+        backend
+            .write("\x1b[3;1H\x1b]8;;https://example.com\x1b\\link\x1b]8;;\x07 \u{e9}".as_bytes())
+            .unwrap();
+        assert_eq!(backend.screen(), ["bo", "third row!", "link \u{e9}"]);
+    }
+
+    #[test]
+    fn empty_screen() {
+        let backend = TestBackend::new(0, 0);
+        backend
+            .write(b"\x1b[2J\x1b[5;5Htext\x1b[2B\x1b[3C\r\n")
+            .unwrap();
+        assert!(backend.screen().is_empty());
+    }
+}
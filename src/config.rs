@@ -0,0 +1,84 @@
+//! Provides the [`PagerConfig`] type
+//!
+//! A [`PagerConfig`] collects the settings that users commonly want to tune in one place so that
+//! applications can load them from a configuration file and apply them with
+//! [`Pager::apply_config`]. When the `serde` feature is enabled, it can be deserialized from any
+//! format supported by [`serde`](https://docs.rs/serde).
+//!
+//! [`Pager::apply_config`]: crate::Pager::apply_config
+
+#[cfg(feature = "__search")]
+use crate::search::{HighlightMode, MatchPlacement};
+use crate::{ExitStrategy, FeedbackMode, LineNumbers, ScrollAmounts, Theme, WindowTitle};
+#[cfg(feature = "__search")]
+use std::time::Duration;
+
+/// Settings that can be applied to a pager all at once
+///
+/// Every setting is optional and settings that are `None` are left as they are. Hence a
+/// configuration file only needs to contain the settings that the user wants to change. Each
+/// setting corresponds to a function on [`Pager`](crate::Pager), see the documentation of the
+/// fields.
+///
+/// ```
+/// use minus::{config::PagerConfig, LineNumbers, Pager};
+///
+/// let pager = Pager::new();
+/// let config = PagerConfig {
+///     line_numbers: Some(LineNumbers::Enabled),
+///     line_wrapping: Some(false),
+///     ..PagerConfig::default()
+/// };
+/// pager.apply_config(&config).expect("Failed to communicate with the pager");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+#[allow(clippy::module_name_repetitions)]
+pub struct PagerConfig {
+    /// See [`Pager::set_line_numbers`](crate::Pager::set_line_numbers)
+    pub line_numbers: Option<LineNumbers>,
+    /// See [`Pager::set_min_line_number_digits`](crate::Pager::set_min_line_number_digits)
+    pub min_line_number_digits: Option<usize>,
+    /// See [`Pager::set_theme`](crate::Pager::set_theme)
+    pub theme: Option<Theme>,
+    /// Whether long lines are wrapped. This is the opposite of
+    /// [`Pager::horizontal_scroll`](crate::Pager::horizontal_scroll).
+    pub line_wrapping: Option<bool>,
+    /// See [`Pager::set_scroll_amounts`](crate::Pager::set_scroll_amounts)
+    pub scroll_amounts: Option<ScrollAmounts>,
+    /// See [`Pager::set_scroll_margin`](crate::Pager::set_scroll_margin)
+    pub scroll_margin: Option<usize>,
+    /// See [`Pager::set_center_on_jump`](crate::Pager::set_center_on_jump)
+    pub center_on_jump: Option<bool>,
+    /// See [`Pager::set_quit_on_eof`](crate::Pager::set_quit_on_eof)
+    pub quit_on_eof: Option<bool>,
+    /// See [`Pager::set_feedback`](crate::Pager::set_feedback)
+    pub feedback: Option<FeedbackMode>,
+    /// See [`Pager::set_exit_strategy`](crate::Pager::set_exit_strategy)
+    pub exit_strategy: Option<ExitStrategy>,
+    /// See [`Pager::set_macros`](crate::Pager::set_macros)
+    pub macros: Option<bool>,
+    /// See [`Pager::set_alternate_scroll`](crate::Pager::set_alternate_scroll)
+    pub alternate_scroll: Option<bool>,
+    /// See [`Pager::set_carriage_return_overwrite`](crate::Pager::set_carriage_return_overwrite)
+    pub carriage_return_overwrite: Option<bool>,
+    /// See [`Pager::set_window_title`](crate::Pager::set_window_title)
+    pub window_title: Option<WindowTitle>,
+    /// See [`Pager::set_match_placement`](crate::Pager::set_match_placement)
+    #[cfg(feature = "__search")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "search")))]
+    pub match_placement: Option<MatchPlacement>,
+    /// See [`Pager::set_highlight_mode`](crate::Pager::set_highlight_mode)
+    #[cfg(feature = "__search")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "search")))]
+    pub highlight_mode: Option<HighlightMode>,
+    /// See [`Pager::set_multiline_search`](crate::Pager::set_multiline_search)
+    #[cfg(feature = "__search")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "search")))]
+    pub multiline_search: Option<bool>,
+    /// See [`Pager::set_incremental_search_debounce`](crate::Pager::set_incremental_search_debounce)
+    #[cfg(feature = "__search")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "search")))]
+    pub incremental_search_debounce: Option<Duration>,
+}
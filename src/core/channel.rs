@@ -0,0 +1,162 @@
+//! Channels through which [`Command`]s reach the reactor
+//!
+//! These are [`crossbeam_channel`] channels. With the `tokio` feature, sending a command also
+//! wakes up the task of [`async_paging`](crate::async_paging) waiting for one and receiving a
+//! command wakes up the tasks waiting for space in a [bounded](crate::Pager::bounded) channel,
+//! hence both sides can be awaited without blocking a thread of the runtime.
+use super::commands::Command;
+use crossbeam_channel::{Receiver, SendError, Sender, TryRecvError, TrySendError};
+
+#[cfg(feature = "tokio")]
+use std::sync::Arc;
+#[cfg(feature = "tokio")]
+use tokio::sync::Notify;
+
+/// Tasks waiting on either side of a channel
+#[cfg(feature = "tokio")]
+#[derive(Default)]
+struct Wakers {
+    /// Notified whenever a command is sent
+    sent: Notify,
+    /// Notified whenever a command is received
+    received: Notify,
+}
+
+/// Create a channel that holds any number of commands
+pub fn unbounded() -> (CommandSender, CommandReceiver) {
+    wrap(crossbeam_channel::unbounded())
+}
+
+/// Create a channel that holds at most `capacity` commands
+pub fn bounded(capacity: usize) -> (CommandSender, CommandReceiver) {
+    wrap(crossbeam_channel::bounded(capacity))
+}
+
+fn wrap((tx, rx): (Sender<Command>, Receiver<Command>)) -> (CommandSender, CommandReceiver) {
+    #[cfg(feature = "tokio")]
+    let wakers = Arc::new(Wakers::default());
+    (
+        CommandSender {
+            tx,
+            #[cfg(feature = "tokio")]
+            wakers: wakers.clone(),
+        },
+        CommandReceiver {
+            rx,
+            #[cfg(feature = "tokio")]
+            wakers,
+        },
+    )
+}
+
+/// Sending end of a channel of [`Command`]s
+#[derive(Clone)]
+pub struct CommandSender {
+    tx: Sender<Command>,
+    #[cfg(feature = "tokio")]
+    wakers: Arc<Wakers>,
+}
+
+impl CommandSender {
+    /// Send `command`, blocking while the channel is full
+    pub fn send(&self, command: Command) -> Result<(), SendError<Command>> {
+        self.tx.send(command)?;
+        self.sent();
+        Ok(())
+    }
+
+    /// Send `command` if the channel has space for it
+    pub fn try_send(&self, command: Command) -> Result<(), TrySendError<Command>> {
+        self.tx.try_send(command)?;
+        self.sent();
+        Ok(())
+    }
+
+    /// Send `command`, waiting for space without blocking the thread while the channel is full
+    #[cfg(feature = "tokio")]
+    pub async fn send_async(&self, mut command: Command) -> Result<(), SendError<Command>> {
+        loop {
+            match self.try_send(command) {
+                Ok(()) => return Ok(()),
+                // A command received after the attempt above leaves a permit behind, hence this
+                // can't miss the space being freed
+                Err(TrySendError::Full(c)) => {
+                    command = c;
+                    self.wakers.received.notified().await;
+                }
+                Err(TrySendError::Disconnected(c)) => return Err(SendError(c)),
+            }
+        }
+    }
+
+    #[allow(clippy::unused_self)]
+    fn sent(&self) {
+        #[cfg(feature = "tokio")]
+        self.wakers.sent.notify_one();
+    }
+}
+
+/// Receiving end of a channel of [`Command`]s
+#[derive(Clone)]
+pub struct CommandReceiver {
+    rx: Receiver<Command>,
+    #[cfg(feature = "tokio")]
+    wakers: Arc<Wakers>,
+}
+
+impl CommandReceiver {
+    /// Receive a command if one is waiting
+    pub fn try_recv(&self) -> Result<Command, TryRecvError> {
+        let command = self.rx.try_recv()?;
+        self.received();
+        Ok(command)
+    }
+
+    /// Receive all the commands that are waiting
+    pub fn try_iter(&self) -> impl Iterator<Item = Command> + '_ {
+        std::iter::from_fn(move || self.try_recv().ok())
+    }
+
+    /// Create an unbounded channel whose commands wake up the same task as the ones of this one
+    ///
+    /// This lets a task wait for commands on both channels at once with
+    /// [`CommandReceiver::sent`].
+    pub fn unbounded_sibling(&self) -> (CommandSender, Self) {
+        let (tx, rx) = crossbeam_channel::unbounded();
+        (
+            CommandSender {
+                tx,
+                #[cfg(feature = "tokio")]
+                wakers: self.wakers.clone(),
+            },
+            Self {
+                rx,
+                #[cfg(feature = "tokio")]
+                wakers: self.wakers.clone(),
+            },
+        )
+    }
+
+    /// Wait until a command is sent to this channel or any of its siblings
+    ///
+    /// A command sent since the last call makes this return right away, hence it can be called
+    /// after finding the channels empty without missing any command sent in between.
+    #[cfg(all(feature = "dynamic_output", feature = "tokio"))]
+    pub async fn sent(&self) {
+        self.wakers.sent.notified().await;
+    }
+
+    /// The underlying channel, for use with [`crossbeam_channel::Select`]
+    ///
+    /// [`CommandReceiver::received`] must be called after receiving a command through it.
+    pub(crate) const fn inner(&self) -> &Receiver<Command> {
+        &self.rx
+    }
+
+    /// Let the senders waiting for space know that a command has been received
+    #[allow(clippy::unused_self)]
+    pub(crate) fn received(&self) {
+        #[cfg(feature = "tokio")]
+        self.wakers.received.notify_one();
+    }
+}
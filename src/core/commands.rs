@@ -3,15 +3,33 @@
 //! This module only declares the [Command] type. To know how they are handled internally see
 //! the [`ev_handler`](super::ev_handler).
 
-use std::fmt::Debug;
+#[cfg(feature = "__search")]
+use std::ops::Range;
+use std::{
+    fmt::Debug,
+    sync::{atomic::AtomicBool, Arc},
+    time::Duration,
+};
 
 use crate::{
+    backend::Backend,
+    config::PagerConfig,
     input::{InputClassifier, InputEvent},
-    ExitStrategy, LineNumbers,
+    minus_core::utils::display::Region,
+    screen::{LineNumberFormat, LineTransformer},
+    state::{DataProvider, PromptRenderer, Session},
+    AnsiMode, BinaryMode, ExitStrategy, FeedbackMode, InterruptBehavior, LineNumbers, PagerEvent,
+    ScrollAmounts, Theme, WindowTitle,
 };
+use crossbeam_channel::Sender;
+use crossterm::event::{Event, KeyboardEnhancementFlags};
+#[cfg(feature = "__regex")]
+use crossterm::style::ContentStyle;
 
-#[cfg(feature = "search")]
-use crate::search::SearchOpts;
+#[cfg(feature = "__regex")]
+use crate::pattern::Regex;
+#[cfg(feature = "__search")]
+use crate::search::{HighlightMode, MatchPlacement, SearchOpts};
 
 /// Different events that can be encountered while the pager is running
 #[non_exhaustive]
@@ -21,30 +39,101 @@ pub enum Command {
 
     // Data related
     AppendData(String),
+    AppendStyledLines(String),
     SetData(String),
+    SetAnsiMode(AnsiMode),
+    SetCarriageReturnOverwrite(bool),
+    SetPlainRender(bool),
+    SetWindowTitle(WindowTitle),
+    SetMacros(bool),
+    PlayMacro(Vec<Event>, usize),
+    AppendBytes(Vec<u8>),
+    SetBinaryMode(BinaryMode),
 
     // Prompt related
     SendMessage(String),
+    ClearMessages,
     ShowPrompt(bool),
     SetPrompt(String),
+    SetPromptRenderer(PromptRenderer),
+    SetEobFiller(String),
+    SetEofMarker(Option<String>),
+    SetFeedback(FeedbackMode),
+    Feedback,
 
     // Screen output configurations
     LineWrapping(bool),
     SetLineNumbers(LineNumbers),
     FollowOutput(bool),
+    SetScrollMargin(usize),
+    SetPageOverlap(usize),
+    SetScrollAmounts(ScrollAmounts),
+    AnimateScroll,
+    SetCenterOnJump(bool),
+    SetQuitOnEof(bool),
+
+    // Buffers
+    AddBuffer(String, String),
+    SwitchToBuffer(String),
+
+    // Navigation
+    ScrollToLine(usize),
+    RestoreSession(Session),
+    Quit,
 
     // Configuration options
+    ApplyConfig(Box<PagerConfig>),
     SetExitStrategy(ExitStrategy),
+    SetInterruptBehavior(InterruptBehavior),
+    #[cfg(feature = "__search")]
+    SetJumpPattern(Option<Regex>),
+    #[cfg(feature = "__search")]
+    SetMatchPlacement(MatchPlacement),
+    #[cfg(feature = "__search")]
+    SetSearchRange(Option<Range<usize>>),
+    #[cfg(feature = "__search")]
+    SetIncrementalSearchDebounce(Duration),
+    #[cfg(feature = "__search")]
+    SetMultilineSearch(bool),
+    #[cfg(feature = "__search")]
+    SetHighlightMode(HighlightMode),
+    #[cfg(feature = "__regex")]
+    AddHighlightRule(Regex, ContentStyle),
+    #[cfg(feature = "__search")]
+    ClearSearch,
     SetInputClassifier(Box<dyn InputClassifier + Send + Sync + 'static>),
     AddExitCallback(Box<dyn FnMut() + Send + Sync + 'static>),
+    SetReloadCallback(Box<dyn FnMut() -> String + Send + Sync + 'static>),
+    SetReloadInterval(Option<Duration>),
+    SetMaxRefreshRate(Duration),
+    SetPollTimeout(Duration),
+    SetIdleCallback(Box<dyn FnMut() + Send + Sync + 'static>),
+    SetCancellationFlag(Arc<AtomicBool>),
+    SetDataProvider(DataProvider),
+    SetLineTransformer(LineTransformer),
+    SetLineNumberFormat(LineNumberFormat),
+    SetTheme(Theme),
+    SetMinLineNumberDigits(usize),
+    SetLineLengthLimit(usize),
+    SetWrapCache(usize),
+    SetBackend(Arc<dyn Backend>),
+    SetTermSize(Option<(usize, usize)>),
+    SetRegion(Option<Region>),
+    SetKeyboardEnhancement(KeyboardEnhancementFlags),
+    SetAlternateScroll(bool),
+    AddEventSubscriber(Sender<PagerEvent>),
+    #[cfg(feature = "static_output")]
+    SetForcePager(bool),
     #[cfg(feature = "static_output")]
-    SetRunNoOverflow(bool),
-    #[cfg(feature = "search")]
+    SetPagerThreshold(Option<usize>),
+    #[cfg(feature = "__search")]
     IncrementalSearchCondition(Box<dyn Fn(&SearchOpts) -> bool + Send + Sync + 'static>),
 
     // Internal commands
     FormatRedrawPrompt,
     FormatRedrawDisplay,
+    RedrawDisplay,
+    Idle,
 }
 
 impl PartialEq for Command {
@@ -52,18 +141,96 @@ impl PartialEq for Command {
         match (self, other) {
             (Self::SetData(d1), Self::SetData(d2))
             | (Self::AppendData(d1), Self::AppendData(d2))
+            | (Self::AppendStyledLines(d1), Self::AppendStyledLines(d2))
             | (Self::SetPrompt(d1), Self::SetPrompt(d2))
+            | (Self::SetEobFiller(d1), Self::SetEobFiller(d2))
             | (Self::SendMessage(d1), Self::SendMessage(d2)) => d1 == d2,
+            (Self::UserInput(d1), Self::UserInput(d2)) => d1 == d2,
+            (Self::SetEofMarker(d1), Self::SetEofMarker(d2)) => d1 == d2,
+            (Self::AddBuffer(n1, t1), Self::AddBuffer(n2, t2)) => n1 == n2 && t1 == t2,
+            (Self::SwitchToBuffer(d1), Self::SwitchToBuffer(d2)) => d1 == d2,
+            (Self::RestoreSession(d1), Self::RestoreSession(d2)) => d1 == d2,
             (Self::LineWrapping(d1), Self::LineWrapping(d2)) => d1 == d2,
             (Self::SetLineNumbers(d1), Self::SetLineNumbers(d2)) => d1 == d2,
+            (Self::SetAnsiMode(d1), Self::SetAnsiMode(d2)) => d1 == d2,
+            (Self::SetCarriageReturnOverwrite(d1), Self::SetCarriageReturnOverwrite(d2)) => {
+                d1 == d2
+            }
+            (Self::SetPlainRender(d1), Self::SetPlainRender(d2)) => d1 == d2,
+            (Self::SetWindowTitle(d1), Self::SetWindowTitle(d2)) => d1 == d2,
+            (Self::SetMacros(d1), Self::SetMacros(d2)) => d1 == d2,
+            (Self::PlayMacro(d1, c1), Self::PlayMacro(d2, c2)) => d1 == d2 && c1 == c2,
+            (Self::SetLineLengthLimit(d1), Self::SetLineLengthLimit(d2)) => d1 == d2,
+            (Self::SetWrapCache(d1), Self::SetWrapCache(d2)) => d1 == d2,
+            (Self::AppendBytes(d1), Self::AppendBytes(d2)) => d1 == d2,
+            (Self::SetBinaryMode(d1), Self::SetBinaryMode(d2)) => d1 == d2,
+            (Self::SetFeedback(d1), Self::SetFeedback(d2)) => d1 == d2,
+            (Self::SetKeyboardEnhancement(d1), Self::SetKeyboardEnhancement(d2)) => d1 == d2,
+            (Self::SetAlternateScroll(d1), Self::SetAlternateScroll(d2)) => d1 == d2,
+            (Self::SetTermSize(d1), Self::SetTermSize(d2)) => d1 == d2,
+            (Self::SetRegion(d1), Self::SetRegion(d2)) => d1 == d2,
             (Self::ShowPrompt(d1), Self::ShowPrompt(d2)) => d1 == d2,
+            (Self::ApplyConfig(d1), Self::ApplyConfig(d2)) => d1 == d2,
             (Self::SetExitStrategy(d1), Self::SetExitStrategy(d2)) => d1 == d2,
+            (Self::SetInterruptBehavior(d1), Self::SetInterruptBehavior(d2)) => d1 == d2,
+            #[cfg(feature = "__search")]
+            (Self::SetJumpPattern(d1), Self::SetJumpPattern(d2)) => {
+                d1.as_ref().map(Regex::as_str) == d2.as_ref().map(Regex::as_str)
+            }
+            #[cfg(feature = "__search")]
+            (Self::SetMatchPlacement(d1), Self::SetMatchPlacement(d2)) => d1 == d2,
+            #[cfg(feature = "__search")]
+            (Self::SetSearchRange(d1), Self::SetSearchRange(d2)) => d1 == d2,
+            #[cfg(feature = "__search")]
+            (Self::SetIncrementalSearchDebounce(d1), Self::SetIncrementalSearchDebounce(d2)) => {
+                d1 == d2
+            }
+            #[cfg(feature = "__search")]
+            (Self::SetMultilineSearch(d1), Self::SetMultilineSearch(d2)) => d1 == d2,
+            #[cfg(feature = "__search")]
+            (Self::SetHighlightMode(d1), Self::SetHighlightMode(d2)) => d1 == d2,
+            #[cfg(feature = "__regex")]
+            (Self::AddHighlightRule(r1, s1), Self::AddHighlightRule(r2, s2)) => {
+                r1.as_str() == r2.as_str() && s1 == s2
+            }
             #[cfg(feature = "static_output")]
-            (Self::SetRunNoOverflow(d1), Self::SetRunNoOverflow(d2)) => d1 == d2,
+            (Self::SetForcePager(d1), Self::SetForcePager(d2)) => d1 == d2,
+            #[cfg(feature = "static_output")]
+            (Self::SetPagerThreshold(d1), Self::SetPagerThreshold(d2)) => d1 == d2,
+            (Self::SetReloadInterval(d1), Self::SetReloadInterval(d2)) => d1 == d2,
+            (Self::SetMaxRefreshRate(d1), Self::SetMaxRefreshRate(d2)) => d1 == d2,
+            (Self::SetPollTimeout(d1), Self::SetPollTimeout(d2)) => d1 == d2,
+            (Self::SetCancellationFlag(d1), Self::SetCancellationFlag(d2)) => Arc::ptr_eq(d1, d2),
+            (Self::SetScrollMargin(d1), Self::SetScrollMargin(d2))
+            | (Self::SetMinLineNumberDigits(d1), Self::SetMinLineNumberDigits(d2))
+            | (Self::SetPageOverlap(d1), Self::SetPageOverlap(d2))
+            | (Self::ScrollToLine(d1), Self::ScrollToLine(d2)) => d1 == d2,
+            (Self::SetCenterOnJump(d1), Self::SetCenterOnJump(d2))
+            | (Self::SetQuitOnEof(d1), Self::SetQuitOnEof(d2)) => d1 == d2,
+            (Self::SetScrollAmounts(d1), Self::SetScrollAmounts(d2)) => d1 == d2,
+            (Self::SetTheme(d1), Self::SetTheme(d2)) => d1 == d2,
             (Self::SetInputClassifier(_), Self::SetInputClassifier(_))
-            | (Self::AddExitCallback(_), Self::AddExitCallback(_)) => true,
-            #[cfg(feature = "search")]
+            | (Self::AddExitCallback(_), Self::AddExitCallback(_))
+            | (Self::SetReloadCallback(_), Self::SetReloadCallback(_))
+            | (Self::SetIdleCallback(_), Self::SetIdleCallback(_))
+            | (Self::AddEventSubscriber(_), Self::AddEventSubscriber(_))
+            | (Self::SetDataProvider(_), Self::SetDataProvider(_))
+            | (Self::SetLineTransformer(_), Self::SetLineTransformer(_))
+            | (Self::SetLineNumberFormat(_), Self::SetLineNumberFormat(_))
+            | (Self::SetPromptRenderer(_), Self::SetPromptRenderer(_))
+            | (Self::SetBackend(_), Self::SetBackend(_)) => true,
+            #[cfg(feature = "__search")]
             (Self::IncrementalSearchCondition(_), Self::IncrementalSearchCondition(_)) => true,
+            (Self::Quit, Self::Quit)
+            | (Self::ClearMessages, Self::ClearMessages)
+            | (Self::AnimateScroll, Self::AnimateScroll)
+            | (Self::FormatRedrawDisplay, Self::FormatRedrawDisplay)
+            | (Self::FormatRedrawPrompt, Self::FormatRedrawPrompt)
+            | (Self::RedrawDisplay, Self::RedrawDisplay)
+            | (Self::Idle, Self::Idle)
+            | (Self::Feedback, Self::Feedback) => true,
+            #[cfg(feature = "__search")]
+            (Self::ClearSearch, Self::ClearSearch) => true,
             _ => false,
         }
     }
@@ -73,23 +240,137 @@ impl Debug for Command {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::SetData(text) => write!(f, "SetData({:?})", text),
+            Self::SetAnsiMode(mode) => write!(f, "SetAnsiMode({mode:?})"),
+            Self::SetCarriageReturnOverwrite(enabled) => {
+                write!(f, "SetCarriageReturnOverwrite({enabled:?})")
+            }
+            Self::SetPlainRender(plain) => write!(f, "SetPlainRender({plain:?})"),
+            Self::SetWindowTitle(title) => write!(f, "SetWindowTitle({title:?})"),
+            Self::SetMacros(enabled) => write!(f, "SetMacros({enabled:?})"),
+            Self::PlayMacro(events, count) => write!(f, "PlayMacro({events:?}, {count:?})"),
+            Self::AppendBytes(bytes) => write!(f, "AppendBytes({bytes:?})"),
+            Self::SetBinaryMode(mode) => write!(f, "SetBinaryMode({mode:?})"),
             Self::AppendData(text) => write!(f, "AppendData({:?})", text),
+            Self::AppendStyledLines(text) => write!(f, "AppendStyledLines({text:?})"),
             Self::SetPrompt(text) => write!(f, "SetPrompt({:?})", text),
+            Self::SetEobFiller(text) => write!(f, "SetEobFiller({text:?})"),
+            Self::SetEofMarker(marker) => write!(f, "SetEofMarker({marker:?})"),
             Self::SendMessage(text) => write!(f, "SendMessage({:?})", text),
             Self::SetLineNumbers(ln) => write!(f, "SetLineNumbers({:?})", ln),
             Self::LineWrapping(lw) => write!(f, "LineWrapping({:?})", lw),
+            Self::ApplyConfig(config) => write!(f, "ApplyConfig({config:?})"),
             Self::SetExitStrategy(es) => write!(f, "SetExitStrategy({:?})", es),
+            Self::SetInterruptBehavior(ib) => write!(f, "SetInterruptBehavior({ib:?})"),
+            #[cfg(feature = "__search")]
+            Self::SetJumpPattern(regex) => {
+                write!(f, "SetJumpPattern({:?})", regex.as_ref().map(Regex::as_str))
+            }
+            #[cfg(feature = "__search")]
+            Self::SetMatchPlacement(placement) => write!(f, "SetMatchPlacement({placement:?})"),
+            #[cfg(feature = "__search")]
+            Self::SetSearchRange(range) => write!(f, "SetSearchRange({range:?})"),
+            #[cfg(feature = "__search")]
+            Self::SetIncrementalSearchDebounce(interval) => {
+                write!(f, "SetIncrementalSearchDebounce({interval:?})")
+            }
+            #[cfg(feature = "__search")]
+            Self::SetMultilineSearch(multiline) => write!(f, "SetMultilineSearch({multiline:?})"),
+            #[cfg(feature = "__search")]
+            Self::SetHighlightMode(mode) => write!(f, "SetHighlightMode({mode:?})"),
+            #[cfg(feature = "__regex")]
+            Self::AddHighlightRule(regex, style) => {
+                write!(f, "AddHighlightRule({:?}, {style:?})", regex.as_str())
+            }
+            #[cfg(feature = "__search")]
+            Self::ClearSearch => write!(f, "ClearSearch"),
             Self::SetInputClassifier(_) => write!(f, "SetInputClassifier"),
             Self::ShowPrompt(show) => write!(f, "ShowPrompt({show:?})"),
             Self::FormatRedrawPrompt => write!(f, "FormatRedrawPrompt"),
+            Self::ClearMessages => write!(f, "ClearMessages"),
+            Self::AnimateScroll => write!(f, "AnimateScroll"),
+            Self::SetFeedback(mode) => write!(f, "SetFeedback({mode:?})"),
+            Self::Feedback => write!(f, "Feedback"),
             Self::FormatRedrawDisplay => write!(f, "FormatRedrawDisplay"),
-            #[cfg(feature = "search")]
+            Self::RedrawDisplay => write!(f, "RedrawDisplay"),
+            Self::Idle => write!(f, "Idle"),
+            #[cfg(feature = "__search")]
             Self::IncrementalSearchCondition(_) => write!(f, "IncrementalSearchCondition"),
             Self::AddExitCallback(_) => write!(f, "AddExitCallback"),
+            Self::SetReloadCallback(_) => write!(f, "SetReloadCallback"),
+            Self::SetReloadInterval(val) => write!(f, "SetReloadInterval({val:?})"),
+            Self::SetMaxRefreshRate(val) => write!(f, "SetMaxRefreshRate({val:?})"),
+            Self::SetPollTimeout(val) => write!(f, "SetPollTimeout({val:?})"),
+            Self::SetIdleCallback(_) => write!(f, "SetIdleCallback"),
+            Self::SetCancellationFlag(flag) => write!(f, "SetCancellationFlag({flag:?})"),
+            Self::AddEventSubscriber(_) => write!(f, "AddEventSubscriber"),
+            Self::SetDataProvider(_) => write!(f, "SetDataProvider"),
+            Self::SetLineTransformer(_) => write!(f, "SetLineTransformer"),
+            Self::SetLineNumberFormat(_) => write!(f, "SetLineNumberFormat"),
+            Self::SetTheme(theme) => write!(f, "SetTheme({theme:?})"),
+            Self::SetMinLineNumberDigits(digits) => write!(f, "SetMinLineNumberDigits({digits})"),
+            Self::SetPromptRenderer(_) => write!(f, "SetPromptRenderer"),
+            Self::SetLineLengthLimit(limit) => write!(f, "SetLineLengthLimit({limit})"),
+            Self::SetWrapCache(lines) => write!(f, "SetWrapCache({lines})"),
+            Self::SetBackend(_) => write!(f, "SetBackend"),
+            Self::SetTermSize(size) => write!(f, "SetTermSize({size:?})"),
+            Self::SetRegion(region) => write!(f, "SetRegion({region:?})"),
+            Self::SetKeyboardEnhancement(flags) => write!(f, "SetKeyboardEnhancement({flags:?})"),
+            Self::SetAlternateScroll(enabled) => write!(f, "SetAlternateScroll({enabled:?})"),
+            #[cfg(feature = "static_output")]
+            Self::SetForcePager(val) => write!(f, "SetForcePager({val:?})"),
             #[cfg(feature = "static_output")]
-            Self::SetRunNoOverflow(val) => write!(f, "SetRunNoOverflow({val:?})"),
+            Self::SetPagerThreshold(rows) => write!(f, "SetPagerThreshold({rows:?})"),
             Self::UserInput(input) => write!(f, "UserInput({input:?})"),
             Self::FollowOutput(follow_output) => write!(f, "FollowOutput({follow_output:?})"),
+            Self::SetScrollMargin(margin) => write!(f, "SetScrollMargin({margin:?})"),
+            Self::SetPageOverlap(overlap) => write!(f, "SetPageOverlap({overlap:?})"),
+            Self::SetScrollAmounts(amounts) => write!(f, "SetScrollAmounts({amounts:?})"),
+            Self::SetCenterOnJump(center) => write!(f, "SetCenterOnJump({center:?})"),
+            Self::SetQuitOnEof(quit) => write!(f, "SetQuitOnEof({quit:?})"),
+            Self::ScrollToLine(line) => write!(f, "ScrollToLine({line:?})"),
+            Self::RestoreSession(session) => write!(f, "RestoreSession({session:?})"),
+            Self::AddBuffer(name, text) => write!(f, "AddBuffer({name:?}, {text:?})"),
+            Self::SwitchToBuffer(name) => write!(f, "SwitchToBuffer({name:?})"),
+            Self::Quit => write!(f, "Quit"),
+        }
+    }
+}
+
+/// Commands that applications can send directly to the pager
+///
+/// Most of the pager's behaviour can be changed through the setters on [`Pager`]. These commands
+/// cover the cases where an application needs to poke the pager itself, like asking it to
+/// reformat the text after changing something that the text is formatted from. Send them using
+/// [`Pager::send_command`].
+///
+/// [`Pager`]: crate::Pager
+/// [`Pager::send_command`]: crate::Pager::send_command
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum PagerCommand {
+    /// Act as if the user had done something
+    UserInput(InputEvent),
+    /// Format the text again and redraw the whole screen
+    FormatRedrawDisplay,
+    /// Format the prompt again and redraw it
+    FormatRedrawPrompt,
+    /// Redraw the whole screen without formatting the text again
+    RedrawDisplay,
+    /// Remove the message shown at the prompt along with any messages waiting to be shown
+    ClearMessages,
+    /// Give the user feedback as set by [`Pager::set_feedback`](crate::Pager::set_feedback)
+    Feedback,
+}
+
+impl From<PagerCommand> for Command {
+    fn from(command: PagerCommand) -> Self {
+        match command {
+            PagerCommand::UserInput(ev) => Self::UserInput(ev),
+            PagerCommand::FormatRedrawDisplay => Self::FormatRedrawDisplay,
+            PagerCommand::FormatRedrawPrompt => Self::FormatRedrawPrompt,
+            PagerCommand::RedrawDisplay => Self::RedrawDisplay,
+            PagerCommand::ClearMessages => Self::ClearMessages,
+            PagerCommand::Feedback => Self::Feedback,
         }
     }
 }
@@ -97,7 +378,7 @@ impl Debug for Command {
 impl Command {
     #[allow(dead_code)]
     pub(crate) const fn is_exit_event(&self) -> bool {
-        matches!(self, Self::UserInput(InputEvent::Exit))
+        matches!(self, Self::UserInput(InputEvent::Exit) | Self::Quit)
     }
 
     #[allow(dead_code)]
@@ -1,18 +1,22 @@
 //! Provides the [`handle_event`] function
 
-use std::convert::TryInto;
 use std::io::Write;
 use std::sync::{atomic::AtomicBool, Arc};
 
-#[cfg(feature = "search")]
+use crossterm::event::Event;
+#[cfg(feature = "__search")]
 use parking_lot::{Condvar, Mutex};
 
 use super::utils::display::{self, AppendStyle};
 use super::CommandQueue;
 use super::{commands::Command, utils::term};
-#[cfg(feature = "search")]
+#[cfg(feature = "__search")]
 use crate::search;
-use crate::{error::MinusError, input::InputEvent, PagerState};
+use crate::{
+    error::{MinusError, TermError},
+    input::{command_line, macros::MacroAction, InputEvent},
+    screen, ExitReason, InterruptBehavior, PagerEvent, PagerState,
+};
 
 /// Respond based on the type of command
 ///
@@ -20,7 +24,7 @@ use crate::{error::MinusError, input::InputEvent, PagerState};
 /// - Mutating fields of [`PagerState`]
 /// - Handle cleanup and exits
 /// - Call search related functions
-#[cfg_attr(not(feature = "search"), allow(unused_mut))]
+#[cfg_attr(not(feature = "__search"), allow(unused_mut))]
 #[allow(clippy::too_many_lines)]
 pub fn handle_event(
     ev: Command,
@@ -28,67 +32,162 @@ pub fn handle_event(
     p: &mut PagerState,
     command_queue: &mut CommandQueue,
     is_exited: &Arc<AtomicBool>,
-    #[cfg(feature = "search")] user_input_active: &Arc<(Mutex<bool>, Condvar)>,
+    #[cfg(feature = "__search")] user_input_active: &Arc<(Mutex<bool>, Condvar)>,
 ) -> Result<(), MinusError> {
+    // Bytes that aren't shown as binary are counted once they are turned into text
+    #[cfg(feature = "metrics")]
+    if let Command::SetData(text) | Command::AppendData(text) = &ev {
+        p.metrics.bytes_received += text.len();
+    }
     match ev {
+        // Text from the application always goes to the first buffer, even when it isn't displayed
+        Command::SetData(text) if p.hidden_text_mut(0).is_some() => {
+            let text = p.process_streamed(text, true);
+            let hidden = p.hidden_view_mut(0).unwrap();
+            hidden.text = text;
+            hidden.styled_lines.clear();
+        }
+        // The lines are appended like any other text once they are marked
+        Command::AppendStyledLines(text) => {
+            p.mark_styled_lines(&text);
+            handle_event(
+                Command::AppendData(text),
+                out,
+                p,
+                command_queue,
+                is_exited,
+                #[cfg(feature = "__search")]
+                user_input_active,
+            )?;
+        }
+        Command::AppendData(text) if p.hidden_text_mut(0).is_some() => {
+            let text = p.process_streamed(text, false);
+            let overwrite = p.screen.carriage_return_overwrite;
+            let hidden = p.hidden_text_mut(0).unwrap();
+            // Same as what Screen::push_screen_buf does with the displayed text
+            let text = match text.strip_prefix('\r') {
+                Some(rest) if overwrite => {
+                    hidden.truncate(hidden.rfind('\n').map_or(0, |pos| pos + 1));
+                    rest
+                }
+                _ => &text,
+            };
+            hidden.push_str(text);
+        }
+        // Binary data can't be put into hidden buffers, add it as text instead
+        Command::AppendBytes(bytes) if p.hidden_text_mut(0).is_some() => {
+            command_queue.push_back_unchecked(Command::AppendData(
+                String::from_utf8_lossy(&bytes).into_owned(),
+            ));
+        }
+        Command::AppendBytes(bytes) => {
+            if p.push_binary(&bytes, false) {
+                #[cfg(feature = "metrics")]
+                {
+                    p.metrics.bytes_received += bytes.len();
+                }
+                if !p.running.lock().is_uninitialized() {
+                    p.format_prompt();
+                    display::draw_full(&mut out, p)?;
+                }
+            } else {
+                command_queue.push_back_unchecked(Command::AppendData(
+                    String::from_utf8_lossy(&bytes).into_owned(),
+                ));
+            }
+        }
         Command::SetData(text) => {
-            p.screen.orig_text = text;
-            p.format_lines();
-            p.screen.line_count = p.screen.orig_text.lines().count();
+            if !p.push_binary(text.as_bytes(), true) {
+                p.screen.orig_text = p.process_streamed(text, true);
+                p.screen.styled_lines.clear();
+                p.format_lines();
+                p.screen.line_count = p.screen.orig_text.lines().count();
+            }
             if !p.running.lock().is_uninitialized() {
                 display::draw_full(&mut out, p)?;
             }
         }
-        Command::UserInput(InputEvent::Exit) => {
+        Command::Quit if p.running.lock().is_uninitialized() => p.quit_requested = true,
+        // Leave the temporary view instead of quitting
+        Command::UserInput(InputEvent::Exit) if !p.view_stack.is_empty() => {
+            p.pop_view();
+            p.message = None;
+            p.format_prompt();
+            display::draw_full(&mut out, p)?;
+        }
+        Command::UserInput(InputEvent::Exit) | Command::Quit => {
+            if p.exit_reason.is_none() {
+                p.exit_reason = Some(if ev == Command::Quit {
+                    ExitReason::AppQuit
+                } else {
+                    ExitReason::UserQuit
+                });
+            }
+            p.emit_event(&PagerEvent::Exit);
             p.exit();
             is_exited.store(true, std::sync::atomic::Ordering::SeqCst);
-            term::cleanup(&mut out, &p.exit_strategy, true)?;
+            term::cleanup(p.backend.as_ref(), &p.term_modes, &p.exit_strategy, true)?;
         }
-        Command::UserInput(InputEvent::UpdateUpperMark(mut um)) => {
-            let line_count = p.screen.formatted_lines_count();
-            // Reduce one row for prompt/messages
-            let writable_rows = p.rows.saturating_sub(1);
-            // Calculate the lower_mark by adding either the rows or line_count depending
-            // on the minimality
-            let lower_mark = p.upper_mark.saturating_add(writable_rows.min(line_count));
-            // If the lower_bound is greater than the available line count, we set it to such a value
-            // so that the last page can be displayed entirely, i.e never scroll past the last line
-            if lower_mark > line_count {
-                p.upper_mark = line_count.saturating_sub(writable_rows);
+        Command::UserInput(InputEvent::UpdateUpperMark(_)) if p.is_term_too_small() => {}
+        Command::UserInput(InputEvent::UpdateUpperMark(um)) => {
+            let um = p.start_scroll_animation(um);
+            scroll_to(um, out, p, command_queue)?;
+        }
+        Command::AnimateScroll => {
+            if let Some(um) = p.next_scroll_frame() {
+                if !p.is_term_too_small() {
+                    scroll_to(um, out, p, command_queue)?;
+                }
             }
-
-            display::draw_for_change(out, p, &mut um)?;
-            p.upper_mark = um;
         }
         Command::UserInput(InputEvent::UpdateLeftMark(lm)) if !p.screen.line_wrapping => {
-            if lm.saturating_add(p.cols) > p.screen.get_max_line_length() && lm > p.left_mark {
+            // Don't scroll past the end of the longest line
+            let lm = lm.min(p.max_left_mark());
+            if lm == p.left_mark {
                 return Ok(());
             }
             p.left_mark = lm;
+            // Update the column indicator in the prompt
+            p.format_prompt();
             display::draw_full(out, p)?;
         }
         Command::UserInput(InputEvent::RestorePrompt) => {
-            // Set the message to None and new messages to false as all messages have been shown
+            // Move on to the next message, if any
+            p.message = p.pending_messages.pop_front();
+            command_queue.push_back_unchecked(Command::FormatRedrawPrompt);
+        }
+        Command::ClearMessages => {
             p.message = None;
+            p.pending_messages.clear();
             command_queue.push_back_unchecked(Command::FormatRedrawPrompt);
         }
         Command::UserInput(InputEvent::UpdateTermArea(c, r)) => {
+            // The size set by the application takes precedence over the one of the terminal
+            let (c, r) = p.term_size_override.unwrap_or((c, r));
             p.rows = r;
             p.cols = c;
-            // Readjust the text wrapping for the new number of columns
-            command_queue.push_back(Command::FormatRedrawDisplay);
+            p.emit_event(&PagerEvent::Resize { cols: c, rows: r });
+            if p.is_term_too_small() {
+                // Don't reformat the text for this size. The text gets reformatted when the
+                // terminal grows back hence the previous view is restored
+                display::draw_full(out, p)?;
+            } else {
+                // Readjust the text wrapping for the new number of columns
+                command_queue.push_back(Command::FormatRedrawDisplay);
+            }
         }
         Command::UserInput(InputEvent::UpdateLineNumber(l)) => {
             p.line_numbers = l;
             command_queue.push_back(Command::FormatRedrawDisplay);
         }
-        #[cfg(feature = "search")]
+        #[cfg(feature = "__search")]
         Command::UserInput(InputEvent::Search(m)) => {
             p.search_mode = m;
             p.search_state.search_mode = m;
             // Reset search mark so it won't be out of bounds if we have
             // less matches in this search than last time
             p.search_state.search_mark = 0;
+            p.emit_event(&PagerEvent::SearchStarted(m));
 
             // Pause the main user input thread, read search query and then restart the main input thread
             let (lock, cvar) = (&user_input_active.0, &user_input_active.1);
@@ -102,14 +201,35 @@ pub fn handle_event(
             drop(active);
             cvar.notify_one();
 
+            // The query was typed on the last row. If the prompt is hidden, that row has to be
+            // given back to the text
+            p.prompt_drawn = true;
             command_queue.push_back_unchecked(Command::FormatRedrawPrompt);
+            let (page_scoped, pattern) = search::split_scope(&search_result.string);
+            if !pattern.is_empty() {
+                p.search_state.page_range = page_scoped.then(|| p.input_context().visible_lines);
+                p.search_state.highlight = true;
+            }
             // If we have incremental search cache directly use it and return
             if let Some(incremental_search_result) = search_result.incremental_search_result {
+                let old_upper_mark = p.upper_mark;
                 p.search_state.search_term = search_result.compiled_regex;
                 p.upper_mark = incremental_search_result.upper_mark;
                 p.search_state.search_mark = incremental_search_result.search_mark;
                 p.search_state.search_idx = incremental_search_result.search_idx;
+                p.search_state.line_matches = incremental_search_result.line_matches;
+                p.search_state.line_matches.built_for =
+                    Some((pattern.to_string(), p.search_state.active_range()));
                 p.screen.formatted_lines = incremental_search_result.formatted_lines;
+                p.emit_event(&PagerEvent::SearchFinished {
+                    matches: p.search_state.search_idx.len(),
+                });
+                if p.search_state.search_idx.is_empty() {
+                    command_queue.push_back_unchecked(Command::Feedback);
+                }
+                if old_upper_mark != p.upper_mark {
+                    p.emit_event(&p.scroll_event(old_upper_mark));
+                }
                 return Ok(());
             }
 
@@ -117,51 +237,90 @@ pub fn handle_event(
             // string query if its not empty
             p.search_state.search_term = if search_result.compiled_regex.is_some() {
                 search_result.compiled_regex
-            } else if !search_result.string.is_empty() {
-                let compiled_regex = regex::Regex::new(&search_result.string).ok();
+            } else if !pattern.is_empty() {
+                let compiled_regex = crate::search::Regex::new(pattern);
+                #[cfg(feature = "tracing")]
+                if let Err(e) = &compiled_regex {
+                    tracing::debug!(error = %e, pattern, "invalid search pattern");
+                }
+                let compiled_regex = compiled_regex.ok();
                 if compiled_regex.is_none() {
                     command_queue.push_back_unchecked(Command::SendMessage(
                         "Invalid regular expression. Press Enter".to_string(),
                     ));
+                    p.emit_event(&PagerEvent::SearchFinished { matches: 0 });
                     return Ok(());
                 }
                 compiled_regex
             } else {
+                p.emit_event(&PagerEvent::SearchFinished { matches: 0 });
                 return Ok(());
             };
 
             // Format the lines, this will automatically generate the PagerState.search_idx
             p.format_lines();
+            if p.search_state.search_idx.is_empty() {
+                command_queue.push_back_unchecked(Command::Feedback);
+            }
+            p.emit_event(&PagerEvent::SearchFinished {
+                matches: p.search_state.search_idx.len(),
+            });
+        }
+        #[cfg(feature = "__search")]
+        Command::ClearSearch | Command::UserInput(InputEvent::ClearSearch) => {
+            if p.search_state.search_term.is_none() {
+                return Ok(());
+            }
+            p.search_state.search_term = None;
+            p.search_state.search_idx.clear();
+            p.search_state.search_mark = 0;
+            p.search_state.page_range = None;
+            p.search_state.highlight = true;
+            // Reformat the text without the highlights
+            command_queue.push_back_unchecked(Command::FormatRedrawDisplay);
+            command_queue.push_back_unchecked(Command::FormatRedrawPrompt);
+        }
+        #[cfg(feature = "__search")]
+        Command::UserInput(InputEvent::ToggleSearchHighlight)
+            if p.search_state.search_term.is_some() =>
+        {
+            p.search_state.highlight = !p.search_state.highlight;
+            command_queue.push_back_unchecked(Command::FormatRedrawDisplay);
         }
-        #[cfg(feature = "search")]
+        #[cfg(feature = "__search")]
         Command::UserInput(InputEvent::NextMatch | InputEvent::MoveToNextMatch(1))
             if p.search_state.search_term.is_some() =>
         {
             // Move to next search match after the current upper_mark
             let position_of_next_match =
-                search::next_nth_match(&p.search_state.search_idx, p.upper_mark, 1);
+                search::next_nth_match(&p.search_state.search_idx, search_anchor(p), 1);
             if let Some(pnm) = position_of_next_match {
                 p.search_state.search_mark = pnm;
-                let upper_mark = *p
-                    .search_state
-                    .search_idx
-                    .iter()
-                    .nth(p.search_state.search_mark)
-                    .unwrap();
+                let upper_mark = p.search_jump_target(
+                    *p.search_state
+                        .search_idx
+                        .iter()
+                        .nth(p.search_state.search_mark)
+                        .unwrap(),
+                );
                 command_queue.push_back_unchecked(Command::UserInput(InputEvent::UpdateUpperMark(
                     upper_mark,
                 )));
                 command_queue.push_back_unchecked(Command::FormatRedrawPrompt);
+            } else {
+                command_queue.push_back_unchecked(Command::Feedback);
             }
         }
-        #[cfg(feature = "search")]
+        #[cfg(feature = "__search")]
         Command::UserInput(InputEvent::PrevMatch | InputEvent::MoveToPrevMatch(1))
             if p.search_state.search_term.is_some() =>
         {
             // If no matches, return immediately
             if p.search_state.search_idx.is_empty() {
+                command_queue.push_back_unchecked(Command::Feedback);
                 return Ok(());
             }
+            let anchor = search_anchor(p);
             // Decrement the s_mark and get the preceding index
             p.search_state.search_mark = p.search_state.search_mark.saturating_sub(1);
             if let Some(y) = p
@@ -171,30 +330,31 @@ pub fn handle_event(
                 .nth(p.search_state.search_mark)
             {
                 // If the index is less than or equal to the upper_mark, then set y to the new upper_mark
-                if *y < p.upper_mark {
-                    p.upper_mark = *y;
+                if *y < anchor {
+                    let upper_mark = p.search_jump_target(*y);
                     command_queue.push_back_unchecked(Command::FormatRedrawPrompt);
                     command_queue.push_back_unchecked(Command::UserInput(
-                        InputEvent::UpdateUpperMark(p.upper_mark),
+                        InputEvent::UpdateUpperMark(upper_mark),
                     ));
                 }
             }
         }
-        #[cfg(feature = "search")]
+        #[cfg(feature = "__search")]
         Command::UserInput(InputEvent::MoveToNextMatch(n))
             if p.search_state.search_term.is_some() =>
         {
             // Move to next nth search match after the current upper_mark
             let position_of_next_match =
-                search::next_nth_match(&p.search_state.search_idx, p.upper_mark, n);
+                search::next_nth_match(&p.search_state.search_idx, search_anchor(p), n);
             if let Some(pnm) = position_of_next_match {
                 p.search_state.search_mark = pnm;
-                let upper_mark = *p
-                    .search_state
-                    .search_idx
-                    .iter()
-                    .nth(p.search_state.search_mark)
-                    .unwrap();
+                let upper_mark = p.search_jump_target(
+                    *p.search_state
+                        .search_idx
+                        .iter()
+                        .nth(p.search_state.search_mark)
+                        .unwrap(),
+                );
 
                 // Ensure there is enough text available after location corresponding to
                 // position_of_next_match so that we can display a pagefull of data. If not,
@@ -215,16 +375,20 @@ pub fn handle_event(
                     upper_mark,
                 )));
                 command_queue.push_back_unchecked(Command::FormatRedrawPrompt);
+            } else {
+                command_queue.push_back_unchecked(Command::Feedback);
             }
         }
-        #[cfg(feature = "search")]
+        #[cfg(feature = "__search")]
         Command::UserInput(InputEvent::MoveToPrevMatch(n))
             if p.search_state.search_term.is_some() =>
         {
             // If no matches, return immediately
             if p.search_state.search_idx.is_empty() {
+                command_queue.push_back_unchecked(Command::Feedback);
                 return Ok(());
             }
+            let anchor = search_anchor(p);
             // Decrement the s_mark and get the preceding index
             p.search_state.search_mark = p.search_state.search_mark.saturating_sub(n);
             if let Some(y) = p
@@ -234,8 +398,8 @@ pub fn handle_event(
                 .nth(p.search_state.search_mark)
             {
                 // If the index is less than or equal to the upper_mark, then set y to the new upper_mark
-                if *y < p.upper_mark {
-                    let upper_mark = *y;
+                if *y < anchor {
+                    let upper_mark = p.search_jump_target(*y);
                     command_queue.push_back_unchecked(Command::UserInput(
                         InputEvent::UpdateUpperMark(upper_mark),
                     ));
@@ -244,39 +408,145 @@ pub fn handle_event(
             }
         }
 
+        Command::UserInput(InputEvent::WaitForData(wait)) => {
+            p.waiting_for_data = wait;
+            if p.follow_output != wait {
+                p.emit_event(&PagerEvent::FollowToggled(wait));
+            }
+            p.follow_output = wait;
+            p.follow_paused = false;
+            if wait {
+                command_queue.push_back_unchecked(Command::UserInput(InputEvent::UpdateUpperMark(
+                    p.screen.formatted_lines_count(),
+                )));
+            }
+            command_queue.push_back_unchecked(Command::FormatRedrawPrompt);
+        }
+        Command::UserInput(InputEvent::Reload) => {
+            if p.reload() && !p.running.lock().is_uninitialized() {
+                display::draw_full(out, p)?;
+            }
+        }
+        Command::UserInput(InputEvent::Click(row, col)) => {
+            // Only bother looking for a link if someone is listening for it
+            if p.event_subscribers.is_empty() || row >= p.text_rows() {
+                return Ok(());
+            }
+            let col = if p.screen.line_wrapping {
+                col
+            } else {
+                col + p.left_mark
+            };
+            let row = p.upper_mark + row;
+            let uri = p
+                .display_rows(row, row + 1)
+                .first()
+                .and_then(|text| screen::hyperlink_at(text, col));
+            if let Some(uri) = uri {
+                p.emit_event(&PagerEvent::LinkClicked(uri));
+            }
+        }
         Command::UserInput(InputEvent::HorizontalScroll(val)) => {
-            p.screen.line_wrapping = val;
+            p.set_line_wrapping(val);
             command_queue.push_back_unchecked(Command::FormatRedrawDisplay);
         }
 
+        Command::RedrawDisplay => {
+            p.redraw_pending = false;
+            p.last_append_draw = Some(std::time::Instant::now());
+            if p.follow_output {
+                p.upper_mark = p.bottom_upper_mark();
+            }
+            if p.waiting_for_data {
+                p.wait_spinner = p.wait_spinner.wrapping_add(1);
+            }
+            p.format_prompt();
+            if !p.running.lock().is_uninitialized() {
+                display::draw_full(&mut out, p)?;
+            }
+        }
         Command::FormatRedrawDisplay => {
-            p.format_lines();
-            display::draw_full(&mut out, p)?;
+            p.reflow_lines();
+            if p.follow_output {
+                // The number of rows may have changed, for example after a resize, so move to the
+                // new end of the text
+                p.upper_mark = p.bottom_upper_mark();
+            }
+            if !p.running.lock().is_uninitialized() {
+                display::draw_full(&mut out, p)?;
+            }
+        }
+        Command::RestoreSession(session) => {
+            p.restore_session(session);
+            if !p.running.lock().is_uninitialized() {
+                display::draw_full(&mut out, p)?;
+            }
+        }
+        Command::ScrollToLine(line) | Command::UserInput(InputEvent::GotoLine(line)) => {
+            // Line numbers start from 1 while the line indices start from 0. If the line does not
+            // exist, go to the bottom of the text
+            let row = p
+                .screen
+                .row_of_line(line.saturating_sub(1))
+                .map_or(usize::MAX - 1, |row| p.jump_target(row));
+            if p.running.lock().is_uninitialized() {
+                p.upper_mark = row;
+            } else {
+                command_queue
+                    .push_back_unchecked(Command::UserInput(InputEvent::UpdateUpperMark(row)));
+            }
         }
         Command::AppendData(text) => {
+            if p.push_binary(text.as_bytes(), false) {
+                if !p.running.lock().is_uninitialized() {
+                    p.format_prompt();
+                    display::draw_full(out, p)?;
+                }
+                return Ok(());
+            }
             let prev_unterminated = p.screen.unterminated;
             let prev_fmt_lines_count = p.screen.formatted_lines_count();
-            let is_running = !p.running.lock().is_uninitialized();
-            let rows = p.rows;
+            let is_running = !p.running.lock().is_uninitialized() && !p.is_term_too_small();
+            let text_rows = p.text_rows();
+            // The filler on the rows after the text needs to be cleared
+            let covers_filler = !p.eob_filler.is_empty() && prev_fmt_lines_count < text_rows;
+            // Too many appends in a short time are drawn together later
+            let should_draw = is_running && p.should_draw_append();
+            let held_back = p.redraw_pending;
+            let text = p.process_streamed(text, false);
             let append_style = p.append_str(text.as_str());
 
-            if is_running {
-                if append_style == AppendStyle::FullRedraw {
+            if should_draw {
+                if held_back {
+                    // Draw the data held back so far along with this one
+                    command_queue.push_back_unchecked(Command::RedrawDisplay);
+                    return Ok(());
+                }
+                if append_style == AppendStyle::FullRedraw || covers_filler {
                     return display::draw_full(out, p);
                 }
                 display::draw_append_text(
                     out,
-                    rows,
+                    text_rows,
                     prev_unterminated,
                     prev_fmt_lines_count,
                     &append_style,
                 )?;
+                // Appended text is only drawn while it fills up the screen
+                #[cfg(feature = "metrics")]
+                if prev_fmt_lines_count < text_rows {
+                    p.metrics.partial_redraws += 1;
+                }
 
                 if p.follow_output {
                     command_queue.push_back_unchecked(Command::UserInput(
                         InputEvent::UpdateUpperMark(p.screen.formatted_lines_count()),
                     ));
                 }
+                if p.waiting_for_data {
+                    p.wait_spinner = p.wait_spinner.wrapping_add(1);
+                    command_queue.push_back_unchecked(Command::FormatRedrawPrompt);
+                }
             }
         }
 
@@ -284,63 +554,547 @@ pub fn handle_event(
             if let Command::SetPrompt(_) = ev {
                 p.prompt = text.to_string();
             } else {
-                p.message = Some(text.to_string());
+                p.push_message(text.to_string());
             }
             p.format_prompt();
             if !p.running.lock().is_uninitialized() {
-                display::write_prompt(out, &p.displayed_prompt, p.rows.try_into().unwrap())?;
+                display::draw_prompt(out, p)?;
             }
         }
+        Command::SetPromptRenderer(renderer) => {
+            p.prompt_renderer = Some(renderer);
+            command_queue.push_back_unchecked(Command::FormatRedrawPrompt);
+        }
+        Command::SetEobFiller(filler) => {
+            p.eob_filler = filler;
+            if !p.running.lock().is_uninitialized() {
+                display::draw_full(out, p)?;
+            }
+        }
+        Command::SetEofMarker(marker) => {
+            p.eof_marker = marker;
+            command_queue.push_back_unchecked(Command::FormatRedrawPrompt);
+        }
         Command::SetLineNumbers(ln) => {
             p.line_numbers = ln;
-            p.format_lines();
+            p.reflow_lines();
             if !p.running.lock().is_uninitialized() {
-                display::write_prompt(out, &p.displayed_prompt, p.rows.try_into().unwrap())?;
+                display::draw_prompt(out, p)?;
             }
         }
         Command::FormatRedrawPrompt => {
             p.format_prompt();
-            display::write_prompt(out, &p.displayed_prompt, p.rows.try_into().unwrap())?;
+            display::draw_prompt(out, p)?;
+        }
+        Command::ApplyConfig(config) => {
+            if p.apply_config(*config) {
+                command_queue.push_back_unchecked(Command::FormatRedrawDisplay);
+            }
         }
         Command::SetExitStrategy(es) => p.exit_strategy = es,
+        Command::SetInterruptBehavior(ib) => p.interrupt_behavior = ib,
+        #[cfg(feature = "__search")]
+        Command::SetJumpPattern(regex) => p.jump_pattern = regex,
+        #[cfg(feature = "__search")]
+        Command::SetMatchPlacement(placement) => p.search_state.match_placement = placement,
+        #[cfg(feature = "__search")]
+        Command::SetIncrementalSearchDebounce(interval) => {
+            p.search_state.incremental_search_debounce = interval;
+        }
+        #[cfg(feature = "__regex")]
+        Command::AddHighlightRule(regex, style) => {
+            p.screen
+                .highlight_rules
+                .push(crate::screen::HighlightRule { regex, style });
+            command_queue.push_back_unchecked(Command::FormatRedrawDisplay);
+        }
+        #[cfg(feature = "__search")]
+        Command::SetHighlightMode(mode) => {
+            p.search_state.highlight_mode = mode;
+            if p.search_state.search_term.is_some() {
+                command_queue.push_back_unchecked(Command::FormatRedrawDisplay);
+            }
+        }
+        #[cfg(feature = "__search")]
+        Command::SetMultilineSearch(multiline) => {
+            p.search_state.multiline = multiline;
+            // The matches of the active search need to be found again
+            p.search_state.line_matches.built_for = None;
+            if p.search_state.search_term.is_some() {
+                command_queue.push_back_unchecked(Command::FormatRedrawDisplay);
+            }
+        }
+        #[cfg(feature = "__search")]
+        Command::SetSearchRange(range) => {
+            p.search_state.search_range = range;
+            // Update the matches of the active search
+            if p.search_state.search_term.is_some() {
+                command_queue.push_back_unchecked(Command::FormatRedrawDisplay);
+            }
+        }
+        #[cfg(feature = "__search")]
+        Command::UserInput(InputEvent::JumpToPattern { regex, direction }) => {
+            let compiled = p
+                .jump_regexes
+                .entry(regex)
+                .or_insert_with(|| crate::search::Regex::new(regex).ok())
+                .clone();
+            let row = compiled
+                .ok_or_else(|| format!("Invalid pattern: {regex}"))
+                .and_then(|re| {
+                    p.find_pattern(&re, direction)
+                        .ok_or_else(|| "Pattern not found".to_string())
+                });
+            jump_to_pattern_match(row, p, command_queue);
+        }
+        #[cfg(feature = "__search")]
+        Command::UserInput(InputEvent::JumpToSetPattern(direction)) => {
+            if let Some(regex) = &p.jump_pattern {
+                let row = p
+                    .find_pattern(regex, direction)
+                    .ok_or_else(|| "Pattern not found".to_string());
+                jump_to_pattern_match(row, p, command_queue);
+            }
+        }
+        Command::AddBuffer(name, text) => {
+            let text = p.process_incoming(text);
+            if p.add_buffer(name, text) && !p.running.lock().is_uninitialized() {
+                display::draw_full(&mut out, p)?;
+            }
+        }
+        Command::SwitchToBuffer(name) => {
+            if let Some(idx) = p.buffers.iter().position(|b| b.name == name) {
+                if p.switch_buffer(idx) && !p.running.lock().is_uninitialized() {
+                    display::draw_full(&mut out, p)?;
+                }
+            }
+        }
+        Command::UserInput(InputEvent::SwitchBuffer(idx)) => {
+            if p.switch_buffer(idx) {
+                p.push_message(p.buffer_list().join("  "));
+                p.format_prompt();
+                display::draw_full(&mut out, p)?;
+            }
+        }
+        Command::UserInput(InputEvent::CenterRow(row)) => {
+            let upper_mark = row.saturating_sub(p.text_rows() / 2);
+            command_queue
+                .push_back_unchecked(Command::UserInput(InputEvent::UpdateUpperMark(upper_mark)));
+        }
+        Command::UserInput(InputEvent::EnterCommand) => {
+            command_queue.push_back_unchecked(Command::FormatRedrawPrompt);
+        }
+        Command::UserInput(InputEvent::ListBuffers) => {
+            let mut list = p.buffer_list().join("\n");
+            list.push('\n');
+            p.push_view(list);
+            p.push_message("Buffers (press q to go back)".to_string());
+            p.format_prompt();
+            display::draw_full(&mut out, p)?;
+        }
+        Command::UserInput(InputEvent::Interrupt) => match &p.interrupt_behavior {
+            InterruptBehavior::Quit => {
+                command_queue.push_back_unchecked(Command::UserInput(InputEvent::Exit));
+            }
+            InterruptBehavior::Callback(cb) => cb(),
+            InterruptBehavior::Ignore => {}
+        },
         Command::LineWrapping(lw) => {
-            p.screen.line_wrapping = lw;
-            p.format_lines();
+            p.set_line_wrapping(lw);
+            p.reflow_lines();
         }
         #[cfg(feature = "static_output")]
-        Command::SetRunNoOverflow(val) => p.run_no_overflow = val,
-        #[cfg(feature = "search")]
+        Command::SetForcePager(val) => p.force_pager = val,
+        #[cfg(feature = "static_output")]
+        Command::SetPagerThreshold(rows) => p.pager_threshold = rows,
+        #[cfg(feature = "__search")]
         Command::IncrementalSearchCondition(cb) => p.search_state.incremental_search_condition = cb,
         Command::SetInputClassifier(clf) => p.input_classifier = clf,
         Command::AddExitCallback(cb) => p.exit_callbacks.push(cb),
-        Command::ShowPrompt(show) => p.show_prompt = show,
+        Command::SetScrollMargin(margin) => p.scroll_margin = margin,
+        Command::SetPageOverlap(overlap) => p.scroll_amounts.page_overlap = overlap,
+        Command::SetScrollAmounts(amounts) => p.scroll_amounts = amounts,
+        Command::SetCenterOnJump(center) => p.center_on_jump = center,
+        Command::SetQuitOnEof(quit) => p.quit_on_eof = quit,
+        Command::SetReloadCallback(cb) => p.reload_callback = Some(cb),
+        Command::AddEventSubscriber(tx) => p.event_subscribers.push(tx),
+        Command::SetDataProvider(provider) => {
+            p.data_provider = Some(provider);
+            if !p.running.lock().is_uninitialized() {
+                if let Some(text) = p.request_data() {
+                    command_queue.push_back_unchecked(Command::AppendData(text));
+                }
+            }
+        }
+        Command::SetAnsiMode(mode) => p.ansi_mode = mode,
+        Command::SetCarriageReturnOverwrite(enabled) => {
+            p.screen.carriage_return_overwrite = enabled;
+        }
+        Command::SetMacros(enabled) => p.macros.enabled = enabled,
+        // The keys are handled one after the other as if the user typed them, so that each of
+        // them sees the changes made by the previous ones. The keys are not repeated up front as
+        // the count comes straight from the user
+        Command::PlayMacro(events, count) => {
+            p.macros.replaying = true;
+            let mut res = Ok(());
+            'replay: for ev in (0..count).flat_map(|_| events.iter()) {
+                let commands = commands_for_event(
+                    ev.clone(),
+                    p,
+                    #[cfg(feature = "__search")]
+                    user_input_active,
+                );
+                for command in commands {
+                    res = handle_event(
+                        command,
+                        out,
+                        p,
+                        command_queue,
+                        is_exited,
+                        #[cfg(feature = "__search")]
+                        user_input_active,
+                    );
+                    if res.is_err() || is_exited.load(std::sync::atomic::Ordering::SeqCst) {
+                        break 'replay;
+                    }
+                }
+            }
+            p.macros.replaying = false;
+            res?;
+        }
+        // The screen gets redrawn by the reactor once the output starts being filtered
+        Command::SetPlainRender(plain) => p.plain_render = plain,
+        Command::SetWindowTitle(title) => p.window_title = title,
+        Command::SetBinaryMode(mode) => p.binary_mode = mode,
+        Command::SetFeedback(mode) => p.feedback = mode,
+        Command::Feedback => display::write_feedback(out, p)?,
+        Command::SetKeyboardEnhancement(flags) => p.keyboard_enhancement = flags,
+        Command::SetAlternateScroll(enabled) => p.alternate_scroll = enabled,
+        // Switching the terminal while running would leave the old one in a broken state
+        Command::SetBackend(backend) if p.running.lock().is_uninitialized() => {
+            if backend.is_tty() && p.term_size_override.is_none() {
+                let (cols, rows) = backend.size().map_err(TermError::from)?;
+                p.cols = cols.into();
+                p.rows = rows.into();
+                p.reflow_lines();
+            }
+            p.backend = backend;
+        }
+        Command::SetBackend(_) => {}
+        Command::SetRegion(region) => {
+            p.region = region;
+            // The text is laid out for the size of the region
+            let size = region.map(|region| (region.cols.into(), region.rows.into()));
+            handle_event(
+                Command::SetTermSize(size),
+                out,
+                p,
+                command_queue,
+                is_exited,
+                #[cfg(feature = "__search")]
+                user_input_active,
+            )?;
+        }
+        Command::SetTermSize(size) => {
+            p.term_size_override = size;
+            let size = match size {
+                Some(size) => Some(size),
+                // Go back to the size of the terminal
+                None if p.backend.is_tty() => {
+                    let (cols, rows) = p.backend.size().map_err(TermError::from)?;
+                    Some((cols.into(), rows.into()))
+                }
+                None => None,
+            };
+            if let Some((cols, rows)) = size {
+                p.cols = cols;
+                p.rows = rows;
+                if p.running.lock().is_uninitialized() {
+                    p.reflow_lines();
+                } else {
+                    command_queue.push_back_unchecked(Command::UserInput(
+                        InputEvent::UpdateTermArea(cols, rows),
+                    ));
+                }
+            }
+        }
+        Command::SetLineTransformer(transformer) => {
+            p.screen.line_transformer = Some(transformer);
+            p.format_lines();
+            command_queue.push_back_unchecked(Command::FormatRedrawDisplay);
+        }
+        Command::SetLineNumberFormat(format) => {
+            p.screen.line_number_format = Some(format);
+            command_queue.push_back_unchecked(Command::FormatRedrawDisplay);
+        }
+        Command::SetMinLineNumberDigits(digits) => {
+            p.screen.min_line_number_digits = digits;
+            if p.line_numbers.is_on() {
+                command_queue.push_back_unchecked(Command::FormatRedrawDisplay);
+            }
+        }
+        Command::SetTheme(theme) => {
+            p.screen.theme = theme;
+            if p.line_numbers.is_on() {
+                command_queue.push_back_unchecked(Command::FormatRedrawDisplay);
+            }
+        }
+        Command::SetLineLengthLimit(limit) => {
+            p.screen.line_length_limit = limit;
+            p.format_lines();
+            command_queue.push_back_unchecked(Command::FormatRedrawDisplay);
+        }
+        Command::SetWrapCache(lines) => p.screen.wrap_cache.set_limit(lines),
+        Command::SetReloadInterval(interval) => {
+            p.reload_interval = interval;
+            p.last_reload = std::time::Instant::now();
+        }
+        Command::SetMaxRefreshRate(interval) => p.refresh_interval = interval,
+        Command::SetPollTimeout(timeout) => p.poll_timeout = timeout,
+        Command::SetIdleCallback(cb) => {
+            p.idle_callback = Some(cb);
+            p.last_activity = std::time::Instant::now();
+        }
+        Command::Idle => {
+            if let Some(cb) = p.idle_callback.as_mut() {
+                cb();
+            }
+            p.last_activity = std::time::Instant::now();
+        }
+        Command::SetCancellationFlag(flag) => p.cancellation_flag = Some(flag),
+        Command::ShowPrompt(show) | Command::UserInput(InputEvent::ShowPrompt(show)) => {
+            if p.show_prompt != show {
+                p.show_prompt = show;
+                // The number of rows available for the text changed
+                command_queue.push_back_unchecked(Command::RedrawDisplay);
+            }
+        }
         Command::FollowOutput(follow_output)
         | Command::UserInput(InputEvent::FollowOutput(follow_output)) => {
+            if p.follow_output != follow_output {
+                p.emit_event(&PagerEvent::FollowToggled(follow_output));
+            }
             p.follow_output = follow_output;
+            p.follow_paused = false;
             command_queue.push_back(Command::UserInput(InputEvent::UpdateUpperMark(
                 p.screen.formatted_lines_count(),
             )));
             command_queue.push_back(Command::FormatRedrawPrompt);
         }
+        // Echo the count typed so far
+        Command::UserInput(InputEvent::Number(_)) => {
+            command_queue.push_back_unchecked(Command::FormatRedrawPrompt);
+        }
         Command::UserInput(_) => {}
     }
     Ok(())
 }
 
+/// Move the view so that the row `um` is at the top of the screen
+fn scroll_to(
+    mut um: usize,
+    out: &mut impl Write,
+    p: &mut PagerState,
+    command_queue: &mut CommandQueue,
+) -> Result<(), MinusError> {
+    let moving_down = um > p.upper_mark;
+    let line_count = p.screen.formatted_lines_count();
+    // Leave out the row of the prompt/messages
+    let writable_rows = p.text_rows();
+    // Calculate the lower_mark by adding either the rows or line_count depending
+    // on the minimality
+    let lower_mark = p.upper_mark.saturating_add(writable_rows.min(line_count));
+    // If the lower_bound is greater than the available line count, we set it to such a value
+    // so that the last page can be displayed entirely, i.e never scroll past the last line
+    if lower_mark > line_count {
+        p.upper_mark = line_count.saturating_sub(writable_rows);
+    }
+
+    let old_upper_mark = p.upper_mark;
+    let end_was_visible = p.is_end_visible();
+    display::draw_for_change(out, p, &mut um)?;
+    p.upper_mark = um;
+    if old_upper_mark != um {
+        p.emit_event(&p.scroll_event(old_upper_mark));
+    }
+    // Show or hide the end of text marker
+    if p.eof_marker.is_some() && p.is_end_visible() != end_was_visible {
+        command_queue.push_back_unchecked(Command::FormatRedrawPrompt);
+    }
+    if let Some(text) = p.request_data() {
+        command_queue.push_back_unchecked(Command::AppendData(text));
+    }
+
+    let at_bottom = p.upper_mark.saturating_add(writable_rows) >= line_count;
+
+    // Quit if the user moves to the end of the text and there is no more data to pull
+    if p.quit_on_eof
+        && moving_down
+        && at_bottom
+        && !p.follow_output
+        && !p.follow_paused
+        && p.data_provider.is_none()
+    {
+        p.exit_reason = Some(ExitReason::Eof);
+        command_queue.push_back_unchecked(Command::UserInput(InputEvent::Exit));
+    }
+
+    // Pause follow mode when the user scrolls away from the bottom and resume it once
+    // they get back there
+    if p.follow_output != at_bottom && (p.follow_output || p.follow_paused) {
+        p.follow_output = at_bottom;
+        p.follow_paused = !at_bottom;
+        p.emit_event(&PagerEvent::FollowToggled(at_bottom));
+        command_queue.push_back_unchecked(Command::FormatRedrawPrompt);
+    }
+    Ok(())
+}
+
+/// Move to the line `row` found by a jump to a pattern, or tell the user why there is none
+#[cfg(feature = "__search")]
+fn jump_to_pattern_match(
+    row: Result<usize, String>,
+    p: &mut PagerState,
+    command_queue: &mut CommandQueue,
+) {
+    match row {
+        Ok(row) => command_queue.push_back_unchecked(Command::UserInput(
+            InputEvent::UpdateUpperMark(p.jump_target(row)),
+        )),
+        Err(message) => {
+            p.push_message(message);
+            command_queue.push_back_unchecked(Command::FormatRedrawPrompt);
+            command_queue.push_back_unchecked(Command::Feedback);
+        }
+    }
+}
+
+/// Row from which the next or previous search match is looked up
+///
+/// If the view hasn't moved since the last jump to a search match, this is the row of that match.
+/// Otherwise it is the row that lies [`jump_offset`](PagerState::jump_offset) rows below the top
+/// of the screen.
+#[cfg(feature = "__search")]
+fn search_anchor(p: &PagerState) -> usize {
+    let current_match = p
+        .search_state
+        .search_idx
+        .iter()
+        .nth(p.search_state.search_mark)
+        .copied();
+    match current_match {
+        Some(row) if p.search_jump_target(row) == p.upper_mark => row,
+        _ => p.upper_mark.saturating_add(p.jump_offset()),
+    }
+}
+
+/// Commands to be sent for an event read from the terminal
+pub fn commands_for_event(
+    ev: Event,
+    ps: &mut PagerState,
+    #[cfg(feature = "__search")] user_input_active: &Arc<(Mutex<bool>, Condvar)>,
+) -> Vec<Command> {
+    // Mouse events are reported relative to the entire terminal
+    let ev = match (ev, ps.region) {
+        (Event::Mouse(mut mouse), Some(region)) => {
+            if !region.contains(mouse.column, mouse.row) {
+                return Vec::new();
+            }
+            mouse.column -= region.x;
+            mouse.row -= region.y;
+            Event::Mouse(mouse)
+        }
+        (ev, _) => ev,
+    };
+    // Keys that record and replay macros. Keys typed into the command line or pressed in a
+    // temporary view are always taken literally
+    let typing = ps.command_line.is_some() || !ps.view_stack.is_empty();
+    match ps.macros.handle_event(&ev, typing) {
+        MacroAction::Pass => {}
+        MacroAction::Consumed => return vec![Command::FormatRedrawPrompt],
+        MacroAction::Play(events) => {
+            let count = ps.prefix_num.parse::<usize>().unwrap_or(1);
+            ps.prefix_num.clear();
+            return vec![
+                Command::FormatRedrawPrompt,
+                Command::PlayMacro(events, count),
+            ];
+        }
+    }
+    // Keys typed after a `:` make up a command rather than being bound to actions
+    if ps.command_line.is_some() && !matches!(ev, Event::Resize(..)) {
+        let command = command_line::handle_key(&ev, ps)
+            .map_or(Command::FormatRedrawPrompt, Command::UserInput);
+        return vec![command];
+    }
+    if ps.edit_prefix_num(&ev) {
+        return vec![Command::FormatRedrawPrompt];
+    }
+    let is_key = matches!(ev, Event::Key(_));
+    // Get the events
+    let input = if ps.waiting_for_data && !matches!(ev, Event::Resize(..)) {
+        // While waiting for data, any key press takes the user back to normal browsing
+        is_key.then_some(InputEvent::WaitForData(false))
+    } else {
+        ps.input_classifier.classify_input(ev, ps)
+    };
+    let mut commands = Vec::with_capacity(2);
+    if let Some(iev) = input {
+        // Stop reading input right away as the search prompt reads the query by itself.
+        // Otherwise we might read the keys typed ahead for the query as normal input.
+        #[cfg(feature = "__search")]
+        if let InputEvent::Search(_) = iev {
+            *user_input_active.0.lock() = false;
+        }
+        if iev == InputEvent::EnterCommand {
+            ps.command_line = Some(String::new());
+        }
+        if matches!(iev, InputEvent::UpdateUpperMark(um) if ps.is_stuck_scroll(um)) {
+            // Scrolling past the top or the bottom of the text
+            commands.push(Command::Feedback);
+        }
+        if let InputEvent::Number(n) = iev {
+            ps.prefix_num.push(n);
+            ps.format_prompt();
+        } else if !ps.prefix_num.is_empty() {
+            ps.prefix_num.clear();
+            ps.format_prompt();
+        }
+        commands.push(Command::UserInput(iev));
+    } else {
+        if !ps.prefix_num.is_empty() {
+            ps.prefix_num.clear();
+            ps.format_prompt();
+        }
+        // Keys that aren't bound to anything
+        if is_key {
+            commands.push(Command::Feedback);
+        }
+    }
+    commands
+}
+
 #[cfg(test)]
 mod tests {
     use super::super::commands::Command;
     use super::handle_event;
-    use crate::{minus_core::CommandQueue, ExitStrategy, PagerState, RunMode};
-    use std::sync::{atomic::AtomicBool, Arc};
-    #[cfg(feature = "search")]
+    use crate::{
+        input::InputEvent, minus_core::CommandQueue, AnsiMode, BinaryMode, ExitReason,
+        ExitStrategy, FeedbackMode, InterruptBehavior, LineNumbers, PagerConfig, PagerEvent,
+        PagerState, RunMode,
+    };
+    use std::{
+        sync::{atomic::AtomicBool, Arc},
+        time::Duration,
+    };
+    #[cfg(feature = "__search")]
     use {
         once_cell::sync::Lazy,
         parking_lot::{Condvar, Mutex},
     };
 
     // Tests constants
-    #[cfg(feature = "search")]
+    #[cfg(feature = "__search")]
     static UIA: Lazy<Arc<(Mutex<bool>, Condvar)>> =
         Lazy::new(|| Arc::new((Mutex::new(true), Condvar::new())));
     const TEST_STR: &str = "This is some sample text";
@@ -368,7 +1122,7 @@ mod tests {
             &mut ps,
             &mut command_queue,
             &Arc::new(AtomicBool::new(false)),
-            #[cfg(feature = "search")]
+            #[cfg(feature = "__search")]
             &UIA,
         )
         .unwrap();
@@ -389,7 +1143,7 @@ mod tests {
             &mut ps,
             &mut command_queue,
             &Arc::new(AtomicBool::new(false)),
-            #[cfg(feature = "search")]
+            #[cfg(feature = "__search")]
             &UIA,
         )
         .unwrap();
@@ -399,7 +1153,7 @@ mod tests {
             &mut ps,
             &mut command_queue,
             &Arc::new(AtomicBool::new(false)),
-            #[cfg(feature = "search")]
+            #[cfg(feature = "__search")]
             &UIA,
         )
         .unwrap();
@@ -431,7 +1185,7 @@ mod tests {
             &mut ps,
             &mut command_queue,
             &Arc::new(AtomicBool::new(false)),
-            #[cfg(feature = "search")]
+            #[cfg(feature = "__search")]
             &UIA,
         )
         .unwrap();
@@ -460,7 +1214,7 @@ mod tests {
             &mut ps,
             &mut command_queue,
             &Arc::new(AtomicBool::new(false)),
-            #[cfg(feature = "search")]
+            #[cfg(feature = "__search")]
             &UIA,
         )
         .unwrap();
@@ -469,9 +1223,9 @@ mod tests {
 
     #[test]
     #[cfg(feature = "static_output")]
-    fn set_run_no_overflow() {
+    fn set_force_pager() {
         let mut ps = PagerState::new().unwrap();
-        let ev = Command::SetRunNoOverflow(false);
+        let ev = Command::SetForcePager(false);
         let mut out = Vec::new();
         let mut command_queue = CommandQueue::new_zero();
 
@@ -481,17 +1235,18 @@ mod tests {
             &mut ps,
             &mut command_queue,
             &Arc::new(AtomicBool::new(false)),
-            #[cfg(feature = "search")]
+            #[cfg(feature = "__search")]
             &UIA,
         )
         .unwrap();
-        assert!(!ps.run_no_overflow);
+        assert!(!ps.force_pager);
     }
 
     #[test]
-    fn set_exit_strategy() {
+    #[cfg(feature = "static_output")]
+    fn set_pager_threshold() {
         let mut ps = PagerState::new().unwrap();
-        let ev = Command::SetExitStrategy(ExitStrategy::PagerQuit);
+        let ev = Command::SetPagerThreshold(Some(10));
         let mut out = Vec::new();
         let mut command_queue = CommandQueue::new_zero();
 
@@ -501,17 +1256,17 @@ mod tests {
             &mut ps,
             &mut command_queue,
             &Arc::new(AtomicBool::new(false)),
-            #[cfg(feature = "search")]
+            #[cfg(feature = "__search")]
             &UIA,
         )
         .unwrap();
-        assert_eq!(ps.exit_strategy, ExitStrategy::PagerQuit);
+        assert_eq!(ps.pager_threshold, Some(10));
     }
 
     #[test]
-    fn add_exit_callback() {
+    fn set_exit_strategy() {
         let mut ps = PagerState::new().unwrap();
-        let ev = Command::AddExitCallback(Box::new(|| println!("Hello World")));
+        let ev = Command::SetExitStrategy(ExitStrategy::PagerQuit);
         let mut out = Vec::new();
         let mut command_queue = CommandQueue::new_zero();
 
@@ -521,10 +1276,1601 @@ mod tests {
             &mut ps,
             &mut command_queue,
             &Arc::new(AtomicBool::new(false)),
-            #[cfg(feature = "search")]
+            #[cfg(feature = "__search")]
             &UIA,
         )
         .unwrap();
-        assert_eq!(ps.exit_callbacks.len(), 1);
+        assert_eq!(ps.exit_strategy, ExitStrategy::PagerQuit);
+    }
+
+    #[test]
+    fn left_mark_is_clamped() {
+        let mut ps = PagerState::new().unwrap();
+        let mut out = Vec::new();
+        let mut command_queue = CommandQueue::new_zero();
+        let mut handle = |ev, ps: &mut PagerState| {
+            handle_event(
+                ev,
+                &mut out,
+                ps,
+                &mut command_queue,
+                &Arc::new(AtomicBool::new(false)),
+                #[cfg(feature = "__search")]
+                &UIA,
+            )
+            .unwrap();
+        };
+        ps.screen.orig_text = format!("{}\nshort\n", "A".repeat(200));
+        ps.set_line_wrapping(false);
+        ps.format_lines();
+
+        // The end of the longest line stays at the right edge of the screen
+        handle(Command::UserInput(InputEvent::UpdateLeftMark(500)), &mut ps);
+        assert_eq!(ps.left_mark, 120);
+
+        // The text got shorter
+        ps.screen.orig_text = format!("{}\n", "A".repeat(100));
+        ps.format_lines();
+        assert_eq!(ps.left_mark, 20);
+
+        // Turning wrapping back on resets it
+        handle(Command::LineWrapping(true), &mut ps);
+        assert_eq!(ps.left_mark, 0);
+    }
+
+    #[test]
+    fn buffers() {
+        let mut ps = PagerState::new().unwrap();
+        let mut out = Vec::new();
+        let mut command_queue = CommandQueue::new_zero();
+        let mut handle = |ev, ps: &mut PagerState| {
+            handle_event(
+                ev,
+                &mut out,
+                ps,
+                &mut command_queue,
+                &Arc::new(AtomicBool::new(false)),
+                #[cfg(feature = "__search")]
+                &UIA,
+            )
+            .unwrap();
+        };
+
+        // The first buffer added to an empty pager replaces the unnamed one
+        let text = "A\n".repeat(100);
+        handle(Command::AddBuffer("a".to_string(), text.clone()), &mut ps);
+        assert_eq!(ps.buffers.len(), 1);
+        assert_eq!(ps.screen.orig_text, text);
+        handle(
+            Command::AddBuffer("b".to_string(), "B\n".to_string()),
+            &mut ps,
+        );
+        assert_eq!(ps.buffers.len(), 2);
+        assert_eq!(ps.screen.orig_text, text);
+
+        ps.upper_mark = 1;
+        handle(Command::SwitchToBuffer("b".to_string()), &mut ps);
+        assert_eq!(ps.active_buffer, 1);
+        assert_eq!(ps.screen.orig_text, "B\n");
+        assert_eq!(ps.upper_mark, 0);
+
+        // Text from the application goes to the first buffer
+        handle(Command::AppendData("more\n".to_string()), &mut ps);
+        assert_eq!(ps.screen.orig_text, "B\n");
+
+        handle(Command::UserInput(InputEvent::SwitchBuffer(0)), &mut ps);
+        assert_eq!(ps.screen.orig_text, text + "more\n");
+        assert_eq!(ps.upper_mark, 1);
+        assert_eq!(ps.message.as_deref(), Some("*1:a  2:b"));
+    }
+
+    #[test]
+    fn temporary_view() {
+        let mut ps = PagerState::new().unwrap();
+        let mut out = Vec::new();
+        let mut command_queue = CommandQueue::new_zero();
+        let mut handle = |ev, ps: &mut PagerState| {
+            handle_event(
+                ev,
+                &mut out,
+                ps,
+                &mut command_queue,
+                &Arc::new(AtomicBool::new(false)),
+                #[cfg(feature = "__search")]
+                &UIA,
+            )
+            .unwrap();
+        };
+
+        let text = "A\n".repeat(100);
+        handle(Command::AddBuffer("a".to_string(), text.clone()), &mut ps);
+        ps.upper_mark = 10;
+        #[cfg(feature = "__search")]
+        {
+            ps.search_state.search_term = Some(crate::search::Regex::new("A").unwrap());
+            ps.format_lines();
+        }
+
+        handle(Command::UserInput(InputEvent::ListBuffers), &mut ps);
+        assert_eq!(ps.screen.orig_text, "*1:a\n");
+        assert_eq!(ps.upper_mark, 0);
+        #[cfg(feature = "__search")]
+        assert!(ps.search_state.search_idx.is_empty());
+
+        // Text is added to the view underneath
+        handle(Command::AppendData("more\n".to_string()), &mut ps);
+        assert_eq!(ps.screen.orig_text, "*1:a\n");
+
+        // Quitting closes the temporary view and restores the one underneath
+        handle(Command::UserInput(InputEvent::Exit), &mut ps);
+        assert!(ps.view_stack.is_empty());
+        assert_eq!(ps.screen.orig_text, text + "more\n");
+        assert_eq!(ps.upper_mark, 10);
+        #[cfg(feature = "__search")]
+        assert_eq!(ps.search_state.search_idx.len(), 100);
+    }
+
+    #[test]
+    #[cfg(feature = "__search")]
+    fn jump_to_pattern() {
+        use crate::SearchMode;
+
+        let mut ps = PagerState::new().unwrap();
+        let mut out = Vec::new();
+        let mut command_queue = CommandQueue::new_zero();
+        let mut jump = |regex, direction, ps: &mut PagerState| {
+            handle_event(
+                Command::UserInput(InputEvent::JumpToPattern { regex, direction }),
+                &mut out,
+                ps,
+                &mut command_queue,
+                &Arc::new(AtomicBool::new(false)),
+                &UIA,
+            )
+            .unwrap();
+            command_queue.pop_front()
+        };
+
+        let hunk = "@@ hunk\n".to_string() + &"A\n".repeat(30);
+        ps.screen.orig_text = hunk.repeat(3);
+        ps.format_lines();
+        let search_term = ps.search_state.search_term.is_some();
+
+        assert_eq!(
+            jump("^@@", SearchMode::Forward, &mut ps),
+            Some(Command::UserInput(InputEvent::UpdateUpperMark(31)))
+        );
+        ps.upper_mark = 40;
+        assert_eq!(
+            jump("^@@", SearchMode::Reverse, &mut ps),
+            Some(Command::UserInput(InputEvent::UpdateUpperMark(31)))
+        );
+        ps.upper_mark = 31;
+        assert_eq!(
+            jump("^@@", SearchMode::Reverse, &mut ps),
+            Some(Command::UserInput(InputEvent::UpdateUpperMark(0)))
+        );
+        // The active search is left untouched
+        assert_eq!(ps.search_state.search_term.is_some(), search_term);
+
+        ps.upper_mark = 62;
+        assert!(matches!(
+            jump("^@@", SearchMode::Forward, &mut ps),
+            Some(Command::FormatRedrawPrompt)
+        ));
+        assert_eq!(ps.message.as_deref(), Some("Pattern not found"));
+        assert_eq!(ps.upper_mark, 62);
+        // The pattern was compiled only once for all the jumps
+        assert_eq!(ps.jump_regexes.len(), 1);
+
+        // The pattern set for `]` and `[` is compiled beforehand
+        ps.jump_pattern = Some(crate::search::Regex::new("^@@").unwrap());
+        ps.upper_mark = 0;
+        let mut command_queue = CommandQueue::new_zero();
+        handle_event(
+            Command::UserInput(InputEvent::JumpToSetPattern(SearchMode::Forward)),
+            &mut out,
+            &mut ps,
+            &mut command_queue,
+            &Arc::new(AtomicBool::new(false)),
+            &UIA,
+        )
+        .unwrap();
+        assert_eq!(
+            command_queue.pop_front(),
+            Some(Command::UserInput(InputEvent::UpdateUpperMark(31)))
+        );
+    }
+
+    #[test]
+    fn interrupt_behavior() {
+        let mut ps = PagerState::new().unwrap();
+        let mut out = Vec::new();
+        let mut command_queue = CommandQueue::new_zero();
+        let interrupted = Arc::new(AtomicBool::new(false));
+        let interrupted2 = interrupted.clone();
+
+        for ev in [
+            Command::SetInterruptBehavior(InterruptBehavior::Callback(Arc::new(move || {
+                interrupted2.store(true, std::sync::atomic::Ordering::SeqCst);
+            }))),
+            Command::UserInput(InputEvent::Interrupt),
+        ] {
+            handle_event(
+                ev,
+                &mut out,
+                &mut ps,
+                &mut command_queue,
+                &Arc::new(AtomicBool::new(false)),
+                #[cfg(feature = "__search")]
+                &UIA,
+            )
+            .unwrap();
+        }
+        assert!(interrupted.load(std::sync::atomic::Ordering::SeqCst));
+        assert!(command_queue.is_empty());
+
+        // By default the interrupt quits the pager
+        ps.interrupt_behavior = InterruptBehavior::Quit;
+        handle_event(
+            Command::UserInput(InputEvent::Interrupt),
+            &mut out,
+            &mut ps,
+            &mut command_queue,
+            &Arc::new(AtomicBool::new(false)),
+            #[cfg(feature = "__search")]
+            &UIA,
+        )
+        .unwrap();
+        assert_eq!(
+            command_queue.pop_front(),
+            Some(Command::UserInput(InputEvent::Exit))
+        );
+    }
+
+    #[test]
+    #[cfg(any(feature = "dynamic_output", feature = "static_output"))]
+    fn term_too_small_restores_view() {
+        let mut ps = PagerState::new().unwrap();
+        #[cfg(feature = "dynamic_output")]
+        {
+            *crate::minus_core::RUNMODE.lock() = RunMode::Dynamic;
+        }
+        #[cfg(feature = "static_output")]
+        {
+            *crate::minus_core::RUNMODE.lock() = RunMode::Static;
+        }
+        ps.screen.orig_text = "A line\n".repeat(30);
+        ps.format_lines();
+        ps.upper_mark = 12;
+        let mut out = Vec::new();
+        let mut command_queue = CommandQueue::new_zero();
+
+        for ev in [
+            Command::UserInput(InputEvent::UpdateTermArea(10, 2)),
+            Command::UserInput(InputEvent::UpdateUpperMark(20)),
+        ] {
+            handle_event(
+                ev,
+                &mut out,
+                &mut ps,
+                &mut command_queue,
+                &Arc::new(AtomicBool::new(false)),
+                #[cfg(feature = "__search")]
+                &UIA,
+            )
+            .unwrap();
+        }
+        assert!(String::from_utf8(out).unwrap().contains("Terminal t"));
+        assert!(command_queue.is_empty());
+        assert_eq!(ps.upper_mark, 12);
+
+        let mut out = Vec::new();
+        handle_event(
+            Command::UserInput(InputEvent::UpdateTermArea(80, 10)),
+            &mut out,
+            &mut ps,
+            &mut command_queue,
+            &Arc::new(AtomicBool::new(false)),
+            #[cfg(feature = "__search")]
+            &UIA,
+        )
+        .unwrap();
+        assert!(matches!(
+            command_queue.pop_front(),
+            Some(Command::FormatRedrawDisplay)
+        ));
+        assert_eq!(ps.upper_mark, 12);
+    }
+
+    #[test]
+    fn reload_keeps_top_line() {
+        let mut ps = PagerState::new().unwrap();
+        let text: String = (0..50).map(|i| format!("{i}\n")).collect();
+        ps.screen.orig_text = text.clone();
+        ps.format_lines();
+        ps.upper_mark = 20;
+        let mut out = Vec::new();
+        let mut command_queue = CommandQueue::new_zero();
+
+        let ev = Command::SetReloadCallback(Box::new(move || format!("new\nlines\n{text}")));
+        for ev in [ev, Command::UserInput(InputEvent::Reload)] {
+            handle_event(
+                ev,
+                &mut out,
+                &mut ps,
+                &mut command_queue,
+                &Arc::new(AtomicBool::new(false)),
+                #[cfg(feature = "__search")]
+                &UIA,
+            )
+            .unwrap();
+        }
+        assert_eq!(ps.screen.line_count(), 52);
+        assert_eq!(ps.upper_mark, 20);
+        assert_eq!(ps.screen.formatted_lines[ps.upper_mark], "18");
+    }
+
+    #[test]
+    fn apply_config_reformats_only_on_change() {
+        let mut ps = PagerState::new().unwrap();
+        let mut command_queue = CommandQueue::new_zero();
+        let config = PagerConfig {
+            line_numbers: Some(LineNumbers::Enabled),
+            scroll_margin: Some(3),
+            theme: Some(crate::Theme {
+                line_number_separator: '│',
+                ..crate::Theme::default()
+            }),
+            ..PagerConfig::default()
+        };
+        for _ in 0..2 {
+            handle_event(
+                Command::ApplyConfig(Box::new(config.clone())),
+                &mut Vec::new(),
+                &mut ps,
+                &mut command_queue,
+                &Arc::new(AtomicBool::new(false)),
+                #[cfg(feature = "__search")]
+                &UIA,
+            )
+            .unwrap();
+        }
+        assert_eq!(ps.line_numbers, LineNumbers::Enabled);
+        assert_eq!(ps.scroll_margin, 3);
+        assert_eq!(ps.screen.theme.line_number_separator, '│');
+        // Applying the same config again doesn't format the text again
+        assert_eq!(
+            command_queue.pop_front(),
+            Some(Command::FormatRedrawDisplay)
+        );
+        assert!(command_queue.is_empty());
+    }
+
+    #[test]
+    fn idle_calls_callback() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let mut ps = PagerState::new().unwrap();
+        let mut out = Vec::new();
+        let mut command_queue = CommandQueue::new_zero();
+        assert!(ps.time_until_idle().is_none());
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        let calls2 = calls.clone();
+        let ev = Command::SetIdleCallback(Box::new(move || {
+            calls2.fetch_add(1, Ordering::SeqCst);
+        }));
+        for ev in [ev, Command::Idle, Command::Idle] {
+            handle_event(
+                ev,
+                &mut out,
+                &mut ps,
+                &mut command_queue,
+                &Arc::new(AtomicBool::new(false)),
+                #[cfg(feature = "__search")]
+                &UIA,
+            )
+            .unwrap();
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        assert!(ps.time_until_idle().is_some());
+    }
+
+    #[test]
+    #[cfg(any(feature = "dynamic_output", feature = "static_output"))]
+    fn scroll_to_line() {
+        let mut ps = PagerState::new().unwrap();
+        ps.screen.orig_text = (0..50).map(|i| format!("{i}\n")).collect();
+        ps.format_lines();
+        let mut out = Vec::new();
+        #[cfg(feature = "dynamic_output")]
+        {
+            *crate::minus_core::RUNMODE.lock() = RunMode::Dynamic;
+        }
+        #[cfg(feature = "static_output")]
+        {
+            *crate::minus_core::RUNMODE.lock() = RunMode::Static;
+        }
+        let mut command_queue = CommandQueue::new();
+
+        for (line, upper_mark) in [(21, 20), (1, 0), (100, usize::MAX - 1)] {
+            for command in [
+                Command::ScrollToLine(line),
+                Command::UserInput(InputEvent::GotoLine(line)),
+            ] {
+                handle_event(
+                    command,
+                    &mut out,
+                    &mut ps,
+                    &mut command_queue,
+                    &Arc::new(AtomicBool::new(false)),
+                    #[cfg(feature = "__search")]
+                    &UIA,
+                )
+                .unwrap();
+                assert_eq!(
+                    command_queue.pop_front(),
+                    Some(Command::UserInput(InputEvent::UpdateUpperMark(upper_mark)))
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn center_row() {
+        let mut ps = PagerState::new().unwrap();
+        let mut out = Vec::new();
+        let mut command_queue = CommandQueue::new_zero();
+
+        // 9 rows are available for text, so the row goes 4 rows below the top
+        for (row, upper_mark) in [(20, 16), (2, 0)] {
+            handle_event(
+                Command::UserInput(InputEvent::CenterRow(row)),
+                &mut out,
+                &mut ps,
+                &mut command_queue,
+                &Arc::new(AtomicBool::new(false)),
+                #[cfg(feature = "__search")]
+                &UIA,
+            )
+            .unwrap();
+            assert_eq!(
+                command_queue.pop_front(),
+                Some(Command::UserInput(InputEvent::UpdateUpperMark(upper_mark)))
+            );
+        }
+    }
+
+    #[test]
+    fn follow_output_survives_resize() {
+        let mut ps = PagerState::new().unwrap();
+        ps.screen.orig_text = "A line that wraps when the terminal is narrow\n".repeat(30);
+        ps.format_lines();
+        ps.follow_output = true;
+        ps.upper_mark = 21;
+        let mut out = Vec::new();
+        let mut command_queue = CommandQueue::new_zero();
+
+        // Each line takes two rows after the terminal shrinks
+        ps.cols = 30;
+        ps.rows = 12;
+        handle_event(
+            Command::FormatRedrawDisplay,
+            &mut out,
+            &mut ps,
+            &mut command_queue,
+            &Arc::new(AtomicBool::new(false)),
+            #[cfg(feature = "__search")]
+            &UIA,
+        )
+        .unwrap();
+        assert_eq!(ps.screen.formatted_lines_count(), 60);
+        assert_eq!(ps.upper_mark, 49);
+    }
+
+    #[test]
+    fn term_size_override() {
+        let mut ps = PagerState::new().unwrap();
+        let mut out = Vec::new();
+        let mut command_queue = CommandQueue::new_zero();
+
+        let mut handle = |ev, ps: &mut PagerState| {
+            handle_event(
+                ev,
+                &mut out,
+                ps,
+                &mut command_queue,
+                &Arc::new(AtomicBool::new(false)),
+                #[cfg(feature = "__search")]
+                &UIA,
+            )
+            .unwrap();
+        };
+
+        handle(Command::SetTermSize(Some((30, 5))), &mut ps);
+        assert_eq!((ps.cols, ps.rows), (30, 5));
+
+        // Going back to the size of the terminal
+        ps.backend = Arc::new(crate::backend::TestBackend::new(100, 20));
+        handle(Command::SetTermSize(None), &mut ps);
+        assert_eq!((ps.cols, ps.rows), (100, 20));
+    }
+
+    #[test]
+    #[cfg(feature = "metrics")]
+    fn metrics() {
+        let mut ps = PagerState::new().unwrap();
+        let mut out = Vec::new();
+        let mut command_queue = CommandQueue::new_zero();
+        let mut handle = |ev, ps: &mut PagerState| {
+            handle_event(
+                ev,
+                &mut out,
+                ps,
+                &mut command_queue,
+                &Arc::new(AtomicBool::new(false)),
+                #[cfg(feature = "__search")]
+                &UIA,
+            )
+            .unwrap();
+        };
+        assert_eq!(ps.metrics().average_format_time(), None);
+
+        handle(Command::AppendData("one\ntwo\n".to_string()), &mut ps);
+        handle(Command::AppendData("three\n".to_string()), &mut ps);
+        let metrics = ps.metrics();
+        assert_eq!(metrics.bytes_received, 14);
+        assert_eq!(metrics.lines_formatted, 3);
+        assert_eq!(metrics.formats, 2);
+        assert!(metrics.average_format_time().is_some());
+
+        // Formatting everything again counts all the lines
+        handle(Command::FormatRedrawDisplay, &mut ps);
+        assert_eq!(ps.metrics().lines_formatted, 6);
+
+        crate::minus_core::utils::display::draw_full(&mut Vec::new(), &mut ps).unwrap();
+        assert_eq!(ps.metrics().full_redraws, 1);
+
+        // Scrolling only draws the rows scrolled into view
+        ps.rows = 3;
+        let mut upper_mark = 1;
+        crate::minus_core::utils::display::draw_for_change(
+            &mut Vec::new(),
+            &mut ps,
+            &mut upper_mark,
+        )
+        .unwrap();
+        assert_eq!(ps.metrics().full_redraws, 1);
+        assert_eq!(ps.metrics().partial_redraws, 1);
+    }
+
+    #[test]
+    fn max_refresh_rate() {
+        let mut ps = PagerState::new().unwrap();
+        let mut out = Vec::new();
+        let mut command_queue = CommandQueue::new_zero();
+        let mut handle = |ev, ps: &mut PagerState| {
+            handle_event(
+                ev,
+                &mut out,
+                ps,
+                &mut command_queue,
+                &Arc::new(AtomicBool::new(false)),
+                #[cfg(feature = "__search")]
+                &UIA,
+            )
+            .unwrap();
+        };
+
+        // Every append is drawn by default
+        assert!(ps.should_draw_append());
+        assert!(ps.should_draw_append());
+        assert_eq!(ps.time_until_redraw(), None);
+
+        handle(Command::SetMaxRefreshRate(Duration::from_secs(60)), &mut ps);
+        assert!(!ps.should_draw_append());
+        assert!(ps.redraw_pending);
+        let remaining = ps.time_until_redraw().unwrap();
+        assert!(remaining > Duration::from_secs(59));
+
+        // The held back data is drawn with the end of the text in view when following
+        ps.screen.orig_text = "A line\n".repeat(30);
+        ps.format_lines();
+        ps.follow_output = true;
+        handle(Command::RedrawDisplay, &mut ps);
+        assert!(!ps.redraw_pending);
+        assert_eq!(ps.time_until_redraw(), None);
+        assert_eq!(ps.upper_mark, 21);
+    }
+
+    #[test]
+    fn follow_output_pauses_on_scroll() {
+        let mut ps = PagerState::new().unwrap();
+        ps.screen.orig_text = "A line\n".repeat(50);
+        ps.format_lines();
+        ps.upper_mark = 41;
+        ps.follow_output = true;
+        let mut out = Vec::new();
+        let mut command_queue = CommandQueue::new_zero();
+
+        let mut scroll_to = |um, ps: &mut PagerState| {
+            handle_event(
+                Command::UserInput(InputEvent::UpdateUpperMark(um)),
+                &mut out,
+                ps,
+                &mut command_queue,
+                &Arc::new(AtomicBool::new(false)),
+                #[cfg(feature = "__search")]
+                &UIA,
+            )
+            .unwrap();
+        };
+
+        scroll_to(10, &mut ps);
+        assert!(!ps.follow_output);
+        assert!(ps.follow_paused);
+
+        scroll_to(30, &mut ps);
+        assert!(!ps.follow_output);
+
+        scroll_to(usize::MAX - 1, &mut ps);
+        assert!(ps.follow_output);
+        assert!(!ps.follow_paused);
+    }
+
+    #[test]
+    fn events_are_emitted() {
+        let mut ps = PagerState::new().unwrap();
+        ps.screen.orig_text = "A line\n".repeat(50);
+        ps.format_lines();
+        ps.upper_mark = 41;
+        ps.follow_output = true;
+        let mut out = Vec::new();
+        let mut command_queue = CommandQueue::new_zero();
+        let (tx, rx) = crossbeam_channel::unbounded();
+
+        for ev in [
+            Command::AddEventSubscriber(tx),
+            Command::UserInput(InputEvent::UpdateUpperMark(10)),
+            Command::UserInput(InputEvent::UpdateTermArea(10, 2)),
+        ] {
+            handle_event(
+                ev,
+                &mut out,
+                &mut ps,
+                &mut command_queue,
+                &Arc::new(AtomicBool::new(false)),
+                #[cfg(feature = "__search")]
+                &UIA,
+            )
+            .unwrap();
+        }
+
+        assert_eq!(
+            rx.try_iter().collect::<Vec<_>>(),
+            [
+                PagerEvent::Scroll {
+                    old: 41,
+                    new: 10,
+                    top_line: 11,
+                    percent: 38,
+                },
+                PagerEvent::FollowToggled(false),
+                PagerEvent::Resize { cols: 10, rows: 2 },
+            ]
+        );
+
+        // Subscribers whose receiver has been dropped are removed
+        drop(rx);
+        ps.emit_event(&PagerEvent::Exit);
+        assert!(ps.event_subscribers.is_empty());
+    }
+
+    #[test]
+    fn data_provider() {
+        let mut ps = PagerState::new().unwrap();
+        ps.screen.orig_text = "A line\n".repeat(20);
+        ps.format_lines();
+        ps.screen.line_count = 20;
+        ps.data_provider = Some(Box::new(|range: std::ops::Range<usize>| {
+            (range.start < 30).then(|| format!("{range:?}\n"))
+        }));
+        let mut out = Vec::new();
+        let mut command_queue = CommandQueue::new();
+
+        handle_event(
+            Command::UserInput(InputEvent::UpdateUpperMark(5)),
+            &mut out,
+            &mut ps,
+            &mut command_queue,
+            &Arc::new(AtomicBool::new(false)),
+            #[cfg(feature = "__search")]
+            &UIA,
+        )
+        .unwrap();
+        // Two pages below the top of the screen are wanted but only 20 lines are available
+        assert_eq!(
+            command_queue.pop_front(),
+            Some(Command::AppendData("20..29\n".to_string()))
+        );
+
+        // No more data is requested once the provider signals the end
+        ps.screen.line_count = 30;
+        assert_eq!(ps.request_data(), None);
+        assert!(ps.data_provider.is_none());
+    }
+
+    #[test]
+    fn quit_on_eof() {
+        let mut ps = PagerState::new().unwrap();
+        ps.screen.orig_text = "A line\n".repeat(50);
+        ps.format_lines();
+        ps.quit_on_eof = true;
+        let mut out = Vec::new();
+        let mut command_queue = CommandQueue::new();
+
+        for um in [20, usize::MAX - 1] {
+            handle_event(
+                Command::UserInput(InputEvent::UpdateUpperMark(um)),
+                &mut out,
+                &mut ps,
+                &mut command_queue,
+                &Arc::new(AtomicBool::new(false)),
+                #[cfg(feature = "__search")]
+                &UIA,
+            )
+            .unwrap();
+        }
+        assert!(matches!(ps.exit_reason, Some(ExitReason::Eof)));
+        assert_eq!(
+            command_queue.pop_front(),
+            Some(Command::UserInput(InputEvent::Exit))
+        );
+        assert!(command_queue.is_empty());
+    }
+
+    #[test]
+    fn wait_for_data() {
+        let mut ps = PagerState::new().unwrap();
+        let mut out = Vec::new();
+        let mut command_queue = CommandQueue::new_zero();
+
+        handle_event(
+            Command::UserInput(InputEvent::WaitForData(true)),
+            &mut out,
+            &mut ps,
+            &mut command_queue,
+            &Arc::new(AtomicBool::new(false)),
+            #[cfg(feature = "__search")]
+            &UIA,
+        )
+        .unwrap();
+        ps.format_prompt();
+        assert!(ps.follow_output);
+        assert!(ps.displayed_prompt.contains("Waiting for data... |"));
+
+        handle_event(
+            Command::UserInput(InputEvent::WaitForData(false)),
+            &mut out,
+            &mut ps,
+            &mut command_queue,
+            &Arc::new(AtomicBool::new(false)),
+            #[cfg(feature = "__search")]
+            &UIA,
+        )
+        .unwrap();
+        ps.format_prompt();
+        assert!(!ps.follow_output);
+        assert!(!ps.displayed_prompt.contains("Waiting for data"));
+    }
+
+    #[test]
+    #[cfg(feature = "__search")]
+    fn search_jump_keeps_scroll_margin() {
+        let mut ps = PagerState::new().unwrap();
+        ps.screen.orig_text = (0..50).map(|i| format!("line {i}\n")).collect();
+        ps.search_state.search_term = Some(crate::search::Regex::new("line [24]0").unwrap());
+        ps.format_lines();
+        ps.scroll_margin = 3;
+        let mut out = Vec::new();
+        let mut command_queue = CommandQueue::new_zero();
+
+        for expected in [17, 37] {
+            handle_event(
+                Command::UserInput(InputEvent::MoveToNextMatch(1)),
+                &mut out,
+                &mut ps,
+                &mut command_queue,
+                &Arc::new(AtomicBool::new(false)),
+                #[cfg(feature = "__search")]
+                &UIA,
+            )
+            .unwrap();
+            assert_eq!(
+                command_queue.pop_front(),
+                Some(Command::UserInput(InputEvent::UpdateUpperMark(expected)))
+            );
+            ps.upper_mark = expected;
+            command_queue.pop_front();
+        }
+
+        ps.center_on_jump = true;
+        assert_eq!(ps.jump_target(40), 36);
+    }
+
+    #[test]
+    #[cfg(feature = "__search")]
+    fn toggle_search_highlight() {
+        let mut ps = PagerState::new().unwrap();
+        ps.append_str("a match\nnothing\nmatch\n");
+        ps.search_state.search_term = Some(crate::search::Regex::new("match").unwrap());
+        ps.format_lines();
+        let highlighted = ps.screen.formatted_lines.clone();
+        assert_ne!(highlighted[0], "a match");
+
+        let mut command_queue = CommandQueue::new_zero();
+        let mut toggle = |ps: &mut PagerState| {
+            handle_event(
+                Command::UserInput(InputEvent::ToggleSearchHighlight),
+                &mut Vec::new(),
+                ps,
+                &mut command_queue,
+                &Arc::new(AtomicBool::new(false)),
+                &UIA,
+            )
+            .unwrap();
+            assert!(matches!(
+                command_queue.pop_front(),
+                Some(Command::FormatRedrawDisplay)
+            ));
+            ps.format_lines();
+        };
+
+        toggle(&mut ps);
+        assert_eq!(ps.screen.formatted_lines, ["a match", "nothing", "match"]);
+        // Matches can still be visited
+        assert_eq!(ps.search_state.search_idx.len(), 2);
+
+        toggle(&mut ps);
+        assert_eq!(ps.screen.formatted_lines, highlighted);
+    }
+
+    #[test]
+    #[cfg(feature = "__search")]
+    fn clear_search() {
+        let mut ps = PagerState::new().unwrap();
+        ps.append_str("a match\nnothing\nmatch\n");
+        ps.search_state.search_term = Some(crate::search::Regex::new("match").unwrap());
+        ps.search_state.search_mark = 1;
+        ps.format_lines();
+
+        let mut command_queue = CommandQueue::new_zero();
+        handle_event(
+            Command::UserInput(InputEvent::ClearSearch),
+            &mut Vec::new(),
+            &mut ps,
+            &mut command_queue,
+            &Arc::new(AtomicBool::new(false)),
+            &UIA,
+        )
+        .unwrap();
+        assert!(ps.search_state.search_term.is_none());
+        assert!(ps.search_state.search_idx.is_empty());
+        assert_eq!(ps.search_state.search_mark, 0);
+        assert!(matches!(
+            command_queue.pop_front(),
+            Some(Command::FormatRedrawDisplay)
+        ));
+        ps.format_lines();
+        assert_eq!(ps.screen.formatted_lines, ["a match", "nothing", "match"]);
+    }
+
+    #[test]
+    #[cfg(feature = "__search")]
+    fn search_range() {
+        let mut ps = PagerState::new().unwrap();
+        ps.append_str(&"match\n".repeat(50));
+        ps.search_state.search_term = Some(crate::search::Regex::new("match").unwrap());
+        ps.format_lines();
+        assert_eq!(ps.search_state.search_idx.len(), 50);
+
+        let mut command_queue = CommandQueue::new_zero();
+        handle_event(
+            Command::SetSearchRange(Some(10..20)),
+            &mut Vec::new(),
+            &mut ps,
+            &mut command_queue,
+            &Arc::new(AtomicBool::new(false)),
+            &UIA,
+        )
+        .unwrap();
+        assert!(matches!(
+            command_queue.pop_front(),
+            Some(Command::FormatRedrawDisplay)
+        ));
+        ps.format_lines();
+        assert_eq!(
+            ps.search_state.search_idx,
+            (10..20).collect::<std::collections::BTreeSet<_>>()
+        );
+
+        // Restricting to the page narrows the range further
+        ps.search_state.page_range = Some(ps.input_context().visible_lines);
+        ps.search_state.search_range = Some(5..100);
+        ps.format_lines();
+        assert_eq!(
+            ps.search_state.search_idx,
+            (5..9).collect::<std::collections::BTreeSet<_>>()
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "__search")]
+    fn styled_lines() {
+        use std::collections::BTreeSet;
+
+        let mut ps = PagerState::new().unwrap();
+        ps.search_state.search_term = Some(crate::search::Regex::new("bc").unwrap());
+        let mut command_queue = CommandQueue::new_zero();
+        for command in [
+            Command::AppendData("plain\nunterminated".to_string()),
+            Command::AppendStyledLines("\x1b[1mab\x1b[0mcd\n\x1b[1mab\x1b[0mcd\n".to_string()),
+        ] {
+            handle_event(
+                command,
+                &mut Vec::new(),
+                &mut ps,
+                &mut command_queue,
+                &Arc::new(AtomicBool::new(false)),
+                &UIA,
+            )
+            .unwrap();
+        }
+        // The first styled line is joined to an unstyled one, so only the second is marked
+        assert_eq!(ps.screen.styled_lines, BTreeSet::from([2]));
+
+        ps.format_lines();
+        // The match stays inverted after the reset inside the styled line
+        assert_eq!(
+            ps.screen.formatted_lines[2],
+            "\x1b[1ma\x1b[7mb\x1b[0m\x1b[7mc\x1b[27md"
+        );
+
+        handle_event(
+            Command::SetData("plain\n".to_string()),
+            &mut Vec::new(),
+            &mut ps,
+            &mut command_queue,
+            &Arc::new(AtomicBool::new(false)),
+            &UIA,
+        )
+        .unwrap();
+        assert!(ps.screen.styled_lines.is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "__search")]
+    fn search_index_reused_on_reflow() {
+        use std::collections::BTreeSet;
+
+        let mut ps = PagerState::new().unwrap();
+        let long_line = format!("{}match\n", "word ".repeat(30));
+        ps.append_str(&format!("first\n{long_line}last match\n"));
+        ps.search_state.search_term = Some(crate::search::Regex::new("match").unwrap());
+        ps.format_lines();
+        // The second line wraps into two rows and its match lies in the second one
+        assert_eq!(ps.search_state.search_idx, BTreeSet::from([2, 3]));
+        assert_eq!(ps.search_state.line_matches.get(1), Some(&[150..155][..]));
+
+        // Plant a fake match to check that the text doesn't get searched again
+        ps.search_state.line_matches.insert(0, vec![0..5]);
+        let mut command_queue = CommandQueue::new_zero();
+        for cmd in [
+            Command::UserInput(InputEvent::UpdateTermArea(200, 10)),
+            Command::FormatRedrawDisplay,
+        ] {
+            handle_event(
+                cmd,
+                &mut Vec::new(),
+                &mut ps,
+                &mut command_queue,
+                &Arc::new(AtomicBool::new(false)),
+                &UIA,
+            )
+            .unwrap();
+        }
+        assert_eq!(ps.search_state.search_idx, BTreeSet::from([0, 1, 2]));
+
+        // Changing the search builds the index again
+        ps.search_state.search_term = Some(crate::search::Regex::new("last").unwrap());
+        ps.reflow_lines();
+        assert_eq!(ps.search_state.search_idx, BTreeSet::from([2]));
+        assert_eq!(ps.search_state.line_matches.get(0), None);
+    }
+
+    #[test]
+    #[cfg(feature = "__search")]
+    fn multiline_search() {
+        use std::collections::BTreeSet;
+
+        let mut ps = PagerState::new().unwrap();
+        ps.append_str("first\nbegin\nmiddle\nend\nlast\n");
+        ps.search_state.search_term = Some(crate::search::Regex::new("(?s)begin.*?end").unwrap());
+        ps.format_lines();
+        assert!(ps.search_state.search_idx.is_empty());
+
+        let mut command_queue = CommandQueue::new_zero();
+        handle_event(
+            Command::SetMultilineSearch(true),
+            &mut Vec::new(),
+            &mut ps,
+            &mut command_queue,
+            &Arc::new(AtomicBool::new(false)),
+            &UIA,
+        )
+        .unwrap();
+        assert!(matches!(
+            command_queue.pop_front(),
+            Some(Command::FormatRedrawDisplay)
+        ));
+        ps.reflow_lines();
+        // Only the row where the match starts is a jump target but all of its rows are highlighted
+        assert_eq!(ps.search_state.search_idx, BTreeSet::from([1]));
+        for row in 1..4 {
+            assert!(ps.screen.formatted_lines[row].contains("\x1b[7m"));
+        }
+        assert!(!ps.screen.formatted_lines[4].contains("\x1b[7m"));
+
+        // Appended text can complete a match
+        ps.search_state.search_term = Some(crate::search::Regex::new("last\nmore").unwrap());
+        ps.format_lines();
+        assert!(ps.search_state.search_idx.is_empty());
+        ps.append_str("more\n");
+        assert_eq!(ps.search_state.search_idx, BTreeSet::from([4]));
+    }
+
+    #[test]
+    #[cfg(feature = "__search")]
+    fn search_match_placement() {
+        use crate::search::MatchPlacement;
+
+        let mut ps = PagerState::new().unwrap();
+        ps.screen.orig_text = (0..50).map(|i| format!("line {i}\n")).collect();
+        ps.search_state.search_term = Some(crate::search::Regex::new("line (4|6|30)$").unwrap());
+        ps.format_lines();
+        let mut out = Vec::new();
+        let mut command_queue = CommandQueue::new_zero();
+        let mut next_match = |ps: &mut PagerState| {
+            handle_event(
+                Command::UserInput(InputEvent::MoveToNextMatch(1)),
+                &mut out,
+                ps,
+                &mut command_queue,
+                &Arc::new(AtomicBool::new(false)),
+                #[cfg(feature = "__search")]
+                &UIA,
+            )
+            .unwrap();
+            let Some(Command::UserInput(InputEvent::UpdateUpperMark(um))) =
+                command_queue.pop_front()
+            else {
+                panic!("no jump to the next match");
+            };
+            command_queue.pop_front();
+            ps.upper_mark = um;
+            um
+        };
+
+        handle_event(
+            Command::SetMatchPlacement(MatchPlacement::Minimal),
+            &mut Vec::new(),
+            &mut ps,
+            &mut CommandQueue::new_zero(),
+            &Arc::new(AtomicBool::new(false)),
+            &UIA,
+        )
+        .unwrap();
+        // The next match is already visible
+        assert_eq!(next_match(&mut ps), 0);
+        // Scroll just enough to show the match on the last row
+        assert_eq!(next_match(&mut ps), 22);
+
+        ps.search_state.match_placement = MatchPlacement::Center;
+        assert_eq!(ps.search_jump_target(30), 26);
+        ps.search_state.match_placement = MatchPlacement::Top;
+        assert_eq!(ps.search_jump_target(30), 30);
+    }
+
+    #[test]
+    fn add_exit_callback() {
+        let mut ps = PagerState::new().unwrap();
+        let ev = Command::AddExitCallback(Box::new(|| println!("Hello World")));
+        let mut out = Vec::new();
+        let mut command_queue = CommandQueue::new_zero();
+
+        handle_event(
+            ev,
+            &mut out,
+            &mut ps,
+            &mut command_queue,
+            &Arc::new(AtomicBool::new(false)),
+            #[cfg(feature = "__search")]
+            &UIA,
+        )
+        .unwrap();
+        assert_eq!(ps.exit_callbacks.len(), 1);
+    }
+
+    #[test]
+    fn ansi_strip() {
+        let mut ps = PagerState::new().unwrap();
+        let mut out = Vec::new();
+        let mut command_queue = CommandQueue::new();
+
+        for ev in [
+            Command::SetAnsiMode(AnsiMode::Strip),
+            Command::SetData("\u{1b}[1mbold\u{1b}[0m\n".to_string()),
+            Command::AppendData("\u{1b}[31mred\u{1b}[0m\n".to_string()),
+        ] {
+            handle_event(
+                ev,
+                &mut out,
+                &mut ps,
+                &mut command_queue,
+                &Arc::new(AtomicBool::new(false)),
+                #[cfg(feature = "__search")]
+                &UIA,
+            )
+            .unwrap();
+        }
+        assert_eq!(ps.screen.orig_text, "bold\nred\n");
+    }
+
+    #[test]
+    fn carriage_returns() {
+        let mut ps = PagerState::new().unwrap();
+        let mut out = Vec::new();
+        let mut command_queue = CommandQueue::new();
+        let mut handle = |ev, ps: &mut PagerState| {
+            handle_event(
+                ev,
+                &mut out,
+                ps,
+                &mut command_queue,
+                &Arc::new(AtomicBool::new(false)),
+                #[cfg(feature = "__search")]
+                &UIA,
+            )
+            .unwrap();
+        };
+
+        // A `\r\n` split across two appends is still a line ending
+        handle(Command::SetData("one\r\ntwo\r".to_string()), &mut ps);
+        handle(Command::AppendData("\nthree\r".to_string()), &mut ps);
+        handle(Command::AppendData("\n".to_string()), &mut ps);
+        assert_eq!(ps.screen.orig_text, "one\ntwo\nthree\n");
+        assert_eq!(ps.screen.line_count(), 3);
+
+        handle(Command::SetCarriageReturnOverwrite(true), &mut ps);
+        handle(Command::AppendData("10%".to_string()), &mut ps);
+        handle(Command::AppendData("\r50%\r".to_string()), &mut ps);
+        assert_eq!(ps.screen.orig_text, "one\ntwo\nthree\n50%");
+        handle(Command::AppendData("100%\rdone\nnext".to_string()), &mut ps);
+        assert_eq!(ps.screen.orig_text, "one\ntwo\nthree\ndone\nnext");
+        assert_eq!(ps.screen.formatted_lines[3..], ["done", "next"]);
+        assert_eq!(ps.screen.line_count(), 5);
+    }
+
+    #[test]
+    fn split_overstrike() {
+        let mut ps = PagerState::new().unwrap();
+        let mut out = Vec::new();
+        let mut command_queue = CommandQueue::new();
+        let mut handle = |ev, ps: &mut PagerState| {
+            handle_event(
+                ev,
+                &mut out,
+                ps,
+                &mut command_queue,
+                &Arc::new(AtomicBool::new(false)),
+                #[cfg(feature = "__search")]
+                &UIA,
+            )
+            .unwrap();
+        };
+
+        handle(Command::SetData("N\u{8}NA\u{8}".to_string()), &mut ps);
+        assert_eq!(ps.screen.orig_text, "\u{1b}[1mN\u{1b}[22m");
+        handle(Command::AppendData("AM\u{8}M _\u{8}".to_string()), &mut ps);
+        handle(Command::AppendData("x\u{8}".to_string()), &mut ps);
+        handle(Command::AppendData("x\n".to_string()), &mut ps);
+        assert_eq!(
+            ps.screen.orig_text,
+            "\u{1b}[1mN\u{1b}[22m\u{1b}[1mAM\u{1b}[22m \u{1b}[1m\u{1b}[4mx\u{1b}[22m\u{1b}[24m\n"
+        );
+
+        // Nothing is held back when the text doesn't use overstrike
+        handle(Command::AppendData("plain".to_string()), &mut ps);
+        assert!(ps.screen.orig_text.ends_with("\nplain"));
+    }
+
+    #[test]
+    fn message_queue() {
+        let mut ps = PagerState::new().unwrap();
+        let mut out = Vec::new();
+        let mut command_queue = CommandQueue::new_zero();
+        let mut handle = |ev, ps: &mut PagerState| {
+            handle_event(
+                ev,
+                &mut out,
+                ps,
+                &mut command_queue,
+                &Arc::new(AtomicBool::new(false)),
+                #[cfg(feature = "__search")]
+                &UIA,
+            )
+            .unwrap();
+        };
+
+        for message in ["first", "second", "third"] {
+            handle(Command::SendMessage(message.to_string()), &mut ps);
+        }
+        assert_eq!(ps.message.as_deref(), Some("first"));
+        assert_eq!(ps.pending_messages, ["second", "third"]);
+        assert!(ps.displayed_prompt.contains("first (+2 more, press Enter)"));
+
+        // Enter moves on to the next message
+        handle(Command::UserInput(InputEvent::RestorePrompt), &mut ps);
+        assert_eq!(ps.message.as_deref(), Some("second"));
+        assert_eq!(ps.pending_messages, ["third"]);
+
+        // Only the newest messages keep waiting
+        for idx in 0..20 {
+            handle(Command::SendMessage(idx.to_string()), &mut ps);
+        }
+        assert_eq!(
+            ps.pending_messages.len(),
+            crate::state::MAX_PENDING_MESSAGES
+        );
+        assert_eq!(ps.pending_messages.back().map(String::as_str), Some("19"));
+
+        handle(Command::ClearMessages, &mut ps);
+        assert!(ps.message.is_none());
+        assert!(ps.pending_messages.is_empty());
+    }
+
+    #[test]
+    fn hidden_prompt() {
+        let mut ps = PagerState::new().unwrap();
+        ps.rows = 10;
+        ps.cols = 80;
+        let text: String = (0..20).map(|i| format!("{i}\n")).collect();
+        ps.append_str(&text);
+        let mut command_queue = CommandQueue::new_zero();
+        let mut handle = |ev, ps: &mut PagerState| {
+            let mut out = Vec::new();
+            handle_event(
+                ev,
+                &mut out,
+                ps,
+                &mut command_queue,
+                &Arc::new(AtomicBool::new(false)),
+                #[cfg(feature = "__search")]
+                &UIA,
+            )
+            .unwrap();
+            String::from_utf8(out).unwrap()
+        };
+
+        handle(Command::ShowPrompt(false), &mut ps);
+        crate::minus_core::utils::display::draw_full(&mut Vec::new(), &mut ps).unwrap();
+        assert_eq!(ps.text_rows(), 10);
+        assert!(handle(Command::FormatRedrawPrompt, &mut ps).is_empty());
+
+        // A message takes the last row back from the text
+        handle(Command::SendMessage("hello".to_string()), &mut ps);
+        let out = handle(Command::FormatRedrawPrompt, &mut ps);
+        assert!(out.contains("\x1b[2J"));
+        assert!(out.contains("hello"));
+        assert_eq!(ps.text_rows(), 9);
+
+        // ...and gives it back once it is dismissed
+        handle(Command::ClearMessages, &mut ps);
+        let out = handle(Command::FormatRedrawPrompt, &mut ps);
+        assert!(out.contains("\x1b[2J"));
+        assert!(!out.contains("hello"));
+        assert_eq!(ps.text_rows(), 10);
+    }
+
+    #[test]
+    fn binary_auto_detection() {
+        let mut ps = PagerState::new().unwrap();
+        let mut out = Vec::new();
+        let mut command_queue = CommandQueue::new_zero();
+        let mut handle = |ev, ps: &mut PagerState| {
+            handle_event(
+                ev,
+                &mut out,
+                ps,
+                &mut command_queue,
+                &Arc::new(AtomicBool::new(false)),
+                #[cfg(feature = "__search")]
+                &UIA,
+            )
+            .unwrap();
+            command_queue.pop_front()
+        };
+
+        handle(Command::SetBinaryMode(BinaryMode::Auto), &mut ps);
+        // Text stays as it is
+        assert_eq!(
+            handle(Command::AppendBytes(b"text\n".to_vec()), &mut ps),
+            Some(Command::AppendData("text\n".to_string()))
+        );
+        handle(Command::AppendData("text\n".to_string()), &mut ps);
+        assert_eq!(ps.screen.orig_text, "text\n");
+        assert!(ps.screen.raw_bytes.is_none());
+
+        // Binary data switches to a hex dump including the earlier text
+        assert_eq!(handle(Command::AppendBytes(vec![0xff]), &mut ps), None);
+        assert_eq!(
+            ps.screen.raw_bytes.as_deref(),
+            Some(b"text\n\xff".as_slice())
+        );
+        assert!(ps
+            .screen
+            .orig_text
+            .starts_with("00000000  74 65 78 74 0a ff"));
+        assert!(ps.message.is_some());
+
+        // Everything after that is added to the dump
+        handle(Command::AppendData("more".to_string()), &mut ps);
+        assert_eq!(ps.screen.raw_bytes.as_ref().unwrap().len(), 10);
+        assert_eq!(ps.screen.line_count(), 1);
+
+        // Setting text goes back to displaying text
+        handle(Command::SetData("text\n".to_string()), &mut ps);
+        assert!(ps.screen.raw_bytes.is_none());
+        assert_eq!(ps.screen.orig_text, "text\n");
+    }
+
+    #[test]
+    fn streamed_hex_dump() {
+        let bytes = (0..=255).cycle().take(1000).collect::<Vec<u8>>();
+        let mut whole = PagerState::new().unwrap();
+        whole.binary_mode = BinaryMode::HexDump;
+        whole.push_binary(&bytes, false);
+
+        let mut ps = PagerState::new().unwrap();
+        ps.line_numbers = LineNumbers::Enabled;
+        let mut command_queue = CommandQueue::new_zero();
+        handle_event(
+            Command::SetBinaryMode(BinaryMode::HexDump),
+            &mut Vec::new(),
+            &mut ps,
+            &mut command_queue,
+            &Arc::new(AtomicBool::new(false)),
+            #[cfg(feature = "__search")]
+            &UIA,
+        )
+        .unwrap();
+        // Chunks end both in the middle and at the end of rows
+        for chunk in bytes.chunks(7).chain([&[][..]]) {
+            handle_event(
+                Command::AppendBytes(chunk.to_vec()),
+                &mut Vec::new(),
+                &mut ps,
+                &mut command_queue,
+                &Arc::new(AtomicBool::new(false)),
+                #[cfg(feature = "__search")]
+                &UIA,
+            )
+            .unwrap();
+        }
+        assert_eq!(ps.screen.orig_text, whole.screen.orig_text);
+        assert_eq!(ps.screen.line_count(), 63);
+
+        whole.line_numbers = LineNumbers::Enabled;
+        whole.format_lines();
+        assert_eq!(ps.screen.formatted_lines, whole.screen.formatted_lines);
+    }
+
+    #[test]
+    fn link_click() {
+        let mut ps = PagerState::new().unwrap();
+        ps.rows = 10;
+        ps.screen.orig_text =
+            "A line\n".repeat(20) + "\u{1b}]8;;https://example.com\u{7}link\u{1b}]8;;\u{7}\n";
+        ps.format_lines();
+        ps.upper_mark = 15;
+        let mut out = Vec::new();
+        let mut command_queue = CommandQueue::new_zero();
+        let (tx, rx) = crossbeam_channel::unbounded();
+
+        for ev in [
+            Command::AddEventSubscriber(tx),
+            Command::UserInput(InputEvent::Click(5, 6)),
+            Command::UserInput(InputEvent::Click(5, 2)),
+        ] {
+            handle_event(
+                ev,
+                &mut out,
+                &mut ps,
+                &mut command_queue,
+                &Arc::new(AtomicBool::new(false)),
+                #[cfg(feature = "__search")]
+                &UIA,
+            )
+            .unwrap();
+        }
+        assert_eq!(
+            rx.try_iter().collect::<Vec<_>>(),
+            [PagerEvent::LinkClicked("https://example.com".to_string())]
+        );
+    }
+
+    #[test]
+    fn smooth_scroll() {
+        let mut ps = PagerState::new().unwrap();
+        let text: String = (0..100).map(|i| format!("{i}\n")).collect();
+        ps.append_str(&text);
+        let mut out = Vec::new();
+        let mut command_queue = CommandQueue::new_zero();
+        let mut handle = |ev, ps: &mut PagerState| {
+            handle_event(
+                ev,
+                &mut out,
+                ps,
+                &mut command_queue,
+                &Arc::new(AtomicBool::new(false)),
+                #[cfg(feature = "__search")]
+                &UIA,
+            )
+            .unwrap();
+        };
+
+        // Turned off by default
+        handle(Command::UserInput(InputEvent::UpdateUpperMark(50)), &mut ps);
+        assert_eq!(ps.upper_mark, 50);
+        handle(Command::UserInput(InputEvent::UpdateUpperMark(0)), &mut ps);
+
+        ps.scroll_amounts.smooth_scroll = Some(Duration::from_millis(100));
+        handle(Command::UserInput(InputEvent::UpdateUpperMark(50)), &mut ps);
+        assert!(ps.upper_mark > 0 && ps.upper_mark < 50);
+        assert!(ps.time_until_scroll_frame().is_some());
+
+        std::thread::sleep(Duration::from_millis(100));
+        handle(Command::AnimateScroll, &mut ps);
+        assert_eq!(ps.upper_mark, 50);
+        assert!(ps.time_until_scroll_frame().is_none());
+
+        // Small moves aren't animated
+        handle(Command::UserInput(InputEvent::UpdateUpperMark(51)), &mut ps);
+        assert_eq!(ps.upper_mark, 51);
+        assert!(ps.time_until_scroll_frame().is_none());
+    }
+
+    #[test]
+    fn feedback() {
+        let mut ps = PagerState::new().unwrap();
+        let text: String = (0..20).map(|i| format!("{i}\n")).collect();
+        ps.append_str(&text);
+        let mut command_queue = CommandQueue::new_zero();
+        let mut handle = |ev, ps: &mut PagerState| {
+            let mut out = Vec::new();
+            handle_event(
+                ev,
+                &mut out,
+                ps,
+                &mut command_queue,
+                &Arc::new(AtomicBool::new(false)),
+                #[cfg(feature = "__search")]
+                &UIA,
+            )
+            .unwrap();
+            out
+        };
+
+        assert!(ps.is_stuck_scroll(0));
+        assert!(!ps.is_stuck_scroll(1));
+        ps.upper_mark = 11;
+        assert!(ps.is_stuck_scroll(12));
+        assert!(!ps.is_stuck_scroll(10));
+
+        // Turned off by default
+        assert!(handle(Command::Feedback, &mut ps).is_empty());
+
+        handle(Command::SetFeedback(FeedbackMode::Bell), &mut ps);
+        assert_eq!(handle(Command::Feedback, &mut ps), b"\x07");
+
+        handle(Command::SetFeedback(FeedbackMode::Flash), &mut ps);
+        handle(Command::FormatRedrawPrompt, &mut ps);
+        let out = String::from_utf8(handle(Command::Feedback, &mut ps)).unwrap();
+        assert!(out.contains("\x1b[7m"));
+        assert!(ps.time_until_flash_end().is_some());
+    }
+
+    #[test]
+    fn mouse_events_in_region() {
+        use super::commands_for_event;
+        use crate::minus_core::utils::display::Region;
+        use crossterm::event::{Event, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+
+        let mut ps = PagerState::new().unwrap();
+        ps.region = Some(Region {
+            x: 10,
+            y: 2,
+            cols: 40,
+            rows: 8,
+        });
+        let mut click = |column, row| {
+            let ev = Event::Mouse(MouseEvent {
+                kind: MouseEventKind::Down(MouseButton::Left),
+                column,
+                row,
+                modifiers: KeyModifiers::NONE,
+            });
+            commands_for_event(
+                ev,
+                &mut ps,
+                #[cfg(feature = "__search")]
+                &UIA,
+            )
+        };
+        assert_eq!(click(15, 4), [Command::UserInput(InputEvent::Click(2, 5))]);
+        // Clicks outside of the region are ignored
+        assert!(click(5, 4).is_empty());
+        assert!(click(15, 10).is_empty());
+    }
+
+    #[test]
+    fn macros() {
+        use super::commands_for_event;
+        use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+
+        let mut ps = PagerState::new().unwrap();
+        ps.screen.orig_text = "line\n".repeat(100);
+        ps.format_lines();
+        let type_key = |c: char, ps: &mut PagerState| {
+            let ev = Event::Key(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE));
+            let commands = commands_for_event(
+                ev,
+                ps,
+                #[cfg(feature = "__search")]
+                &UIA,
+            );
+            let mut command_queue = CommandQueue::new_zero();
+            for command in commands {
+                handle_event(
+                    command,
+                    &mut Vec::new(),
+                    ps,
+                    &mut command_queue,
+                    &Arc::new(AtomicBool::new(false)),
+                    #[cfg(feature = "__search")]
+                    &UIA,
+                )
+                .unwrap();
+            }
+        };
+
+        ps.macros.enabled = true;
+        type_key('q', &mut ps);
+        type_key('a', &mut ps);
+        assert_eq!(ps.macros.recording(), Some('a'));
+        for c in "jjq".chars() {
+            type_key(c, &mut ps);
+        }
+        assert_eq!(ps.upper_mark, 2);
+        assert_eq!(ps.macros.recording(), None);
+
+        // Each key of the macro sees the changes made by the ones before it
+        for c in "3@a".chars() {
+            type_key(c, &mut ps);
+        }
+        assert_eq!(ps.upper_mark, 8);
+        assert!(ps.prefix_num.is_empty());
+        assert!(!ps.macros.replaying);
     }
 }
@@ -1,42 +1,55 @@
 //! Contains functions that initialize minus
 //!
-//! This module provides two main functions:-
+//! This module provides three main functions:-
 //! * The [`init_core`] function which is responsible for setting the initial state of the
-//! Pager, do environment checks and initializing various core functions on either async
-//! tasks or native threads depending on the feature set
+//! Pager, do environment checks and initializing various core functions on native threads
+//!
+//! * The `init_core_async` function which does the same from async code. The reactor runs in
+//!   the calling task and the user input is read from an async event source when the backend
+//!   has one
 //!
 //! * The [`start_reactor`] function displays the displays the output and also polls
-//! the [`Receiver`] held inside the [`Pager`] for events. Whenever a event is
+//! the [`CommandReceiver`] held inside the [`Pager`] for events. Whenever a event is
 //! detected, it reacts to it accordingly.
 #[cfg(feature = "static_output")]
 use crate::minus_core::utils::display;
 use crate::{
+    backend::{Backend, BackendWriter},
     error::MinusError,
     input::InputEvent,
     minus_core::{
+        channel::{CommandReceiver, CommandSender},
         commands::Command,
-        ev_handler::handle_event,
-        utils::{display::draw_full, term},
+        ev_handler::{commands_for_event, handle_event},
+        utils::{
+            display::{draw_full, PlainWriter, RegionWriter},
+            strip_ansi,
+            term::{self, TermModes},
+        },
         RunMode,
     },
-    Pager, PagerState,
+    ExitReason, Pager, PagerExit, PagerState, WindowTitle,
 };
 
-use crossbeam_channel::{Receiver, Sender, TrySendError};
-use crossterm::event;
+use crossbeam_channel::{RecvError, RecvTimeoutError, Select};
 use std::{
-    io::{stdout, Stdout},
+    io::Write,
     panic,
     sync::{
         atomic::{AtomicBool, Ordering},
         Arc,
     },
+    time::{Duration, Instant},
 };
 
 #[cfg(feature = "static_output")]
-use {super::utils::display::write_raw_lines, crossterm::tty::IsTty};
+use super::utils::display::write_raw_lines;
+#[cfg(all(feature = "dynamic_output", feature = "tokio"))]
+use crate::backend::EventStream;
+#[cfg(all(feature = "dynamic_output", feature = "tokio", feature = "__search"))]
+use tokio::sync::Notify;
 
-#[cfg(feature = "search")]
+#[cfg(feature = "__search")]
 use parking_lot::Condvar;
 use parking_lot::Mutex;
 
@@ -55,9 +68,9 @@ use super::{utils::display::draw_for_change, CommandQueue, RUNMODE};
 /// device, minus will write all the data at once to the stdout and quit
 ///
 /// * If the size of the data is less than the available number of rows in the terminal
-/// then it displays everything on the main stdout screen at once and quits. This
-/// behaviour can be turned off if [`Pager::set_run_no_overflow(true)`] is called
-/// by the main application
+/// then it displays everything on the main stdout screen at once and quits. The number of
+/// rows can be changed with [`Pager::set_pager_threshold`] and this behaviour can be turned
+/// off if [`Pager::force_pager(true)`] is called by the main application
 // Sorry... this behaviour would have been cool to have in async mode, just think about it!!! Many
 // implementations were proposed but none were perfect
 // It is because implementing this especially with line wrapping and terminal scrolling
@@ -68,6 +81,8 @@ use super::{utils::display::draw_for_change, CommandQueue, RUNMODE};
 /// Next it initializes the runtime and calls [`start_reactor`] and a [`event reader`]` which is
 /// selected based on the enabled feature set:-
 ///
+/// Once minus quits, it returns a [`PagerExit`] describing why it quit.
+///
 /// # Errors
 ///
 /// Setting/cleaning up the terminal can fail and IO to/from the terminal can
@@ -75,16 +90,246 @@ use super::{utils::display::draw_for_change, CommandQueue, RUNMODE};
 ///
 /// [`event reader`]: event_reader
 #[allow(clippy::module_name_repetitions)]
-#[allow(clippy::too_many_lines)]
-pub fn init_core(pager: &Pager, rm: RunMode) -> std::result::Result<(), MinusError> {
-    #[allow(unused_mut)]
-    let mut out = stdout();
-    // Is the event reader running
-    #[cfg(feature = "search")]
-    let input_thread_running = Arc::new((Mutex::new(true), Condvar::new()));
+pub fn init_core(pager: &Pager, rm: RunMode) -> std::result::Result<PagerExit, MinusError> {
+    let running = match setup(pager, rm)? {
+        Setup::Exited(exit) => return Ok(exit),
+        Setup::Ready(running) => running,
+    };
+    let (backend, term_modes) = running.terminal();
+
+    // User input has its own channel so that it isn't held up behind the data of a bounded pager.
+    // The sender is kept here until the pager has quit so that the channel never disconnects.
+    let (input_tx, input_rx) = pager.rx.unbounded_sibling();
+
+    std::thread::scope(|s| -> crate::Result {
+        let t1 = s.spawn(|| {
+            let res = event_reader(
+                &input_tx,
+                &running.ps,
+                #[cfg(feature = "__search")]
+                &running.input_thread_running,
+                &running.is_exited,
+            );
+            abort_on_error(
+                res,
+                "failed to read user input",
+                &running.is_exited,
+                backend.as_ref(),
+                &term_modes,
+            )
+        });
+        let t2 = s.spawn(|| {
+            let res = start_reactor(
+                &Inbox {
+                    input: &input_rx,
+                    data: &pager.rx,
+                },
+                &running.ps,
+                backend.as_ref(),
+                #[cfg(feature = "__search")]
+                &running.input_thread_running,
+                &running.is_exited,
+            );
+            abort_on_error(
+                res,
+                "failed to handle a command",
+                &running.is_exited,
+                backend.as_ref(),
+                &term_modes,
+            )
+        });
 
+        let r1 = t1.join().unwrap();
+        let r2 = t2.join().unwrap();
+
+        r1?;
+        r2?;
+        Ok(())
+    })?;
+    drop(input_tx);
+
+    Ok(running.exit())
+}
+
+/// The main entry point of minus in async code
+///
+/// This is called by [`async_paging`](crate::async_paging). The pager is set up just like
+/// [`init_core`] does but the reactor runs in the calling task with [`start_reactor_async`],
+/// waiting for commands and timers without blocking the thread.
+///
+/// The user input is read from [`Backend::event_stream`] in the same task when the backend
+/// provides a stream. Otherwise [`event_reader`] polls the backend on a thread of its own like it
+/// does with [`init_core`].
+///
+/// # Errors
+///
+/// Setting/cleaning up the terminal can fail and IO to/from the terminal can
+/// fail.
+#[cfg(all(feature = "dynamic_output", feature = "tokio"))]
+pub async fn init_core_async(pager: &Pager) -> std::result::Result<PagerExit, MinusError> {
+    let running = match setup(pager, RunMode::Dynamic)? {
+        Setup::Exited(exit) => return Ok(exit),
+        Setup::Ready(running) => running,
+    };
+    let (backend, term_modes) = running.terminal();
+    let (input_tx, input_rx) = pager.rx.unbounded_sibling();
+    #[cfg(feature = "__search")]
+    let handled = Notify::new();
+
+    let reactor = async {
+        let res = start_reactor_async(
+            &Inbox {
+                input: &input_rx,
+                data: &pager.rx,
+            },
+            &running.ps,
+            backend.as_ref(),
+            #[cfg(feature = "__search")]
+            &running.input_thread_running,
+            #[cfg(feature = "__search")]
+            &handled,
+            &running.is_exited,
+        )
+        .await;
+        abort_on_error(
+            res,
+            "failed to handle a command",
+            &running.is_exited,
+            backend.as_ref(),
+            &term_modes,
+        )
+    };
+
+    if let Some(mut events) = backend.event_stream() {
+        let reader = async {
+            let res = read_event_stream(
+                &mut events,
+                &input_tx,
+                &running.ps,
+                #[cfg(feature = "__search")]
+                &running.input_thread_running,
+                #[cfg(feature = "__search")]
+                &handled,
+            )
+            .await;
+            abort_on_error(
+                res,
+                "failed to read user input",
+                &running.is_exited,
+                backend.as_ref(),
+                &term_modes,
+            )
+        };
+        // The stream never ends on its own, hence it is simply dropped once the pager quits
+        run_with_reader(reactor, reader).await.0?;
+    } else {
+        let (done_tx, mut done_rx) = tokio::sync::oneshot::channel();
+        {
+            let input_tx = input_tx.clone();
+            let ps = running.ps.clone();
+            #[cfg(feature = "__search")]
+            let input_thread_running = running.input_thread_running.clone();
+            let is_exited = running.is_exited.clone();
+            let backend = backend.clone();
+            let term_modes = term_modes.clone();
+            std::thread::spawn(move || {
+                let res = event_reader(
+                    &input_tx,
+                    &ps,
+                    #[cfg(feature = "__search")]
+                    &input_thread_running,
+                    &is_exited,
+                );
+                let res = abort_on_error(
+                    res,
+                    "failed to read user input",
+                    &is_exited,
+                    backend.as_ref(),
+                    &term_modes,
+                );
+                done_tx.send(res).ok();
+            });
+        }
+        let reader = async { (&mut done_rx).await.unwrap_or(Ok(())) };
+        let (res, reader_finished) = run_with_reader(reactor, reader).await;
+        // The thread stops within a poll timeout of the pager having quit
+        if !reader_finished {
+            done_rx.await.ok();
+        }
+        res?;
+    }
+    drop(input_tx);
+
+    Ok(running.exit())
+}
+
+/// Drive `reactor` along with `reader` until the reactor stops or either of them fails
+///
+/// Returns the result along with whether `reader` has finished as well.
+#[cfg(all(feature = "dynamic_output", feature = "tokio"))]
+async fn run_with_reader(
+    reactor: impl std::future::Future<Output = crate::Result>,
+    reader: impl std::future::Future<Output = crate::Result>,
+) -> (crate::Result, bool) {
+    tokio::pin!(reactor, reader);
+    let mut reading = true;
+    loop {
+        tokio::select! {
+            res = &mut reactor => return (res, !reading),
+            res = &mut reader, if reading => {
+                if res.is_err() {
+                    return (res, true);
+                }
+                reading = false;
+            }
+        }
+    }
+}
+
+/// Result of [`setup`]
+enum Setup {
+    /// The pager quit without needing to be run
+    Exited(PagerExit),
+    /// The terminal has been set up and the pager is ready to run
+    Ready(ReadyPager),
+}
+
+/// A pager that has been set up and is ready to run
+struct ReadyPager {
+    ps: Arc<Mutex<PagerState>>,
+    /// Has the user quit
+    is_exited: Arc<AtomicBool>,
+    /// Is the event reader running
+    #[cfg(feature = "__search")]
+    input_thread_running: Arc<(Mutex<bool>, Condvar)>,
+}
+
+impl ReadyPager {
+    /// The backend and the modes of the terminal that need to be cleaned up if the pager fails
+    fn terminal(&self) -> (Arc<dyn Backend>, Arc<TermModes>) {
+        let p = self.ps.lock();
+        (p.backend.clone(), p.term_modes.clone())
+    }
+
+    /// Describe why the pager quit
+    fn exit(&self) -> PagerExit {
+        let mut p = self.ps.lock();
+        PagerExit {
+            reason: p.exit_reason.take().unwrap_or(ExitReason::UserQuit),
+            top_line: p.top_line(),
+        }
+    }
+}
+
+/// Create the initial state, do the static mode checks and set up the terminal
+///
+/// See [`init_core`] for the details.
+#[allow(clippy::too_many_lines)]
+fn setup(pager: &Pager, rm: RunMode) -> std::result::Result<Setup, MinusError> {
     #[allow(unused_mut)]
-    let mut ps = crate::state::PagerState::generate_initial_state(&pager.rx, &mut out)?;
+    let mut ps = crate::state::PagerState::generate_initial_state(&pager.rx)?;
+    let backend = ps.backend.clone();
+    let term_modes = ps.term_modes.clone();
 
     {
         let mut runmode = super::RUNMODE.lock();
@@ -93,31 +338,72 @@ pub fn init_core(pager: &Pager, rm: RunMode) -> std::result::Result<(), MinusErr
         drop(runmode);
     }
 
+    // The application has already asked to quit, hence don't even bother setting up the terminal
+    if ps.quit_requested || ps.is_cancelled() {
+        ps.exit();
+        *RUNMODE.lock() = RunMode::Uninitialized;
+        return Ok(Setup::Exited(PagerExit {
+            reason: ExitReason::AppQuit,
+            top_line: ps.top_line(),
+        }));
+    }
+
     // Static mode checks
     #[cfg(feature = "static_output")]
     if *RUNMODE.lock() == RunMode::Static {
         // If stdout is not a tty, write everything and quit
-        if !out.is_tty() {
-            write_raw_lines(&mut out, &[ps.screen.orig_text], None)?;
+        if !backend.is_tty() {
+            write_raw_lines(
+                &mut PlainWriter::new(BackendWriter(backend.as_ref()), ps.plain_render),
+                &[ps.screen.orig_text],
+                None,
+            )
+            .map_err(MinusError::or_terminal_gone)?;
             let mut rm = RUNMODE.lock();
             *rm = RunMode::Uninitialized;
             drop(rm);
-            return Ok(());
+            return Ok(Setup::Exited(PagerExit {
+                reason: ExitReason::Eof,
+                top_line: 1,
+            }));
         }
         // If number of lines of text is less than available rows, write everything and quit
-        // unless run_no_overflow is set to true
-        if ps.screen.formatted_lines_count() <= ps.rows && !ps.run_no_overflow {
-            write_raw_lines(&mut out, &ps.screen.formatted_lines, Some("\r"))?;
+        // unless the pager is forced to start
+        let threshold = ps.pager_threshold.unwrap_or(ps.rows);
+        if ps.screen.formatted_lines_count() <= threshold && !ps.force_pager {
+            write_raw_lines(
+                &mut PlainWriter::new(BackendWriter(backend.as_ref()), ps.plain_render),
+                &ps.display_rows(0, ps.screen.formatted_lines_count()),
+                Some("\r"),
+            )
+            .map_err(MinusError::or_terminal_gone)?;
             ps.exit();
             let mut rm = RUNMODE.lock();
             *rm = RunMode::Uninitialized;
             drop(rm);
-            return Ok(());
+            return Ok(Setup::Exited(PagerExit {
+                reason: ExitReason::Eof,
+                top_line: 1,
+            }));
         }
     }
 
-    // Setup terminal, adjust line wraps and get rows
-    term::setup(&out)?;
+    // Detect what the terminal supports, setup terminal, adjust line wraps and get rows
+    if backend.is_tty() {
+        ps.capabilities = backend.capabilities();
+        if !ps.keyboard_enhancement.is_empty() {
+            if let Some(supported) = backend.supports_keyboard_enhancement() {
+                ps.capabilities.kitty_keyboard = supported;
+            }
+        }
+    }
+    term::setup(
+        backend.as_ref(),
+        ps.keyboard_enhancement,
+        ps.capabilities,
+        ps.alternate_scroll,
+        &term_modes,
+    )?;
 
     // Has the user quit
     let is_exited = Arc::new(AtomicBool::new(false));
@@ -129,77 +415,128 @@ pub fn init_core(pager: &Pager, rm: RunMode) -> std::result::Result<(), MinusErr
             is_exited2.store(true, std::sync::atomic::Ordering::SeqCst);
             // While silently ignoring error is considered a bad practice, we are forced to do it here
             // as we cannot use the ? and panicking here will cause UB.
-            drop(term::cleanup(
-                stdout(),
+            let res = term::cleanup(
+                backend.as_ref(),
+                &term_modes,
                 &crate::ExitStrategy::PagerQuit,
                 true,
-            ));
+            );
+            #[cfg(feature = "tracing")]
+            if let Err(e) = &res {
+                tracing::warn!(error = %e, "failed to clean up the terminal after a panic");
+            }
+            drop(res);
             panic_hook(pinfo);
         }));
     }
 
     let ps_mutex = Arc::new(Mutex::new(ps));
+    // Let the Pager read the state while we are running
+    *pager.state.lock() = Some(ps_mutex.clone());
 
-    let evtx = pager.tx.clone();
-    let rx = pager.rx.clone();
-    let out = stdout();
-
-    let p1 = ps_mutex.clone();
+    Ok(Setup::Ready(ReadyPager {
+        ps: ps_mutex,
+        is_exited,
+        #[cfg(feature = "__search")]
+        input_thread_running: Arc::new((Mutex::new(true), Condvar::new())),
+    }))
+}
 
-    #[cfg(feature = "search")]
-    let input_thread_running2 = input_thread_running.clone();
+/// Stop the pager if `res`, the result of the reactor or the event reader, is an error
+///
+/// The terminal is cleaned up and `message` is logged along with the error.
+#[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+fn abort_on_error(
+    res: crate::Result,
+    message: &str,
+    is_exited: &AtomicBool,
+    backend: &dyn Backend,
+    term_modes: &TermModes,
+) -> crate::Result {
+    let Err(e) = res.map_err(MinusError::or_terminal_gone) else {
+        return Ok(());
+    };
+    #[cfg(feature = "tracing")]
+    tracing::error!(error = %e, "{message}");
+    is_exited.store(true, std::sync::atomic::Ordering::SeqCst);
+    let mut rm = RUNMODE.lock();
+    *rm = RunMode::Uninitialized;
+    drop(rm);
+    let cleanup = term::cleanup(backend, term_modes, &crate::ExitStrategy::PagerQuit, true);
+    // Cleaning up a terminal that is gone is bound to fail, hence it is only done on a
+    // best-effort basis
+    if !matches!(e, MinusError::TerminalGone) {
+        cleanup?;
+    }
+    Err(e)
+}
 
-    std::thread::scope(|s| -> crate::Result {
-        let out = Arc::new(out);
-        let out_copy = out.clone();
-        let is_exited3 = is_exited.clone();
-        let is_exited4 = is_exited.clone();
+/// Output of the reactor along with what it keeps track of between commands
+struct Reactor<W: Write> {
+    out: PlainWriter<RegionWriter<W>>,
+    command_queue: CommandQueue,
+    scrolls: ScrollCoalescer,
+    /// The title set on the window, if any
+    window_title: Option<String>,
+}
 
-        let t1 = s.spawn(move || {
-            let res = event_reader(
-                &evtx,
-                &p1,
-                #[cfg(feature = "search")]
-                &input_thread_running2,
-                &is_exited3,
-            );
+impl<W: Write> Reactor<W> {
+    /// Draw the pager on `out` for the first time
+    fn start(out: W, ps: &Mutex<PagerState>) -> Result<Self, MinusError> {
+        let mut reactor = Self {
+            out: PlainWriter::new(RegionWriter::new(out), false),
+            command_queue: CommandQueue::new(),
+            scrolls: ScrollCoalescer::default(),
+            window_title: None,
+        };
+        let mut p = ps.lock();
 
-            if res.is_err() {
-                is_exited3.store(true, std::sync::atomic::Ordering::SeqCst);
-                let mut rm = RUNMODE.lock();
-                *rm = RunMode::Uninitialized;
-                drop(rm);
-                term::cleanup(out.as_ref(), &crate::ExitStrategy::PagerQuit, true)?;
-            }
-            res
-        });
-        let t2 = s.spawn(move || {
-            let res = start_reactor(
-                &rx,
-                &ps_mutex,
-                &out_copy,
-                #[cfg(feature = "search")]
-                &input_thread_running,
-                &is_exited4,
-            );
+        reactor.out.plain = p.plain_render;
+        reactor.out.get_mut().region = p.region;
+        draw_full(&mut reactor.out, &mut p)?;
+        apply_window_title(&mut reactor.out, &p, &mut reactor.window_title)?;
 
-            if res.is_err() {
-                is_exited4.store(true, std::sync::atomic::Ordering::SeqCst);
-                let mut rm = RUNMODE.lock();
-                *rm = RunMode::Uninitialized;
-                drop(rm);
-                term::cleanup(out_copy.as_ref(), &crate::ExitStrategy::PagerQuit, true)?;
-            }
-            res
-        });
+        if p.follow_output {
+            draw_for_change(&mut reactor.out, &mut p, &mut (usize::MAX - 1))?;
+        }
 
-        let r1 = t1.join().unwrap();
-        let r2 = t2.join().unwrap();
+        // Fill the screen if a data provider has been set
+        if let Some(text) = p.request_data() {
+            reactor
+                .command_queue
+                .push_back_unchecked(Command::AppendData(text));
+        }
+        drop(p);
+        Ok(reactor)
+    }
 
-        r1?;
-        r2?;
+    /// Handle `command` and apply the settings it changed to the output
+    fn react(
+        &mut self,
+        command: Command,
+        ps: &Mutex<PagerState>,
+        #[cfg(feature = "__search")] input_thread_running: &Arc<(Mutex<bool>, Condvar)>,
+        is_exited: &Arc<AtomicBool>,
+    ) -> Result<(), MinusError> {
+        let mut p = ps.lock();
+        self.scrolls.restore(&mut p);
+        handle_event(
+            command,
+            &mut self.out,
+            &mut p,
+            &mut self.command_queue,
+            is_exited,
+            #[cfg(feature = "__search")]
+            input_thread_running,
+        )?;
+        apply_plain_render(&mut self.out, &mut p)?;
+        // The terminal has been cleaned up once the pager quits
+        if !is_exited.load(Ordering::SeqCst) {
+            apply_region(&mut self.out, &p)?;
+            apply_window_title(&mut self.out, &p, &mut self.window_title)?;
+        }
         Ok(())
-    })
+    }
 }
 
 /// Continuously displays the output and reacts to events
@@ -215,26 +552,14 @@ pub fn init_core(pager: &Pager, rm: RunMode) -> std::result::Result<(), MinusErr
 /// [`AppendData`](super::events::Event::AppendData) event occurs, it is absolutely necessory
 /// to update the screen immediately; while if all rows are filled, we can omit to redraw the
 /// screen.
-#[allow(clippy::too_many_lines)]
 fn start_reactor(
-    rx: &Receiver<Command>,
+    rx: &Inbox<'_>,
     ps: &Arc<Mutex<PagerState>>,
-    out: &Stdout,
-    #[cfg(feature = "search")] input_thread_running: &Arc<(Mutex<bool>, Condvar)>,
+    backend: &dyn Backend,
+    #[cfg(feature = "__search")] input_thread_running: &Arc<(Mutex<bool>, Condvar)>,
     is_exited: &Arc<AtomicBool>,
 ) -> Result<(), MinusError> {
-    let mut out_lock = out.lock();
-    let mut command_queue = CommandQueue::new();
-
-    {
-        let mut p = ps.lock();
-
-        draw_full(&mut out_lock, &mut p)?;
-
-        if p.follow_output {
-            draw_for_change(&mut out_lock, &mut p, &mut (usize::MAX - 1))?;
-        }
-    }
+    let mut reactor = Reactor::start(BackendWriter(backend), ps)?;
 
     let run_mode = *RUNMODE.lock();
     match run_mode {
@@ -247,22 +572,16 @@ fn start_reactor(
                 break;
             }
 
-            let next_command = if command_queue.is_empty() {
-                rx.recv()
-            } else {
-                Ok(command_queue.pop_front().unwrap())
-            };
+            let next_command =
+                next_command(rx, ps, &mut reactor.command_queue, &mut reactor.scrolls);
 
             if let Ok(command) = next_command {
-                let mut p = ps.lock();
-                handle_event(
+                reactor.react(
                     command,
-                    &mut out_lock,
-                    &mut p,
-                    &mut command_queue,
-                    is_exited,
-                    #[cfg(feature = "search")]
+                    ps,
+                    #[cfg(feature = "__search")]
                     input_thread_running,
+                    is_exited,
                 )?;
             }
         },
@@ -271,7 +590,7 @@ fn start_reactor(
             {
                 let mut p = ps.lock();
                 if p.follow_output {
-                    display::draw_for_change(&mut out_lock, &mut p, &mut (usize::MAX - 1))?;
+                    display::draw_for_change(&mut reactor.out, &mut p, &mut (usize::MAX - 1))?;
                 }
             }
 
@@ -280,7 +599,9 @@ fn start_reactor(
                     // Cleanup the screen
                     //
                     // This is not needed in dynamic paging because this is already handled by handle_event
-                    term::cleanup(&mut out_lock, &ps.lock().exit_strategy, true)?;
+                    let p = ps.lock();
+                    term::cleanup(backend, &p.term_modes, &p.exit_strategy, true)?;
+                    drop(p);
 
                     let mut rm = RUNMODE.lock();
                     *rm = RunMode::Uninitialized;
@@ -288,22 +609,16 @@ fn start_reactor(
 
                     break;
                 }
-                let next_command = if command_queue.is_empty() {
-                    rx.recv()
-                } else {
-                    Ok(command_queue.pop_front().unwrap())
-                };
+                let next_command =
+                    next_command(rx, ps, &mut reactor.command_queue, &mut reactor.scrolls);
 
                 if let Ok(command) = next_command {
-                    let mut p = ps.lock();
-                    handle_event(
+                    reactor.react(
                         command,
-                        &mut out_lock,
-                        &mut p,
-                        &mut command_queue,
-                        is_exited,
-                        #[cfg(feature = "search")]
+                        ps,
+                        #[cfg(feature = "__search")]
                         input_thread_running,
+                        is_exited,
                     )?;
                 }
             }
@@ -316,18 +631,445 @@ This is most likely a bug. Please open an issue to the developers"
     Ok(())
 }
 
+/// Same as [`start_reactor`] but waits for the commands and timers without blocking the thread
+///
+/// Handling a command still blocks, hence the search prompt, which reads the user input on its
+/// own, blocks the task while it is open. `handled` is notified after every command.
+#[cfg(all(feature = "dynamic_output", feature = "tokio"))]
+async fn start_reactor_async(
+    rx: &Inbox<'_>,
+    ps: &Arc<Mutex<PagerState>>,
+    backend: &dyn Backend,
+    #[cfg(feature = "__search")] input_thread_running: &Arc<(Mutex<bool>, Condvar)>,
+    #[cfg(feature = "__search")] handled: &Notify,
+    is_exited: &Arc<AtomicBool>,
+) -> Result<(), MinusError> {
+    let mut reactor = Reactor::start(BackendWriter(backend), ps)?;
+
+    loop {
+        if is_exited.load(Ordering::SeqCst) {
+            let mut rm = RUNMODE.lock();
+            *rm = RunMode::Uninitialized;
+            drop(rm);
+            break;
+        }
+
+        let command = if let Some(command) = reactor.command_queue.pop_front() {
+            command
+        } else {
+            let command = recv_command_async(rx, ps).await;
+            reactor
+                .scrolls
+                .coalesce_async(command, rx, ps, &mut reactor.command_queue)
+                .await
+        };
+        reactor.react(
+            command,
+            ps,
+            #[cfg(feature = "__search")]
+            input_thread_running,
+            is_exited,
+        )?;
+        #[cfg(feature = "__search")]
+        handled.notify_one();
+    }
+    Ok(())
+}
+
+/// Start or stop removing the styling from the output according to [`PagerState::plain_render`]
+///
+/// The screen is redrawn whenever this changes so that it matches the new setting.
+fn apply_plain_render(
+    out: &mut PlainWriter<impl std::io::Write>,
+    ps: &mut PagerState,
+) -> Result<(), MinusError> {
+    if out.plain == ps.plain_render {
+        return Ok(());
+    }
+    out.plain = ps.plain_render;
+    draw_full(out, ps)
+}
+
+/// Move the output into [`PagerState::region`] when it changes
+///
+/// The area covered by the previous region is cleared. The pager is redrawn in the new one once
+/// it has been resized to it.
+fn apply_region(
+    out: &mut PlainWriter<RegionWriter<impl std::io::Write>>,
+    ps: &PagerState,
+) -> Result<(), MinusError> {
+    if out.get_mut().region == ps.region {
+        return Ok(());
+    }
+    term::clear_entire_screen(out, true)?;
+    out.get_mut().region = ps.region;
+    Ok(())
+}
+
+/// Set the title of the window according to [`PagerState::window_title`]
+///
+/// `current` is the title set on the window, which is only written again when it changes.
+fn apply_window_title(
+    out: &mut impl std::io::Write,
+    ps: &PagerState,
+    current: &mut Option<String>,
+) -> Result<(), MinusError> {
+    // Nothing but a terminal has a window title
+    if !ps.backend.is_tty() {
+        return Ok(());
+    }
+    let title = match &ps.window_title {
+        WindowTitle::Unchanged => None,
+        WindowTitle::Prompt => Some(strip_ansi(&ps.prompt).into_owned()),
+        WindowTitle::Text(text) => Some(text.clone()),
+    };
+    if title != *current {
+        term::set_window_title(out, &ps.term_modes, title.as_deref())?;
+        *current = title;
+    }
+    Ok(())
+}
+
+/// How often the cancellation flag is checked while waiting for commands
+const CANCELLATION_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Minimum time between two redraws caused by scrolling
+const SCROLL_FRAME_BUDGET: Duration = Duration::from_millis(16);
+
+/// Get the next command to be handled by the reactor
+///
+/// Commands present in the `command_queue` are given priority over the ones in the channel. If
+/// automatic reloading is turned on, this generates a [`InputEvent::Reload`] when the reload
+/// interval elapses before any other command is received. Similarly, this generates a
+/// [`Command::Quit`] as soon as the cancellation flag is set. Runs of scroll commands are merged
+/// together, see [`ScrollCoalescer`].
+fn next_command(
+    rx: &Inbox<'_>,
+    ps: &Arc<Mutex<PagerState>>,
+    command_queue: &mut CommandQueue,
+    scrolls: &mut ScrollCoalescer,
+) -> Result<Command, RecvError> {
+    if !command_queue.is_empty() {
+        return Ok(command_queue.pop_front().unwrap());
+    }
+    let command = recv_command(rx, ps)?;
+    Ok(scrolls.coalesce(command, rx, ps, command_queue))
+}
+
+/// Merges runs of scroll commands so that at most one scroll is drawn per frame
+///
+/// Fast scrolling with a trackpad generates hundreds of events and drawing each of them queues up
+/// far more work than the terminal can show. As [`InputEvent::UpdateUpperMark`] carries the
+/// absolute position, only the last one of a run needs to be drawn.
+#[derive(Default)]
+struct ScrollCoalescer {
+    /// When the last scroll was drawn
+    last_draw: Option<Instant>,
+    /// Upper mark that is actually on the screen while the state holds a merged one
+    drawn_upper_mark: Option<usize>,
+}
+
+impl ScrollCoalescer {
+    /// Merge the scroll commands received within [`SCROLL_FRAME_BUDGET`] of the previous scroll
+    /// into `command`
+    ///
+    /// The first command that is not a scroll ends the run and is put in the `command_queue` to be
+    /// handled next. Bindings compute the new upper mark from the current one, hence each merged
+    /// upper mark is stored in the state right away so that the following scrolls build on it.
+    fn coalesce(
+        &mut self,
+        command: Command,
+        rx: &Inbox<'_>,
+        ps: &Arc<Mutex<PagerState>>,
+        command_queue: &mut CommandQueue,
+    ) -> Command {
+        let Command::UserInput(InputEvent::UpdateUpperMark(mut upper_mark)) = command else {
+            return command;
+        };
+        let deadline = self.deadline();
+        loop {
+            let next = deadline.map_or_else(
+                || rx.try_recv(),
+                |deadline| rx.recv_deadline(Some(deadline)).ok(),
+            );
+            if !self.merge(next, &mut upper_mark, ps, command_queue) {
+                break;
+            }
+        }
+        self.last_draw = Some(Instant::now());
+        Command::UserInput(InputEvent::UpdateUpperMark(upper_mark))
+    }
+
+    /// Same as [`ScrollCoalescer::coalesce`] but waits for the scrolls without blocking the thread
+    #[cfg(all(feature = "dynamic_output", feature = "tokio"))]
+    async fn coalesce_async(
+        &mut self,
+        command: Command,
+        rx: &Inbox<'_>,
+        ps: &Arc<Mutex<PagerState>>,
+        command_queue: &mut CommandQueue,
+    ) -> Command {
+        let Command::UserInput(InputEvent::UpdateUpperMark(mut upper_mark)) = command else {
+            return command;
+        };
+        let deadline = self.deadline();
+        loop {
+            let next = match deadline {
+                Some(deadline) => rx.recv_deadline_async(Some(deadline)).await.ok(),
+                None => rx.try_recv(),
+            };
+            if !self.merge(next, &mut upper_mark, ps, command_queue) {
+                break;
+            }
+        }
+        self.last_draw = Some(Instant::now());
+        Command::UserInput(InputEvent::UpdateUpperMark(upper_mark))
+    }
+
+    /// End of the frame of the last scroll, if it hasn't passed yet
+    fn deadline(&self) -> Option<Instant> {
+        self.last_draw
+            .map(|t| t + SCROLL_FRAME_BUDGET)
+            .filter(|d| *d > Instant::now())
+    }
+
+    /// Merge `next` into the run of scrolls ending at `upper_mark`
+    ///
+    /// Returns whether the run goes on.
+    fn merge(
+        &mut self,
+        next: Option<Command>,
+        upper_mark: &mut usize,
+        ps: &Arc<Mutex<PagerState>>,
+        command_queue: &mut CommandQueue,
+    ) -> bool {
+        match next {
+            Some(Command::UserInput(InputEvent::UpdateUpperMark(um))) => {
+                let mut p = ps.lock();
+                self.drawn_upper_mark.get_or_insert(p.upper_mark);
+                p.upper_mark = um;
+                drop(p);
+                *upper_mark = um;
+                true
+            }
+            Some(command) => {
+                command_queue.push_back_unchecked(command);
+                false
+            }
+            None => false,
+        }
+    }
+
+    /// Put back the upper mark that is on the screen so that the merged scroll is drawn correctly
+    ///
+    /// This must be called with the same lock under which the next command is handled.
+    fn restore(&mut self, ps: &mut PagerState) {
+        if let Some(um) = self.drawn_upper_mark.take() {
+            ps.upper_mark = um;
+        }
+    }
+}
+
+/// Receiving ends of the channels from which the reactor gets its commands
+///
+/// User input comes through its own unbounded channel and is received before anything else, hence
+/// key presses are handled right away even when a [bounded](Pager::bounded) pager is full of data.
+struct Inbox<'a> {
+    /// Commands generated from the user input by [`event_reader`]
+    input: &'a CommandReceiver,
+    /// Data and commands sent by the application through the [`Pager`]
+    data: &'a CommandReceiver,
+}
+
+impl Inbox<'_> {
+    /// Get a command if one is waiting
+    fn try_recv(&self) -> Option<Command> {
+        self.input.try_recv().or_else(|_| self.data.try_recv()).ok()
+    }
+
+    /// Wait for a command until `deadline`, or for as long as needed if it is `None`
+    fn recv_deadline(&self, deadline: Option<Instant>) -> Result<Command, RecvTimeoutError> {
+        if let Some(command) = self.try_recv() {
+            return Ok(command);
+        }
+        let mut select = Select::new();
+        let input = select.recv(self.input.inner());
+        select.recv(self.data.inner());
+        let operation = match deadline {
+            Some(deadline) => select
+                .select_deadline(deadline)
+                .map_err(|_| RecvTimeoutError::Timeout)?,
+            None => select.select(),
+        };
+        let rx = if operation.index() == input {
+            self.input
+        } else {
+            self.data
+        };
+        let command = operation
+            .recv(rx.inner())
+            .map_err(|_| RecvTimeoutError::Disconnected)?;
+        rx.received();
+        Ok(command)
+    }
+
+    /// Same as [`Inbox::recv_deadline`] but waits without blocking the thread
+    ///
+    /// The channels are never disconnected while the pager runs, hence this only fails on a
+    /// timeout.
+    #[cfg(all(feature = "dynamic_output", feature = "tokio"))]
+    async fn recv_deadline_async(
+        &self,
+        deadline: Option<Instant>,
+    ) -> Result<Command, RecvTimeoutError> {
+        loop {
+            if let Some(command) = self.try_recv() {
+                return Ok(command);
+            }
+            match deadline {
+                Some(deadline) => tokio::time::timeout_at(deadline.into(), self.data.sent())
+                    .await
+                    .map_err(|_| RecvTimeoutError::Timeout)?,
+                None => self.data.sent().await,
+            }
+        }
+    }
+}
+
+/// Timers of the reactor which generate commands when they are due
+struct Timers {
+    reload: Option<Duration>,
+    scroll_frame: Option<Duration>,
+    flash_end: Option<Duration>,
+    redraw: Option<Duration>,
+    idle: Option<Duration>,
+    cancellable: bool,
+}
+
+impl Timers {
+    /// Read the timers from the state
+    ///
+    /// Fails with the [`Command::Quit`] to handle right away if the pager has been cancelled.
+    fn read(ps: &Mutex<PagerState>) -> Result<Self, Command> {
+        let p = ps.lock();
+        if p.is_cancelled() {
+            return Err(Command::Quit);
+        }
+        Ok(Self {
+            reload: p.time_until_reload(),
+            scroll_frame: p.time_until_scroll_frame(),
+            flash_end: p.time_until_flash_end(),
+            redraw: p.time_until_redraw(),
+            idle: p.time_until_idle(),
+            cancellable: p.cancellation_flag.is_some(),
+        })
+    }
+
+    /// How long to wait for a command before one of the timers is due
+    fn timeout(&self) -> Option<Duration> {
+        [
+            self.reload,
+            self.scroll_frame,
+            self.flash_end,
+            self.redraw,
+            self.idle,
+            self.cancellable.then_some(CANCELLATION_POLL_INTERVAL),
+        ]
+        .iter()
+        .flatten()
+        .min()
+        .copied()
+    }
+
+    /// Command to handle after no command was received within `timeout`, if a timer is due
+    fn expired(&self, timeout: Duration, ps: &Mutex<PagerState>) -> Option<Command> {
+        if self.scroll_frame.is_some_and(|t| t <= timeout) {
+            Some(Command::AnimateScroll)
+        } else if self.flash_end.is_some_and(|t| t <= timeout) {
+            // Restore the flashed prompt
+            ps.lock().flash_until = None;
+            Some(Command::FormatRedrawPrompt)
+        } else if self.redraw.is_some_and(|t| t <= timeout) {
+            Some(Command::RedrawDisplay)
+        } else if self.reload.is_some_and(|t| t <= timeout) {
+            Some(Command::UserInput(InputEvent::Reload))
+        } else if self.idle.is_some_and(|t| t <= timeout) {
+            Some(Command::Idle)
+        } else {
+            None
+        }
+    }
+
+    /// Note that a command has been received
+    fn received(&self, ps: &Mutex<PagerState>) {
+        if self.idle.is_some() {
+            ps.lock().last_activity = Instant::now();
+        }
+    }
+}
+
+/// Wait for a command from the channel, generating reloads, idle calls and quits as needed
+fn recv_command(rx: &Inbox<'_>, ps: &Arc<Mutex<PagerState>>) -> Result<Command, RecvError> {
+    loop {
+        let timers = match Timers::read(ps) {
+            Ok(timers) => timers,
+            Err(command) => return Ok(command),
+        };
+        let timeout = timers.timeout();
+        match rx.recv_deadline(timeout.map(|t| Instant::now() + t)) {
+            Ok(command) => {
+                timers.received(ps);
+                return Ok(command);
+            }
+            Err(RecvTimeoutError::Timeout) => {
+                if let Some(command) = timeout.and_then(|t| timers.expired(t, ps)) {
+                    return Ok(command);
+                }
+            }
+            Err(RecvTimeoutError::Disconnected) => return Err(RecvError),
+        }
+    }
+}
+
+/// Same as [`recv_command`] but waits without blocking the thread
+#[cfg(all(feature = "dynamic_output", feature = "tokio"))]
+async fn recv_command_async(rx: &Inbox<'_>, ps: &Arc<Mutex<PagerState>>) -> Command {
+    loop {
+        let timers = match Timers::read(ps) {
+            Ok(timers) => timers,
+            Err(command) => return command,
+        };
+        let timeout = timers.timeout();
+        match rx
+            .recv_deadline_async(timeout.map(|t| Instant::now() + t))
+            .await
+        {
+            Ok(command) => {
+                timers.received(ps);
+                return command;
+            }
+            Err(_) => {
+                if let Some(command) = timeout.and_then(|t| timers.expired(t, ps)) {
+                    return command;
+                }
+            }
+        }
+    }
+}
+
 fn event_reader(
-    evtx: &Sender<Command>,
+    evtx: &CommandSender,
     ps: &Arc<Mutex<PagerState>>,
-    #[cfg(feature = "search")] user_input_active: &Arc<(Mutex<bool>, Condvar)>,
+    #[cfg(feature = "__search")] user_input_active: &Arc<(Mutex<bool>, Condvar)>,
     is_exited: &Arc<AtomicBool>,
 ) -> Result<(), MinusError> {
+    let backend = ps.lock().backend.clone();
     loop {
         if is_exited.load(Ordering::SeqCst) {
             break;
         }
 
-        #[cfg(feature = "search")]
+        #[cfg(feature = "__search")]
         {
             let (lock, cvar) = (&user_input_active.0, &user_input_active.1);
             let mut active = lock.lock();
@@ -336,25 +1078,256 @@ fn event_reader(
             }
         }
 
-        let ev = event::read().map_err(|e| MinusError::HandleEvent(e.into()))?;
-        let mut guard = ps.lock();
-        // Get the events
-        let input = guard.input_classifier.classify_input(ev, &guard);
-        if let Some(iev) = input {
-            if let InputEvent::Number(n) = iev {
-                guard.prefix_num.push(n);
-                guard.format_prompt();
-            } else if !guard.prefix_num.is_empty() {
-                guard.prefix_num.clear();
-                guard.format_prompt();
-            }
-            if let Err(TrySendError::Disconnected(_)) = evtx.try_send(Command::UserInput(iev)) {
-                break;
+        // Poll with a timeout rather than blocking on a read so that we notice when the pager
+        // has been closed without any user input, like from a PagerHandle
+        let poll_timeout = ps.lock().poll_timeout;
+        if !backend
+            .poll_event(poll_timeout)
+            .map_err(|e| MinusError::HandleEvent(e.into()))?
+        {
+            continue;
+        }
+        let ev = backend
+            .read_event()
+            .map_err(|e| MinusError::HandleEvent(e.into()))?;
+        let Some(ev) = crate::input::normalize_event(ev) else {
+            continue;
+        };
+        let commands = commands_for_event(
+            ev,
+            &mut ps.lock(),
+            #[cfg(feature = "__search")]
+            user_input_active,
+        );
+        for command in commands {
+            if evtx.send(command).is_err() {
+                return Ok(());
             }
-        } else if !guard.prefix_num.is_empty() {
-            guard.prefix_num.clear();
-            guard.format_prompt();
         }
     }
     Result::<(), MinusError>::Ok(())
 }
+
+/// Same as [`event_reader`] but reads the user input from `events`
+///
+/// This runs in the same task as [`start_reactor_async`], hence it is never polled while the
+/// search prompt reads the user input on its own. Once a key opens the prompt, it waits for the
+/// reactor to report through `handled` that the prompt has been closed before reading further.
+#[cfg(all(feature = "dynamic_output", feature = "tokio"))]
+async fn read_event_stream(
+    events: &mut EventStream,
+    evtx: &CommandSender,
+    ps: &Arc<Mutex<PagerState>>,
+    #[cfg(feature = "__search")] user_input_active: &Arc<(Mutex<bool>, Condvar)>,
+    #[cfg(feature = "__search")] handled: &Notify,
+) -> Result<(), MinusError> {
+    loop {
+        // Leave the input to the search prompt until the reactor has handled it
+        #[cfg(feature = "__search")]
+        while !*user_input_active.0.lock() {
+            handled.notified().await;
+        }
+        let next = std::future::poll_fn(|cx| futures_core::Stream::poll_next(events.as_mut(), cx));
+        let Some(ev) = next.await else {
+            return Ok(());
+        };
+        let ev = ev.map_err(|e| MinusError::HandleEvent(e.into()))?;
+        let Some(ev) = crate::input::normalize_event(ev) else {
+            continue;
+        };
+        let commands = commands_for_event(
+            ev,
+            &mut ps.lock(),
+            #[cfg(feature = "__search")]
+            user_input_active,
+        );
+        for command in commands {
+            if evtx.send(command).is_err() {
+                return Ok(());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{apply_window_title, Inbox, ScrollCoalescer};
+    use crate::{
+        input::InputEvent,
+        minus_core::{channel, commands::Command, CommandQueue},
+        PagerState, WindowTitle,
+    };
+    use parking_lot::Mutex;
+    use std::{
+        sync::Arc,
+        time::{Duration, Instant},
+    };
+
+    const fn scroll(um: usize) -> Command {
+        Command::UserInput(InputEvent::UpdateUpperMark(um))
+    }
+
+    #[test]
+    fn coalesce_queued_scrolls() {
+        let (tx, rx) = channel::unbounded();
+        let (_input_tx, input_rx) = channel::unbounded();
+        let rx = Inbox {
+            input: &input_rx,
+            data: &rx,
+        };
+        let ps = Arc::new(Mutex::new(PagerState::new().unwrap()));
+        let mut queue = CommandQueue::new_zero();
+        let mut scrolls = ScrollCoalescer::default();
+
+        tx.send(scroll(3)).unwrap();
+        tx.send(scroll(7)).unwrap();
+        tx.send(Command::AppendData("text".to_string())).unwrap();
+        tx.send(scroll(9)).unwrap();
+
+        assert_eq!(scrolls.coalesce(scroll(1), &rx, &ps, &mut queue), scroll(7));
+        // Later scrolls see the merged upper mark until it is drawn
+        assert_eq!(ps.lock().upper_mark, 7);
+        scrolls.restore(&mut ps.lock());
+        assert_eq!(ps.lock().upper_mark, 0);
+        // The scroll after another command isn't merged
+        assert_eq!(
+            queue.pop_front(),
+            Some(Command::AppendData("text".to_string()))
+        );
+        assert_eq!(rx.try_recv(), Some(scroll(9)));
+
+        // Other commands pass through
+        assert_eq!(
+            scrolls.coalesce(Command::Quit, &rx, &ps, &mut queue),
+            Command::Quit
+        );
+    }
+
+    #[test]
+    fn coalesce_scrolls_within_frame() {
+        let (tx, rx) = channel::unbounded();
+        let (_input_tx, input_rx) = channel::unbounded();
+        let rx = Inbox {
+            input: &input_rx,
+            data: &rx,
+        };
+        let ps = Arc::new(Mutex::new(PagerState::new().unwrap()));
+        let mut queue = CommandQueue::new_zero();
+        let mut scrolls = ScrollCoalescer {
+            last_draw: Some(Instant::now()),
+            drawn_upper_mark: None,
+        };
+
+        let sender = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(2));
+            tx.send(scroll(5)).unwrap();
+        });
+        assert_eq!(scrolls.coalesce(scroll(1), &rx, &ps, &mut queue), scroll(5));
+        sender.join().unwrap();
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn input_before_data() {
+        let (data_tx, data_rx) = channel::bounded(1);
+        let (input_tx, input_rx) = channel::unbounded();
+        let inbox = Inbox {
+            input: &input_rx,
+            data: &data_rx,
+        };
+        data_tx
+            .send(Command::AppendData("text".to_string()))
+            .unwrap();
+        input_tx.send(Command::Quit).unwrap();
+        assert_eq!(inbox.recv_deadline(None).unwrap(), Command::Quit);
+        assert_eq!(
+            inbox.recv_deadline(None).unwrap(),
+            Command::AppendData("text".to_string())
+        );
+        assert!(inbox
+            .recv_deadline(Some(Instant::now() + Duration::from_millis(1)))
+            .is_err());
+    }
+
+    #[cfg(all(feature = "dynamic_output", feature = "tokio"))]
+    #[tokio::test]
+    async fn recv_without_blocking() {
+        let (data_tx, data_rx) = channel::unbounded();
+        let (input_tx, input_rx) = data_rx.unbounded_sibling();
+        let inbox = Inbox {
+            input: &input_rx,
+            data: &data_rx,
+        };
+        assert!(inbox
+            .recv_deadline_async(Some(Instant::now() + Duration::from_millis(1)))
+            .await
+            .is_err());
+
+        // Commands sent to either channel wake up the task
+        let sender = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(2));
+            data_tx.send(Command::RedrawDisplay).unwrap();
+            std::thread::sleep(Duration::from_millis(2));
+            input_tx.send(Command::Quit).unwrap();
+        });
+        assert_eq!(
+            inbox.recv_deadline_async(None).await.unwrap(),
+            Command::RedrawDisplay
+        );
+        assert_eq!(
+            inbox.recv_deadline_async(None).await.unwrap(),
+            Command::Quit
+        );
+        sender.join().unwrap();
+    }
+
+    #[cfg(all(feature = "dynamic_output", feature = "tokio"))]
+    #[test]
+    fn async_paging_is_send() {
+        fn assert_send<T: Send>(_: T) {}
+        // The future is only created, not run
+        assert_send(crate::async_paging(crate::Pager::new()));
+    }
+
+    #[test]
+    fn window_title() {
+        let mut ps = PagerState::new().unwrap();
+        ps.backend = Arc::new(crate::backend::TestBackend::new(80, 24));
+        ps.prompt = "\u{1b}[1mfile.txt\u{1b}[0m".to_string();
+        let mut current = None;
+        let mut out = Vec::new();
+
+        apply_window_title(&mut out, &ps, &mut current).unwrap();
+        assert!(out.is_empty());
+
+        ps.window_title = WindowTitle::Prompt;
+        apply_window_title(&mut out, &ps, &mut current).unwrap();
+        assert_eq!(out, b"\x1b[22;0t\x1b]0;file.txt\x07");
+        assert_eq!(current.as_deref(), Some("file.txt"));
+
+        // Nothing is written while the title stays the same
+        out.clear();
+        apply_window_title(&mut out, &ps, &mut current).unwrap();
+        assert!(out.is_empty());
+
+        ps.window_title = WindowTitle::Text("minus\nexample".to_string());
+        apply_window_title(&mut out, &ps, &mut current).unwrap();
+        assert_eq!(out, b"\x1b]0;minusexample\x07");
+
+        out.clear();
+        ps.window_title = WindowTitle::Unchanged;
+        apply_window_title(&mut out, &ps, &mut current).unwrap();
+        assert_eq!(out, b"\x1b[23;0t");
+
+        // Only terminals have a window title
+        let path = std::env::temp_dir().join("minus-window-title.txt");
+        ps.backend = Arc::new(crate::backend::CrosstermBackend::with_writer(
+            std::fs::File::create(&path).unwrap(),
+        ));
+        ps.window_title = WindowTitle::Prompt;
+        out.clear();
+        apply_window_title(&mut out, &ps, &mut current).unwrap();
+        assert!(out.is_empty());
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -1,5 +1,6 @@
 use std::collections::VecDeque;
 
+pub mod channel;
 pub mod commands;
 pub mod ev_handler;
 #[cfg(any(feature = "dynamic_output", feature = "static_output"))]
@@ -1,22 +1,27 @@
 #![allow(dead_code)]
 
 use crossterm::{
-    cursor::MoveTo,
+    cursor::{MoveDown, MoveTo, MoveToColumn, RestorePosition, SavePosition},
     execute, queue,
-    terminal::{Clear, ClearType},
+    terminal::{BeginSynchronizedUpdate, Clear, ClearType, EndSynchronizedUpdate},
 };
 
-use std::{cmp::Ordering, convert::TryInto, io::Write};
+use std::{borrow::Cow, cmp::Ordering, convert::TryInto, io::Write};
 
 use super::term;
-use crate::screen::Row;
-use crate::{error::MinusError, minus_core, LineNumbers, PagerState};
+use crate::screen::{self, Row};
+use crate::{error::MinusError, minus_core, FeedbackMode, LineNumbers, PagerState};
+
+/// Minimum number of rows required to display the text along with the prompt
+pub const MIN_ROWS: usize = 3;
+/// Minimum number of columns required to display the text
+pub const MIN_COLS: usize = 20;
 
 /// How should the incoming text be drawn on the screen
 #[derive(Debug, PartialEq, Eq)]
 pub enum AppendStyle<'a> {
     /// Draw only the region that needs to change
-    PartialUpdate(&'a [Row]),
+    PartialUpdate(Cow<'a, [Row]>),
 
     /// Redraw the entire screen
     FullRedraw,
@@ -28,103 +33,122 @@ pub enum AppendStyle<'a> {
 /// very high if that redrawing is required on every movement of the pager, even for small changes.
 /// This function calculates what part of screen needs to be redrawed on scrolling up/down and based on that, it redraws
 /// only that part of the terminal.
+///
+/// On terminals that cannot scroll, the entire screen is redrawn instead.
 pub fn draw_for_change(
     out: &mut impl Write,
     ps: &mut PagerState,
     new_upper_mark: &mut usize,
 ) -> Result<(), MinusError> {
+    let sync = ps.capabilities.synchronized_output;
+    synchronized(out, sync, |out| scroll_to(out, ps, new_upper_mark))
+}
+
+fn scroll_to(
+    out: &mut impl Write,
+    ps: &mut PagerState,
+    new_upper_mark: &mut usize,
+) -> Result<(), MinusError> {
+    // Nothing can be drawn while the placeholder is shown. Keep the previous upper mark so that
+    // the view can be restored when the terminal grows back
+    if ps.is_term_too_small() {
+        *new_upper_mark = ps.upper_mark;
+        ps.screen.visible_rows = 0..0;
+        return Ok(());
+    }
     let line_count = ps.screen.formatted_lines_count();
 
-    // Reduce one row for prompt/messages
+    // Leave out the row of the prompt/messages
     //
     // NOTE This should be the value of rows that should be used throughout this function.
     // Don't use PagerState::rows, it might lead to wrong output
-    let writable_rows = ps.rows.saturating_sub(1);
+    let writable_rows = ps.text_rows();
 
-    // Calculate the lower_bound for current and new upper marks
-    // by adding either the rows or line_count depending on the minimality
-    let lower_bound = ps.upper_mark.saturating_add(writable_rows.min(line_count));
-    let new_lower_bound = new_upper_mark.saturating_add(writable_rows.min(line_count));
+    // Never scroll past the last line, i.e the last page is always displayed entirely
+    *new_upper_mark = (*new_upper_mark).min(line_count.saturating_sub(writable_rows));
 
-    // If the lower_bound is greater than the available line count, we set it to such a value
-    // so that the last page can be displayed entirely, i.e never scroll past the last line
-    if new_lower_bound > line_count {
-        *new_upper_mark = line_count.saturating_sub(writable_rows);
+    // The number of rows for the text changed since the last draw, nothing can be kept
+    if ps.prompt_shown() != ps.prompt_drawn {
+        ps.upper_mark = *new_upper_mark;
+        return redraw(out, ps);
+    }
+
+    // Scrolling the terminal would also move whatever surrounds the region
+    if !ps.capabilities.scrolling || ps.region.is_some() {
+        if *new_upper_mark != ps.upper_mark {
+            ps.upper_mark = *new_upper_mark;
+            redraw(out, ps)?;
+        }
+        return Ok(());
     }
 
-    let delta = new_upper_mark.abs_diff(ps.upper_mark);
-    // Sometimes the value of delta is too large that we can rather use the value of the writable rows to
-    // achieve the same effect with better performance. This means that we have draw to less lines to the terminal
+    // The rows that are displayed both before and after the change are kept by scrolling the
+    // terminal, hence only the rows that get scrolled in have to be written. When the jump is
+    // larger than the screen, nothing can be kept and the entire screen is scrolled out.
     //
     // Think of it like this:-
     // Let's say the current upper mark is at 100 and writable rows is 25. Now if there is a jump of 200th line,
     // then instead of writing 100 lines, we can just jump to the 200 line and display the next 25 lines from there on.
-    //
-    // Hence here we can take the minimum of the delta or writable rows for displaying
-    //
-    // NOTE that the large delta case may not always be true in case of scrolling down. Actually this method produces
-    // wrong output if this is not the case hence we still rely on using lower bounds method. But for scrolling up, we
-    // need this value whatever the value of delta be.
-    let normalized_delta = delta.min(writable_rows);
+    let scrolled = new_upper_mark.abs_diff(ps.upper_mark).min(writable_rows);
+    let scroll_by = scrolled.try_into().map_err(|_| MinusError::Conversion)?;
 
     let lines = match (*new_upper_mark).cmp(&ps.upper_mark) {
         Ordering::Greater => {
-            // Scroll down `normalized_delta` lines, and put the cursor one line above, where the old prompt would present.
-            // Clear it off and start displaying new dta.
-            queue!(
-                out,
-                crossterm::terminal::ScrollUp(normalized_delta.try_into().unwrap())
-            )?;
+            // Scroll down `scrolled` rows, and put the cursor on the first row that got scrolled
+            // in, where the old prompt is now present. Clear it off and display the last
+            // `scrolled` rows of the new page from there
+            queue!(out, crossterm::terminal::ScrollUp(scroll_by))?;
             term::move_cursor(
                 out,
                 0,
-                ps.rows
-                    .saturating_sub(normalized_delta + 1)
+                writable_rows
+                    .saturating_sub(scrolled)
                     .try_into()
-                    .unwrap(),
+                    .map_err(|_| MinusError::Conversion)?,
                 false,
             )?;
             queue!(out, Clear(ClearType::CurrentLine))?;
 
-            if delta < writable_rows {
-                ps.screen
-                    .get_formatted_lines_with_bounds(lower_bound, new_lower_bound)
-            } else {
-                ps.screen.get_formatted_lines_with_bounds(
-                    *new_upper_mark,
-                    new_upper_mark.saturating_add(normalized_delta),
-                )
-            }
+            let new_lower_bound = new_upper_mark.saturating_add(writable_rows);
+            ps.display_rows(new_lower_bound - scrolled, new_lower_bound)
         }
         Ordering::Less => {
-            execute!(
-                out,
-                crossterm::terminal::ScrollDown(normalized_delta.try_into().unwrap())
-            )?;
+            execute!(out, crossterm::terminal::ScrollDown(scroll_by))?;
             term::move_cursor(out, 0, 0, false)?;
 
-            ps.screen.get_formatted_lines_with_bounds(
-                *new_upper_mark,
-                new_upper_mark.saturating_add(normalized_delta),
-            )
+            // Display the first `scrolled` rows of the new page from the top
+            ps.display_rows(*new_upper_mark, new_upper_mark.saturating_add(scrolled))
         }
         Ordering::Equal => return Ok(()),
     };
 
     write_lines(
-        out,
-        lines,
+        &mut HoldNewline::new(&mut *out, !ps.prompt_shown()),
+        &lines,
         ps.cols,
         ps.screen.line_wrapping,
         ps.left_mark,
         ps.line_numbers.is_on(),
-        ps.screen.line_count(),
+        ps.screen.line_number_digits(),
     )?;
 
+    #[cfg(feature = "metrics")]
+    {
+        ps.metrics.partial_redraws += 1;
+    }
     ps.upper_mark = *new_upper_mark;
+    ps.screen.visible_rows =
+        ps.upper_mark..new_upper_mark.saturating_add(writable_rows).min(line_count);
 
-    if ps.show_prompt {
-        super::display::write_prompt(out, &ps.displayed_prompt, ps.rows.try_into().unwrap())?;
+    if ps.prompt_shown() {
+        if ps.prompt_renderer.is_some() {
+            ps.format_prompt();
+        }
+        super::display::write_prompt(
+            out,
+            &ps.displayed_prompt,
+            ps.rows.try_into().map_err(|_| MinusError::Conversion)?,
+        )?;
     }
     out.flush()?;
 
@@ -138,6 +162,55 @@ pub fn write_prompt(out: &mut impl Write, text: &str, rows: u16) -> Result<(), M
     Ok(())
 }
 
+/// Redraw the prompt after it changed
+///
+/// While the prompt is hidden, its row is taken only to show a message or the command line. The
+/// entire screen is redrawn whenever the row is taken or given back to the text.
+pub fn draw_prompt(out: &mut impl Write, ps: &mut PagerState) -> Result<(), MinusError> {
+    if ps.is_term_too_small() {
+        return Ok(());
+    }
+    if ps.prompt_shown() != ps.prompt_drawn {
+        return draw_full(out, ps);
+    }
+    if ps.prompt_shown() {
+        write_prompt(
+            out,
+            &ps.displayed_prompt,
+            ps.rows.try_into().map_err(|_| MinusError::Conversion)?,
+        )?;
+    }
+    Ok(())
+}
+
+/// Tell the user that an action could not be carried out according to [`PagerState::feedback`]
+///
+/// With [`FeedbackMode::Flash`], the prompt is drawn in reverse video. It is restored by redrawing
+/// it once [`PagerState::flash_until`] passes.
+pub fn write_feedback(out: &mut impl Write, ps: &mut PagerState) -> Result<(), MinusError> {
+    match ps.feedback {
+        FeedbackMode::None => {}
+        FeedbackMode::Bell => {
+            write!(out, "\x07")?;
+            out.flush()?;
+        }
+        FeedbackMode::Flash => {
+            if !ps.prompt_shown() || ps.is_term_too_small() {
+                return Ok(());
+            }
+            // The prompt's own colors would override the reverse video
+            let text = minus_core::utils::strip_ansi(&ps.displayed_prompt);
+            write_prompt(
+                out,
+                &format!("\x1b[7m{text}\x1b[0m"),
+                ps.rows.try_into().map_err(|_| MinusError::Conversion)?,
+            )?;
+            ps.start_flash();
+        }
+    }
+    Ok(())
+}
+
 // The below functions are just a subset of functionality of the above draw_for_change function.
 // Although, separate they are tightly coupled together.
 
@@ -150,23 +223,78 @@ pub fn write_prompt(out: &mut impl Write, text: &str, rows: u16) -> Result<(), M
 ///   - If there is one, it will display it at the prompt site
 ///   - If there isn't one, it will display the prompt in place of it
 pub fn draw_full(out: &mut impl Write, ps: &mut PagerState) -> Result<(), MinusError> {
+    let sync = ps.capabilities.synchronized_output;
+    synchronized(out, sync, |out| redraw(out, ps))
+}
+
+fn redraw(out: &mut impl Write, ps: &mut PagerState) -> Result<(), MinusError> {
+    #[cfg(feature = "tracing")]
+    let _span = tracing::debug_span!("minus::draw_full").entered();
+    #[cfg(feature = "metrics")]
+    {
+        ps.metrics.full_redraws += 1;
+    }
     super::term::move_cursor(out, 0, 0, false)?;
     queue!(out, Clear(ClearType::All))?;
 
+    if ps.is_term_too_small() {
+        ps.screen.visible_rows = 0..0;
+        return draw_too_small(out, ps.cols);
+    }
+
+    ps.prompt_drawn = ps.prompt_shown();
     write_from_pagerstate(out, ps)?;
 
     let pager_rows: u16 = ps.rows.try_into().map_err(|_| MinusError::Conversion)?;
 
-    if ps.show_prompt {
+    if ps.prompt_shown() {
+        if ps.prompt_renderer.is_some() {
+            ps.format_prompt();
+        }
         write_prompt(out, &ps.displayed_prompt, pager_rows)?;
     }
 
     out.flush().map_err(MinusError::Draw)
 }
 
+/// Run `draw` so that the terminal displays everything it writes at once when `sync` is true
+///
+/// This prevents the terminal from showing a half drawn screen on terminals that support
+/// [synchronized output](crate::backend::Capabilities::synchronized_output).
+fn synchronized<W: Write>(
+    out: &mut W,
+    sync: bool,
+    draw: impl FnOnce(&mut W) -> Result<(), MinusError>,
+) -> Result<(), MinusError> {
+    if !sync {
+        return draw(out);
+    }
+    queue!(out, BeginSynchronizedUpdate)?;
+    // The update must be ended even if drawing fails, otherwise the terminal stops displaying
+    // anything until it times out
+    let res = draw(out);
+    execute!(out, EndSynchronizedUpdate)?;
+    res
+}
+
+/// Draw a placeholder telling the user that the terminal is too small
+///
+/// This is displayed in place of the text whenever the terminal is shrunk below [`MIN_ROWS`] or
+/// [`MIN_COLS`].
+pub fn draw_too_small(out: &mut impl Write, cols: usize) -> Result<(), MinusError> {
+    const PLACEHOLDER: &str = "Terminal too small";
+
+    let text = &PLACEHOLDER[..cols.min(PLACEHOLDER.len())];
+    write!(out, "{mv}\r{text}", mv = MoveTo(0, 0))?;
+    out.flush().map_err(MinusError::Draw)
+}
+
+/// Draw the rows appended to the text in the free space below the text already displayed
+///
+/// `text_rows` is the number of rows available for the text, see [`PagerState::text_rows`].
 pub fn draw_append_text(
     out: &mut impl Write,
-    rows: usize,
+    text_rows: usize,
     prev_unterminated: usize,
     prev_fmt_lines_count: usize,
     append_style: &AppendStyle,
@@ -175,7 +303,7 @@ pub fn draw_append_text(
         unreachable!()
     };
 
-    if prev_fmt_lines_count < rows {
+    if prev_fmt_lines_count < text_rows {
         // Move the cursor to the very next line after the last displayed line
         term::move_cursor(
             out,
@@ -183,18 +311,15 @@ pub fn draw_append_text(
             prev_fmt_lines_count
                 .saturating_sub(prev_unterminated)
                 .try_into()
-                .unwrap(),
+                .map_err(|_| MinusError::Conversion)?,
             false,
         )?;
         // available_rows -> Rows that are still unfilled
-        //      rows - number of lines displayed -1 (for prompt)
-        // For example if 20 rows are in total in a terminal
+        //      text rows - number of lines displayed
+        // For example if 19 rows are available for the text
         // and 10 rows are already occupied, then this will be equal to 9
-        let available_rows = rows.saturating_sub(
-            prev_fmt_lines_count
-                .saturating_sub(prev_unterminated)
-                .saturating_add(1),
-        );
+        let available_rows =
+            text_rows.saturating_sub(prev_fmt_lines_count.saturating_sub(prev_unterminated));
         // Minimum amount of text that an be appended
         // If available_rows is less, than this will be available rows else it will be
         // the length of the formatted text
@@ -206,9 +331,12 @@ pub fn draw_append_text(
         if num_appendable >= 1 {
             crossterm::execute!(out, crossterm::terminal::Clear(ClearType::CurrentLine))?;
         }
-        for line in fmt_text.iter() {
-            write!(out, "{}\n\r", line)?;
-        }
+        // The last row may be the bottom row of the terminal, where a newline would scroll it
+        write_raw_lines(
+            &mut HoldNewline::new(&mut *out, true),
+            &fmt_text[..num_appendable],
+            Some("\r"),
+        )?;
         out.flush()?;
     }
     Ok(())
@@ -240,7 +368,7 @@ pub fn write_text_checked(
     line_wrapping: bool,
     left_mark: usize,
     line_numbers: LineNumbers,
-    total_line_count: usize,
+    len_line_number: usize,
 ) -> Result<(), MinusError> {
     let line_count = lines.len();
 
@@ -271,15 +399,16 @@ pub fn write_text_checked(
         line_wrapping,
         left_mark,
         line_numbers.is_on(),
-        total_line_count,
+        len_line_number,
     )
 }
 
 pub fn write_from_pagerstate(out: &mut impl Write, ps: &mut PagerState) -> Result<(), MinusError> {
     let line_count = ps.screen.formatted_lines_count();
+    let mut out = HoldNewline::new(out, !ps.prompt_shown());
 
-    // Reduce one row for prompt/messages
-    let writable_rows = ps.rows.saturating_sub(1);
+    // Leave out the row of the prompt/messages
+    let writable_rows = ps.text_rows();
 
     // Calculate the lower_mark by adding either the rows or line_count depending
     // on the minimality
@@ -287,21 +416,28 @@ pub fn write_from_pagerstate(out: &mut impl Write, ps: &mut PagerState) -> Resul
     if lower_mark > line_count {
         ps.upper_mark = line_count.saturating_sub(writable_rows);
     }
+    ps.screen.visible_rows = ps.upper_mark..lower_mark;
 
     // Add \r to ensure cursor is placed at the beginning of each row
-    let display_lines: &[String] = ps
-        .screen
-        .get_formatted_lines_with_bounds(ps.upper_mark, lower_mark);
+    let display_lines = ps.display_rows(ps.upper_mark, lower_mark);
 
     write_lines(
-        out,
-        display_lines,
+        &mut out,
+        &display_lines,
         ps.cols,
         ps.screen.line_wrapping,
         ps.left_mark,
         ps.line_numbers.is_on(),
-        ps.screen.line_count(),
-    )
+        ps.screen.line_number_digits(),
+    )?;
+
+    // Fill the rows after the end of the text
+    if !ps.eob_filler.is_empty() {
+        for _ in display_lines.len()..writable_rows {
+            writeln!(out, "\r{}", ps.eob_filler)?;
+        }
+    }
+    Ok(())
 }
 
 pub fn write_lines(
@@ -311,53 +447,37 @@ pub fn write_lines(
     line_wrapping: bool,
     left_mark: usize,
     line_numbers: bool,
-    line_count: usize,
+    len_line_number: usize,
 ) -> crate::Result {
     if line_wrapping {
         write_raw_lines(out, lines, Some("\r"))
     } else {
-        write_lines_in_horizontal_scroll(out, lines, cols, left_mark, line_numbers, line_count)
+        write_lines_in_horizontal_scroll(out, lines, cols, left_mark, line_numbers, len_line_number)
     }
 }
 
+/// Write the part of the `lines` visible with horizontal scrolling to the output
+///
+/// `start` is the first column of the text to be shown. Line numbers, if present, stay in place.
+/// `len_line_number` is the number of digits for which space is made for the line numbers.
 pub fn write_lines_in_horizontal_scroll(
     out: &mut impl Write,
     lines: &[String],
     cols: usize,
     start: usize,
     line_numbers: bool,
-    line_count: usize,
+    len_line_number: usize,
 ) -> crate::Result {
-    let line_number_ascii_seq_len = if line_numbers { 8 } else { 0 };
-    let line_number_padding = if line_numbers {
-        minus_core::utils::digits(line_count) + LineNumbers::EXTRA_PADDING + 3
+    // Width of the line number followed by a '.' and a space
+    let keep = if line_numbers {
+        len_line_number + LineNumbers::EXTRA_PADDING + 2
     } else {
         0
     };
-    let shifted_start = if line_numbers {
-        start + line_number_padding + line_number_ascii_seq_len
-    } else {
-        start
-    };
+    let width = screen::text_width(cols, line_numbers, len_line_number);
 
     for line in lines {
-        let end = shifted_start + cols.min(line.len().saturating_sub(shifted_start))
-            - line_number_padding;
-
-        if start < line.len() {
-            if line_numbers {
-                writeln!(
-                    out,
-                    "\r{}{}",
-                    &line[0..line_number_padding + line_number_ascii_seq_len],
-                    &line[shifted_start..end]
-                )?;
-            } else {
-                writeln!(out, "\r{}", &line[shifted_start..end])?;
-            }
-        } else {
-            writeln!(out, "\r")?;
-        }
+        writeln!(out, "\r{}", screen::slice_columns(line, keep, start, width))?;
     }
     Ok(())
 }
@@ -379,5 +499,246 @@ pub fn write_raw_lines(
     Ok(())
 }
 
+/// Adapter that holds back a newline at the end of the output until more output follows
+///
+/// When the prompt is hidden, the text reaches the last row of the terminal. The newline ending
+/// the last line would then scroll the screen, hence it is dropped when `hold` is true.
+struct HoldNewline<W: Write> {
+    out: W,
+    hold: bool,
+    held: bool,
+}
+
+impl<W: Write> HoldNewline<W> {
+    const fn new(out: W, hold: bool) -> Self {
+        Self {
+            out,
+            hold,
+            held: false,
+        }
+    }
+}
+
+impl<W: Write> Write for HoldNewline<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if !self.hold || buf.is_empty() {
+            return self.out.write(buf);
+        }
+        if std::mem::take(&mut self.held) {
+            self.out.write_all(b"\n")?;
+        }
+        if let Some(rest) = buf.strip_suffix(b"\n") {
+            self.out.write_all(rest)?;
+            self.held = true;
+        } else {
+            self.out.write_all(buf)?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.out.flush()
+    }
+}
+
+/// Adapter that removes the escape sequences setting colors and text attributes from the output
+/// while `plain` is true
+///
+/// Escape sequences may be split across several writes, hence an unfinished one is held back
+/// until its end is seen.
+pub struct PlainWriter<W: Write> {
+    out: W,
+    /// Whether the escape sequences are removed
+    pub plain: bool,
+    /// Start of an escape sequence whose end hasn't been written yet
+    pending: Vec<u8>,
+}
+
+impl<W: Write> PlainWriter<W> {
+    pub const fn new(out: W, plain: bool) -> Self {
+        Self {
+            out,
+            plain,
+            pending: Vec::new(),
+        }
+    }
+
+    /// Get the writer to which the output goes
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.out
+    }
+}
+
+impl<W: Write> Write for PlainWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if !self.plain {
+            let pending = std::mem::take(&mut self.pending);
+            self.out.write_all(&pending)?;
+            return self.out.write(buf);
+        }
+        let mut filtered = Vec::with_capacity(buf.len());
+        for &byte in buf {
+            match (self.pending.as_slice(), byte) {
+                ([], 0x1b) | ([0x1b], b'[') => self.pending.push(byte),
+                ([], _) => filtered.push(byte),
+                // Not a CSI sequence, it might start another escape sequence though
+                ([0x1b], _) => {
+                    filtered.push(0x1b);
+                    if byte == 0x1b {
+                        continue;
+                    }
+                    self.pending.clear();
+                    filtered.push(byte);
+                }
+                // Parameter and intermediate bytes
+                (_, 0x20..=0x3f) => self.pending.push(byte),
+                // Select Graphic Rendition, which is what sets the colors and attributes
+                (_, b'm') => self.pending.clear(),
+                (_, _) => {
+                    filtered.append(&mut self.pending);
+                    filtered.push(byte);
+                }
+            }
+        }
+        self.out.write_all(&filtered)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.out.flush()
+    }
+}
+
+/// Rectangle of the terminal in which the pager is drawn
+///
+/// See [`Pager::set_region`](crate::Pager::set_region).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Region {
+    /// Column of the left edge
+    pub x: u16,
+    /// Row of the top edge
+    pub y: u16,
+    /// Number of columns
+    pub cols: u16,
+    /// Number of rows
+    pub rows: u16,
+}
+
+impl Region {
+    /// Whether the cell at `column` and `row` of the terminal lies in the region
+    pub const fn contains(self, column: u16, row: u16) -> bool {
+        column >= self.x && column - self.x < self.cols && row >= self.y && row - self.y < self.rows
+    }
+
+    /// Write the equivalent of the CSI sequence with `params` and `final_byte` for the region
+    fn translate_csi(self, params: &str, final_byte: u8, out: &mut Vec<u8>) -> std::io::Result<()> {
+        // Positions are 1-based and missing ones default to 1
+        let mut numbers = params
+            .split(';')
+            .map(|n| n.parse::<u16>().unwrap_or(1).max(1) - 1);
+        let mut next = || numbers.next().unwrap_or(0);
+        // Like the terminal does at its edges, the cursor is stopped at the edges of the region
+        let col = |col: u16| self.x.saturating_add(col.min(self.cols.saturating_sub(1)));
+        match (final_byte, params) {
+            (b'H' | b'f', _) => {
+                let (row, column) = (next(), next());
+                let row = self.y.saturating_add(row.min(self.rows.saturating_sub(1)));
+                write!(out, "{}", MoveTo(col(column), row))
+            }
+            (b'G', _) => write!(out, "{}", MoveToColumn(col(next()))),
+            // Clear the current line
+            (b'K', "2") => write!(
+                out,
+                "{SavePosition}{}\x1b[{}X{RestorePosition}",
+                MoveToColumn(self.x),
+                self.cols
+            ),
+            // Clear the entire screen
+            (b'J', "2") => {
+                write!(out, "{SavePosition}")?;
+                for row in 0..self.rows {
+                    write!(
+                        out,
+                        "{}\x1b[{}X",
+                        MoveTo(self.x, self.y.saturating_add(row)),
+                        self.cols
+                    )?;
+                }
+                write!(out, "{RestorePosition}")
+            }
+            _ => {
+                write!(out, "\x1b[{params}")?;
+                out.push(final_byte);
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Adapter that moves the output into a [`Region`] of the terminal
+///
+/// The output is written as if the pager had the entire terminal. Cursor movements are shifted
+/// by the position of the region and clearing a line or the screen only clears the part inside
+/// the region. Carriage returns and newlines are turned into cursor movements so that they stay
+/// inside the region and never scroll the terminal. Nothing is changed while `region` is `None`.
+pub struct RegionWriter<W: Write> {
+    out: W,
+    /// The region in which the output is moved
+    pub region: Option<Region>,
+    /// Start of an escape sequence whose end hasn't been written yet
+    pending: Vec<u8>,
+}
+
+impl<W: Write> RegionWriter<W> {
+    pub const fn new(out: W) -> Self {
+        Self {
+            out,
+            region: None,
+            pending: Vec::new(),
+        }
+    }
+}
+
+impl<W: Write> Write for RegionWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let Some(region) = self.region else {
+            let pending = std::mem::take(&mut self.pending);
+            self.out.write_all(&pending)?;
+            return self.out.write(buf);
+        };
+        let mut translated = Vec::with_capacity(buf.len());
+        for &byte in buf {
+            match (self.pending.as_slice(), byte) {
+                ([], 0x1b) | ([0x1b], b'[') => self.pending.push(byte),
+                ([], b'\r') => write!(translated, "{}", MoveToColumn(region.x))?,
+                ([], b'\n') => write!(translated, "{}", MoveDown(1))?,
+                ([], _) => translated.push(byte),
+                // Not a CSI sequence, it might start another escape sequence though
+                ([0x1b], _) => {
+                    translated.push(0x1b);
+                    if byte == 0x1b {
+                        continue;
+                    }
+                    self.pending.clear();
+                    translated.push(byte);
+                }
+                // Parameter and intermediate bytes
+                (_, 0x20..=0x3f) => self.pending.push(byte),
+                (_, _) => {
+                    let params = String::from_utf8_lossy(&self.pending[2..]).into_owned();
+                    self.pending.clear();
+                    region.translate_csi(&params, byte, &mut translated)?;
+                }
+            }
+        }
+        self.out.write_all(&translated)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.out.flush()
+    }
+}
+
 #[cfg(test)]
 mod tests;
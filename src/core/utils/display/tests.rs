@@ -11,6 +11,37 @@ use std::fmt::Write;
 // Wherever the tests require this 80x10 configuration, no explicit assignment is done
 // In other cases, the tests do set the their required values
 
+#[test]
+fn visible_text() {
+    let mut pager = PagerState::new().unwrap();
+    pager.screen.orig_text = (0..20).fold(String::new(), |mut s, i| {
+        writeln!(s, "{i}").unwrap();
+        s
+    });
+    pager.format_lines();
+    assert!(pager.screen.visible_text().is_empty());
+
+    let mut out = Vec::new();
+    write_from_pagerstate(&mut out, &mut pager).unwrap();
+    assert_eq!(
+        pager.screen.visible_text(),
+        &pager.screen.formatted_lines[0..9]
+    );
+
+    draw_for_change(&mut out, &mut pager, &mut 5).unwrap();
+    assert_eq!(
+        pager.screen.visible_text(),
+        &pager.screen.formatted_lines[5..14]
+    );
+
+    // Never goes past the end of the text
+    draw_for_change(&mut out, &mut pager, &mut 100).unwrap();
+    assert_eq!(
+        pager.screen.visible_text(),
+        &pager.screen.formatted_lines[11..20]
+    );
+}
+
 #[test]
 fn short_no_line_numbers() {
     let lines = "A line\nAnother line";
@@ -431,6 +462,134 @@ fn draw_help_message() {
     assert!(res.contains("minus"));
 }
 
+#[test]
+fn eob_filler() {
+    let mut pager = PagerState::new().unwrap();
+    pager.screen.orig_text = "A line\nAnother line\n".to_string();
+    pager.format_lines();
+    pager.eob_filler = "~".to_string();
+
+    let mut out = Vec::new();
+    write_from_pagerstate(&mut out, &mut pager).unwrap();
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        "\rA line\n\rAnother line\n".to_string() + &"\r~\n".repeat(7)
+    );
+}
+
+#[test]
+fn hidden_prompt_uses_all_rows() {
+    let mut pager = PagerState::new().unwrap();
+    pager.screen.orig_text = (0..20).map(|i| format!("{i}\n")).collect();
+    pager.format_lines();
+    pager.show_prompt = false;
+
+    let mut out = Vec::new();
+    write_from_pagerstate(&mut out, &mut pager).unwrap();
+    // The newline after the last row is left out so that the terminal doesn't scroll
+    let expected: Vec<String> = (0..pager.rows).map(|i| format!("\r{i}")).collect();
+    assert_eq!(String::from_utf8(out).unwrap(), expected.join("\n"));
+}
+
+#[test]
+fn eof_marker() {
+    let mut pager = PagerState::new().unwrap();
+    pager.screen.orig_text = "A line\n".repeat(20);
+    pager.format_lines();
+    pager.eof_marker = Some("(END)".to_string());
+    pager.format_prompt();
+    assert!(!pager.displayed_prompt.contains("(END)"));
+
+    pager.upper_mark = 11;
+    pager.format_prompt();
+    assert!(pager.displayed_prompt.contains("(END)"));
+}
+
+#[test]
+fn prompt_renderer() {
+    let mut pager = PagerState::new().unwrap();
+    pager.screen.orig_text = "A line\n".repeat(20);
+    pager.screen.line_count = 20;
+    pager.format_lines();
+    pager.prompt = "file.txt".to_string();
+    pager.prompt_renderer = Some(Box::new(|ctx| {
+        format!(
+            "{} {}/{} {}%",
+            ctx.prompt, ctx.top_line, ctx.total_lines, ctx.percent
+        )
+    }));
+    pager.format_prompt();
+    assert_eq!(pager.displayed_prompt, "file.txt 1/20 45%");
+
+    // The prompt is rendered again whenever the screen is drawn
+    let mut out = Vec::new();
+    draw_for_change(&mut out, &mut pager, &mut 11).unwrap();
+    assert_eq!(pager.displayed_prompt, "file.txt 12/20 100%");
+
+    // The command line is still displayed by minus
+    pager.command_line = Some("buf".to_string());
+    pager.format_prompt();
+    assert!(pager.displayed_prompt.contains(":buf"));
+}
+
+#[test]
+fn rows_beyond_terminal_limits() {
+    let mut pager = PagerState::new().unwrap();
+    pager.rows = usize::from(u16::MAX) + 2;
+    pager.screen.orig_text = "A line\n".repeat(70_000);
+    pager.format_lines();
+
+    // An error is returned instead of panicking
+    let mut out = Vec::new();
+    assert!(matches!(
+        draw_full(&mut out, &mut pager),
+        Err(crate::error::MinusError::Conversion)
+    ));
+    assert!(matches!(
+        draw_for_change(&mut out, &mut pager, &mut 10),
+        Err(crate::error::MinusError::Conversion)
+    ));
+}
+
+#[test]
+fn draw_horizontal_position() {
+    let mut pager = PagerState::new().unwrap();
+    pager.screen.line_wrapping = false;
+    pager.format_prompt();
+    assert!(!pager.displayed_prompt.contains("cols"));
+
+    pager.left_mark = 80;
+    pager.format_prompt();
+    assert!(pager.displayed_prompt.contains("cols 81-160"));
+
+    // Not shown when line wrapping is on
+    pager.screen.line_wrapping = true;
+    pager.format_prompt();
+    assert!(!pager.displayed_prompt.contains("cols"));
+}
+
+#[test]
+fn horizontal_scroll_keeps_escapes_whole() {
+    use super::write_lines_in_horizontal_scroll;
+
+    let lines = ["\u{1b}[32mhello world\u{1b}[0m".to_string()];
+    let mut out = Vec::new();
+    write_lines_in_horizontal_scroll(&mut out, &lines, 20, 6, false, 1).unwrap();
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        "\r\u{1b}[32mworld\u{1b}[0m\n"
+    );
+
+    // Line numbers stay in place
+    let lines = ["     1. \u{1b}[32mhello world\u{1b}[0m".to_string()];
+    let mut out = Vec::new();
+    write_lines_in_horizontal_scroll(&mut out, &lines, 20, 6, true, 1).unwrap();
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        "\r     1. \u{1b}[32mworld\u{1b}[0m\n"
+    );
+}
+
 #[test]
 fn test_draw_no_overflow() {
     const TEXT: &str = "This is a line of text to the pager";
@@ -445,12 +604,91 @@ fn test_draw_no_overflow() {
 }
 
 #[cfg(test)]
+#[test]
+fn plain_writer() {
+    use super::PlainWriter;
+    use std::io::Write as _;
+
+    let mut out = PlainWriter::new(Vec::new(), true);
+    write!(out, "\x1b[1mbold\x1b[0m \x1b[2J\x1b[5;1H\x1b").unwrap();
+    // Sequences split across writes are still recognised
+    write!(out, "[38;5;").unwrap();
+    write!(out, "42mgreen\x1b\x1b[m\x1b]8;;x\x1b\\").unwrap();
+    assert_eq!(out.out, b"bold \x1b[2J\x1b[5;1Hgreen\x1b\x1b]8;;x\x1b\\");
+
+    let mut out = PlainWriter::new(Vec::new(), false);
+    write!(out, "\x1b[1mbold").unwrap();
+    assert_eq!(out.out, b"\x1b[1mbold");
+}
+
+#[test]
+fn region_writer() {
+    use super::{draw_for_change, Region, RegionWriter};
+    use crate::backend::{BackendWriter, TestBackend};
+    use std::io::Write as _;
+
+    let backend = TestBackend::new(30, 8);
+    for row in 0..8 {
+        write!(
+            BackendWriter(&backend),
+            "\x1b[{};1H{}",
+            row + 1,
+            "#".repeat(30)
+        )
+        .unwrap();
+    }
+    let region = Region {
+        x: 5,
+        y: 2,
+        cols: 20,
+        rows: 5,
+    };
+    let mut ps = PagerState::new().unwrap();
+    (ps.cols, ps.rows) = (20, 5);
+    ps.region = Some(region);
+    ps.prompt = "prompt".to_string();
+    ps.screen.orig_text = (1..=10).map(|i| format!("Line {i}\n")).collect();
+    ps.format_lines();
+    ps.format_prompt();
+
+    let mut out = RegionWriter::new(BackendWriter(&backend));
+    out.region = Some(region);
+    draw_full(&mut out, &mut ps).unwrap();
+    draw_for_change(&mut out, &mut ps, &mut 3).unwrap();
+
+    let border = "#".repeat(30);
+    let row = |text: &str| format!("#####{text:<20}#####");
+    assert_eq!(
+        backend.screen(),
+        [
+            border.clone(),
+            border.clone(),
+            row("Line 4"),
+            row("Line 5"),
+            row("Line 6"),
+            row("Line 7"),
+            row("prompt"),
+            border,
+        ]
+    );
+    assert!(region.contains(5, 6) && !region.contains(25, 2) && !region.contains(4, 2));
+
+    // Sequences split across writes are still recognised
+    let mut out = RegionWriter::new(Vec::new());
+    out.region = Some(region);
+    write!(out, "\x1b[2;").unwrap();
+    write!(out, "3H\x1b[1mab\r\n").unwrap();
+    assert_eq!(out.out, b"\x1b[4;8H\x1b[1mab\x1b[6G\x1b[1B");
+}
+
 mod draw_for_change_tests {
-    use super::{draw_for_change, write_prompt};
+    use super::{draw_for_change, draw_full, write_prompt};
     use crate::state::PagerState;
     use crossterm::{
         cursor::MoveTo,
-        terminal::{Clear, ClearType, ScrollDown, ScrollUp},
+        terminal::{
+            BeginSynchronizedUpdate, Clear, ClearType, EndSynchronizedUpdate, ScrollDown, ScrollUp,
+        },
     };
     use std::fmt::Write as FmtWrite;
     use std::io::Write as IOWrite;
@@ -495,6 +733,39 @@ mod draw_for_change_tests {
         assert_eq!(out, res);
     }
 
+    #[test]
+    fn without_scrolling() {
+        let mut ps = create_pager_state();
+        ps.capabilities.scrolling = false;
+        let mut out = Vec::new();
+        draw_for_change(&mut out, &mut ps, &mut 3).unwrap();
+
+        let mut res = Vec::new();
+        draw_full(&mut res, &mut ps).unwrap();
+        assert_eq!(out, res);
+        assert_eq!(ps.upper_mark, 3);
+
+        let mut out = Vec::new();
+        draw_for_change(&mut out, &mut ps, &mut 3).unwrap();
+        assert!(out.is_empty());
+    }
+
+    #[test]
+    fn synchronized_output() {
+        let mut ps = create_pager_state();
+        let mut plain = Vec::new();
+        draw_for_change(&mut plain, &mut create_pager_state(), &mut 3).unwrap();
+
+        ps.capabilities.synchronized_output = true;
+        let mut out = Vec::new();
+        draw_for_change(&mut out, &mut ps, &mut 3).unwrap();
+        let mut res = Vec::new();
+        crossterm::queue!(res, BeginSynchronizedUpdate).unwrap();
+        res.extend_from_slice(&plain);
+        crossterm::queue!(res, EndSynchronizedUpdate).unwrap();
+        assert_eq!(out, res);
+    }
+
     #[test]
     fn large_scrolldown() {
         let mut ps = create_pager_state();
@@ -574,4 +845,76 @@ mod draw_for_change_tests {
 
         assert_eq!(out, res);
     }
+
+    /// Generates the same sequence of pseudo random numbers on every run
+    struct XorShift(u64);
+
+    impl XorShift {
+        fn below(&mut self, bound: usize) -> usize {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            (self.0 % bound as u64) as usize
+        }
+    }
+
+    #[test]
+    fn matches_full_redraw() {
+        use crate::backend::{BackendWriter, TestBackend};
+        use crate::LineNumbers;
+
+        let mut rng = XorShift(0x2545_f491_4f6c_dd1d);
+        for _ in 0..1000 {
+            let mut ps = PagerState::new().unwrap();
+            ps.cols = 20 + rng.below(30);
+            ps.rows = 3 + rng.below(20);
+            ps.line_numbers = if rng.below(2) == 0 {
+                LineNumbers::Enabled
+            } else {
+                LineNumbers::Disabled
+            };
+            ps.show_prompt = rng.below(4) != 0;
+            if rng.below(4) == 0 {
+                ps.eob_filler = "~".to_string();
+            }
+            // Lines of various lengths so that some of them get wrapped
+            let line_count = rng.below(60);
+            for i in 0..line_count {
+                writeln!(ps.screen.orig_text, "{i}{}", " word".repeat(rng.below(20))).unwrap();
+            }
+            ps.format_lines();
+            ps.format_prompt();
+            let rows = ps.screen.formatted_lines_count();
+
+            ps.upper_mark = rng.below(rows + 10);
+            let backend = TestBackend::new(ps.cols as u16, ps.rows as u16);
+            draw_full(&mut BackendWriter(&backend), &mut ps).unwrap();
+
+            // Both small scrolls and jumps larger than the screen
+            let mut new_upper_mark = if rng.below(2) == 0 {
+                (ps.upper_mark + rng.below(ps.rows * 2)).saturating_sub(ps.rows)
+            } else {
+                rng.below(rows + 10)
+            };
+            let old_upper_mark = ps.upper_mark;
+            draw_for_change(&mut BackendWriter(&backend), &mut ps, &mut new_upper_mark).unwrap();
+            assert_eq!(ps.upper_mark, new_upper_mark);
+            assert_eq!(
+                ps.screen.visible_text(),
+                ps.screen.get_formatted_lines_with_bounds(
+                    new_upper_mark,
+                    new_upper_mark + ps.text_rows()
+                )
+            );
+
+            let expected = TestBackend::new(ps.cols as u16, ps.rows as u16);
+            draw_full(&mut BackendWriter(&expected), &mut ps).unwrap();
+            assert_eq!(
+                backend.screen(),
+                expected.screen(),
+                "scrolling from {old_upper_mark} to {new_upper_mark} with {} rows",
+                ps.rows
+            );
+        }
+    }
 }
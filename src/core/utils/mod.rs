@@ -1,11 +1,141 @@
 pub mod display;
 pub mod term;
 
+use std::borrow::Cow;
+
 /// Return the number of digits in `num`
 pub const fn digits(num: usize) -> usize {
     (if num == 0 { 0 } else { num.ilog10() as usize }) + 1
 }
 
+/// Remove all ANSI escape sequences from `text`
+///
+/// This handles CSI sequences like colors and cursor movements, OSC sequences like hyperlinks
+/// and window titles as well as two byte escape sequences.
+pub fn strip_ansi(text: &str) -> Cow<'_, str> {
+    if !text.contains(['\u{1b}', '\u{9b}']) {
+        return Cow::Borrowed(text);
+    }
+    let mut stripped = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\u{1b}' => match chars.next() {
+                // CSI sequence: ends at the first byte in the range 0x40..=0x7E
+                Some('[') => {
+                    for c in chars.by_ref() {
+                        if ('\u{40}'..='\u{7e}').contains(&c) {
+                            break;
+                        }
+                    }
+                }
+                // OSC sequence: ends with BEL or ST (ESC \)
+                Some(']') => {
+                    while let Some(c) = chars.next() {
+                        if c == '\u{7}' {
+                            break;
+                        }
+                        if c == '\u{1b}' && chars.peek() == Some(&'\\') {
+                            chars.next();
+                            break;
+                        }
+                    }
+                }
+                // Two byte sequence, already consumed
+                _ => {}
+            },
+            '\u{9b}' => {
+                for c in chars.by_ref() {
+                    if ('\u{40}'..='\u{7e}').contains(&c) {
+                        break;
+                    }
+                }
+            }
+            c => stripped.push(c),
+        }
+    }
+    Cow::Owned(stripped)
+}
+
+/// Turn the overstrike sequences in `text` into ANSI escape sequences
+///
+/// Text formatted by `nroff`, like man pages, makes a character bold by printing it twice with a
+/// backspace in between like `X\bX` and underlines it with `_\bX`. These are turned into the
+/// escape sequences for bold and underlined text the same way as `less` displays them. Backspaces
+/// that aren't part of such a sequence are kept.
+pub fn overstrike_to_ansi(text: &str) -> Cow<'_, str> {
+    if !text.contains('\u{8}') {
+        return Cow::Borrowed(text);
+    }
+    let mut converted = String::with_capacity(text.len());
+    let (mut bold, mut underline) = (false, false);
+    let mut chars = text.chars().peekable();
+
+    while let Some(mut c) = chars.next() {
+        let (mut is_bold, mut is_underline) = (false, false);
+        // A character can be struck over several times, like `_\bX\bX` for bold underlined text
+        while !c.is_control() && chars.peek() == Some(&'\u{8}') {
+            let mut ahead = chars.clone();
+            ahead.next();
+            let Some(next) = ahead.next().filter(|next| !next.is_control()) else {
+                break;
+            };
+            match (c, next) {
+                _ if c == next => is_bold = true,
+                ('_', _) => {
+                    is_underline = true;
+                    c = next;
+                }
+                (_, '_') => is_underline = true,
+                // Like on a terminal, the last character struck is the one that remains
+                _ => c = next,
+            }
+            chars = ahead;
+        }
+        if is_bold != bold {
+            converted.push_str(if is_bold { "\u{1b}[1m" } else { "\u{1b}[22m" });
+            bold = is_bold;
+        }
+        if is_underline != underline {
+            converted.push_str(if is_underline {
+                "\u{1b}[4m"
+            } else {
+                "\u{1b}[24m"
+            });
+            underline = is_underline;
+        }
+        converted.push(c);
+    }
+    if bold {
+        converted.push_str("\u{1b}[22m");
+    }
+    if underline {
+        converted.push_str("\u{1b}[24m");
+    }
+    Cow::Owned(converted)
+}
+
+/// Find where the overstrike sequence at the end of `text` starts
+///
+/// The sequence may still be continued by text that arrives later, like a character after a
+/// trailing backspace or a backspace after the last character. This also covers a character that
+/// is already struck over, like `_\bX`, since it may be struck once more. Returns `text.len()` if
+/// `text` doesn't end with a printable character or a backspace following one.
+pub fn overstrike_tail(text: &str) -> usize {
+    let mut start = text.len();
+    let mut rest = text.strip_suffix('\u{8}').unwrap_or(text);
+    while let Some(c) = rest.chars().next_back().filter(|c| !c.is_control()) {
+        rest = &rest[..rest.len() - c.len_utf8()];
+        start = rest.len();
+        match rest.strip_suffix('\u{8}') {
+            Some(struck) => rest = struck,
+            None => break,
+        }
+    }
+    start
+}
+
 /// Stores the location of first row each line
 ///
 /// Due to line wrapping, each line may or may not occupy exactly one row on the terminal
@@ -36,4 +166,57 @@ impl LinesRowMap {
     pub fn get(&self, ln: usize) -> Option<&usize> {
         self.0.get(ln)
     }
+
+    /// Get the line which the given `row` belongs to
+    pub fn line_of_row(&self, row: usize) -> usize {
+        self.0.partition_point(|&r| r <= row).saturating_sub(1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{overstrike_tail, overstrike_to_ansi, strip_ansi};
+
+    #[test]
+    fn strip_ansi_removes_escapes() {
+        assert_eq!(strip_ansi("plain text"), "plain text");
+        assert_eq!(
+            strip_ansi("\u{1b}[1;31mred\u{1b}[0m and \u{1b}[4munderlined\u{1b}[24m"),
+            "red and underlined"
+        );
+        assert_eq!(
+            strip_ansi("\u{1b}]8;;https://example.com\u{1b}\\link\u{1b}]8;;\u{7}"),
+            "link"
+        );
+        assert_eq!(strip_ansi("\u{1b}7saved\u{1b}8"), "saved");
+    }
+
+    #[test]
+    fn overstrike_to_ansi_styles() {
+        assert_eq!(overstrike_to_ansi("plain text"), "plain text");
+        assert_eq!(
+            overstrike_to_ansi("N\u{8}NA\u{8}A a\u{8}b\n\u{8}x"),
+            "\u{1b}[1mNA\u{1b}[22m b\n\u{8}x"
+        );
+        assert_eq!(
+            overstrike_to_ansi("_\u{8}f_\u{8}i\n_\u{8}_"),
+            "\u{1b}[4mfi\u{1b}[24m\n\u{1b}[1m_\u{1b}[22m"
+        );
+        assert_eq!(
+            overstrike_to_ansi("_\u{8}X\u{8}X\u{8}"),
+            "\u{1b}[1m\u{1b}[4mX\u{1b}[22m\u{1b}[24m\u{8}"
+        );
+    }
+
+    #[test]
+    fn overstrike_tail_start() {
+        assert_eq!(overstrike_tail("ab"), 1);
+        assert_eq!(overstrike_tail("ab\u{8}"), 1);
+        assert_eq!(overstrike_tail("a_\u{8}X\u{8}X"), 1);
+        assert_eq!(overstrike_tail("a_\u{8}X\u{8}"), 1);
+        assert_eq!(overstrike_tail("N\u{8}NA\u{8}A"), 3);
+        assert_eq!(overstrike_tail("ab\n"), 3);
+        assert_eq!(overstrike_tail("\n\u{8}"), 2);
+        assert_eq!(overstrike_tail(""), 0);
+    }
 }
@@ -2,44 +2,114 @@
 
 #![allow(dead_code)]
 
-use crate::error::{CleanupError, MinusError, SetupError};
+use crate::{
+    backend::{Backend, Capabilities},
+    error::{CleanupError, MinusError, SetupError},
+};
 use crossterm::{
-    cursor, event, execute, queue,
+    cursor,
+    event::KeyboardEnhancementFlags,
+    queue,
     terminal::{self, Clear},
-    tty::IsTty,
 };
-use std::io;
+use std::{
+    io,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+/// Whether keyboard enhancement flags were pushed by [`setup`] and need to be popped in [`cleanup`]
+static KEYBOARD_ENHANCED: AtomicBool = AtomicBool::new(false);
+/// Whether mouse capture was enabled by [`setup`] and needs to be disabled in [`cleanup`]
+static MOUSE_CAPTURED: AtomicBool = AtomicBool::new(false);
+
+/// Escape sequence making the terminal send arrow keys for the mouse wheel in the alternate screen
+const ENABLE_ALTERNATE_SCROLL: &str = "\x1b[?1007h";
+/// Escape sequence undoing [`ENABLE_ALTERNATE_SCROLL`]
+const DISABLE_ALTERNATE_SCROLL: &str = "\x1b[?1007l";
+/// Escape sequence pushing the window title on the title stack of the terminal
+const SAVE_TITLE: &str = "\x1b[22;0t";
+/// Escape sequence popping the window title from the title stack of the terminal
+const RESTORE_TITLE: &str = "\x1b[23;0t";
+
+/// Changes made to the terminal by a pager that have to be undone in [`cleanup`]
+///
+/// Each pager has its own, so that pagers running one after another or at the same time don't
+/// undo each other's changes.
+#[derive(Debug, Default)]
+pub struct TermModes {
+    /// Whether alternate scroll mode was enabled by [`setup`] and needs to be disabled
+    alternate_scroll: AtomicBool,
+    /// Whether the window title was saved by [`set_window_title`] and needs to be restored
+    title_saved: AtomicBool,
+}
 
 /// Setup the terminal
 ///
 /// It will
 /// - Switch the terminal's view to the [alternate screen]
 /// - Then enable [raw mode]
+/// - Enable mouse capture if the terminal supports mouse events, unless `alternate_scroll` is set
+///   in which case alternate scroll mode is enabled instead and recorded in `modes`
 /// - Clear the entire screen and hide the cursor.
+/// - Push the `keyboard_enhancement` flags if any of them are set and the terminal supports the
+///   kitty keyboard protocol
 ///
 /// # Errors
-/// The function will return with an error if `stdout` is not a terminal. It will qlso fail
+/// The function will return with an error if the backend is not a terminal. It will qlso fail
 /// if it cannot executo commands on the terminal See [`SetupError`].
 ///
 /// [alternate screen]: ../../../crossterm/terminal/index.html#alternate-screen
 /// [raw mode]: ../../../crossterm/terminal/index.html#raw-mode
 // This function should be kept close to `cleanup` to help ensure both are
 // doing the opposite of the other.
-pub fn setup(stdout: &io::Stdout) -> std::result::Result<(), SetupError> {
-    let mut out = stdout.lock();
-
-    if out.is_tty() {
+pub fn setup(
+    backend: &dyn Backend,
+    keyboard_enhancement: KeyboardEnhancementFlags,
+    capabilities: Capabilities,
+    alternate_scroll: bool,
+    modes: &TermModes,
+) -> std::result::Result<(), SetupError> {
+    #[cfg(feature = "tracing")]
+    tracing::debug!(
+        is_tty = backend.is_tty(),
+        ?keyboard_enhancement,
+        ?capabilities,
+        alternate_scroll,
+        "setting up the terminal"
+    );
+    if backend.is_tty() {
         Ok(())
     } else {
         Err(SetupError::InvalidTerminal)
     }?;
 
-    execute!(out, terminal::EnterAlternateScreen)
+    backend
+        .enter_alternate_screen()
         .map_err(|e| SetupError::AlternateScreen(e.into()))?;
-    terminal::enable_raw_mode().map_err(|e| SetupError::RawMode(e.into()))?;
-    execute!(out, event::EnableMouseCapture)
-        .map_err(|e| SetupError::EnableMouseCapture(e.into()))?;
-    execute!(out, cursor::Hide).map_err(|e| SetupError::HideCursor(e.into()))?;
+    backend
+        .enable_raw_mode()
+        .map_err(|e| SetupError::RawMode(e.into()))?;
+    if alternate_scroll {
+        backend
+            .write(ENABLE_ALTERNATE_SCROLL.as_bytes())
+            .and_then(|_| backend.flush())
+            .map_err(|e| SetupError::EnableAlternateScroll(e.into()))?;
+        modes.alternate_scroll.store(true, Ordering::SeqCst);
+    } else if capabilities.mouse {
+        backend
+            .enable_mouse_capture()
+            .map_err(|e| SetupError::EnableMouseCapture(e.into()))?;
+        MOUSE_CAPTURED.store(true, Ordering::SeqCst);
+    }
+    backend
+        .hide_cursor()
+        .map_err(|e| SetupError::HideCursor(e.into()))?;
+    if capabilities.kitty_keyboard && !keyboard_enhancement.is_empty() {
+        backend
+            .push_keyboard_enhancement_flags(keyboard_enhancement)
+            .map_err(|e| SetupError::EnableKeyboardEnhancement(e.into()))?;
+        KEYBOARD_ENHANCED.store(true, Ordering::SeqCst);
+    }
     Ok(())
 }
 
@@ -50,6 +120,7 @@ pub fn setup(stdout: &io::Stdout) -> std::result::Result<(), SetupError> {
 /// - First the cursor is displayed
 /// - [Raw mode] is disabled
 /// - Switch the terminal's view to the main screen
+/// - Restore the window title if it was changed by [`set_window_title`] with the same `modes`
 ///
 /// ## Errors
 /// The function will return with an error if it fails to do execute commands on the
@@ -57,25 +128,76 @@ pub fn setup(stdout: &io::Stdout) -> std::result::Result<(), SetupError> {
 ///
 /// [raw mode]: ../../../crossterm/terminal/index.html#raw-mode
 pub fn cleanup(
-    mut out: impl io::Write,
+    backend: &dyn Backend,
+    modes: &TermModes,
     es: &crate::ExitStrategy,
     cleanup_screen: bool,
 ) -> std::result::Result<(), CleanupError> {
+    #[cfg(feature = "tracing")]
+    tracing::debug!(cleanup_screen, "cleaning up the terminal");
     if cleanup_screen {
         // Reverse order of setup.
-        execute!(out, cursor::Show).map_err(|e| CleanupError::ShowCursor(e.into()))?;
-        execute!(out, event::DisableMouseCapture)
-            .map_err(|e| CleanupError::DisableMouseCapture(e.into()))?;
-        terminal::disable_raw_mode().map_err(|e| CleanupError::DisableRawMode(e.into()))?;
-        execute!(out, terminal::LeaveAlternateScreen)
+        if KEYBOARD_ENHANCED.swap(false, Ordering::SeqCst) {
+            backend
+                .pop_keyboard_enhancement_flags()
+                .map_err(|e| CleanupError::DisableKeyboardEnhancement(e.into()))?;
+        }
+        backend
+            .show_cursor()
+            .map_err(|e| CleanupError::ShowCursor(e.into()))?;
+        if MOUSE_CAPTURED.swap(false, Ordering::SeqCst) {
+            backend
+                .disable_mouse_capture()
+                .map_err(|e| CleanupError::DisableMouseCapture(e.into()))?;
+        }
+        if modes.alternate_scroll.swap(false, Ordering::SeqCst) {
+            backend
+                .write(DISABLE_ALTERNATE_SCROLL.as_bytes())
+                .and_then(|_| backend.flush())
+                .map_err(|e| CleanupError::DisableAlternateScroll(e.into()))?;
+        }
+        backend
+            .disable_raw_mode()
+            .map_err(|e| CleanupError::DisableRawMode(e.into()))?;
+        backend
+            .leave_alternate_screen()
             .map_err(|e| CleanupError::LeaveAlternateScreen(e.into()))?;
     }
+    if modes.title_saved.swap(false, Ordering::SeqCst) && backend.is_tty() {
+        backend
+            .write(RESTORE_TITLE.as_bytes())
+            .and_then(|_| backend.flush())
+            .map_err(|e| CleanupError::RestoreWindowTitle(e.into()))?;
+    }
 
-    if *es == crate::ExitStrategy::ProcessQuit {
-        std::process::exit(0);
-    } else {
-        Ok(())
+    match es {
+        crate::ExitStrategy::ProcessQuit => std::process::exit(0),
+        crate::ExitStrategy::ProcessQuitWithCode(code) => std::process::exit(*code),
+        crate::ExitStrategy::ProcessQuitWithCallback(cb) => std::process::exit(cb()),
+        crate::ExitStrategy::PagerQuit => Ok(()),
+    }
+}
+
+/// Set the title of the terminal window, or restore the previous title if `title` is `None`
+///
+/// The title the window had before is saved in `modes` the first time this sets a title.
+pub fn set_window_title(
+    out: &mut impl io::Write,
+    modes: &TermModes,
+    title: Option<&str>,
+) -> Result<(), MinusError> {
+    if let Some(title) = title {
+        if !modes.title_saved.swap(true, Ordering::SeqCst) {
+            write!(out, "{SAVE_TITLE}")?;
+        }
+        // A control character would end the escape sequence early
+        let title: String = title.chars().filter(|c| !c.is_control()).collect();
+        queue!(out, terminal::SetTitle(title))?;
+    } else if modes.title_saved.swap(false, Ordering::SeqCst) {
+        write!(out, "{RESTORE_TITLE}")?;
     }
+    out.flush()?;
+    Ok(())
 }
 
 /// Moves the terminal cursor to given x, y coordinates
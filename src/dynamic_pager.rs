@@ -1,6 +1,7 @@
 use crate::error::MinusError;
-use crate::minus_core::init;
-use crate::Pager;
+use crate::minus_core::{commands::Command, init};
+use crate::{ExitReason, Pager, PagerExit};
+use std::thread::{self, JoinHandle};
 
 /// Starts a asynchronously running pager
 ///
@@ -16,5 +17,145 @@ use crate::Pager;
 #[cfg_attr(docsrs, doc(cfg(feature = "dynamic_output")))]
 #[allow(clippy::needless_pass_by_value)]
 pub fn dynamic_paging(pager: Pager) -> Result<(), MinusError> {
-    init::init_core(&pager, crate::RunMode::Dynamic)
+    init::init_core(&pager, crate::RunMode::Dynamic).map(|_| ())
+}
+
+/// Starts a asynchronously running pager from async code
+///
+/// This is same as [`dynamic_paging`] except that it returns a future which resolves once the
+/// pager quits. The pager runs inside this future, hence the application does not need to manage a
+/// separate thread or call [`tokio::task::spawn_blocking`] for it. While waiting for data,
+/// commands or user input, the future yields to the runtime rather than blocking the thread.
+///
+/// The user input is read from [`Backend::event_stream`](crate::backend::Backend::event_stream),
+/// which the default backend provides through crossterm's `EventStream`. Backends that don't
+/// provide a stream are polled on a thread of their own. Drawing the output and the search prompt
+/// are not async and block the task while they run. To feed a [bounded](Pager::bounded) pager
+/// from async code, use [`Pager::push_str_async`], which waits for space without blocking the
+/// thread.
+///
+/// # Panics
+/// This function will panic if another instance of minus is already running or if the runtime
+/// it is polled in doesn't have the time driver enabled.
+///
+/// # Errors
+/// The function will return with an error if it encounters a error during paging.
+///
+/// # Example
+/// ```rust,no_run
+/// use minus::{async_paging, MinusError, Pager};
+/// use std::fmt::Write;
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), MinusError> {
+///     let mut pager = Pager::new();
+///     for i in 0..=100_u32 {
+///         writeln!(pager, "{}", i)?;
+///     }
+///     async_paging(pager).await
+/// }
+/// ```
+#[cfg(feature = "tokio")]
+#[cfg_attr(docsrs, doc(cfg(all(feature = "dynamic_output", feature = "tokio"))))]
+#[allow(clippy::needless_pass_by_value)]
+pub async fn async_paging(pager: Pager) -> Result<(), MinusError> {
+    init::init_core_async(&pager).await.map(|_| ())
+}
+
+/// A handle to control a pager started with [`Pager::start`]
+///
+/// Unlike the [`Pager`], which is mainly used to feed data into minus, this allows the
+/// application to control the running pager, like closing it or moving the view to a specific
+/// line.
+///
+/// Dropping the handle does not stop the pager. Call [`quit`](PagerHandle::quit) for that.
+///
+/// # Example
+/// ```rust,no_run
+/// use minus::{ExitReason, MinusError, Pager};
+///
+/// fn main() -> Result<(), MinusError> {
+///     let pager = Pager::new();
+///     let handle = pager.start();
+///
+///     for i in 0..=100_u32 {
+///         pager.push_str(format!("{i}\n"))?;
+///     }
+///     // Show the 50th line at the top of the screen
+///     handle.scroll_to(50)?;
+///
+///     let exit = handle.wait();
+///     if let ExitReason::Error(e) = exit.reason {
+///         return Err(e);
+///     }
+///     println!("Stopped reading at line {}", exit.top_line);
+///     Ok(())
+/// }
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "dynamic_output")))]
+pub struct PagerHandle {
+    pager: Pager,
+    thread: JoinHandle<PagerExit>,
+}
+
+impl PagerHandle {
+    pub(crate) fn new(pager: Pager) -> Self {
+        let pager2 = pager.clone();
+        let thread = thread::spawn(move || {
+            init::init_core(&pager2, crate::RunMode::Dynamic).unwrap_or_else(|e| PagerExit {
+                reason: ExitReason::Error(e),
+                top_line: 1,
+            })
+        });
+        Self { pager, thread }
+    }
+
+    /// Close the pager
+    ///
+    /// This is same as calling [`Pager::quit`].
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    pub fn quit(&self) -> Result<(), MinusError> {
+        self.pager.quit()
+    }
+
+    /// Reformat the text and redraw the entire screen
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    pub fn redraw(&self) -> Result<(), MinusError> {
+        Ok(self.pager.tx.send(Command::FormatRedrawDisplay)?)
+    }
+
+    /// Move the view so that the given line is at the top of the screen
+    ///
+    /// Line numbers start from 1. If the line does not exist, the view moves to the bottom of the
+    /// text.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    pub fn scroll_to(&self, line: usize) -> Result<(), MinusError> {
+        self.pager.scroll_to_line(line)
+    }
+
+    /// Returns true if the pager hasn't quit yet
+    #[must_use]
+    pub fn is_running(&self) -> bool {
+        !self.thread.is_finished()
+    }
+
+    /// Block until the pager quits and return why it quit
+    ///
+    /// Errors encountered while paging are reported as [`ExitReason::Error`].
+    ///
+    /// # Panics
+    /// This function will panic if the thread running the pager panicked.
+    #[must_use = "the exit reason tells whether the pager quit because of an error"]
+    pub fn wait(self) -> PagerExit {
+        self.thread.join().unwrap()
+    }
 }
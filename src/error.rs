@@ -23,12 +23,12 @@ pub struct TermError(
 #[derive(Debug, thiserror::Error)]
 #[error(transparent)]
 #[allow(clippy::module_name_repetitions)]
-#[cfg(feature = "search")]
-#[cfg_attr(docsrs, doc(cfg(feature = "search")))]
+#[cfg(feature = "__regex")]
+#[cfg_attr(docsrs, doc(cfg(any(feature = "search", feature = "highlight"))))]
 pub struct RegexError(
     // This member is private to avoid leaking the regex error type up the
     // dependency chain.
-    #[from] regex::Error,
+    #[from] crate::pattern::EngineError,
 );
 
 /// Errors that can occur during setup.
@@ -50,6 +50,12 @@ pub enum SetupError {
     #[error("Failed to enable mouse capture")]
     EnableMouseCapture(TermError),
 
+    #[error("Failed to enable keyboard enhancements")]
+    EnableKeyboardEnhancement(TermError),
+
+    #[error("Failed to enable alternate scroll mode")]
+    EnableAlternateScroll(TermError),
+
     #[error("Couldn't determine the terminal size")]
     TerminalSize(TermError),
 }
@@ -61,6 +67,15 @@ pub enum CleanupError {
     #[error("Failed to disable mouse capture")]
     DisableMouseCapture(TermError),
 
+    #[error("Failed to disable keyboard enhancements")]
+    DisableKeyboardEnhancement(TermError),
+
+    #[error("Failed to restore the window title")]
+    RestoreWindowTitle(TermError),
+
+    #[error("Failed to disable alternate scroll mode")]
+    DisableAlternateScroll(TermError),
+
     #[error("Failed to show the cursor")]
     ShowCursor(TermError),
 
@@ -71,6 +86,61 @@ pub enum CleanupError {
     LeaveAlternateScreen(TermError),
 }
 
+/// A key or mouse binding description could not be parsed
+///
+/// Returned by [`try_parse_key_event`](crate::input::try_parse_key_event),
+/// [`try_parse_mouse_event`](crate::input::try_parse_mouse_event) and the fallible methods of
+/// [`HashedEventRegister`](crate::input::HashedEventRegister).
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("'{text}': {reason} at position {position}")]
+#[allow(clippy::module_name_repetitions)]
+pub struct BindingParseError {
+    /// The description that failed to parse
+    pub text: String,
+    /// Byte offset into `text` where the problem was found
+    pub position: usize,
+    /// Why the description is invalid
+    pub reason: BindingParseErrorReason,
+}
+
+impl BindingParseError {
+    pub(crate) fn new(text: &str, position: usize, reason: BindingParseErrorReason) -> Self {
+        Self {
+            text: text.to_string(),
+            position,
+            reason,
+        }
+    }
+}
+
+/// The reason behind a [`BindingParseError`]
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[non_exhaustive]
+pub enum BindingParseErrorReason {
+    #[error("non ascii character found")]
+    NonAscii,
+    #[error("empty description")]
+    Empty,
+    #[error("multiple separators found consecutively")]
+    ConsecutiveSeparators,
+    #[error("multiple instances of same modifier given")]
+    DuplicateModifier,
+    #[error("unknown key '{0}'")]
+    UnknownKey(String),
+    #[error("unknown mouse action '{0}'")]
+    UnknownMouseAction(String),
+    #[error("more than one key given")]
+    MultipleKeys,
+    #[error("more than one mouse action given")]
+    MultipleMouseActions,
+    #[error("invalid modifier '{0}'")]
+    InvalidModifier(char),
+    #[error("modifier not followed by a separator")]
+    MissingSeparator,
+    #[error("no mouse action given")]
+    MissingMouseAction,
+}
+
 /// Errors that can happen during runtime.
 #[derive(Debug, thiserror::Error)]
 #[allow(clippy::module_name_repetitions)]
@@ -96,12 +166,21 @@ pub enum MinusError {
     #[error("Failed to send data to the receiver")]
     Communication(#[from] crossbeam_channel::SendError<Command>),
 
+    #[error("The pager can't take more data until it catches up")]
+    WouldBlock,
+
     #[error("Failed to convert between some primitives")]
     Conversion,
 
+    /// The terminal went away while the pager was running, for example because its window was
+    /// closed or the SSH connection dropped. The terminal is restored as far as possible but
+    /// there may be nobody left to show an error message to.
+    #[error("The terminal was closed")]
+    TerminalGone,
+
     #[error(transparent)]
-    #[cfg(feature = "search")]
-    #[cfg_attr(docsrs, doc(cfg(feature = "search")))]
+    #[cfg(feature = "__regex")]
+    #[cfg_attr(docsrs, doc(cfg(any(feature = "search", feature = "highlight"))))]
     SearchExpError(#[from] RegexError),
 
     #[cfg(feature = "tokio")]
@@ -111,9 +190,39 @@ pub enum MinusError {
 }
 
 // Just for  convenience helper which is useful in many places
-#[cfg(feature = "search")]
-impl From<regex::Error> for MinusError {
-    fn from(e: regex::Error) -> Self {
+#[cfg(feature = "__regex")]
+impl From<crate::pattern::EngineError> for MinusError {
+    fn from(e: crate::pattern::EngineError) -> Self {
         Self::SearchExpError(RegexError::from(e))
     }
 }
+
+impl MinusError {
+    /// Turn an error caused by the terminal going away into [`MinusError::TerminalGone`]
+    pub(crate) fn or_terminal_gone(self) -> Self {
+        let (Self::Draw(e) | Self::HandleEvent(TermError(e)) | Self::Cursor(TermError(e))) = &self
+        else {
+            return self;
+        };
+        if is_terminal_gone(e) {
+            Self::TerminalGone
+        } else {
+            self
+        }
+    }
+}
+
+/// Whether `error` tells that the terminal is no longer there
+///
+/// Writing to a closed pipe or socket fails with a broken pipe while a terminal whose window was
+/// closed gives `ENXIO` or `EIO`.
+fn is_terminal_gone(error: &io::Error) -> bool {
+    /// Values of `EIO` and `ENXIO`, which are the same on all unix platforms
+    const GONE_ERRNOS: [i32; 2] = [5, 6];
+
+    error.kind() == io::ErrorKind::BrokenPipe
+        || (cfg!(unix)
+            && error
+                .raw_os_error()
+                .is_some_and(|code| GONE_ERRNOS.contains(&code)))
+}
@@ -0,0 +1,46 @@
+//! Provides the [`PagerEvent`] type
+//!
+//! Applications can receive these events by calling [`Pager::subscribe_events`] to react to
+//! things happening inside the pager, like the user scrolling through the text.
+//!
+//! [`Pager::subscribe_events`]: crate::Pager::subscribe_events
+
+#[cfg(feature = "__search")]
+use crate::search::SearchMode;
+
+/// Things that happen inside a running pager
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum PagerEvent {
+    /// The view was scrolled vertically
+    ///
+    /// `old` and `new` are the rows that were displayed at the top of the screen before and
+    /// after scrolling. `top_line` is the line number, starting from 1, of the line at the top of
+    /// the screen and `percent` is how much of the text has been read, counting till the bottom
+    /// of the screen.
+    Scroll {
+        old: usize,
+        new: usize,
+        top_line: usize,
+        percent: usize,
+    },
+    /// The user started entering a search query
+    #[cfg(feature = "__search")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "search")))]
+    SearchStarted(SearchMode),
+    /// The user finished entering a search query
+    ///
+    /// `matches` is the number of rows that matched the query. This is 0 if the search was
+    /// cancelled or the query was invalid.
+    #[cfg(feature = "__search")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "search")))]
+    SearchFinished { matches: usize },
+    /// The terminal was resized to the given number of columns and rows
+    Resize { cols: usize, rows: usize },
+    /// Follow mode was turned on or off, either by the user or automatically while scrolling
+    FollowToggled(bool),
+    /// The user clicked on an OSC 8 hyperlink. Contains the URI of the link.
+    LinkClicked(String),
+    /// The pager is about to quit
+    Exit,
+}
@@ -0,0 +1,92 @@
+//! Handles the commands typed at the prompt after pressing `:`
+//!
+//! See [`InputEvent::EnterCommand`] for the list of commands.
+
+use super::InputEvent;
+use crate::PagerState;
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+
+/// Handle a key press while a command is being typed
+///
+/// This updates the command in [`PagerState::command_line`] and returns the event to run once
+/// the command is complete. Unknown commands are reported through the message at the prompt.
+pub fn handle_key(ev: &Event, ps: &mut PagerState) -> Option<InputEvent> {
+    let Event::Key(KeyEvent {
+        code, modifiers, ..
+    }) = ev
+    else {
+        return None;
+    };
+    let command = ps.command_line.as_mut()?;
+
+    match code {
+        KeyCode::Char(c) if !modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) => {
+            command.push(*c);
+            // Single letter commands run immediately like in less
+            if command.len() > 1 {
+                return None;
+            }
+            match *c {
+                'n' | 'p' | 'x' => {}
+                _ => return None,
+            }
+        }
+        KeyCode::Backspace => {
+            if command.pop().is_none() {
+                ps.command_line = None;
+            }
+            return None;
+        }
+        KeyCode::Enter => {}
+        _ => {
+            ps.command_line = None;
+            return None;
+        }
+    }
+
+    let command = ps.command_line.take()?;
+    let buffers = ps.buffers.len();
+    match command.as_str() {
+        "" => None,
+        "n" if ps.active_buffer + 1 < buffers => {
+            Some(InputEvent::SwitchBuffer(ps.active_buffer + 1))
+        }
+        "n" => {
+            ps.push_message("No next buffer".to_string());
+            None
+        }
+        "p" if ps.active_buffer > 0 => Some(InputEvent::SwitchBuffer(ps.active_buffer - 1)),
+        "p" => {
+            ps.push_message("No previous buffer".to_string());
+            None
+        }
+        "x" => Some(InputEvent::SwitchBuffer(0)),
+        "buffers" => Some(InputEvent::ListBuffers),
+        _ if command.starts_with(|c: char| c.is_ascii_digit() || c == '+' || c == '-') => {
+            goto_line(&command, ps)
+        }
+        _ => {
+            ps.push_message(format!("Unknown command: {command}"));
+            None
+        }
+    }
+}
+
+/// Parse a `N`, `+N` or `-N` command into the line to go to
+fn goto_line(command: &str, ps: &mut PagerState) -> Option<InputEvent> {
+    let (sign, digits) = match command.split_at(1) {
+        ("+", rest) => (Some('+'), rest),
+        ("-", rest) => (Some('-'), rest),
+        _ => (None, command),
+    };
+    let Ok(n) = digits.parse::<usize>() else {
+        ps.push_message(format!("Invalid line number: {command}"));
+        return None;
+    };
+    let line = match sign {
+        Some('+') => ps.top_line().saturating_add(n),
+        Some(_) => ps.top_line().saturating_sub(n).max(1),
+        None => n,
+    };
+    Some(InputEvent::GotoLine(line))
+}
@@ -1,6 +1,7 @@
 #![allow(dead_code)]
 
-use super::{Token, MODIFIERS};
+use super::{Token, MODIFIERS, NAMED_MODIFIERS};
+use crate::error::{BindingParseError, BindingParseErrorReason};
 use std::collections::HashMap;
 
 use crossterm::event::{KeyCode, KeyEvent, KeyEventState, KeyModifiers};
@@ -24,27 +25,68 @@ static SPECIAL_KEYS: Lazy<HashMap<&str, KeyCode>> = Lazy::new(|| {
     map.insert("insert", KeyCode::Insert);
     map.insert("delete", KeyCode::Delete);
     map.insert("esc", KeyCode::Esc);
-    map.insert("f1", KeyCode::F(1));
-    map.insert("f2", KeyCode::F(2));
-    map.insert("f3", KeyCode::F(3));
-    map.insert("f4", KeyCode::F(4));
-    map.insert("f5", KeyCode::F(5));
-    map.insert("f6", KeyCode::F(6));
-    map.insert("f7", KeyCode::F(7));
-    map.insert("f8", KeyCode::F(8));
-    map.insert("f9", KeyCode::F(9));
-    map.insert("f10", KeyCode::F(10));
-    map.insert("f11", KeyCode::F(11));
-    map.insert("f12", KeyCode::F(12));
     map.insert("dash", KeyCode::Char('-'));
     map.insert("space", KeyCode::Char(' '));
+    map.insert("minus", KeyCode::Char('-'));
+    map.insert("plus", KeyCode::Char('+'));
+    map.insert("capslock", KeyCode::CapsLock);
+    map.insert("numlock", KeyCode::NumLock);
+    map.insert("scrolllock", KeyCode::ScrollLock);
+    map.insert("printscreen", KeyCode::PrintScreen);
+    map.insert("pause", KeyCode::Pause);
+    map.insert("menu", KeyCode::Menu);
 
     map
 });
 
+/// Keys on the numeric keypad
+///
+/// These are only reported separately by terminals which support the kitty keyboard protocol
+static KEYPAD_KEYS: Lazy<HashMap<&str, KeyCode>> = Lazy::new(|| {
+    let mut map = HashMap::new();
+
+    map.insert("kp0", KeyCode::Char('0'));
+    map.insert("kp1", KeyCode::Char('1'));
+    map.insert("kp2", KeyCode::Char('2'));
+    map.insert("kp3", KeyCode::Char('3'));
+    map.insert("kp4", KeyCode::Char('4'));
+    map.insert("kp5", KeyCode::Char('5'));
+    map.insert("kp6", KeyCode::Char('6'));
+    map.insert("kp7", KeyCode::Char('7'));
+    map.insert("kp8", KeyCode::Char('8'));
+    map.insert("kp9", KeyCode::Char('9'));
+    map.insert("kpplus", KeyCode::Char('+'));
+    map.insert("kpminus", KeyCode::Char('-'));
+    map.insert("kpmultiply", KeyCode::Char('*'));
+    map.insert("kpdivide", KeyCode::Char('/'));
+    map.insert("kpdecimal", KeyCode::Char('.'));
+    map.insert("kpequal", KeyCode::Char('='));
+    map.insert("kpenter", KeyCode::Enter);
+    map.insert("kpbegin", KeyCode::KeypadBegin);
+
+    map
+});
+
+/// Lock states that a binding can require, written like modifiers as `numlock-kp5`
+static LOCK_STATES: Lazy<HashMap<&str, KeyEventState>> = Lazy::new(|| {
+    let mut map = HashMap::new();
+
+    map.insert("numlock", KeyEventState::NUM_LOCK);
+    map.insert("capslock", KeyEventState::CAPS_LOCK);
+
+    map
+});
+
+/// Parse function keys like `f1` or `f13`
+fn function_key(name: &str) -> Option<KeyCode> {
+    let n: u8 = name.strip_prefix('f')?.parse().ok()?;
+    (n > 0).then_some(KeyCode::F(n))
+}
+
 struct KeySeq {
     code: Option<KeyCode>,
     modifiers: KeyModifiers,
+    state: KeyEventState,
 }
 
 impl Default for KeySeq {
@@ -52,75 +94,118 @@ impl Default for KeySeq {
         Self {
             code: None,
             modifiers: KeyModifiers::NONE,
+            state: KeyEventState::NONE,
         }
     }
 }
 
+/// Parse a key binding description
+///
+/// # Panics
+/// This function panics if `text` is not a valid description. See [`try_parse_key_event`].
 pub fn parse_key_event(text: &str) -> KeyEvent {
-    let token_list = super::parse_tokens(text);
+    try_parse_key_event(text).unwrap_or_else(|e| panic!("{}", e))
+}
+
+/// Parse a key binding description like `c-down` into a [`KeyEvent`]
+///
+/// See the [input](crate::input) module for the format of the description.
+///
+/// # Errors
+/// Returns a [`BindingParseError`] telling where and why the parsing failed if `text` is not a
+/// valid description.
+///
+/// # Example
+/// ```
+/// use minus::input::{crossterm_event::{KeyCode, KeyModifiers}, try_parse_key_event};
+///
+/// let ev = try_parse_key_event("c-down").unwrap();
+/// assert_eq!(ev.code, KeyCode::Down);
+/// assert_eq!(ev.modifiers, KeyModifiers::CONTROL);
+///
+/// let err = try_parse_key_event("c-dwn").unwrap_err();
+/// assert_eq!(err.position, 2);
+/// ```
+pub fn try_parse_key_event(text: &str) -> Result<KeyEvent, BindingParseError> {
+    let token_list = super::parse_tokens(text)?;
 
     KeySeq::gen_keyevent_from_tokenlist(&token_list, text)
 }
 
 impl KeySeq {
-    fn gen_keyevent_from_tokenlist(token_list: &[Token], text: &str) -> KeyEvent {
+    fn gen_keyevent_from_tokenlist(
+        token_list: &[(usize, Token)],
+        text: &str,
+    ) -> Result<KeyEvent, BindingParseError> {
         let mut ks = Self::default();
 
         let mut token_iter = token_list.iter().peekable();
+        let error = |pos, reason| Err(BindingParseError::new(text, pos, reason));
 
-        while let Some(token) = token_iter.peek() {
+        while let Some((pos, token)) = token_iter.next() {
+            let pos = *pos;
             match token {
                 Token::Separator => {
-                    token_iter.next();
-                    assert!(
-                        !(token_iter.peek() == Some(&&Token::Separator)),
-                        "'{}': Multiple separators found consecutively",
-                        text
-                    );
+                    if let Some((next, Token::Separator)) = token_iter.peek() {
+                        return error(*next, BindingParseErrorReason::ConsecutiveSeparators);
+                    }
                 }
                 Token::SingleChar(c) => {
-                    token_iter.next();
                     if let Some(m) = MODIFIERS.get(c) {
-                        if token_iter.next() == Some(&Token::Separator) {
-                            assert!(
-                                !ks.modifiers.contains(*m),
-                                "'{}': Multiple instances of same modifier given",
-                                text
-                            );
+                        if matches!(token_iter.peek(), Some((_, Token::Separator))) {
+                            if ks.modifiers.contains(*m) {
+                                return error(pos, BindingParseErrorReason::DuplicateModifier);
+                            }
                             ks.modifiers.insert(*m);
-                        } else if ks.code.is_none() {
-                            ks.code = Some(KeyCode::Char(*c));
-                        } else {
-                            panic!("'{}' Invalid key input sequence given", text);
+                            continue;
                         }
-                    } else if ks.code.is_none() {
-                        ks.code = Some(KeyCode::Char(*c));
-                    } else {
-                        panic!("'{}': Invalid key input sequence given", text);
                     }
+                    if ks.code.is_some() {
+                        return error(pos, BindingParseErrorReason::MultipleKeys);
+                    }
+                    ks.code = Some(KeyCode::Char(*c));
                 }
                 Token::MultipleChar(c) => {
-                    let c = c.to_ascii_lowercase().to_string();
-                    SPECIAL_KEYS.get(c.as_str()).map_or_else(
-                        || panic!("'{}': Invalid key input sequence given", text),
-                        |key| {
-                            if ks.code.is_none() {
-                                ks.code = Some(*key);
-                            } else {
-                                panic!("'{}': Invalid key input sequence given", text);
+                    let name = c.to_ascii_lowercase();
+                    if matches!(token_iter.peek(), Some((_, Token::Separator))) {
+                        if let Some(m) = NAMED_MODIFIERS.get(name.as_str()) {
+                            if ks.modifiers.contains(*m) {
+                                return error(pos, BindingParseErrorReason::DuplicateModifier);
                             }
-                        },
-                    );
-                    token_iter.next();
+                            ks.modifiers.insert(*m);
+                            continue;
+                        }
+                        if let Some(st) = LOCK_STATES.get(name.as_str()) {
+                            if ks.state.contains(*st) {
+                                return error(pos, BindingParseErrorReason::DuplicateModifier);
+                            }
+                            ks.state.insert(*st);
+                            continue;
+                        }
+                    }
+                    let (key, state) = if let Some(key) = SPECIAL_KEYS.get(name.as_str()) {
+                        (*key, KeyEventState::NONE)
+                    } else if let Some(key) = KEYPAD_KEYS.get(name.as_str()) {
+                        (*key, KeyEventState::KEYPAD)
+                    } else if let Some(key) = function_key(&name) {
+                        (key, KeyEventState::NONE)
+                    } else {
+                        return error(pos, BindingParseErrorReason::UnknownKey(c.clone()));
+                    };
+                    if ks.code.is_some() {
+                        return error(pos, BindingParseErrorReason::MultipleKeys);
+                    }
+                    ks.code = Some(key);
+                    ks.state.insert(state);
                 }
             }
         }
-        KeyEvent {
+        Ok(KeyEvent {
             code: ks.code.unwrap_or(KeyCode::Null),
             modifiers: ks.modifiers,
             kind: crossterm::event::KeyEventKind::Press,
-            state: KeyEventState::NONE,
-        }
+            state: ks.state,
+        })
     }
 }
 
@@ -318,3 +403,102 @@ fn test_parse_key_event() {
         }
     );
 }
+
+#[cfg(test)]
+#[test]
+fn test_try_parse_key_event_errors() {
+    let err = |text| try_parse_key_event(text).unwrap_err();
+
+    assert_eq!(err("").reason, BindingParseErrorReason::Empty);
+    assert_eq!(err("c-é").position, 2);
+    assert_eq!(err("c-é").reason, BindingParseErrorReason::NonAscii);
+
+    let e = err("c--down");
+    assert_eq!(
+        (e.position, e.reason),
+        (2, BindingParseErrorReason::ConsecutiveSeparators)
+    );
+    let e = err("c-c-down");
+    assert_eq!(
+        (e.position, e.reason),
+        (2, BindingParseErrorReason::DuplicateModifier)
+    );
+    let e = err("  m-dwn");
+    assert_eq!(
+        (e.position, e.reason),
+        (4, BindingParseErrorReason::UnknownKey("dwn".to_string()))
+    );
+    let e = err("up-down");
+    assert_eq!(
+        (e.position, e.reason),
+        (3, BindingParseErrorReason::MultipleKeys)
+    );
+    assert_eq!(
+        err("c-dwn").to_string(),
+        "'c-dwn': unknown key 'dwn' at position 2"
+    );
+}
+
+#[cfg(test)]
+#[test]
+fn test_extended_keys() {
+    let key = |code, modifiers, state| KeyEvent {
+        code,
+        modifiers,
+        kind: crossterm::event::KeyEventKind::Press,
+        state,
+    };
+
+    assert_eq!(
+        parse_key_event("SPACE"),
+        key(KeyCode::Char(' '), KeyModifiers::NONE, KeyEventState::NONE)
+    );
+    assert_eq!(
+        parse_key_event("c-minus"),
+        key(
+            KeyCode::Char('-'),
+            KeyModifiers::CONTROL,
+            KeyEventState::NONE
+        )
+    );
+    assert_eq!(
+        parse_key_event("Plus"),
+        key(KeyCode::Char('+'), KeyModifiers::NONE, KeyEventState::NONE)
+    );
+    assert_eq!(
+        parse_key_event("F13"),
+        key(KeyCode::F(13), KeyModifiers::NONE, KeyEventState::NONE)
+    );
+    assert_eq!(
+        parse_key_event("super-meta-f24"),
+        key(
+            KeyCode::F(24),
+            KeyModifiers::SUPER | KeyModifiers::META,
+            KeyEventState::NONE
+        )
+    );
+    assert_eq!(
+        parse_key_event("kpenter"),
+        key(KeyCode::Enter, KeyModifiers::NONE, KeyEventState::KEYPAD)
+    );
+    assert_eq!(
+        parse_key_event("numlock-kp5"),
+        key(
+            KeyCode::Char('5'),
+            KeyModifiers::NONE,
+            KeyEventState::KEYPAD | KeyEventState::NUM_LOCK
+        )
+    );
+    assert_eq!(
+        parse_key_event("numlock"),
+        key(KeyCode::NumLock, KeyModifiers::NONE, KeyEventState::NONE)
+    );
+    assert_eq!(
+        try_parse_key_event("f0").unwrap_err().reason,
+        BindingParseErrorReason::UnknownKey("f0".to_string())
+    );
+    assert_eq!(
+        try_parse_key_event("super-super-up").unwrap_err().reason,
+        BindingParseErrorReason::DuplicateModifier
+    );
+}
@@ -3,53 +3,59 @@
 pub mod keydefs;
 pub mod mousedefs;
 
+use crate::error::{BindingParseError, BindingParseErrorReason};
 use crossterm::event::KeyModifiers;
 use once_cell::sync::Lazy;
 use std::collections::HashMap;
 
-fn parse_tokens(mut text: &str) -> Vec<Token> {
-    assert!(
-        text.chars().all(|c| c.is_ascii()),
-        "'{}': Non ascii sequence found in input sequence",
-        text
-    );
-    text = text.trim();
-    assert!(
-        text.chars().any(|c| !c.is_whitespace()),
-        "'{}': Whitespace character found in input sequence",
-        text
-    );
-
-    let mut token_list = Vec::with_capacity(text.len());
+/// Split `text` into tokens along with the position at which each of them starts
+fn parse_tokens(text: &str) -> Result<Vec<(usize, Token)>, BindingParseError> {
+    if let Some(pos) = text.find(|c: char| !c.is_ascii()) {
+        return Err(BindingParseError::new(
+            text,
+            pos,
+            BindingParseErrorReason::NonAscii,
+        ));
+    }
+    let offset = text.len() - text.trim_start().len();
+    let trimmed = text.trim();
+    if trimmed.is_empty() {
+        return Err(BindingParseError::new(
+            text,
+            0,
+            BindingParseErrorReason::Empty,
+        ));
+    }
 
-    let mut chars_peek = text.chars().peekable();
+    let mut token_list = Vec::with_capacity(trimmed.len());
 
     let mut s = String::with_capacity(5);
+    let mut start = offset;
 
-    let flush_s = |s: &mut String, token_list: &mut Vec<Token>| {
+    let flush_s = |s: &mut String, start: usize, token_list: &mut Vec<(usize, Token)>| {
         match s.len() {
-            1 => token_list.push(Token::SingleChar(s.chars().next().unwrap())),
-            2.. => token_list.push(Token::MultipleChar(s.clone())),
+            1 => token_list.push((start, Token::SingleChar(s.chars().next().unwrap()))),
+            2.. => token_list.push((start, Token::MultipleChar(s.clone()))),
             _ => {}
         }
         s.clear();
     };
 
-    while let Some(chr) = chars_peek.peek() {
-        match chr {
-            '-' => {
-                flush_s(&mut s, &mut token_list);
-                token_list.push(Token::Separator);
-            }
-            c => {
-                s.push(*c);
+    for (idx, chr) in trimmed.char_indices() {
+        let pos = offset + idx;
+        if chr == '-' {
+            flush_s(&mut s, start, &mut token_list);
+            token_list.push((pos, Token::Separator));
+        } else {
+            if s.is_empty() {
+                start = pos;
             }
+            s.push(chr);
         }
-        chars_peek.next();
     }
-    flush_s(&mut s, &mut token_list);
+    flush_s(&mut s, start, &mut token_list);
 
-    token_list
+    Ok(token_list)
 }
 
 pub static MODIFIERS: Lazy<HashMap<char, KeyModifiers>> = Lazy::new(|| {
@@ -61,6 +67,16 @@ pub static MODIFIERS: Lazy<HashMap<char, KeyModifiers>> = Lazy::new(|| {
     map
 });
 
+/// Modifiers without a single character shorthand
+pub static NAMED_MODIFIERS: Lazy<HashMap<&str, KeyModifiers>> = Lazy::new(|| {
+    let mut map = HashMap::new();
+    map.insert("super", KeyModifiers::SUPER);
+    map.insert("meta", KeyModifiers::META);
+    map.insert("hyper", KeyModifiers::HYPER);
+
+    map
+});
+
 #[derive(Debug, PartialEq)]
 enum Token {
     Separator, // -
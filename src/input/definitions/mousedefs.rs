@@ -1,6 +1,7 @@
 use std::collections::HashMap;
 
-use super::{Token, MODIFIERS};
+use super::{Token, MODIFIERS, NAMED_MODIFIERS};
+use crate::error::{BindingParseError, BindingParseErrorReason};
 use crossterm::event::{KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
 use once_cell::sync::Lazy;
 
@@ -22,78 +23,112 @@ static MOUSE_ACTIONS: Lazy<HashMap<&str, MouseEventKind>> = Lazy::new(|| {
     map.insert("move", MouseEventKind::Moved);
     map.insert("scroll:up", MouseEventKind::ScrollUp);
     map.insert("scroll:down", MouseEventKind::ScrollDown);
+    map.insert("scroll:left", MouseEventKind::ScrollLeft);
+    map.insert("scroll:right", MouseEventKind::ScrollRight);
 
     map
 });
 
+/// Parse a mouse binding description
+///
+/// # Panics
+/// This function panics if `text` is not a valid description. See [`try_parse_mouse_event`].
 pub fn parse_mouse_event(text: &str) -> MouseEvent {
-    let token_list = super::parse_tokens(text);
+    try_parse_mouse_event(text).unwrap_or_else(|e| panic!("{}", e))
+}
+
+/// Parse a mouse binding description like `c-scroll:up` into a [`MouseEvent`]
+///
+/// See the [input](crate::input) module for the format of the description.
+///
+/// # Errors
+/// Returns a [`BindingParseError`] telling where and why the parsing failed if `text` is not a
+/// valid description.
+///
+/// # Example
+/// ```
+/// use minus::input::{crossterm_event::MouseEventKind, try_parse_mouse_event};
+///
+/// let ev = try_parse_mouse_event("c-scroll:up").unwrap();
+/// assert_eq!(ev.kind, MouseEventKind::ScrollUp);
+///
+/// let err = try_parse_mouse_event("x-scroll:up").unwrap_err();
+/// assert_eq!(err.position, 0);
+/// ```
+pub fn try_parse_mouse_event(text: &str) -> Result<MouseEvent, BindingParseError> {
+    let token_list = super::parse_tokens(text)?;
     gen_mouse_event_from_tokenlist(&token_list, text)
 }
 
-fn gen_mouse_event_from_tokenlist(token_list: &[Token], text: &str) -> MouseEvent {
+fn gen_mouse_event_from_tokenlist(
+    token_list: &[(usize, Token)],
+    text: &str,
+) -> Result<MouseEvent, BindingParseError> {
     let mut kind = None;
     let mut modifiers = KeyModifiers::NONE;
 
     let mut token_iter = token_list.iter().peekable();
+    let error = |pos, reason| Err(BindingParseError::new(text, pos, reason));
 
-    while let Some(token) = token_iter.peek() {
+    while let Some((pos, token)) = token_iter.next() {
+        let pos = *pos;
         match token {
             Token::Separator => {
-                token_iter.next();
-                assert!(
-                    !(token_iter.peek() == Some(&&Token::Separator)),
-                    "'{}': Multiple separators found consecutively",
-                    text
-                );
+                if let Some((next, Token::Separator)) = token_iter.peek() {
+                    return error(*next, BindingParseErrorReason::ConsecutiveSeparators);
+                }
             }
             Token::SingleChar(c) => {
-                token_iter.next();
-                MODIFIERS.get(c).map_or_else(
-                    || {
-                        panic!("'{}': Invalid keymodifier '{}' given", text, c);
-                    },
-                    |m| {
-                        if token_iter.next() == Some(&Token::Separator) {
-                            assert!(
-                                !modifiers.contains(*m),
-                                "'{}': Multiple instances of same modifier given",
-                                text
-                            );
-                            modifiers.insert(*m);
-                        } else {
-                            panic!("'{}' Invalid key input sequence given", text);
-                        }
-                    },
-                );
+                let Some(m) = MODIFIERS.get(c) else {
+                    return error(pos, BindingParseErrorReason::InvalidModifier(*c));
+                };
+                if !matches!(token_iter.peek(), Some((_, Token::Separator))) {
+                    return error(pos + 1, BindingParseErrorReason::MissingSeparator);
+                }
+                if modifiers.contains(*m) {
+                    return error(pos, BindingParseErrorReason::DuplicateModifier);
+                }
+                modifiers.insert(*m);
             }
             Token::MultipleChar(c) => {
-                let c = c.to_ascii_lowercase().to_string();
-                MOUSE_ACTIONS.get(c.as_str()).map_or_else(
-                    || panic!("'{}': Invalid key input sequence given", text),
-                    |k| {
-                        if kind.is_none() {
-                            kind = Some(*k);
-                        } else {
-                            panic!("'{}': Invalid key input sequence given", text);
-                        }
-                    },
-                );
-                token_iter.next();
+                let name = c.to_ascii_lowercase();
+                if let Some(m) = NAMED_MODIFIERS.get(name.as_str()) {
+                    if !matches!(token_iter.peek(), Some((_, Token::Separator))) {
+                        return error(pos + c.len(), BindingParseErrorReason::MissingSeparator);
+                    }
+                    if modifiers.contains(*m) {
+                        return error(pos, BindingParseErrorReason::DuplicateModifier);
+                    }
+                    modifiers.insert(*m);
+                    continue;
+                }
+                let Some(k) = MOUSE_ACTIONS.get(name.as_str()) else {
+                    return error(pos, BindingParseErrorReason::UnknownMouseAction(c.clone()));
+                };
+                if kind.is_some() {
+                    return error(pos, BindingParseErrorReason::MultipleMouseActions);
+                }
+                kind = Some(*k);
             }
         }
     }
-    MouseEvent {
-        kind: kind.unwrap_or_else(|| panic!("No MouseEventKind found for '{}", text)),
-        modifiers,
-        row: 0,
-        column: 0,
-    }
+    kind.map_or_else(
+        || error(text.len(), BindingParseErrorReason::MissingMouseAction),
+        |kind| {
+            Ok(MouseEvent {
+                kind,
+                modifiers,
+                row: 0,
+                column: 0,
+            })
+        },
+    )
 }
 
 #[cfg(test)]
 mod tests {
-    use super::parse_mouse_event;
+    use super::{parse_mouse_event, try_parse_mouse_event};
+    use crate::error::BindingParseErrorReason;
     use crossterm::event::{KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
 
     #[test]
@@ -136,6 +171,15 @@ mod tests {
                 column: 0,
             }
         );
+        assert_eq!(
+            parse_mouse_event("scroll:right"),
+            MouseEvent {
+                kind: MouseEventKind::ScrollRight,
+                modifiers: KeyModifiers::NONE,
+                row: 0,
+                column: 0,
+            }
+        );
         assert_eq!(
             parse_mouse_event("move"),
             MouseEvent {
@@ -187,4 +231,37 @@ mod tests {
             }
         );
     }
+
+    #[test]
+    fn test_try_parse_errors() {
+        let err = |text| {
+            let e = try_parse_mouse_event(text).unwrap_err();
+            (e.position, e.reason)
+        };
+
+        assert_eq!(
+            err("x-scroll:up"),
+            (0, BindingParseErrorReason::InvalidModifier('x'))
+        );
+        assert_eq!(
+            err("c-m-c-move"),
+            (4, BindingParseErrorReason::DuplicateModifier)
+        );
+        assert_eq!(
+            err("c--move"),
+            (2, BindingParseErrorReason::ConsecutiveSeparators)
+        );
+        assert_eq!(
+            err("c-scroll:sideways"),
+            (
+                2,
+                BindingParseErrorReason::UnknownMouseAction("scroll:sideways".to_string())
+            )
+        );
+        assert_eq!(
+            err("move-move"),
+            (5, BindingParseErrorReason::MultipleMouseActions)
+        );
+        assert_eq!(err("c-"), (2, BindingParseErrorReason::MissingMouseAction));
+    }
 }
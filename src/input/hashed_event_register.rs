@@ -5,79 +5,151 @@
 //! If their is a match related to that event, the associated callback is called
 
 use super::{InputClassifier, InputEvent};
-use crate::PagerState;
-use crossterm::event::{Event, MouseEvent};
+use crate::{error::BindingParseError, PagerState};
+use crossterm::event::{Event, KeyCode, KeyEventState, KeyModifiers, MouseEventKind};
 use std::{
-    collections::hash_map::RandomState, collections::HashMap, hash::BuildHasher, hash::Hash,
+    collections::hash_map::RandomState,
+    collections::HashMap,
+    hash::{BuildHasher, Hasher},
     sync::Arc,
 };
 
 /// A convenient type for the return type of [`HashedEventRegister::get`]
 type EventReturnType = Arc<dyn Fn(Event, &PagerState) -> InputEvent + Send + Sync>;
 
+/// A matcher added with [`HashedEventRegister::add_event_matcher`] along with its priority
+type EventMatcher = (
+    i32,
+    Box<dyn Fn(Event, &PagerState) -> Option<InputEvent> + Send + Sync>,
+);
+
 // //////////////////////////////
 // EVENTWRAPPER TYPE
 // //////////////////////////////
 
-#[derive(Clone, Eq)]
+/// Key under which the callback of an event is stored
+///
+/// Only the parts of the event that bindings can describe are kept so that building it on every
+/// event never allocates. Mouse positions, resize dimensions and pasted text are ignored.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
 enum EventWrapper {
-    ExactMatchEvent(Event),
+    Key(KeyCode, KeyModifiers, KeyEventState),
+    Mouse(MouseEventKind, KeyModifiers),
+    Resize,
+    FocusGained,
+    FocusLost,
+    Paste,
     WildEvent,
 }
 
 impl From<Event> for EventWrapper {
     fn from(e: Event) -> Self {
-        Self::ExactMatchEvent(e)
+        Self::from(&e)
     }
 }
 
 impl From<&Event> for EventWrapper {
     fn from(e: &Event) -> Self {
-        Self::ExactMatchEvent(e.clone())
+        match e {
+            Event::Key(key) => {
+                // Same as how crossterm compares key events, so that `G` and `s-g` are equal
+                let mut code = key.code;
+                let mut modifiers = key.modifiers;
+                if let KeyCode::Char(c) = code {
+                    if c.is_ascii_uppercase() {
+                        modifiers.insert(KeyModifiers::SHIFT);
+                    } else if modifiers.contains(KeyModifiers::SHIFT) {
+                        code = KeyCode::Char(c.to_ascii_uppercase());
+                    }
+                }
+                Self::Key(code, modifiers, key.state)
+            }
+            Event::Mouse(mouse) => Self::Mouse(mouse.kind, mouse.modifiers),
+            Event::Resize(..) => Self::Resize,
+            Event::FocusGained => Self::FocusGained,
+            Event::FocusLost => Self::FocusLost,
+            Event::Paste(_) => Self::Paste,
+        }
     }
 }
 
-impl PartialEq for EventWrapper {
-    fn eq(&self, other: &Self) -> bool {
-        match (self, other) {
-            (
-                Self::ExactMatchEvent(Event::Mouse(MouseEvent {
-                    kind, modifiers, ..
-                })),
-                Self::ExactMatchEvent(Event::Mouse(MouseEvent {
-                    kind: o_kind,
-                    modifiers: o_modifiers,
-                    ..
-                })),
-            ) => kind == o_kind && modifiers == o_modifiers,
-            (
-                Self::ExactMatchEvent(Event::Resize(..)),
-                Self::ExactMatchEvent(Event::Resize(..)),
-            )
-            | (Self::WildEvent, Self::WildEvent) => true,
-            (Self::ExactMatchEvent(ev), Self::ExactMatchEvent(o_ev)) => ev == o_ev,
-            _ => false,
-        }
+// //////////////////////////////
+// FXHASH
+// //////////////////////////////
+
+/// A fast non-cryptographic hasher based on the one used inside the Rust compiler
+///
+/// Event lookups are done on every key press and mouse movement and their keys are a few small
+/// integers, for which this is much faster than the default SipHash. It offers no protection
+/// against collision attacks, which doesn't matter as the bindings are set by the application.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct FxHasher {
+    hash: u64,
+}
+
+const FX_SEED: u64 = 0x51_7c_c1_b7_27_22_0a_95;
+
+impl FxHasher {
+    #[inline]
+    fn add(&mut self, word: u64) {
+        self.hash = (self.hash.rotate_left(5) ^ word).wrapping_mul(FX_SEED);
     }
 }
 
-impl Hash for EventWrapper {
-    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
-        let tag = std::mem::discriminant(self);
-        tag.hash(state);
-        match self {
-            Self::ExactMatchEvent(Event::Mouse(MouseEvent {
-                kind, modifiers, ..
-            })) => {
-                kind.hash(state);
-                modifiers.hash(state);
-            }
-            Self::WildEvent | Self::ExactMatchEvent(Event::Resize(..)) => {}
-            Self::ExactMatchEvent(v) => {
-                v.hash(state);
-            }
+impl Hasher for FxHasher {
+    #[inline]
+    fn write(&mut self, bytes: &[u8]) {
+        for chunk in bytes.chunks(8) {
+            let mut word = [0; 8];
+            word[..chunk.len()].copy_from_slice(chunk);
+            self.add(u64::from_le_bytes(word));
         }
     }
+
+    #[inline]
+    fn write_u8(&mut self, i: u8) {
+        self.add(i.into());
+    }
+
+    #[inline]
+    fn write_u16(&mut self, i: u16) {
+        self.add(i.into());
+    }
+
+    #[inline]
+    fn write_u32(&mut self, i: u32) {
+        self.add(i.into());
+    }
+
+    #[inline]
+    fn write_u64(&mut self, i: u64) {
+        self.add(i);
+    }
+
+    #[inline]
+    fn write_usize(&mut self, i: usize) {
+        self.add(i as u64);
+    }
+
+    #[inline]
+    fn finish(&self) -> u64 {
+        self.hash
+    }
+}
+
+/// [`BuildHasher`] for [`FxHasher`]
+///
+/// This is used by the default bindings of minus. See
+/// [`HashedEventRegister::with_fx_hasher`].
+#[derive(Debug, Default, Clone, Copy)]
+pub struct FxBuildHasher;
+
+impl BuildHasher for FxBuildHasher {
+    type Hasher = FxHasher;
+
+    fn build_hasher(&self) -> FxHasher {
+        FxHasher::default()
+    }
 }
 
 // /////////////////////////////////////////////////
@@ -89,7 +161,7 @@ impl Hash for EventWrapper {
 /// Each item is a key value pair, where the key is a event and it's value is a callback. When a
 /// event occurs, it is matched inside and when the related match is found, it's related callback
 /// is called.
-pub struct HashedEventRegister<S>(HashMap<EventWrapper, EventReturnType, S>);
+pub struct HashedEventRegister<S>(HashMap<EventWrapper, EventReturnType, S>, Vec<EventMatcher>);
 
 impl HashedEventRegister<RandomState> {
     /// Create a new [HashedEventRegister] with the default hasher
@@ -99,6 +171,27 @@ impl HashedEventRegister<RandomState> {
     }
 }
 
+impl HashedEventRegister<FxBuildHasher> {
+    /// Create a new [HashedEventRegister] which uses the faster [`FxBuildHasher`]
+    ///
+    /// Prefer this over [`HashedEventRegister::default`], especially when there are bindings
+    /// for high rate events like mouse drags or scrolls. Unlike `default`, this doesn't insert
+    /// the default bindings; call [`generate_default_bindings`](super::generate_default_bindings)
+    /// for that.
+    ///
+    /// # Example
+    /// ```
+    /// use minus::input::{generate_default_bindings, HashedEventRegister};
+    ///
+    /// let mut input_register = HashedEventRegister::with_fx_hasher();
+    /// generate_default_bindings(&mut input_register);
+    /// ```
+    #[must_use]
+    pub fn with_fx_hasher() -> Self {
+        Self::new(FxBuildHasher)
+    }
+}
+
 impl Default for HashedEventRegister<RandomState> {
     /// Create a new [HashedEventRegister] with the default hasher and insert the default bindings
     fn default() -> Self {
@@ -113,7 +206,19 @@ where
     S: BuildHasher,
 {
     fn classify_input(&self, ev: Event, ps: &crate::PagerState) -> Option<InputEvent> {
-        self.get(&ev).map(|c| c(ev, ps))
+        // Matchers are sorted by priority, the ones above 0 take precedence over the bindings
+        let (before, after) = self.1.split_at(self.1.partition_point(|(p, _)| *p > 0));
+        let run_matchers =
+            |matchers: &[EventMatcher]| matchers.iter().find_map(|(_, m)| m(ev.clone(), ps));
+
+        run_matchers(before)
+            .or_else(|| self.get(&ev).map(|c| c(ev.clone(), ps)))
+            .or_else(|| run_matchers(after))
+            .or_else(|| {
+                self.0
+                    .get(&EventWrapper::WildEvent)
+                    .map(|c| c(ev.clone(), ps))
+            })
     }
 }
 
@@ -126,7 +231,7 @@ where
 {
     /// Create a new HashedEventRegister with the Hasher `s`
     pub fn new(s: S) -> Self {
-        Self(HashMap::with_hasher(s))
+        Self(HashMap::with_hasher(s), Vec::new())
     }
 
     /// Adds a callback to handle all events that failed to match
@@ -138,6 +243,10 @@ where
     ///
     /// This is also helpful when you need to do some action, like sending a message when the user
     /// presses wrong keyboard/mouse buttons.
+    ///
+    /// There can be only one such callback and it is called after all the matchers added with
+    /// [add_event_matcher](HashedEventRegister::add_event_matcher). The default bindings use it
+    /// for reading the count prefix.
     pub fn insert_wild_event_matcher(
         &mut self,
         cb: impl Fn(Event, &PagerState) -> InputEvent + Send + Sync + 'static,
@@ -145,10 +254,75 @@ where
         self.0.insert(EventWrapper::WildEvent, Arc::new(cb));
     }
 
+    /// Adds a callback which can look at events before or after they are matched against the
+    /// bindings
+    ///
+    /// The callback can consume the event by returning `Some` or let it pass through to the
+    /// next matcher by returning `None`. Matchers with a priority greater than 0 see all events
+    /// before the bindings are checked. Others only see the events that no binding matched,
+    /// after which the [wild event matcher](HashedEventRegister::insert_wild_event_matcher) is
+    /// called. Higher priorities are tried first and matchers with equal priorities are tried
+    /// in the order they were added.
+    ///
+    /// # Example
+    /// A "press any key to continue" flow that doesn't disturb the other bindings once done.
+    /// ```
+    /// use minus::input::{InputEvent, HashedEventRegister};
+    /// use std::sync::{atomic::{AtomicBool, Ordering}, Arc};
+    ///
+    /// let mut input_register = HashedEventRegister::default();
+    /// let waiting = Arc::new(AtomicBool::new(true));
+    ///
+    /// input_register.add_event_matcher(10, move |_, _| {
+    ///     waiting.swap(false, Ordering::SeqCst).then_some(InputEvent::Ignore)
+    /// });
+    /// // Log keys that aren't bound without consuming them
+    /// input_register.add_event_matcher(-1, |ev, _| {
+    ///     eprintln!("Unbound event {:?}", ev);
+    ///     None
+    /// });
+    /// ```
+    pub fn add_event_matcher(
+        &mut self,
+        priority: i32,
+        cb: impl Fn(Event, &PagerState) -> Option<InputEvent> + Send + Sync + 'static,
+    ) {
+        let idx = self.1.partition_point(|(p, _)| *p >= priority);
+        self.1.insert(idx, (priority, Box::new(cb)));
+    }
+
+    /// Removes all the callbacks added with
+    /// [add_event_matcher](HashedEventRegister::add_event_matcher)
+    pub fn clear_event_matchers(&mut self) {
+        self.1.clear();
+    }
+
     fn get(&self, k: &Event) -> Option<&EventReturnType> {
-        self.0
-            .get(&k.into())
-            .map_or_else(|| self.0.get(&EventWrapper::WildEvent), |k| Some(k))
+        let k = EventWrapper::from(k);
+        self.0.get(&k).or_else(|| self.get_without_state(k))
+    }
+
+    /// Match a key event reported with lock or keypad states against bindings which don't ask
+    /// for them
+    ///
+    /// Lock states are dropped first so that `numlock-kp5` is preferred over `kp5` and `kp5`
+    /// over `5`.
+    fn get_without_state(&self, k: EventWrapper) -> Option<&EventReturnType> {
+        let EventWrapper::Key(code, modifiers, mut state) = k else {
+            return None;
+        };
+        [
+            KeyEventState::CAPS_LOCK | KeyEventState::NUM_LOCK,
+            KeyEventState::KEYPAD,
+        ]
+        .iter()
+        .find_map(|&st| {
+            if !state.intersects(st) {
+                return None;
+            }
+            state.remove(st);
+            self.0.get(&EventWrapper::Key(code, modifiers, state))
+        })
     }
 
     /// Adds a callback for handling resize events
@@ -176,14 +350,12 @@ where
         let v = Arc::new(cb);
         // The 0, 0 are present just to ensure everything compiles and they can be anything.
         // These values are never hashed or stored into the HashedEventRegister
-        self.0
-            .insert(EventWrapper::ExactMatchEvent(Event::Resize(0, 0)), v);
+        self.0.insert(EventWrapper::Resize, v);
     }
 
     /// Removes the currently active resize event callback
     pub fn remove_resize_event(&mut self) {
-        self.0
-            .remove(&EventWrapper::ExactMatchEvent(Event::Resize(0, 0)));
+        self.0.remove(&EventWrapper::Resize);
     }
 }
 
@@ -223,6 +395,46 @@ where
         }
     }
 
+    /// Fallible version of [add_key_events](HashedEventRegister::add_key_events)
+    ///
+    /// All the elements of `desc` are parsed before any binding is added, so nothing is
+    /// registered if one of them is invalid.
+    ///
+    /// # Errors
+    /// Returns a [`BindingParseError`] for the first element of `desc` that is not a valid
+    /// description.
+    ///
+    /// # Example
+    /// ```
+    /// use minus::input::{InputEvent, HashedEventRegister};
+    ///
+    /// let mut input_register = HashedEventRegister::default();
+    ///
+    /// assert!(input_register
+    ///     .try_add_key_events(&["c-down"], |_, ps| {
+    ///         InputEvent::UpdateUpperMark(ps.upper_mark.saturating_sub(5))
+    ///     })
+    ///     .is_ok());
+    /// assert!(input_register
+    ///     .try_add_key_events(&["foo"], |_, ps| InputEvent::UpdateUpperMark(ps.upper_mark))
+    ///     .is_err());
+    /// ```
+    pub fn try_add_key_events(
+        &mut self,
+        desc: &[&str],
+        cb: impl Fn(Event, &PagerState) -> InputEvent + Send + Sync + 'static,
+    ) -> Result<(), BindingParseError> {
+        let events = desc
+            .iter()
+            .map(|k| super::definitions::keydefs::try_parse_key_event(k))
+            .collect::<Result<Vec<_>, _>>()?;
+        let v = Arc::new(cb);
+        for ev in events {
+            self.0.insert(Event::Key(ev).into(), v.clone());
+        }
+        Ok(())
+    }
+
     /// Add all elemnts of `desc` as key bindings that minus should respond to with the callback `cb`
     ///
     /// This will panic if you the keybinding has been previously defined, unless the `remap`
@@ -308,6 +520,46 @@ where
         }
     }
 
+    /// Fallible version of [add_mouse_events](HashedEventRegister::add_mouse_events)
+    ///
+    /// All the elements of `desc` are parsed before any binding is added, so nothing is
+    /// registered if one of them is invalid.
+    ///
+    /// # Errors
+    /// Returns a [`BindingParseError`] for the first element of `desc` that is not a valid
+    /// description.
+    ///
+    /// # Example
+    /// ```
+    /// use minus::input::{InputEvent, HashedEventRegister};
+    ///
+    /// let mut input_register = HashedEventRegister::default();
+    ///
+    /// assert!(input_register
+    ///     .try_add_mouse_events(&["c-scroll:down"], |_, ps| {
+    ///         InputEvent::UpdateUpperMark(ps.upper_mark.saturating_sub(5))
+    ///     })
+    ///     .is_ok());
+    /// assert!(input_register
+    ///     .try_add_mouse_events(&["foo"], |_, ps| InputEvent::UpdateUpperMark(ps.upper_mark))
+    ///     .is_err());
+    /// ```
+    pub fn try_add_mouse_events(
+        &mut self,
+        desc: &[&str],
+        cb: impl Fn(Event, &PagerState) -> InputEvent + Send + Sync + 'static,
+    ) -> Result<(), BindingParseError> {
+        let events = desc
+            .iter()
+            .map(|k| super::definitions::mousedefs::try_parse_mouse_event(k))
+            .collect::<Result<Vec<_>, _>>()?;
+        let v = Arc::new(cb);
+        for ev in events {
+            self.0.insert(Event::Mouse(ev).into(), v.clone());
+        }
+        Ok(())
+    }
+
     /// Add all elemnts of `desc` as mouse bindings that minus should respond to with the callback `cb`
     ///
     /// This will panic if you the keybinding has been previously defined, unless the `remap`
@@ -0,0 +1,166 @@
+//! Handles recording and replaying keystrokes with `q{register}` and `@{register}`
+//!
+//! See [`Pager::set_macros`](crate::Pager::set_macros).
+
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+use std::collections::HashMap;
+
+/// What to do with an event after it went through [`Macros::handle_event`]
+#[derive(Debug, PartialEq, Eq)]
+pub enum MacroAction {
+    /// The event is not related to macros and should be handled normally
+    Pass,
+    /// The event started or stopped a recording, or is waiting for a register name
+    Consumed,
+    /// Replay these events, they are the contents of a register
+    Play(Vec<Event>),
+}
+
+/// Registers of recorded keystrokes and the recording in progress
+#[derive(Debug, Default)]
+pub struct Macros {
+    /// Whether `q` and `@` record and replay macros
+    pub enabled: bool,
+    /// Whether a macro is being replayed. Keys of a macro are never recorded again nor interpreted
+    /// as macro keys.
+    pub replaying: bool,
+    registers: HashMap<char, Vec<Event>>,
+    /// The register being recorded into with the events recorded so far
+    recording: Option<(char, Vec<Event>)>,
+    /// `q` or `@` when the next key is expected to be a register name
+    pending: Option<char>,
+    /// Register that was replayed last, replayed again by `@@`
+    last_played: Option<char>,
+}
+
+impl Macros {
+    /// Register being recorded into, if any
+    pub const fn recording(&self) -> Option<char> {
+        match self.recording {
+            Some((register, _)) => Some(register),
+            None => None,
+        }
+    }
+
+    /// Handle an event read from the terminal
+    ///
+    /// `typing` tells whether the keys are being typed into something like the command line, in
+    /// which case `q` and `@` are taken literally. Events that are passed on are added to the
+    /// recording in progress.
+    pub fn handle_event(&mut self, ev: &Event, typing: bool) -> MacroAction {
+        if !self.enabled || self.replaying {
+            return MacroAction::Pass;
+        }
+        let key = match ev {
+            Event::Key(KeyEvent {
+                code: KeyCode::Char(c),
+                modifiers,
+                ..
+            }) if (*modifiers - KeyModifiers::SHIFT).is_empty() => Some(*c),
+            _ => None,
+        };
+
+        if let Some(prefix) = self.pending.take() {
+            let register = key.filter(char::is_ascii_alphanumeric);
+            return match (prefix, key, register) {
+                ('q', _, Some(register)) => {
+                    self.recording = Some((register, Vec::new()));
+                    MacroAction::Consumed
+                }
+                ('@', Some('@'), _) => self.play(self.last_played),
+                ('@', _, Some(register)) => self.play(Some(register)),
+                // Any other key, like `Esc`, cancels
+                _ => MacroAction::Consumed,
+            };
+        }
+
+        match key {
+            Some('q' | '@') if !typing => {
+                if let (Some('q'), Some((register, events))) = (key, self.recording.take()) {
+                    self.registers.insert(register, events);
+                } else {
+                    self.pending = key;
+                }
+                return MacroAction::Consumed;
+            }
+            _ => {}
+        }
+        if let Some((_, events)) = &mut self.recording {
+            if matches!(ev, Event::Key(_) | Event::Mouse(_)) {
+                events.push(ev.clone());
+            }
+        }
+        MacroAction::Pass
+    }
+
+    fn play(&mut self, register: Option<char>) -> MacroAction {
+        let Some((register, events)) =
+            register.and_then(|register| Some((register, self.registers.get(&register)?.clone())))
+        else {
+            return MacroAction::Consumed;
+        };
+        self.last_played = Some(register);
+        // Replaying while recording puts the keys of the macro into the recording
+        if let Some((_, recording)) = &mut self.recording {
+            recording.extend(events.iter().cloned());
+        }
+        MacroAction::Play(events)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MacroAction, Macros};
+    use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+
+    fn key(c: char) -> Event {
+        Event::Key(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE))
+    }
+
+    #[test]
+    fn record_and_play() {
+        let mut macros = Macros {
+            enabled: true,
+            ..Macros::default()
+        };
+        assert_eq!(macros.handle_event(&key('q'), false), MacroAction::Consumed);
+        assert_eq!(macros.handle_event(&key('a'), false), MacroAction::Consumed);
+        assert_eq!(macros.recording(), Some('a'));
+        for c in ['2', 'j', 'z'] {
+            assert_eq!(macros.handle_event(&key(c), false), MacroAction::Pass);
+        }
+        // `q` is taken literally while typing a command
+        assert_eq!(macros.handle_event(&key('q'), true), MacroAction::Pass);
+        assert_eq!(macros.handle_event(&key('q'), false), MacroAction::Consumed);
+        assert_eq!(macros.recording(), None);
+
+        let recorded = vec![key('2'), key('j'), key('z'), key('q')];
+        assert_eq!(macros.handle_event(&key('@'), false), MacroAction::Consumed);
+        assert_eq!(
+            macros.handle_event(&key('a'), false),
+            MacroAction::Play(recorded.clone())
+        );
+        macros.handle_event(&key('@'), false);
+        assert_eq!(
+            macros.handle_event(&key('@'), false),
+            MacroAction::Play(recorded)
+        );
+
+        // Empty registers and other keys after `@` do nothing
+        macros.handle_event(&key('@'), false);
+        assert_eq!(macros.handle_event(&key('b'), false), MacroAction::Consumed);
+        macros.handle_event(&key('@'), false);
+        assert_eq!(
+            macros.handle_event(&Event::Key(KeyCode::Esc.into()), false),
+            MacroAction::Consumed
+        );
+        assert_eq!(macros.handle_event(&key('j'), false), MacroAction::Pass);
+    }
+
+    #[test]
+    fn disabled() {
+        let mut macros = Macros::default();
+        assert_eq!(macros.handle_event(&key('q'), false), MacroAction::Pass);
+        assert_eq!(macros.handle_event(&key('@'), false), MacroAction::Pass);
+    }
+}
@@ -46,10 +46,16 @@
 //! The general syntax for defining keybindings is `[MODIFIER]-[MODIFIER]-[MODIFIER]-{SINGLE KEY}`
 //!
 //! `MODIFIER`s include or or more of the `Ctrl` `Alt` and `Shift` keys. They are writeen with
-//! the shorthands `c`, `m` and `s` respectively.
+//! the shorthands `c`, `m` and `s` respectively. The `super`, `meta` and `hyper` modifiers are
+//! written out in full. `numlock` and `capslock` can also be used like modifiers to require
+//! that lock to be on.
 //!
 //! `SINGLE CHAR` includes any key on the keyboard which is not a modifier like `a`, `z`, `1`, `F1`
-//! or `enter`. Each of these pieces are separated by a `-`.
+//! or `enter`. Each of these pieces are separated by a `-`. Since `-` is the separator, the keys
+//! `-`, `+` and ` ` are written as `minus` (or `dash`), `plus` and `space`. Function keys go all
+//! the way from `f1` to `f255` and keys on the numeric keypad are written as `kp0`-`kp9`,
+//! `kpplus`, `kpminus`, `kpmultiply`, `kpdivide`, `kpdecimal`, `kpequal`, `kpenter` and
+//! `kpbegin`. Names of keys are case insensitive.
 //!
 //! Here are some examples
 //!
@@ -63,6 +69,8 @@
 //! | `s-2`        | `Shift+2`                                  |
 //! | `backspace`  | `Backspace` Key                            |
 //! | `left`       | `Left Arrow` key                           |
+//! | `super-f13`  | `Super+F13`                                |
+//! | `numlock-kp5`| `5` on the keypad while num lock is on     |
 //!
 //! ### Defining Mouse Bindings
 //!
@@ -72,7 +80,7 @@
 //! with the mouse action. They are writeen with the shorthands `c`, `m` and `s` respectively.
 //!
 //! `MOUSE ACTION` includes actions like pressing down the left mouse button or taking up the right
-//! mouse button. It also includes scrolling up/down or pressing the middle click.
+//! mouse button. It also includes scrolling up/down/left/right or pressing the middle click.
 //!
 //! Here are some examples
 //!
@@ -178,24 +186,109 @@
 //! }
 //! ```
 
+pub(crate) mod command_line;
 pub(crate) mod definitions;
 pub(crate) mod hashed_event_register;
+pub(crate) mod macros;
 
 pub use crossterm::event as crossterm_event;
+pub use definitions::{keydefs::try_parse_key_event, mousedefs::try_parse_mouse_event};
 
-#[cfg(feature = "search")]
+#[cfg(feature = "__search")]
 use crate::search::SearchMode;
-use crate::{LineNumbers, PagerState};
-use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers, MouseEvent, MouseEventKind};
-pub use hashed_event_register::HashedEventRegister;
+use crate::{screen::Screen, LineNumbers, PagerState};
+use crossterm::event::{
+    Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, MouseEvent, MouseEventKind,
+};
+pub use hashed_event_register::{FxBuildHasher, FxHasher, HashedEventRegister};
+#[cfg(feature = "__search")]
+use std::collections::BTreeSet;
+use std::ops::Range;
+
+/// Information about the text and the viewport for implementing custom bindings
+///
+/// Get it inside a callback by calling [`PagerState::input_context`] on the state that is passed
+/// to it.
+///
+/// # Example
+/// Jump to the next line that begins with a `#`.
+/// ```
+/// use minus::input::{HashedEventRegister, InputEvent};
+///
+/// let mut input_register = HashedEventRegister::default();
+///
+/// input_register.add_key_events(&["#"], |_, ps| {
+///     let ctx = ps.input_context();
+///     (ctx.visible_lines.start + 1..ctx.total_lines)
+///         .find(|&l| ctx.screen.get_line(l).is_some_and(|t| t.starts_with('#')))
+///         .and_then(|l| ctx.screen.row_of_line(l))
+///         .map_or(InputEvent::Ignore, InputEvent::UpdateUpperMark)
+/// });
+/// ```
+#[non_exhaustive]
+pub struct InputContext<'a> {
+    /// The text and its analysis. See [`Screen`] for the available methods
+    pub screen: &'a Screen,
+    /// Index of the row at the top of the screen
+    pub upper_mark: usize,
+    /// Number of columns scrolled horizontally
+    pub left_mark: usize,
+    /// Number of rows available for displaying the text
+    pub text_rows: usize,
+    /// Number of columns of the terminal
+    pub cols: usize,
+    /// Indices of the lines that are at least partially on the screen
+    pub visible_lines: Range<usize>,
+    /// Total number of lines in the text
+    pub total_lines: usize,
+    /// Total number of rows that the text takes after wrapping
+    pub total_rows: usize,
+    /// Length of the longest line
+    pub max_line_length: usize,
+    /// Whether line wrapping is on
+    pub line_wrapping: bool,
+    /// The active search query if any
+    #[cfg(feature = "__search")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "search")))]
+    pub search_query: Option<&'a str>,
+    /// Indices of the rows that contain a search match
+    #[cfg(feature = "__search")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "search")))]
+    pub search_matches: &'a BTreeSet<usize>,
+    /// Index of the row containing the current search match
+    #[cfg(feature = "__search")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "search")))]
+    pub current_match: Option<usize>,
+}
+
+/// Normalize an event read from the terminal before it is matched against the bindings
+///
+/// Terminals using the kitty keyboard protocol can also report key repeats and releases. Releases
+/// are dropped and repeats are treated like normal key presses.
+pub(crate) fn normalize_event(ev: Event) -> Option<Event> {
+    match ev {
+        Event::Key(KeyEvent {
+            kind: KeyEventKind::Release,
+            ..
+        }) => None,
+        Event::Key(key) => Some(Event::Key(KeyEvent {
+            kind: KeyEventKind::Press,
+            ..key
+        })),
+        ev => Some(ev),
+    }
+}
 
 /// Events handled by the `minus` pager.
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 #[allow(clippy::module_name_repetitions)]
 #[non_exhaustive]
 pub enum InputEvent {
-    /// `Ctrl+C` or `Q`, exits the application.
+    /// `q`, exits the application. When macros are enabled, `Q` does so instead, see
+    /// [Pager::set_macros](crate::pager::Pager::set_macros).
     Exit,
+    /// `Ctrl+C`, handled according to the [`InterruptBehavior`](crate::InterruptBehavior)
+    Interrupt,
     /// The terminal was resized. Contains the new number of rows.
     UpdateTermArea(usize, usize),
     /// Sent by movement keys like `Up` `Down`, `PageUp`, 'PageDown', 'g', `G` etc.
@@ -221,26 +314,40 @@ pub enum InputEvent {
     /// Actions on User Events](./index.html#custom-actions-on-user-events).
     Ignore,
     /// `/`, Searching for certain pattern of text
-    #[cfg(feature = "search")]
+    #[cfg(feature = "__search")]
     Search(SearchMode),
     /// Get to the next match in forward mode
     ///
     /// **WARNING: This has been deprecated in favour of `MoveToNextMatch`. This will likely be
     /// removed in the next major release.**
-    #[cfg(feature = "search")]
+    #[cfg(feature = "__search")]
     NextMatch,
     /// Get to the previous match in forward mode
     ///
     /// **WARNING: This has been deprecated in favour of `MoveToPrevMatch`. This will likely be
     /// removed in the next major release.**
-    #[cfg(feature = "search")]
+    #[cfg(feature = "__search")]
     PrevMatch,
     /// Move to the next nth match in the given direction
-    #[cfg(feature = "search")]
+    #[cfg(feature = "__search")]
     MoveToNextMatch(usize),
     /// Move to the previous nth match in the given direction
-    #[cfg(feature = "search")]
+    #[cfg(feature = "__search")]
     MoveToPrevMatch(usize),
+    /// Hide or show the highlighting of search matches
+    ///
+    /// The search query is kept while the highlights are hidden so moving between the matches
+    /// still works. Starting a new search shows them again.
+    #[cfg(feature = "__search")]
+    ToggleSearchHighlight,
+    /// Leave the active search
+    ///
+    /// This removes the highlights and forgets the matches and the query. It is sent by `Esc`.
+    ///
+    /// This is similar to [Pager::clear_search](crate::pager::Pager::clear_search) except that
+    /// this is used to control it from the user's side.
+    #[cfg(feature = "__search")]
+    ClearSearch,
     /// Control follow mode.
     ///
     /// When set to true, minus ensures that the user's screen always follows the end part of the
@@ -249,6 +356,77 @@ pub enum InputEvent {
     /// This is similar to [Pager::follow_output](crate::pager::Pager::follow_output) except that
     /// this is used to control it from the user's side.
     FollowOutput(bool),
+    /// Show or hide the prompt
+    ///
+    /// While the prompt is hidden, the text also takes up the last row of the terminal.
+    ///
+    /// This is similar to [Pager::show_prompt](crate::pager::Pager::show_prompt) except that
+    /// this is used to control it from the user's side.
+    ShowPrompt(bool),
+    /// Enter or leave the waiting for data state
+    ///
+    /// This is similar to the `+F` mode in `less`. When set to true, minus jumps to the end of the
+    /// output, turns on follow mode and displays a "Waiting for data" message at the prompt. Any key
+    /// press leaves this state and returns to normal browsing.
+    WaitForData(bool),
+    /// Fetch fresh content using the reload callback and replace the text with it
+    ///
+    /// See [Pager::set_reload_callback](crate::pager::Pager::set_reload_callback) for more info.
+    Reload,
+    /// The user clicked on the screen. Contains the row and column of the click.
+    ///
+    /// If there is a hyperlink at that position, a
+    /// [`PagerEvent::LinkClicked`](crate::PagerEvent::LinkClicked) is sent.
+    Click(usize, usize),
+    /// `:`, start typing a command at the prompt
+    ///
+    /// The available commands are
+    /// - `n`: switch to the next buffer
+    /// - `p`: switch to the previous buffer
+    /// - `x`: switch to the first buffer
+    /// - `buffers`: list all the buffers
+    /// - `N`: go to line `N`
+    /// - `+N` or `-N`: go `N` lines below or above the line at the top of the screen
+    ///
+    /// Single letter commands run as soon as they are typed while others need `Enter`.
+    EnterCommand,
+    /// Move the view so that the given line is at the top of the screen
+    ///
+    /// Line numbers start from 1 and refer to the lines of the text, not the rows they wrap
+    /// into. If the line does not exist, the view moves to the bottom of the text. Sent by
+    /// `[n]g` and by typing a line number after `:`.
+    ///
+    /// This is similar to [Pager::scroll_to_line](crate::pager::Pager::scroll_to_line) except
+    /// that this is used to control it from the user's side.
+    GotoLine(usize),
+    /// Move the view so that the given row sits in the middle of the screen
+    ///
+    /// Sent by `z`, which centers the row at the top of the screen, and by `[n]z`, which centers
+    /// the first row of line `n`.
+    CenterRow(usize),
+    /// Display the buffer at the given index
+    ///
+    /// See [Pager::add_buffer](crate::pager::Pager::add_buffer) for more info.
+    SwitchBuffer(usize),
+    /// Show the names of all the buffers
+    ///
+    /// The list is shown in place of the text until the user presses `q`.
+    ListBuffers,
+    /// Move to the next or previous line matching `regex`, depending on `direction`
+    ///
+    /// Unlike searching, this doesn't highlight anything or change the active search. This is
+    /// useful for structural navigation like moving between the hunks of a diff. The pattern is
+    /// compiled the first time it is used and reused afterwards.
+    #[cfg(feature = "__search")]
+    JumpToPattern {
+        regex: &'static str,
+        direction: SearchMode,
+    },
+    /// `]` or `[`, move to the next or previous line matching the pattern set with
+    /// [Pager::set_jump_pattern](crate::pager::Pager::set_jump_pattern), depending on the
+    /// [`SearchMode`]
+    #[cfg(feature = "__search")]
+    JumpToSetPattern(SearchMode),
 }
 
 /// Classifies the input and returns the appropriate [`InputEvent`]
@@ -268,7 +446,33 @@ pub fn generate_default_bindings<S>(map: &mut HashedEventRegister<S>)
 where
     S: std::hash::BuildHasher,
 {
-    map.add_key_events(&["q", "c-c"], |_, _| InputEvent::Exit);
+    map.add_key_events(&["q"], |_, _| InputEvent::Exit);
+    // `q` is taken for recording macros, `Q` only quits in its place
+    map.add_key_events(&["Q", "s-q"], |_, ps| {
+        if ps.macros.enabled {
+            InputEvent::Exit
+        } else {
+            InputEvent::Ignore
+        }
+    });
+    map.add_key_events(&["c-c"], |_, _| InputEvent::Interrupt);
+    map.add_key_events(&[":"], |_, _| InputEvent::EnterCommand);
+    #[cfg(feature = "__search")]
+    map.add_key_events(&["]"], |_, ps| {
+        if ps.jump_pattern.is_some() {
+            InputEvent::JumpToSetPattern(SearchMode::Forward)
+        } else {
+            InputEvent::Ignore
+        }
+    });
+    #[cfg(feature = "__search")]
+    map.add_key_events(&["["], |_, ps| {
+        if ps.jump_pattern.is_some() {
+            InputEvent::JumpToSetPattern(SearchMode::Reverse)
+        } else {
+            InputEvent::Ignore
+        }
+    });
 
     map.add_key_events(&["up", "k"], |_, ps| {
         let position = ps.prefix_num.parse::<usize>().unwrap_or(1);
@@ -281,6 +485,7 @@ where
     map.add_key_events(&["c-f"], |_, ps| {
         InputEvent::FollowOutput(!ps.follow_output)
     });
+    map.add_key_events(&["c-p"], |_, ps| InputEvent::ShowPrompt(!ps.show_prompt));
     map.add_key_events(&["enter"], |_, ps| {
         if ps.message.is_some() {
             InputEvent::RestorePrompt
@@ -290,16 +495,20 @@ where
         }
     });
     map.add_key_events(&["u", "c-u"], |_, ps| {
-        let half_screen = ps.rows / 2;
+        let half_screen = ps.half_page_rows();
         InputEvent::UpdateUpperMark(ps.upper_mark.saturating_sub(half_screen))
     });
     map.add_key_events(&["d", "c-d"], |_, ps| {
-        let half_screen = ps.rows / 2;
+        let half_screen = ps.half_page_rows();
         InputEvent::UpdateUpperMark(ps.upper_mark.saturating_add(half_screen))
     });
-    map.add_key_events(&["g"], |_, _| InputEvent::UpdateUpperMark(0));
+    map.add_key_events(&["g"], |_, ps| {
+        ps.prefix_num
+            .parse::<usize>()
+            .map_or(InputEvent::UpdateUpperMark(0), InputEvent::GotoLine)
+    });
 
-    map.add_key_events(&["s-g", "G"], |_, ps| {
+    map.add_key_events(&["s-g", "G", "end"], |_, ps| {
         let mut position = ps
             .prefix_num
             .parse::<usize>()
@@ -313,22 +522,37 @@ where
         // Get the exact row number where first row of this line is placed in
         // [`PagerState::formatted_lines`] and jump to that location.If the line number does not
         // exist, directly jump to the bottom of text.
-        let row_to_go = *ps
-            .lines_to_row_map
-            .get(position)
-            .unwrap_or(&(usize::MAX - 1));
+        let row_to_go = ps
+            .screen
+            .row_of_line(position)
+            .map_or(usize::MAX - 1, |row| ps.jump_target(row));
         InputEvent::UpdateUpperMark(row_to_go)
     });
+    map.add_key_events(&["z"], |_, ps| {
+        let row = ps
+            .prefix_num
+            .parse::<usize>()
+            .map_or(Some(ps.upper_mark), |line| {
+                // Line numbers start from 1 while the line indices start from 0
+                ps.screen.row_of_line(line.saturating_sub(1))
+            });
+        row.map_or(
+            InputEvent::UpdateUpperMark(usize::MAX - 1),
+            InputEvent::CenterRow,
+        )
+    });
     map.add_key_events(&["pageup"], |_, ps| {
-        InputEvent::UpdateUpperMark(ps.upper_mark.saturating_sub(ps.rows - 1))
+        InputEvent::UpdateUpperMark(ps.upper_mark.saturating_sub(ps.page_scroll_rows()))
     });
     map.add_key_events(&["pagedown", "space"], |_, ps| {
-        InputEvent::UpdateUpperMark(ps.upper_mark.saturating_add(ps.rows - 1))
+        InputEvent::UpdateUpperMark(ps.upper_mark.saturating_add(ps.page_scroll_rows()))
     });
+    map.add_key_events(&["F", "s-f"], |_, _| InputEvent::WaitForData(true));
+    map.add_key_events(&["R", "s-r"], |_, _| InputEvent::Reload);
     map.add_key_events(&["c-l"], |_, ps| {
         InputEvent::UpdateLineNumber(!ps.line_numbers)
     });
-    #[cfg(feature = "search")]
+    #[cfg(feature = "__search")]
     {
         map.add_key_events(&["/"], |_, _| InputEvent::Search(SearchMode::Forward));
         map.add_key_events(&["?"], |_, _| InputEvent::Search(SearchMode::Reverse));
@@ -354,13 +578,40 @@ where
                 InputEvent::Ignore
             }
         });
+        map.add_key_events(&["m-u"], |_, _| InputEvent::ToggleSearchHighlight);
+        map.add_key_events(&["esc"], |_, _| InputEvent::ClearSearch);
     }
 
+    map.add_mouse_events(&["left:down"], |ev, _| {
+        if let Event::Mouse(MouseEvent { row, column, .. }) = ev {
+            InputEvent::Click(row.into(), column.into())
+        } else {
+            unreachable!()
+        }
+    });
     map.add_mouse_events(&["scroll:up"], |_, ps| {
-        InputEvent::UpdateUpperMark(ps.upper_mark.saturating_sub(5))
+        InputEvent::UpdateUpperMark(ps.upper_mark.saturating_sub(ps.scroll_amounts.wheel))
     });
     map.add_mouse_events(&["scroll:down"], |_, ps| {
-        InputEvent::UpdateUpperMark(ps.upper_mark.saturating_add(5))
+        InputEvent::UpdateUpperMark(ps.upper_mark.saturating_add(ps.scroll_amounts.wheel))
+    });
+    map.add_mouse_events(&["scroll:left", "s-scroll:up"], |_, ps| {
+        if ps.screen.line_wrapping {
+            return InputEvent::Ignore;
+        }
+        InputEvent::UpdateLeftMark(
+            ps.left_mark
+                .saturating_sub(ps.scroll_amounts.horizontal_wheel),
+        )
+    });
+    map.add_mouse_events(&["scroll:right", "s-scroll:down"], |_, ps| {
+        if ps.screen.line_wrapping {
+            return InputEvent::Ignore;
+        }
+        InputEvent::UpdateLeftMark(
+            ps.left_mark
+                .saturating_add(ps.scroll_amounts.horizontal_wheel),
+        )
     });
 
     map.add_key_events(&["c-s-h", "c-h"], |_, ps| {
@@ -374,7 +625,17 @@ where
         let position = ps.prefix_num.parse::<usize>().unwrap_or(1);
         InputEvent::UpdateLeftMark(ps.left_mark.saturating_add(position))
     });
-    // TODO: Add keybindings for left right scrolling
+    map.add_key_events(&["H", "s-h"], |_, ps| {
+        InputEvent::UpdateLeftMark(ps.left_mark.saturating_sub(ps.cols / 2))
+    });
+    map.add_key_events(&["L", "s-l"], |_, ps| {
+        let position = ps.left_mark.saturating_add(ps.cols / 2);
+        InputEvent::UpdateLeftMark(position.min(ps.max_left_mark().max(ps.left_mark)))
+    });
+    map.add_key_events(&["^"], |_, _| InputEvent::UpdateLeftMark(0));
+    map.add_key_events(&["$"], |_, ps| {
+        InputEvent::UpdateLeftMark(ps.max_left_mark())
+    });
 
     map.add_resize_event(|ev, _| {
         let Event::Resize(cols, rows) = ev else {
@@ -538,14 +799,14 @@ impl InputClassifier for DefaultInputClassifier {
                 modifiers: KeyModifiers::NONE,
                 ..
             }) => Some(InputEvent::UpdateUpperMark(
-                ps.upper_mark.saturating_sub(ps.rows - 1),
+                ps.upper_mark.saturating_sub(ps.text_rows()),
             )),
             Event::Key(KeyEvent {
                 code: c,
                 modifiers: KeyModifiers::NONE,
                 ..
             }) if c == KeyCode::PageDown || c == KeyCode::Char(' ') => Some(
-                InputEvent::UpdateUpperMark(ps.upper_mark.saturating_add(ps.rows - 1)),
+                InputEvent::UpdateUpperMark(ps.upper_mark.saturating_add(ps.text_rows())),
             ),
 
             // Resize event from the terminal.
@@ -564,12 +825,12 @@ impl InputClassifier for DefaultInputClassifier {
                 code: KeyCode::Char('q'),
                 modifiers: KeyModifiers::NONE,
                 ..
-            })
-            | Event::Key(KeyEvent {
+            }) => Some(InputEvent::Exit),
+            Event::Key(KeyEvent {
                 code: KeyCode::Char('c'),
                 modifiers: KeyModifiers::CONTROL,
                 ..
-            }) => Some(InputEvent::Exit),
+            }) => Some(InputEvent::Interrupt),
 
             // Horizontal scrolling
             Event::Key(KeyEvent {
@@ -602,19 +863,19 @@ impl InputClassifier for DefaultInputClassifier {
             }) => Some(InputEvent::UpdateLeftMark(ps.left_mark.saturating_add(1))),
 
             // Search
-            #[cfg(feature = "search")]
+            #[cfg(feature = "__search")]
             Event::Key(KeyEvent {
                 code: KeyCode::Char('/'),
                 modifiers: KeyModifiers::NONE,
                 ..
             }) => Some(InputEvent::Search(SearchMode::Forward)),
-            #[cfg(feature = "search")]
+            #[cfg(feature = "__search")]
             Event::Key(KeyEvent {
                 code: KeyCode::Char('?'),
                 modifiers: KeyModifiers::NONE,
                 ..
             }) => Some(InputEvent::Search(SearchMode::Reverse)),
-            #[cfg(feature = "search")]
+            #[cfg(feature = "__search")]
             Event::Key(KeyEvent {
                 code: KeyCode::Char('n'),
                 modifiers: KeyModifiers::NONE,
@@ -627,7 +888,7 @@ impl InputClassifier for DefaultInputClassifier {
                     Some(InputEvent::MoveToNextMatch(position))
                 }
             }
-            #[cfg(feature = "search")]
+            #[cfg(feature = "__search")]
             Event::Key(KeyEvent {
                 code: KeyCode::Char('p'),
                 modifiers: KeyModifiers::NONE,
@@ -640,6 +901,18 @@ impl InputClassifier for DefaultInputClassifier {
                     Some(InputEvent::MoveToPrevMatch(position))
                 }
             }
+            #[cfg(feature = "__search")]
+            Event::Key(KeyEvent {
+                code: KeyCode::Char('u'),
+                modifiers: KeyModifiers::ALT,
+                ..
+            }) => Some(InputEvent::ToggleSearchHighlight),
+            #[cfg(feature = "__search")]
+            Event::Key(KeyEvent {
+                code: KeyCode::Esc,
+                modifiers: KeyModifiers::NONE,
+                ..
+            }) => Some(InputEvent::ClearSearch),
             _ => None,
         }
     }
@@ -1,8 +1,11 @@
-#[cfg(feature = "search")]
+#[cfg(feature = "__search")]
 use crate::SearchMode;
-use crate::{input::InputEvent, LineNumbers, PagerState};
+use crate::{
+    input::{command_line, InputEvent},
+    LineNumbers, PagerState, ScrollAmounts,
+};
 use crossterm::event::{
-    Event, KeyCode, KeyEvent, KeyEventState, KeyModifiers, MouseEvent, MouseEventKind,
+    Event, KeyCode, KeyEvent, KeyEventState, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
 };
 
 // Just a transparent function to fix incompatibility issues between
@@ -241,6 +244,181 @@ fn test_mouse_nav() {
     }
 }
 
+#[test]
+fn test_page_overlap() {
+    let mut pager = PagerState::new().unwrap();
+    pager.upper_mark = 12;
+    pager.rows = 10;
+    pager.scroll_amounts.page_overlap = 2;
+
+    let ev = Event::Key(KeyEvent {
+        code: KeyCode::PageDown,
+        modifiers: KeyModifiers::NONE,
+        kind: crossterm::event::KeyEventKind::Press,
+        state: KeyEventState::NONE,
+    });
+    // rows is 10 and overlap is 2, therefore upper_mark = upper_mark + rows - 1 - 2
+    assert_eq!(
+        Some(InputEvent::UpdateUpperMark(19)),
+        handle_input(ev, &pager)
+    );
+
+    // Overlap larger than the screen still moves by at least one row
+    pager.scroll_amounts.page_overlap = 20;
+    let ev = Event::Key(KeyEvent {
+        code: KeyCode::PageUp,
+        modifiers: KeyModifiers::NONE,
+        kind: crossterm::event::KeyEventKind::Press,
+        state: KeyEventState::NONE,
+    });
+    assert_eq!(
+        Some(InputEvent::UpdateUpperMark(11)),
+        handle_input(ev, &pager)
+    );
+}
+
+#[test]
+fn test_scroll_amounts() {
+    let mut pager = PagerState::new().unwrap();
+    pager.upper_mark = 12;
+    pager.rows = 10;
+    pager.scroll_amounts = ScrollAmounts {
+        wheel: 2,
+        horizontal_wheel: 4,
+        half_page: Some(3),
+        page_overlap: 0,
+        smooth_scroll: None,
+    };
+
+    let ev = Event::Mouse(MouseEvent {
+        kind: MouseEventKind::ScrollDown,
+        row: 0,
+        column: 0,
+        modifiers: KeyModifiers::NONE,
+    });
+    assert_eq!(
+        Some(InputEvent::UpdateUpperMark(14)),
+        handle_input(ev, &pager)
+    );
+
+    let ev = Event::Key(KeyEvent {
+        code: KeyCode::Char('u'),
+        modifiers: KeyModifiers::NONE,
+        kind: crossterm::event::KeyEventKind::Press,
+        state: KeyEventState::NONE,
+    });
+    assert_eq!(
+        Some(InputEvent::UpdateUpperMark(9)),
+        handle_input(ev, &pager)
+    );
+
+    // Without an explicit half page amount, half of the rows are used
+    pager.scroll_amounts.half_page = None;
+    let ev = Event::Key(KeyEvent {
+        code: KeyCode::Char('d'),
+        modifiers: KeyModifiers::NONE,
+        kind: crossterm::event::KeyEventKind::Press,
+        state: KeyEventState::NONE,
+    });
+    assert_eq!(
+        Some(InputEvent::UpdateUpperMark(17)),
+        handle_input(ev, &pager)
+    );
+}
+
+#[test]
+fn test_horizontal_mouse_scroll() {
+    let mut pager = PagerState::new().unwrap();
+    pager.left_mark = 10;
+    pager.scroll_amounts.horizontal_wheel = 4;
+    let ev = |kind, modifiers| {
+        Event::Mouse(MouseEvent {
+            kind,
+            row: 0,
+            column: 0,
+            modifiers,
+        })
+    };
+
+    // Ignored while the lines are wrapped
+    assert_eq!(
+        Some(InputEvent::Ignore),
+        handle_input(ev(MouseEventKind::ScrollRight, KeyModifiers::NONE), &pager)
+    );
+
+    pager.screen.line_wrapping = false;
+    assert_eq!(
+        Some(InputEvent::UpdateLeftMark(14)),
+        handle_input(ev(MouseEventKind::ScrollRight, KeyModifiers::NONE), &pager)
+    );
+    assert_eq!(
+        Some(InputEvent::UpdateLeftMark(6)),
+        handle_input(ev(MouseEventKind::ScrollLeft, KeyModifiers::NONE), &pager)
+    );
+    // Shift turns the vertical wheel into a horizontal one
+    assert_eq!(
+        Some(InputEvent::UpdateLeftMark(14)),
+        handle_input(ev(MouseEventKind::ScrollDown, KeyModifiers::SHIFT), &pager)
+    );
+    assert_eq!(
+        Some(InputEvent::UpdateLeftMark(6)),
+        handle_input(ev(MouseEventKind::ScrollUp, KeyModifiers::SHIFT), &pager)
+    );
+}
+
+#[test]
+fn test_mouse_click() {
+    let pager = PagerState::new().unwrap();
+    let ev = Event::Mouse(MouseEvent {
+        kind: MouseEventKind::Down(MouseButton::Left),
+        row: 4,
+        column: 17,
+        modifiers: KeyModifiers::NONE,
+    });
+    assert_eq!(Some(InputEvent::Click(4, 17)), handle_input(ev, &pager));
+}
+
+#[test]
+fn test_horizontal_jumps() {
+    let mut pager = PagerState::new().unwrap();
+    pager.cols = 20;
+    pager.screen.max_line_length = 50;
+    pager.left_mark = 5;
+
+    let key = |c| {
+        Event::Key(KeyEvent {
+            code: KeyCode::Char(c),
+            modifiers: KeyModifiers::NONE,
+            kind: crossterm::event::KeyEventKind::Press,
+            state: KeyEventState::NONE,
+        })
+    };
+
+    assert_eq!(
+        Some(InputEvent::UpdateLeftMark(15)),
+        handle_input(key('L'), &pager)
+    );
+    assert_eq!(
+        Some(InputEvent::UpdateLeftMark(0)),
+        handle_input(key('H'), &pager)
+    );
+    assert_eq!(
+        Some(InputEvent::UpdateLeftMark(0)),
+        handle_input(key('^'), &pager)
+    );
+    assert_eq!(
+        Some(InputEvent::UpdateLeftMark(30)),
+        handle_input(key('$'), &pager)
+    );
+
+    // Half screen jumps to the right stop at the end of the longest line
+    pager.left_mark = 25;
+    assert_eq!(
+        Some(InputEvent::UpdateLeftMark(30)),
+        handle_input(key('L'), &pager)
+    );
+}
+
 #[test]
 fn test_saturation() {
     let mut pager = PagerState::new().unwrap();
@@ -323,6 +501,20 @@ fn test_misc_events() {
         assert_eq!(Some(InputEvent::Exit), handle_input(ev, &pager));
     }
 
+    {
+        // `Q` only quits in place of `q` when macros are enabled
+        let ev = Event::Key(KeyEvent {
+            code: KeyCode::Char('Q'),
+            modifiers: KeyModifiers::SHIFT,
+            kind: crossterm::event::KeyEventKind::Press,
+            state: KeyEventState::NONE,
+        });
+        assert_eq!(Some(InputEvent::Ignore), handle_input(ev.clone(), &pager));
+        pager.macros.enabled = true;
+        assert_eq!(Some(InputEvent::Exit), handle_input(ev, &pager));
+        pager.macros.enabled = false;
+    }
+
     {
         let ev = Event::Key(KeyEvent {
             code: KeyCode::Char('c'),
@@ -330,7 +522,20 @@ fn test_misc_events() {
             kind: crossterm::event::KeyEventKind::Press,
             state: KeyEventState::NONE,
         });
-        assert_eq!(Some(InputEvent::Exit), handle_input(ev, &pager));
+        assert_eq!(Some(InputEvent::Interrupt), handle_input(ev, &pager));
+    }
+
+    {
+        let ev = Event::Key(KeyEvent {
+            code: KeyCode::Char('p'),
+            modifiers: KeyModifiers::CONTROL,
+            kind: crossterm::event::KeyEventKind::Press,
+            state: KeyEventState::NONE,
+        });
+        assert_eq!(
+            Some(InputEvent::ShowPrompt(!pager.show_prompt)),
+            handle_input(ev, &pager)
+        );
     }
 
     {
@@ -343,6 +548,19 @@ fn test_misc_events() {
         assert_eq!(Some(InputEvent::Ignore), handle_input(ev, &pager));
     }
 
+    {
+        let ev = Event::Key(KeyEvent {
+            code: KeyCode::Char('F'),
+            modifiers: KeyModifiers::NONE,
+            kind: crossterm::event::KeyEventKind::Press,
+            state: KeyEventState::NONE,
+        });
+        assert_eq!(
+            Some(InputEvent::WaitForData(true)),
+            handle_input(ev, &pager)
+        );
+    }
+
     {
         let ev = Event::Key(KeyEvent {
             code: KeyCode::Char('5'),
@@ -356,7 +574,7 @@ fn test_misc_events() {
 
 #[test]
 #[allow(clippy::too_many_lines)]
-#[cfg(feature = "search")]
+#[cfg(feature = "__search")]
 fn test_search_bindings() {
     let mut pager = PagerState::new().unwrap();
     pager.upper_mark = 12;
@@ -388,6 +606,27 @@ fn test_search_bindings() {
             handle_input(ev, &pager)
         );
     }
+
+    {
+        let ev = Event::Key(KeyEvent {
+            code: KeyCode::Char('u'),
+            modifiers: KeyModifiers::ALT,
+            kind: crossterm::event::KeyEventKind::Press,
+            state: KeyEventState::NONE,
+        });
+        assert_eq!(
+            Some(InputEvent::ToggleSearchHighlight),
+            handle_input(ev, &pager)
+        );
+
+        let ev = Event::Key(KeyEvent {
+            code: KeyCode::Esc,
+            modifiers: KeyModifiers::NONE,
+            kind: crossterm::event::KeyEventKind::Press,
+            state: KeyEventState::NONE,
+        });
+        assert_eq!(Some(InputEvent::ClearSearch), handle_input(ev, &pager));
+    }
     {
         pager.search_state.search_mode = SearchMode::Forward;
         // NextMatch and PrevMatch forward search
@@ -440,3 +679,331 @@ fn test_search_bindings() {
         );
     }
 }
+
+#[test]
+#[cfg(feature = "__search")]
+fn test_jump_pattern() {
+    use crate::SearchMode;
+
+    let key = |c| Event::Key(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE));
+    let mut pager = PagerState::new().unwrap();
+
+    // Without a pattern the keys do nothing
+    assert_eq!(Some(InputEvent::Ignore), handle_input(key(']'), &pager));
+
+    pager.jump_pattern = Some(crate::search::Regex::new("^@@").unwrap());
+    assert_eq!(
+        Some(InputEvent::JumpToSetPattern(SearchMode::Forward)),
+        handle_input(key(']'), &pager)
+    );
+    assert_eq!(
+        Some(InputEvent::JumpToSetPattern(SearchMode::Reverse)),
+        handle_input(key('['), &pager)
+    );
+}
+
+#[test]
+fn test_prefix_num() {
+    let key = |code| Event::Key(KeyEvent::new(code, KeyModifiers::NONE));
+    let mut pager = PagerState::new().unwrap();
+    assert!(!pager.edit_prefix_num(&key(KeyCode::Esc)));
+
+    pager.prefix_num = "125".to_string();
+    pager.format_prompt();
+    assert!(pager.displayed_prompt.contains(":125"));
+
+    assert!(pager.edit_prefix_num(&key(KeyCode::Backspace)));
+    assert_eq!(pager.prefix_num, "12");
+    assert!(pager.displayed_prompt.contains(":12 "));
+
+    // Other keys are left to the input classifier
+    assert!(!pager.edit_prefix_num(&key(KeyCode::Char('j'))));
+
+    assert!(pager.edit_prefix_num(&key(KeyCode::Esc)));
+    assert!(pager.prefix_num.is_empty());
+    assert!(!pager.displayed_prompt.contains(':'));
+}
+
+#[test]
+fn test_center_row() {
+    let key = Event::Key(KeyEvent::new(KeyCode::Char('z'), KeyModifiers::NONE));
+    let mut pager = PagerState::new().unwrap();
+    pager.screen.orig_text = "A line that is wrapped\n".repeat(20);
+    pager.cols = 10;
+    pager.format_lines();
+    pager.upper_mark = 7;
+
+    // Without a count, the row at the top of the screen is centered
+    assert_eq!(
+        Some(InputEvent::CenterRow(7)),
+        handle_input(key.clone(), &pager)
+    );
+
+    // With a count, the first row of that line is centered
+    pager.prefix_num = "4".to_string();
+    assert_eq!(
+        Some(InputEvent::CenterRow(9)),
+        handle_input(key.clone(), &pager)
+    );
+
+    pager.prefix_num = "100".to_string();
+    assert_eq!(
+        Some(InputEvent::UpdateUpperMark(usize::MAX - 1)),
+        handle_input(key, &pager)
+    );
+}
+
+#[test]
+fn test_command_line() {
+    let key = |c| Event::Key(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE));
+    let mut pager = PagerState::new().unwrap();
+    pager.add_buffer("first".to_string(), "1\n".to_string());
+    pager.add_buffer("second".to_string(), "2\n".to_string());
+
+    assert_eq!(
+        Some(InputEvent::EnterCommand),
+        handle_input(key(':'), &pager)
+    );
+
+    // Single letter commands run immediately
+    pager.command_line = Some(String::new());
+    assert_eq!(
+        Some(InputEvent::SwitchBuffer(1)),
+        command_line::handle_key(&key('n'), &mut pager)
+    );
+    assert_eq!(pager.command_line, None);
+
+    pager.command_line = Some(String::new());
+    assert_eq!(None, command_line::handle_key(&key('p'), &mut pager));
+    assert_eq!(pager.message.as_deref(), Some("No previous buffer"));
+
+    // Others need Enter
+    pager.command_line = Some(String::new());
+    for c in "buffers".chars() {
+        assert_eq!(None, command_line::handle_key(&key(c), &mut pager));
+    }
+    assert_eq!(pager.command_line.as_deref(), Some("buffers"));
+    let enter = Event::Key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+    assert_eq!(
+        Some(InputEvent::ListBuffers),
+        command_line::handle_key(&enter, &mut pager)
+    );
+
+    // Line numbers go to that line, or relative to the top line with a sign
+    let goto = |command: &str, pager: &mut PagerState| {
+        pager.command_line = Some(command.to_string());
+        command_line::handle_key(&enter, pager)
+    };
+    pager.upper_mark = 0;
+    assert_eq!(Some(InputEvent::GotoLine(42)), goto("42", &mut pager));
+    assert_eq!(Some(InputEvent::GotoLine(6)), goto("+5", &mut pager));
+    assert_eq!(Some(InputEvent::GotoLine(1)), goto("-5", &mut pager));
+    // Errors wait for the message shown before to be dismissed
+    assert_eq!(None, goto("+x", &mut pager));
+    assert_eq!(pager.message.as_deref(), Some("No previous buffer"));
+    assert_eq!(pager.pending_messages, ["Invalid line number: +x"]);
+
+    let mut pager = PagerState::new().unwrap();
+    pager.prefix_num = "12".to_string();
+    assert_eq!(
+        Some(InputEvent::GotoLine(12)),
+        handle_input(key('g'), &pager)
+    );
+
+    // Esc cancels the command
+    pager.command_line = Some("buf".to_string());
+    let esc = Event::Key(KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE));
+    assert_eq!(None, command_line::handle_key(&esc, &mut pager));
+    assert_eq!(pager.command_line, None);
+}
+
+#[test]
+fn test_enhanced_key_events() {
+    use super::normalize_event;
+    use crossterm::event::KeyEventKind;
+
+    let pager = PagerState::new().unwrap();
+    let key = |code, kind, state| {
+        Event::Key(KeyEvent {
+            code,
+            modifiers: KeyModifiers::NONE,
+            kind,
+            state,
+        })
+    };
+    let q = |kind, state| key(KeyCode::Char('q'), kind, state);
+
+    // Releases are dropped and repeats act like presses
+    assert_eq!(
+        None,
+        normalize_event(q(KeyEventKind::Release, KeyEventState::NONE))
+    );
+    assert_eq!(
+        Some(q(KeyEventKind::Press, KeyEventState::NONE)),
+        normalize_event(q(KeyEventKind::Repeat, KeyEventState::NONE))
+    );
+
+    // Lock and keypad states fall back to bindings that don't ask for them
+    assert_eq!(
+        Some(InputEvent::Exit),
+        handle_input(q(KeyEventKind::Press, KeyEventState::CAPS_LOCK), &pager)
+    );
+    assert_eq!(
+        handle_input(
+            key(KeyCode::Enter, KeyEventKind::Press, KeyEventState::NONE),
+            &pager
+        ),
+        handle_input(
+            key(
+                KeyCode::Enter,
+                KeyEventKind::Press,
+                KeyEventState::KEYPAD | KeyEventState::NUM_LOCK
+            ),
+            &pager
+        ),
+    );
+
+    // Unless a binding asks for them
+    let mut pager = PagerState::new().unwrap();
+    let mut register = crate::input::HashedEventRegister::default();
+    crate::input::generate_default_bindings(&mut register);
+    register.add_key_events(&["numlock-kp5"], |_, _| InputEvent::Exit);
+    pager.input_classifier = Box::new(register);
+    assert_eq!(
+        Some(InputEvent::Exit),
+        handle_input(
+            key(
+                KeyCode::Char('5'),
+                KeyEventKind::Press,
+                KeyEventState::KEYPAD | KeyEventState::NUM_LOCK
+            ),
+            &pager
+        )
+    );
+    assert_eq!(
+        Some(InputEvent::Number('5')),
+        handle_input(
+            key(
+                KeyCode::Char('5'),
+                KeyEventKind::Press,
+                KeyEventState::KEYPAD
+            ),
+            &pager
+        )
+    );
+}
+
+#[test]
+fn test_event_matchers() {
+    use std::sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc,
+    };
+
+    let key = |c| Event::Key(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE));
+    let mut pager = PagerState::new().unwrap();
+    let mut register = crate::input::HashedEventRegister::default();
+
+    let waiting = Arc::new(AtomicBool::new(true));
+    let waiting2 = waiting.clone();
+
+    // Added before the higher priority one but must run after it
+    register.add_event_matcher(1, |_, _| Some(InputEvent::Ignore));
+    register.add_event_matcher(5, move |_, _| {
+        waiting2
+            .load(Ordering::SeqCst)
+            .then_some(InputEvent::RestorePrompt)
+    });
+    pager.input_classifier = Box::new(register);
+
+    assert_eq!(
+        Some(InputEvent::RestorePrompt),
+        handle_input(key('q'), &pager)
+    );
+    waiting.store(false, Ordering::SeqCst);
+    assert_eq!(Some(InputEvent::Ignore), handle_input(key('q'), &pager));
+
+    let mut register = crate::input::HashedEventRegister::default();
+    let unbound = Arc::new(AtomicUsize::new(0));
+    let unbound2 = unbound.clone();
+    register.add_event_matcher(-1, move |_, _| {
+        unbound2.fetch_add(1, Ordering::SeqCst);
+        None
+    });
+    pager.input_classifier = Box::new(register);
+
+    // Bound keys never reach the low priority matcher
+    assert_eq!(Some(InputEvent::Exit), handle_input(key('q'), &pager));
+    assert_eq!(unbound.load(Ordering::SeqCst), 0);
+    // Unbound ones pass through it to the count prefix
+    assert_eq!(
+        Some(InputEvent::Number('4')),
+        handle_input(key('4'), &pager)
+    );
+    assert_eq!(unbound.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn test_input_context() {
+    let mut pager = PagerState::new().unwrap();
+    pager.rows = 4;
+    let text: String = (0..10).map(|i| format!("line {i}\n")).collect();
+    pager.append_str(&text);
+    pager.upper_mark = 2;
+
+    let ctx = pager.input_context();
+    assert_eq!(ctx.upper_mark, 2);
+    assert_eq!(ctx.text_rows, 3);
+    assert_eq!(ctx.visible_lines, 2..5);
+    assert_eq!(ctx.total_lines, 10);
+    assert_eq!(ctx.total_rows, 10);
+    assert_eq!(ctx.max_line_length, 6);
+    assert_eq!(ctx.screen.get_line(ctx.visible_lines.start), Some("line 2"));
+
+    #[cfg(feature = "__search")]
+    {
+        assert_eq!(ctx.search_query, None);
+        assert_eq!(ctx.current_match, None);
+    }
+}
+
+#[test]
+fn test_fx_register_lookup() {
+    let mut pager = PagerState::new().unwrap();
+    let mut register = crate::input::HashedEventRegister::with_fx_hasher();
+    register.add_key_events(&["G"], |_, _| InputEvent::Exit);
+    register.add_key_events(&["c-s-h"], |_, _| InputEvent::RestorePrompt);
+    register.add_mouse_events(&["left:drag"], |_, _| InputEvent::Ignore);
+    pager.input_classifier = Box::new(register);
+
+    let key = |c, modifiers| Event::Key(KeyEvent::new(KeyCode::Char(c), modifiers));
+
+    // Uppercase letters and shifted lowercase ones are the same key
+    assert_eq!(
+        Some(InputEvent::Exit),
+        handle_input(key('G', KeyModifiers::NONE), &pager)
+    );
+    assert_eq!(
+        Some(InputEvent::Exit),
+        handle_input(key('g', KeyModifiers::SHIFT), &pager)
+    );
+    assert_eq!(
+        Some(InputEvent::RestorePrompt),
+        handle_input(key('H', KeyModifiers::CONTROL), &pager)
+    );
+    assert_eq!(None, handle_input(key('g', KeyModifiers::NONE), &pager));
+
+    // Mouse positions don't matter
+    assert_eq!(
+        Some(InputEvent::Ignore),
+        handle_input(
+            Event::Mouse(MouseEvent {
+                kind: MouseEventKind::Drag(MouseButton::Left),
+                column: 13,
+                row: 7,
+                modifiers: KeyModifiers::NONE,
+            }),
+            &pager
+        )
+    );
+}
@@ -57,11 +57,15 @@
 //!
 //! ## tokio
 //!
+//! With the `tokio` feature enabled, the pager can be awaited using [async_paging]. The pager
+//! runs inside the returned future, reading the user input through crossterm's `EventStream` and
+//! waiting for data without blocking the thread.
+//!
 //! ```rust,no_run
-//! use minus::{dynamic_paging, MinusError, Pager};
+//! use minus::{async_paging, MinusError, Pager};
 //! use std::time::Duration;
 //! use std::fmt::Write;
-//! use tokio::{join, task::spawn_blocking, time::sleep};
+//! use tokio::{join, time::sleep};
 //!
 //! #[tokio::main]
 //! async fn main() -> Result<(), MinusError> {
@@ -76,14 +80,9 @@
 //!         }
 //!         Result::<_, MinusError>::Ok(())
 //!     };
-//!     // spawn_blocking(dynamic_paging(...)) creates a separate thread managed by the tokio
-//!     // runtime and runs the async_paging inside it
-//!     let pager = pager.clone();
-//!     let (res1, res2) = join!(spawn_blocking(move || dynamic_paging(pager)), increment);
-//!     // .unwrap() unwraps any error while creating the tokio task
-//!     //  The ? mark unpacks any error that might have occurred while the
-//!     // pager is running
-//!     res1.unwrap()?;
+//!     // The ? mark unpacks any error that might have occurred while the pager is running
+//!     let (res1, res2) = join!(async_paging(pager.clone()), increment);
+//!     res1?;
 //!     res2?;
 //!     Ok(())
 //! }
@@ -120,29 +119,48 @@
 //!
 //! | Action              | Description                                                                  |
 //! |---------------------|------------------------------------------------------------------------------|
-//! | Ctrl+C/q            | Quit the pager                                                               |
+//! | Ctrl+C/q            | Quit the pager. See [`Pager::set_interrupt_behavior`] to change `Ctrl+C`     |
 //! | \[n\] Arrow Up/k    | Scroll up by n number of line(s). If n is omitted, scroll up by 1 line       |
 //! | \[n\] Arrow Down/j  | Scroll down by n number of line(s). If n is omitted, scroll down by 1 line   |
 //! | Ctrl+h              | Turn off line wrapping and allow horizontal scrolling                        |
 //! | \[n\] Arrow left/h  | Scroll left by n number of line(s). If n is omitted, scroll up by 1 line     |
 //! | \[n\] Arrow right/l | Scroll right by n number of line(s). If n is omitted, scroll down by 1 line  |
+//! | H                   | Scroll left by half the screen width                                         |
+//! | L                   | Scroll right by half the screen width                                        |
+//! | ^                   | Scroll to the start of the lines                                             |
+//! | $                   | Scroll to the end of the longest line                                        |
 //! | Page Up             | Scroll up by entire page                                                     |
 //! | Page Down           | Scroll down by entire page                                                   |
 //! | \[n\] Enter         | Scroll down by n number of line(s).                                          |
 //! | Space               | Scroll down by one page                                                      |
 //! | Ctrl+U/u            | Scroll up by half a screen                                                   |
 //! | Ctrl+D/d            | Scroll down by half a screen                                                 |
-//! | g                   | Go to the very top of the output                                             |
-//! | \[n\] G             | Go to the very bottom of the output. If n is present, goes to that line      |
-//! | Mouse scroll Up     | Scroll up by 5 lines                                                         |
-//! | Mouse scroll Down   | Scroll down by 5 lines                                                       |
+//! | \[n\] g             | Go to the very top of the output. If n is present, goes to that line         |
+//! | \[n\] G/End         | Go to the very bottom of the output. If n is present, goes to that line      |
+//! | \[n\] z             | Center the top line, or line n if present, on the screen                     |
+//! | \[n\] Esc/Backspace | Cancel n or remove its last digit                                            |
+//! | Mouse scroll Up     | Scroll up by 5 lines. See [ScrollAmounts]                                    |
+//! | Mouse scroll Down   | Scroll down by 5 lines. See [ScrollAmounts]                                  |
+//! | Mouse scroll Left   | Scroll left by 5 columns. Same as Shift+Mouse scroll Up                      |
+//! | Mouse scroll Right  | Scroll right by 5 columns. Same as Shift+Mouse scroll Down                   |
+//! | Mouse left click    | Open the hyperlink under the mouse. See [Pager::on_link_click]               |
 //! | Ctrl+L              | Toggle line numbers if not forced enabled/disabled                           |
 //! | Ctrl+f              | Toggle [follow-mode]                                                         |
+//! | Ctrl+p              | Hide or show the prompt. See [Pager::show_prompt]                            |
+//! | F                   | Follow the output and wait for more data until any key is pressed            |
+//! | R                   | Reload the content using the [reload callback]                               |
+//! | :n / :p             | Switch to the next/previous buffer. See [Pager::add_buffer]                  |
+//! | :x                  | Switch to the first buffer                                                   |
+//! | :buffers            | List all the buffers                                                         |
+//! | :\[n\] / :+n / :-n  | Go to line n, or n lines below/above the top line                            |
+//! | ] / [               | Jump to the next/previous line matching [Pager::set_jump_pattern]            |
 //! | /                   | Start forward search                                                         |
 //! | ?                   | Start backward search                                                        |
 //! | Esc                 | Cancel search input                                                          |
 //! | n                   | Go to the next search match                                                  |
 //! | p                   | Go to the next previous match                                                |
+//! | Alt-u               | Hide or show the highlighting of search matches                              |
+//! | Esc                 | Leave the search, removing the highlights                                    |
 //!
 //! End-applications are free to change these bindings to better suit their needs. See docs for
 //! [Pager::set_input_classifier] function and [input] module.
@@ -164,40 +182,61 @@
 //!
 //! Currently these cannot be changed by applications but this may be supported in the future.
 //!
+//! Starting the query with `@` searches only the lines visible on the screen, for example `@error`.
+//! To search the whole text for a pattern starting with `@`, escape it as `\@`, for example
+//! `\@user` finds `@user` anywhere in the text.
+//!
 //! [`tokio`]: https://docs.rs/tokio
 //! [`async-std`]: https://docs.rs/async-std
 //! [`Threads`]: std::thread
 //! [follow-mode]: struct.Pager.html#method.follow_output
+//! [reload callback]: struct.Pager.html#method.set_reload_callback
 //! [paging]: https://en.wikipedia.org/wiki/Terminal_pager
 //! [README]: https://github.com/arijit79/minus#motivation
+pub mod backend;
+pub mod config;
 #[cfg(feature = "dynamic_output")]
 mod dynamic_pager;
 pub mod error;
+pub mod events;
 pub mod input;
 #[path = "core/mod.rs"]
 mod minus_core;
 mod pager;
+#[cfg(feature = "__regex")]
+mod pattern;
 pub mod screen;
-#[cfg(feature = "search")]
+#[cfg(feature = "__search")]
 #[cfg_attr(docsrs, doc(cfg(feature = "search")))]
 pub mod search;
 pub mod state;
 #[cfg(feature = "static_output")]
 mod static_pager;
+#[cfg(feature = "ratatui")]
+#[cfg_attr(docsrs, doc(cfg(feature = "ratatui")))]
+pub mod widget;
 
+#[cfg(all(feature = "dynamic_output", feature = "tokio"))]
+pub use dynamic_pager::async_paging;
 #[cfg(feature = "dynamic_output")]
-pub use dynamic_pager::dynamic_paging;
+pub use dynamic_pager::{dynamic_paging, PagerHandle};
 #[cfg(feature = "static_output")]
-pub use static_pager::page_all;
+pub use static_pager::{page_all, page_all_to, page_debug, page_display};
 
-pub use minus_core::RunMode;
-#[cfg(feature = "search")]
+pub use minus_core::{commands::PagerCommand, RunMode};
+#[cfg(feature = "__search")]
 pub use search::SearchMode;
 
+pub use config::PagerConfig;
 pub use error::MinusError;
+pub use events::PagerEvent;
 pub use pager::Pager;
 pub use state::PagerState;
 
+pub use crossterm::style::ContentStyle;
+
+use std::{sync::Arc, time::Duration};
+
 /// A convenient type for `Vec<Box<dyn FnMut() + Send + Sync + 'static>>`
 pub type ExitCallbacks = Vec<Box<dyn FnMut() + Send + Sync + 'static>>;
 
@@ -205,7 +244,11 @@ pub type ExitCallbacks = Vec<Box<dyn FnMut() + Send + Sync + 'static>>;
 type Result<T = (), E = MinusError> = std::result::Result<T, E>;
 
 /// Behaviour that happens when the pager is exited
-#[derive(PartialEq, Clone, Debug, Eq)]
+///
+/// When the `serde` feature is enabled, this can be serialized and deserialized except for
+/// [`ExitStrategy::ProcessQuitWithCallback`].
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ExitStrategy {
     /// Kill the entire application immediately.
     ///
@@ -221,6 +264,270 @@ pub enum ExitStrategy {
     /// if you've file system locks or you want to close database connectiions after
     /// the pager has done i's job, you probably want to go for this option
     PagerQuit,
+    /// Kill the entire application with the given exit code.
+    ///
+    /// This is same as [`ProcessQuit`](ExitStrategy::ProcessQuit) except that the application
+    /// can choose the status code with which it quits.
+    ProcessQuitWithCode(i32),
+    /// Call the function and kill the entire application with the exit code returned by it.
+    ///
+    /// The function is called after the terminal has been restored. This can be used to flush
+    /// logs or release locks before the application quits.
+    ///
+    /// ```
+    /// use minus::{ExitStrategy, Pager};
+    /// use std::sync::Arc;
+    ///
+    /// let pager = Pager::new();
+    /// pager.set_exit_strategy(ExitStrategy::ProcessQuitWithCallback(Arc::new(|| {
+    ///     eprintln!("Flushing logs");
+    ///     0
+    /// }))).expect("Failed to communicate with the pager");
+    /// ```
+    #[cfg_attr(feature = "serde", serde(skip))]
+    ProcessQuitWithCallback(Arc<dyn Fn() -> i32 + Send + Sync + 'static>),
+}
+
+impl PartialEq for ExitStrategy {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::ProcessQuit, Self::ProcessQuit) | (Self::PagerQuit, Self::PagerQuit) => true,
+            (Self::ProcessQuitWithCode(c1), Self::ProcessQuitWithCode(c2)) => c1 == c2,
+            (Self::ProcessQuitWithCallback(cb1), Self::ProcessQuitWithCallback(cb2)) => {
+                Arc::ptr_eq(cb1, cb2)
+            }
+            _ => false,
+        }
+    }
+}
+
+impl Eq for ExitStrategy {}
+
+impl std::fmt::Debug for ExitStrategy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::ProcessQuit => write!(f, "ProcessQuit"),
+            Self::PagerQuit => write!(f, "PagerQuit"),
+            Self::ProcessQuitWithCode(code) => write!(f, "ProcessQuitWithCode({code:?})"),
+            Self::ProcessQuitWithCallback(_) => write!(f, "ProcessQuitWithCallback"),
+        }
+    }
+}
+
+/// What to do when the user presses `Ctrl+C`
+///
+/// See [`Pager::set_interrupt_behavior`] for more info.
+#[derive(Clone, Default)]
+pub enum InterruptBehavior {
+    /// Quit the pager just like pressing `q`.
+    ///
+    /// **This is the default behavior.**
+    #[default]
+    Quit,
+    /// Call the function and keep the pager running.
+    ///
+    /// This can be used to forward the interrupt to a child process whose output is being paged.
+    ///
+    /// ```
+    /// use minus::{InterruptBehavior, Pager};
+    /// use std::sync::Arc;
+    ///
+    /// let pager = Pager::new();
+    /// pager.set_interrupt_behavior(InterruptBehavior::Callback(Arc::new(|| {
+    ///     eprintln!("Interrupting the child");
+    /// }))).expect("Failed to communicate with the pager");
+    /// ```
+    Callback(Arc<dyn Fn() + Send + Sync + 'static>),
+    /// Do nothing.
+    Ignore,
+}
+
+impl PartialEq for InterruptBehavior {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Quit, Self::Quit) | (Self::Ignore, Self::Ignore) => true,
+            (Self::Callback(cb1), Self::Callback(cb2)) => Arc::ptr_eq(cb1, cb2),
+            _ => false,
+        }
+    }
+}
+
+impl Eq for InterruptBehavior {}
+
+impl std::fmt::Debug for InterruptBehavior {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Quit => write!(f, "Quit"),
+            Self::Callback(_) => write!(f, "Callback"),
+            Self::Ignore => write!(f, "Ignore"),
+        }
+    }
+}
+
+/// Why the pager quit
+///
+/// This is reported through [`PagerExit`] by [`PagerHandle::wait`].
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum ExitReason {
+    /// The user quit the pager, for example by pressing `q`
+    UserQuit,
+    /// The application closed the pager using [`Pager::quit`]
+    AppQuit,
+    /// All the text has been shown
+    ///
+    /// This happens when the user reaches the end of the text while [`Pager::set_quit_on_eof`]
+    /// is turned on, or when all the text fits on the screen in static mode.
+    Eof,
+    /// The pager quit because it encountered an error
+    Error(MinusError),
+}
+
+/// Information about how the pager quit
+///
+/// This lets the application distinguish a user who quit early from one who read everything and
+/// resume from where they left.
+#[derive(Debug)]
+pub struct PagerExit {
+    /// Why the pager quit
+    pub reason: ExitReason,
+    /// Line number, starting from 1, of the line that was at the top of the screen when the pager
+    /// quit
+    pub top_line: usize,
+}
+
+/// Number of rows moved by the default scrolling bindings and how scrolling looks
+///
+/// The default keymap reads these values from [`PagerState::scroll_amounts`], hence they can be
+/// changed using [`Pager::set_scroll_amounts`] without redefining any bindings.
+///
+/// ```
+/// use minus::{Pager, ScrollAmounts};
+///
+/// let pager = Pager::new();
+/// pager.set_scroll_amounts(ScrollAmounts {
+///     wheel: 3,
+///     ..ScrollAmounts::default()
+/// }).expect("Failed to communicate with the pager");
+/// ```
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct ScrollAmounts {
+    /// Rows moved on each mouse wheel scroll. Defaults to 5.
+    pub wheel: usize,
+    /// Columns moved on each horizontal mouse wheel scroll or on scrolling the wheel with Shift
+    /// held, when line wrapping is off. Defaults to 5.
+    pub horizontal_wheel: usize,
+    /// Rows moved by the half page bindings like `u` and `d`.
+    ///
+    /// When set to `None`, which is also the default, half of the terminal rows are used.
+    pub half_page: Option<usize>,
+    /// Rows of the previous page that stay visible when scrolling by an entire page. Defaults
+    /// to 0.
+    pub page_overlap: usize,
+    /// Time taken to animate large jumps
+    ///
+    /// When set, jumps of half a page or more, like moving by a page, going to the end or jumping
+    /// to a search match, scroll over a few frames in this much time instead of moving at once.
+    /// Defaults to `None` which turns the animation off.
+    pub smooth_scroll: Option<Duration>,
+}
+
+impl Default for ScrollAmounts {
+    fn default() -> Self {
+        Self {
+            wheel: 5,
+            horizontal_wheel: 5,
+            half_page: None,
+            page_overlap: 0,
+            smooth_scroll: None,
+        }
+    }
+}
+
+/// Styles used for the parts of the screen that minus adds to the text
+///
+/// See [`Pager::set_theme`].
+///
+/// ```
+/// use minus::{ContentStyle, Pager, Theme};
+/// use crossterm::style::{Color, Stylize};
+///
+/// let pager = Pager::new();
+/// // Show dim gray line numbers separated from the text by a bar
+/// pager.set_theme(Theme {
+///     line_number: ContentStyle::new().with(Color::DarkGrey).dim(),
+///     line_number_separator: '│',
+/// }).expect("Failed to communicate with the pager");
+/// ```
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct Theme {
+    /// Style of the line numbers along with their separator. Defaults to bold.
+    #[cfg_attr(feature = "serde", serde(with = "content_style_serde"))]
+    pub line_number: ContentStyle,
+    /// Character displayed after each line number. This should take up a single column.
+    /// Defaults to `.`.
+    ///
+    /// This is not used when the line numbers are made by the function set with
+    /// [`Pager::set_line_number_format`].
+    pub line_number_separator: char,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        use crossterm::style::Stylize;
+
+        Self {
+            line_number: ContentStyle::new().bold(),
+            line_number_separator: '.',
+        }
+    }
+}
+
+/// (De)serializes a [`ContentStyle`], which doesn't implement the traits of serde by itself
+#[cfg(feature = "serde")]
+mod content_style_serde {
+    use crossterm::style::{Attribute, Color, ContentStyle};
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(Serialize, Deserialize, Default)]
+    #[serde(default)]
+    struct Style {
+        foreground_color: Option<Color>,
+        background_color: Option<Color>,
+        underline_color: Option<Color>,
+        attributes: Vec<Attribute>,
+    }
+
+    pub fn serialize<S: Serializer>(
+        style: &ContentStyle,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        Style {
+            foreground_color: style.foreground_color,
+            background_color: style.background_color,
+            underline_color: style.underline_color,
+            attributes: Attribute::iterator()
+                .filter(|attr| style.attributes.has(*attr))
+                .collect(),
+        }
+        .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<ContentStyle, D::Error> {
+        let style = Style::deserialize(deserializer)?;
+        Ok(ContentStyle {
+            foreground_color: style.foreground_color,
+            background_color: style.background_color,
+            underline_color: style.underline_color,
+            attributes: style.attributes.as_slice().into(),
+        })
+    }
 }
 
 /// Enum indicating whether to display the line numbers or not.
@@ -232,6 +539,7 @@ pub enum ExitStrategy {
 /// This implements [`Not`](std::ops::Not) to allow turning on/off line numbers
 /// when they where not locked in by the binary displaying the text.
 #[derive(Debug, PartialEq, Eq, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum LineNumbers {
     /// Enable line numbers permanently, cannot be turned off by user.
     AlwaysOn,
@@ -275,5 +583,78 @@ impl std::ops::Not for LineNumbers {
     }
 }
 
+/// Enum indicating what to do with ANSI escape sequences in the incoming text
+///
+/// See [`Pager::set_ansi`] for more info.
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Default)]
+pub enum AnsiMode {
+    /// Keep the escape sequences and let the terminal interpret them. This is the default.
+    #[default]
+    Passthrough,
+    /// Remove all escape sequences from the text as it is pushed into the pager.
+    Strip,
+}
+
+/// Enum indicating how binary data pushed into the pager is displayed
+///
+/// Data is considered binary if it contains NUL bytes or isn't valid UTF-8. See
+/// [`Pager::set_binary_mode`] for more info.
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Default)]
+pub enum BinaryMode {
+    /// Always display the data as text. Invalid UTF-8 sequences are replaced with `�`. This is
+    /// the default.
+    #[default]
+    Text,
+    /// Switch to a hex dump as soon as binary data is pushed
+    Auto,
+    /// Always display the data as a hex dump
+    HexDump,
+}
+
+/// Enum indicating how the user is told that an action could not be carried out
+///
+/// Feedback is given when scrolling past the top or the bottom of the text, when a search finds
+/// no matches and when a key that isn't bound to anything is pressed. See [`Pager::set_feedback`]
+/// for more info.
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FeedbackMode {
+    /// Don't give any feedback. This is the default.
+    #[default]
+    None,
+    /// Ring the terminal bell
+    Bell,
+    /// Briefly show the prompt in reverse video
+    Flash,
+}
+
+/// Enum indicating which stream the pager writes its output to
+///
+/// See [`Pager::set_output`] for more info.
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Default)]
+pub enum OutputTarget {
+    /// Write to the standard output. This is the default.
+    #[default]
+    Stdout,
+    /// Write to the standard error.
+    Stderr,
+}
+
+/// Enum indicating what the title of the terminal window is set to while paging
+///
+/// The title the window had before is restored when the pager quits. See
+/// [`Pager::set_window_title`] for more info.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum WindowTitle {
+    /// Leave the title of the window alone. This is the default.
+    #[default]
+    Unchanged,
+    /// Set the title to the prompt, without any styling. See [`Pager::set_prompt`]
+    Prompt,
+    /// Set the title to the given text
+    Text(String),
+}
+
 #[cfg(test)]
 mod tests;
@@ -1,11 +1,34 @@
 //! Proivdes the [Pager] type
 
-use crate::{error::MinusError, input, minus_core::commands::Command, ExitStrategy, LineNumbers};
-use crossbeam_channel::{Receiver, Sender};
-use std::fmt;
+use crate::{
+    backend::{Backend, Capabilities, CrosstermBackend},
+    config::PagerConfig,
+    error::MinusError,
+    input,
+    minus_core::{
+        channel::{self, CommandReceiver, CommandSender},
+        commands::{Command, PagerCommand},
+        utils::display::Region,
+    },
+    screen::{LineNumberFormat, LineTransformer},
+    state::{PagerState, PromptContext, Session, StateSnapshot},
+    AnsiMode, BinaryMode, ExitStrategy, FeedbackMode, InterruptBehavior, LineNumbers, OutputTarget,
+    PagerEvent, ScrollAmounts, Theme, WindowTitle,
+};
+use crossbeam_channel::{Receiver, SendError, TrySendError};
+use crossterm::{event::KeyboardEnhancementFlags, style::ContentStyle};
+use parking_lot::Mutex;
+use std::{
+    fmt::{self, Write as _},
+    ops::Range,
+    sync::{atomic::AtomicBool, Arc},
+    time::Duration,
+};
 
-#[cfg(feature = "search")]
-use crate::search::SearchOpts;
+#[cfg(feature = "__search")]
+use crate::search::{HighlightMode, SearchOpts, SearchSummary};
+#[cfg(feature = "metrics")]
+use crate::state::Metrics;
 
 /// A communication bridge between the main application and the pager.
 ///
@@ -36,8 +59,10 @@ use crate::search::SearchOpts;
 /// pager.push_str(format!("Hello {WHO}\n")).unwrap();
 #[derive(Clone)]
 pub struct Pager {
-    pub(crate) tx: Sender<Command>,
-    pub(crate) rx: Receiver<Command>,
+    pub(crate) tx: CommandSender,
+    pub(crate) rx: CommandReceiver,
+    /// State of the pager once it has been started
+    pub(crate) state: Arc<Mutex<Option<Arc<Mutex<PagerState>>>>>,
 }
 
 impl Pager {
@@ -49,8 +74,57 @@ impl Pager {
     /// ```
     #[must_use]
     pub fn new() -> Self {
-        let (tx, rx) = crossbeam_channel::unbounded();
-        Self { tx, rx }
+        let (tx, rx) = channel::unbounded();
+        Self {
+            tx,
+            rx,
+            state: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Initialize a new pager which holds at most `capacity` pending commands
+    ///
+    /// By default, the data and commands sent to the pager are queued without limit. If the
+    /// application produces data faster than the terminal can show it, this queue and hence the
+    /// memory usage keep growing. With a bounded pager, functions like [`Pager::push_str`] and
+    /// the [`write!`]/[`writeln!`] macros block once `capacity` commands are waiting until the
+    /// pager catches up. Use [`Pager::try_push_str`] to get an error instead of blocking, or
+    /// `Pager::push_str_async` with the `tokio` feature to wait without blocking the thread. User
+    /// input isn't affected by the limit and is handled right away.
+    ///
+    /// The pager must be running on another thread, like with
+    /// [`dynamic_paging`](crate::dynamic_paging), or task, like with `async_paging`, while the
+    /// queue is full. Otherwise, sending more than `capacity` commands before starting it blocks
+    /// forever.
+    ///
+    /// # Example
+    /// ```
+    /// let pager = minus::Pager::bounded(1024);
+    /// ```
+    #[must_use]
+    pub fn bounded(capacity: usize) -> Self {
+        let (tx, rx) = channel::bounded(capacity);
+        Self {
+            tx,
+            rx,
+            state: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Initialize a new pager with the given text
+    ///
+    /// This is same as calling [`Pager::set_text`] on a new pager.
+    ///
+    /// # Example
+    /// ```
+    /// let pager = minus::Pager::with_text("Hello\nWorld\n");
+    /// ```
+    #[must_use]
+    pub fn with_text(text: impl Into<String>) -> Self {
+        let pager = Self::new();
+        // This can't fail because the pager holds the receiving end
+        pager.tx.send(Command::SetData(text.into())).ok();
+        pager
     }
 
     /// Set the output text to this `t`
@@ -97,58 +171,169 @@ impl Pager {
         Ok(self.tx.send(Command::AppendData(s.into()))?)
     }
 
-    /// Set line number configuration for the pager
+    /// Appends text to the pager output from async code
     ///
-    /// See [`LineNumbers`] for available options
+    /// This is same as [`Pager::push_str`] except that when a pager created with
+    /// [`Pager::bounded`] is behind, the returned future waits for it to catch up instead of
+    /// blocking the thread. This is what should be used to feed a bounded pager running with
+    /// [`async_paging`](crate::async_paging), which may share the thread with the caller.
     ///
     /// # Errors
     /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
     /// could not be sent to the receiver
     ///
     /// # Example
+    /// ```rust,no_run
+    /// use minus::{async_paging, MinusError, Pager};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), MinusError> {
+    ///     let pager = Pager::bounded(64);
+    ///     let feed = async {
+    ///         for i in 0..=10_000_u32 {
+    ///             pager.push_str_async(format!("{i}\n")).await?;
+    ///         }
+    ///         Result::<_, MinusError>::Ok(())
+    ///     };
+    ///     let (res1, res2) = tokio::join!(async_paging(pager.clone()), feed);
+    ///     res1?;
+    ///     res2
+    /// }
     /// ```
-    /// use minus::{Pager, LineNumbers};
+    #[cfg(feature = "tokio")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+    pub async fn push_str_async(&self, s: impl Into<String>) -> Result<(), MinusError> {
+        Ok(self.tx.send_async(Command::AppendData(s.into())).await?)
+    }
+
+    /// Appends text to the pager output without blocking
     ///
-    /// let pager = Pager::new();
-    /// pager.set_line_numbers(LineNumbers::Enabled).expect("Failed to communicate with the pager");
+    /// This is same as [`Pager::push_str`] except that when a pager created with
+    /// [`Pager::bounded`] is behind, it returns an error instead of waiting. The text is not
+    /// appended in that case.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::WouldBlock)`](MinusError::WouldBlock) if the
+    /// pager can't take more data right now or a
+    /// [`Err(MinusError::Communication)`](MinusError::Communication) if the data could not be sent
+    /// to the receiver
+    ///
+    /// # Example
     /// ```
-    pub fn set_line_numbers(&self, l: LineNumbers) -> Result<(), MinusError> {
-        Ok(self.tx.send(Command::SetLineNumbers(l))?)
+    /// use minus::error::MinusError;
+    ///
+    /// let pager = minus::Pager::bounded(1);
+    /// pager.try_push_str("This is some text").expect("Failed to send data to the pager");
+    /// assert!(matches!(pager.try_push_str("More text"), Err(MinusError::WouldBlock)));
+    /// ```
+    pub fn try_push_str(&self, s: impl Into<String>) -> Result<(), MinusError> {
+        match self.tx.try_send(Command::AppendData(s.into())) {
+            Ok(()) => Ok(()),
+            Err(TrySendError::Full(_)) => Err(MinusError::WouldBlock),
+            Err(TrySendError::Disconnected(command)) => Err(SendError(command).into()),
+        }
     }
 
-    /// Set the text displayed at the bottom prompt
+    /// Appends raw bytes to the text
     ///
-    /// # Panics
-    /// This function panics if the given text contains newline characters.
-    /// This is because, the pager reserves only one line for showing the prompt
-    /// and a newline will cause it to span multiple lines, breaking the display
+    /// Unlike [`push_str`](Pager::push_str), the data doesn't need to be valid UTF-8. How it is
+    /// displayed depends on the [`BinaryMode`] set with [`set_binary_mode`](Pager::set_binary_mode).
+    /// When it is displayed as text, invalid UTF-8 sequences are replaced with `�`, hence each
+    /// push should contain complete characters.
     ///
     /// # Errors
     /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
     /// could not be sent to the receiver
     ///
-    /// Example
+    /// # Example
     /// ```
-    /// use minus::Pager;
+    /// let pager = minus::Pager::new();
+    /// pager.push_bytes(b"\x7fELF\x02\x01\x01\0").expect("Failed to send data to the pager");
+    /// ```
+    pub fn push_bytes(&self, bytes: impl Into<Vec<u8>>) -> Result<(), MinusError> {
+        Ok(self.tx.send(Command::AppendBytes(bytes.into()))?)
+    }
+
+    /// Appends each item of `lines` as a separate line to the text
+    ///
+    /// All the lines are sent to the pager at once, hence they are formatted in a single pass.
+    /// This is much faster than calling [`Pager::push_str`] for each line when you already have
+    /// a collection of lines.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// let pager = minus::Pager::new();
+    /// let lines = vec!["Hello", "World"];
+    /// pager.push_lines(&lines).expect("Failed to send data to the pager");
+    /// ```
+    pub fn push_lines<I>(&self, lines: I) -> Result<(), MinusError>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        let mut text = String::new();
+        for line in lines {
+            text.push_str(line.as_ref());
+            text.push('\n');
+        }
+        self.push_str(text)
+    }
+
+    /// Appends lines made of pre-styled segments to the text
+    ///
+    /// Each line is given as a sequence of `(style, text)` segments. minus generates the escape
+    /// sequences for these styles itself, resetting the style after every segment. This lets
+    /// applications that own their styling avoid building ANSI strings by hand and ensures that
+    /// wrapping and search highlighting work correctly with the styled text.
+    ///
+    /// These lines are marked as pre-styled: search matches inside them stay highlighted across
+    /// segment boundaries and highlight rules are not applied to them.
+    ///
+    /// Like [`Pager::push_lines`], all the lines are sent to the pager at once.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::{ContentStyle, Pager};
+    /// use crossterm::style::Stylize;
     ///
     /// let pager = Pager::new();
-    /// pager.set_prompt("my prompt").expect("Failed to send data to the pager");
+    /// pager.push_styled_lines([
+    ///     vec![(ContentStyle::new().bold(), "error"), (ContentStyle::new(), ": file not found")],
+    ///     vec![(ContentStyle::new().red(), "aborting")],
+    /// ]).expect("Failed to send data to the pager");
     /// ```
-    pub fn set_prompt(&self, text: impl Into<String>) -> Result<(), MinusError> {
-        let text: String = text.into();
-        assert!(!text.contains('\n'), "Prompt cannot contain newlines");
-        Ok(self.tx.send(Command::SetPrompt(text))?)
+    pub fn push_styled_lines<'a, I, L>(&self, lines: I) -> Result<(), MinusError>
+    where
+        I: IntoIterator<Item = L>,
+        L: IntoIterator<Item = (ContentStyle, &'a str)>,
+    {
+        let mut text = String::new();
+        for line in lines {
+            for (style, segment) in line {
+                // Styles are applied separately to each segment hence they can't leak into the
+                // next segment or line
+                write!(text, "{}", style.apply(segment)).ok();
+            }
+            text.push('\n');
+        }
+        Ok(self.tx.send(Command::AppendStyledLines(text))?)
     }
 
-    /// Send a message to be displayed the prompt area
+    /// Set the terminal on which the pager runs
     ///
-    /// The text message is temporary and will get cleared whenever the use
-    /// rdoes a action on the terminal like pressing a key or scrolling using the mouse.
+    /// By default minus runs on the standard output using the
+    /// [`CrosstermBackend`](crate::backend::CrosstermBackend). This allows plugging in other
+    /// terminal libraries or a backend for testing. See [`Backend`] for more info.
     ///
-    /// # Panics
-    /// This function panics if the given text contains newline characters.
-    /// This is because, the pager reserves only one line for showing the prompt
-    /// and a newline will cause it to span multiple lines, breaking the display
+    /// This has no effect once the pager has started.
     ///
     /// # Errors
     /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
@@ -156,133 +341,152 @@ impl Pager {
     ///
     /// # Example
     /// ```
-    /// use minus::Pager;
+    /// use minus::{backend::CrosstermBackend, Pager};
     ///
     /// let pager = Pager::new();
-    /// pager.send_message("An error occurred").expect("Failed to send data to the pager");
+    /// pager.set_backend(Box::new(CrosstermBackend::new()))
+    ///     .expect("Failed to communicate with the pager");
     /// ```
-    pub fn send_message(&self, text: impl Into<String>) -> Result<(), MinusError> {
-        let text: String = text.into();
-        assert!(!text.contains('\n'), "Message cannot contain newlines");
-        Ok(self.tx.send(Command::SendMessage(text))?)
+    pub fn set_backend(&self, backend: Box<dyn Backend>) -> Result<(), MinusError> {
+        Ok(self.tx.send(Command::SetBackend(Arc::from(backend)))?)
     }
 
-    /// Set the default exit strategy.
+    /// Set the [kitty keyboard protocol] enhancements requested from the terminal
     ///
-    /// This controls how the pager will behave when the user presses `q` or `Ctrl+C`.
-    /// See [`ExitStrategy`] for available options
+    /// Legacy terminal input can't tell apart many key combinations, for example `ctrl+shift+h`
+    /// is reported as `ctrl+h`. Terminals supporting the protocol report such keys correctly
+    /// once these flags are enabled, which makes bindings like `c-s-h` or `kpenter` usable.
+    /// Terminals which don't support it simply ignore the flags. Key releases reported due to
+    /// [`REPORT_EVENT_TYPES`](KeyboardEnhancementFlags::REPORT_EVENT_TYPES) are ignored and key
+    /// repeats are treated like normal key presses.
+    ///
+    /// By default no enhancements are enabled. This has no effect once the pager has started.
     ///
     /// # Errors
     /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
     /// could not be sent to the receiver
     ///
+    /// # Example
     /// ```
-    /// use minus::{Pager, ExitStrategy};
+    /// use minus::{input::crossterm_event::KeyboardEnhancementFlags, Pager};
     ///
     /// let pager = Pager::new();
-    /// pager.set_exit_strategy(ExitStrategy::ProcessQuit).expect("Failed to communicate with the pager");
+    /// pager.set_keyboard_enhancement(
+    ///     KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES
+    ///         | KeyboardEnhancementFlags::REPORT_ALL_KEYS_AS_ESCAPE_CODES,
+    /// ).expect("Failed to communicate with the pager");
     /// ```
-    pub fn set_exit_strategy(&self, es: ExitStrategy) -> Result<(), MinusError> {
-        Ok(self.tx.send(Command::SetExitStrategy(es))?)
+    ///
+    /// [kitty keyboard protocol]: https://sw.kovidgoyal.net/kitty/keyboard-protocol/
+    pub fn set_keyboard_enhancement(
+        &self,
+        flags: KeyboardEnhancementFlags,
+    ) -> Result<(), MinusError> {
+        Ok(self.tx.send(Command::SetKeyboardEnhancement(flags))?)
     }
 
-    /// Set whether to display pager if there's less data than
-    /// available screen height
+    /// Scroll with the mouse wheel through alternate scroll mode instead of mouse capture
     ///
-    /// When this is set to false, the pager will simply print all the lines
-    /// to the main screen and immediately quit if the number of lines to
-    /// display is less than the available columns in the terminal.
-    /// Setting this to true will cause a full pager to start and display the data
-    /// even if there is less number of lines to display than available rows.
+    /// Capturing the mouse keeps the user from selecting text with it. When this is enabled, the
+    /// mouse is never captured and the terminal is asked to send arrow keys for the mouse wheel
+    /// instead, which many terminals do in the alternate screen. Clicks on hyperlinks are not
+    /// reported in this mode.
     ///
-    /// This is only available in static output mode as the size of the data is
-    /// known beforehand.
-    /// In async output the pager can receive more data anytime
+    /// These arrow keys can't be told apart from the ones pressed on the keyboard, hence each of
+    /// them scrolls by a line like an arrow key does. The wheel then scrolls by as many lines as
+    /// the terminal sends arrow keys for each step of it, rather than by the amount set with
+    /// [`set_scroll_amounts`](Pager::set_scroll_amounts).
     ///
-    /// By default this is set to false
+    /// By default this is disabled. This has no effect once the pager has started.
     ///
     /// # Errors
     /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
     /// could not be sent to the receiver
     ///
+    /// # Example
     /// ```
     /// use minus::Pager;
     ///
     /// let pager = Pager::new();
-    /// pager.set_run_no_overflow(true).expect("Failed to communicate with the pager");
+    /// pager.set_alternate_scroll(true).expect("Failed to communicate with the pager");
     /// ```
-    #[cfg(feature = "static_output")]
-    #[cfg_attr(docsrs, doc(cfg(feature = "static_output")))]
-    pub fn set_run_no_overflow(&self, val: bool) -> Result<(), MinusError> {
-        Ok(self.tx.send(Command::SetRunNoOverflow(val))?)
+    pub fn set_alternate_scroll(&self, enabled: bool) -> Result<(), MinusError> {
+        Ok(self.tx.send(Command::SetAlternateScroll(enabled))?)
     }
 
-    /// Whether to allow scrolling horizontally
+    /// Use a fixed size for the terminal instead of the detected one
     ///
-    /// Setting this to `true` implicitly disables line wrapping
+    /// The pager lays out the text for `cols` columns and `rows` rows, including the row of the
+    /// prompt, and keeps this size when the terminal is resized. This is useful under test
+    /// harnesses, when the pager is drawn in a part of the terminal or with terminals that report
+    /// a wrong size. Call [`reset_term_size`](Pager::reset_term_size) to use the size of the
+    /// terminal again.
     ///
     /// # Errors
     /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
     /// could not be sent to the receiver
     ///
+    /// # Example
     /// ```
     /// use minus::Pager;
     ///
     /// let pager = Pager::new();
-    /// pager.horizontal_scroll(true).expect("Failed to communicate with the pager");
+    /// pager.set_term_size(80, 24).expect("Failed to communicate with the pager");
     /// ```
-    pub fn horizontal_scroll(&self, value: bool) -> Result<(), MinusError> {
-        Ok(self.tx.send(Command::LineWrapping(!value))?)
+    pub fn set_term_size(&self, cols: usize, rows: usize) -> Result<(), MinusError> {
+        Ok(self.tx.send(Command::SetTermSize(Some((cols, rows))))?)
     }
 
-    /// Set a custom input classifer type.
+    /// Go back to using the size of the terminal after [`set_term_size`](Pager::set_term_size)
     ///
-    /// An input classifier type is a type that implements the [InputClassifier]
-    /// trait. It only has one required function, [InputClassifier::classify_input]
-    /// which matches user input events and maps them to a [InputEvent]s.
-    /// When the pager encounters a user input, it calls the input classifier with
-    /// the event and [PagerState] as parameters.
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
     ///
-    /// Previously, whenever any application wanted to change the default key/mouse bindings
-    /// they neededd to create a new type, implement the [InputClassifier] type by copying and
-    /// pasting the default minus's implementation of it available in the [DefaultInputClassifier]
-    /// and change the parts they wanted to change. This is not only unergonomic but also
-    /// extreemely prone to bugs. Hence a newer and much simpler method was developed.
-    /// This method is still allowed to avoid breaking backwards compatiblity but will be dropped
-    /// in the next major release.
+    /// # Example
+    /// ```
+    /// use minus::Pager;
     ///
-    /// With the newer method, minus already provides a type called [HashedEventRegister]
-    /// which implementing the [InputClassifier] and is based on a
-    /// [HashMap] storing all the key/mouse bindings and its associated callback function.
-    /// This allows easy addition/updation/deletion of the default bindings with simple functions
-    /// like [HashedEventRegister::add_key_events] and [HashedEventRegister::add_mouse_events]
+    /// let pager = Pager::new();
+    /// pager.set_term_size(80, 24).expect("Failed to communicate with the pager");
+    /// pager.reset_term_size().expect("Failed to communicate with the pager");
+    /// ```
+    pub fn reset_term_size(&self) -> Result<(), MinusError> {
+        Ok(self.tx.send(Command::SetTermSize(None))?)
+    }
+
+    /// Draw the pager only in a rectangle of the terminal
     ///
-    /// See the [input] module for information about implementing it.
+    /// The pager is drawn in the `cols` columns and `rows` rows starting at column `x` and row `y`,
+    /// counting from 0, and leaves the rest of the terminal alone. This lets applications keep
+    /// their own header or sidebar around the text. The region keeps its size when the terminal
+    /// is resized, like with [`set_term_size`](Pager::set_term_size). Mouse events outside the
+    /// region are ignored.
+    ///
+    /// The terminal can't scroll only a part of the screen, hence the entire region is redrawn when
+    /// the view moves.
     ///
     /// # Errors
     /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
     /// could not be sent to the receiver
     ///
-    /// [HashedEventRegister::add_key_events]: input::HashedEventRegister::add_key_events
-    /// [HashedEventRegister::add_mouse_events]: input::HashedEventRegister::add_mouse_events
-    /// [HashMap]: std::collections::HashMap
-    /// [PagerState]: crate::state::PagerState
-    /// [InputEvent]: input::InputEvent
-    /// [InputClassifier]: input::InputClassifier
-    /// [InputClassifier::classify_input]: input::InputClassifier
-    /// [HashedEventRegister]: input::HashedEventRegister
-    /// [DefaultInputClassifier]: input::DefaultInputClassifier
-    pub fn set_input_classifier(
-        &self,
-        handler: Box<dyn input::InputClassifier + Send + Sync>,
-    ) -> Result<(), MinusError> {
-        Ok(self.tx.send(Command::SetInputClassifier(handler))?)
+    /// # Example
+    /// ```
+    /// use minus::Pager;
+    ///
+    /// let pager = Pager::new();
+    /// // Leave the two first rows and a sidebar of 20 columns to the application
+    /// pager.set_region(20, 2, 60, 22).expect("Failed to communicate with the pager");
+    /// ```
+    pub fn set_region(&self, x: u16, y: u16, cols: u16, rows: u16) -> Result<(), MinusError> {
+        Ok(self
+            .tx
+            .send(Command::SetRegion(Some(Region { x, y, cols, rows })))?)
     }
 
-    /// Adds a function that will be called when the user quits the pager
+    /// Draw the pager on the entire terminal again after [`set_region`](Pager::set_region)
     ///
-    /// Multiple functions can be stored for calling when the user quits. These functions
-    /// run sequentially in the order they were added
+    /// This also drops any size set with [`set_term_size`](Pager::set_term_size).
     ///
     /// # Errors
     /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
@@ -292,92 +496,1870 @@ impl Pager {
     /// ```
     /// use minus::Pager;
     ///
-    /// fn hello() {
-    ///     println!("Hello");
-    /// }
+    /// let pager = Pager::new();
+    /// pager.reset_region().expect("Failed to communicate with the pager");
+    /// ```
+    pub fn reset_region(&self) -> Result<(), MinusError> {
+        Ok(self.tx.send(Command::SetRegion(None))?)
+    }
+
+    /// Add a buffer named `name` holding `text`
+    ///
+    /// Buffers let a single pager session hold several texts, like `less file1 file2`. The user
+    /// can move between them by typing `:n` for the next buffer, `:p` for the previous one and
+    /// `:x` for the first one, and list them with `:buffers`. Each buffer remembers its own
+    /// scroll position and search.
+    ///
+    /// The text pushed through functions like [`Pager::push_str`] always goes to the first buffer.
+    /// If that buffer is still empty when the first buffer is added, the added buffer takes its
+    /// place. If a buffer named `name` already exists, its text is replaced.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::Pager;
     ///
     /// let pager = Pager::new();
-    /// pager.add_exit_callback(Box::new(hello)).expect("Failed to communicate with the pager");
+    /// pager.add_buffer("Cargo.toml", std::fs::read_to_string("Cargo.toml").unwrap())
+    ///     .expect("Failed to communicate with the pager");
+    /// pager.add_buffer("README.md", std::fs::read_to_string("README.md").unwrap())
+    ///     .expect("Failed to communicate with the pager");
     /// ```
-    pub fn add_exit_callback(
+    pub fn add_buffer(
         &self,
-        cb: Box<dyn FnMut() + Send + Sync + 'static>,
+        name: impl Into<String>,
+        text: impl Into<String>,
     ) -> Result<(), MinusError> {
-        Ok(self.tx.send(Command::AddExitCallback(cb))?)
+        Ok(self.tx.send(Command::AddBuffer(name.into(), text.into()))?)
     }
 
-    /// Override the condition for running incremental search
+    /// Display the buffer named `name`
     ///
-    /// See [Incremental Search](../search/index.html#incremental-search) to know more on how this
-    /// works
+    /// Nothing happens if there is no such buffer. See [`Pager::add_buffer`] for more info.
     ///
     /// # Errors
-    /// This function will returns a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
-    /// could not be send to the receiver end.
-    #[cfg(feature = "search")]
-    #[cfg_attr(docsrs, doc(cfg(feature = "search")))]
-    pub fn set_incremental_search_condition(
-        &self,
-        cb: Box<dyn Fn(&SearchOpts) -> bool + Send + Sync + 'static>,
-    ) -> crate::Result {
-        self.tx.send(Command::IncrementalSearchCondition(cb))?;
-        Ok(())
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::Pager;
+    ///
+    /// let pager = Pager::new();
+    /// pager.add_buffer("first", "Hello").expect("Failed to communicate with the pager");
+    /// pager.add_buffer("second", "World").expect("Failed to communicate with the pager");
+    /// pager.switch_to("second").expect("Failed to communicate with the pager");
+    /// ```
+    pub fn switch_to(&self, name: impl Into<String>) -> Result<(), MinusError> {
+        Ok(self.tx.send(Command::SwitchToBuffer(name.into()))?)
     }
 
-    /// Control whether to show the prompt
-    ///
-    /// Many applications don't want the prompt to be displayed at all. This function can be used to completely turn
-    /// off the prompt. Passing `false` to this will stops the prompt from displaying and instead a blank line will
-    /// be displayed.
+    /// Move the view so that the given line is at the top of the screen
     ///
-    /// Note that This merely stop the prompt from being shown. Your application can still update the
-    /// prompt and send messages to the user but it won't be shown until the prompt isn't re-enabled.
-    /// The prompt section will also be used when user opens the search prompt to type a search query.
+    /// Line numbers start from 1 and refer to the lines of the text, not the rows they wrap into.
+    /// If the line does not exist, the view moves to the bottom of the text. Users can do the same
+    /// by typing `:` followed by the line number.
     ///
     /// # Errors
     /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
-    /// could not be sent to the mus's receiving end
+    /// could not be sent to the receiver
     ///
     /// # Example
     /// ```
     /// use minus::Pager;
     ///
     /// let pager = Pager::new();
-    /// pager.show_prompt(false).unwrap();
+    /// pager.scroll_to_line(42).expect("Failed to communicate with the pager");
     /// ```
-    pub fn show_prompt(&self, show: bool) -> crate::Result {
-        self.tx.send(Command::ShowPrompt(show))?;
-        Ok(())
+    pub fn scroll_to_line(&self, line: usize) -> Result<(), MinusError> {
+        Ok(self.tx.send(Command::ScrollToLine(line))?)
     }
 
-    /// Configures follow output
+    /// Set the pattern to which the user can jump with `]` and `[`
     ///
-    /// When set to true, minus ensures that the user's screen always follows the end part of the
-    /// output. By default it is turned off.
+    /// Pressing `]` moves the view to the next line matching `pattern` and `[` to the previous
+    /// one, without disturbing the active search. For example, diff viewers can set it to
+    /// `"^@@"` to move between hunks. Passing `None` turns off these bindings, which is also the
+    /// default. The pattern uses the same syntax as search queries and is compiled once here.
     ///
-    /// This is similar to [InputEvent::FollowOutput](crate::input::InputEvent::FollowOutput) except that
-    /// this is used to control it from the application's side.
+    /// To bind other keys or several patterns, use [`InputEvent::JumpToPattern`] with a custom
+    /// input classifier.
+    ///
+    /// [`InputEvent::JumpToPattern`]: crate::input::InputEvent::JumpToPattern
     ///
     /// # Errors
-    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
-    /// could not be sent to the mus's receiving end
+    /// This function will return a [`Err(MinusError::SearchExpError)`](MinusError::SearchExpError)
+    /// if `pattern` is not a valid regex and a
+    /// [`Err(MinusError::Communication)`](MinusError::Communication) if the data could not be sent
+    /// to the receiver
     ///
     /// # Example
     /// ```
     /// use minus::Pager;
     ///
     /// let pager = Pager::new();
-    /// pager.follow_output(true).unwrap();
+    /// pager.set_jump_pattern(Some("^diff --git")).expect("Failed to communicate with the pager");
     /// ```
-    pub fn follow_output(&self, follow_output: bool) -> crate::Result {
-        self.tx.send(Command::FollowOutput(follow_output))?;
-        Ok(())
+    #[cfg(feature = "__search")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "search")))]
+    pub fn set_jump_pattern(&self, pattern: Option<&str>) -> Result<(), MinusError> {
+        let regex = pattern.map(crate::search::Regex::new).transpose()?;
+        Ok(self.tx.send(Command::SetJumpPattern(regex))?)
     }
-}
 
-impl Default for Pager {
-    fn default() -> Self {
-        Self::new()
+    /// Set where the view is placed when jumping to a search match
+    ///
+    /// By default the match is put at the top of the screen, below the
+    /// [scroll margin](Pager::set_scroll_margin). With [`MatchPlacement::Minimal`], the view
+    /// doesn't move when the next match is already visible, which avoids disorienting jumps
+    /// while stepping through matches close to each other.
+    ///
+    /// See [`MatchPlacement`] for available options
+    ///
+    /// [`MatchPlacement`]: crate::search::MatchPlacement
+    /// [`MatchPlacement::Minimal`]: crate::search::MatchPlacement::Minimal
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::{search::MatchPlacement, Pager};
+    ///
+    /// let pager = Pager::new();
+    /// pager.set_match_placement(MatchPlacement::Minimal).expect("Failed to communicate with the pager");
+    /// ```
+    #[cfg(feature = "__search")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "search")))]
+    pub fn set_match_placement(
+        &self,
+        placement: crate::search::MatchPlacement,
+    ) -> Result<(), MinusError> {
+        Ok(self.tx.send(Command::SetMatchPlacement(placement))?)
+    }
+
+    /// Restrict searches to the given range of lines
+    ///
+    /// Lines are indexed from 0. Matches outside of `range` are neither highlighted nor visited
+    /// by `n` and `p`, which is useful for features like finding text within a selection. Passing
+    /// `None` searches the whole text again, which is also the default.
+    ///
+    /// Users can also restrict a single search to the lines visible on the screen by starting the
+    /// query with `@`, while a query starting with `\@` searches for a literal `@`. Both
+    /// restrictions apply if both are set.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::Pager;
+    ///
+    /// let pager = Pager::new();
+    /// pager.set_search_range(Some(100..200)).expect("Failed to communicate with the pager");
+    /// ```
+    #[cfg(feature = "__search")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "search")))]
+    pub fn set_search_range(&self, range: Option<Range<usize>>) -> Result<(), MinusError> {
+        Ok(self.tx.send(Command::SetSearchRange(range))?)
+    }
+
+    /// Leave the active search
+    ///
+    /// This removes the highlights and forgets the matches and the query, so that `n` and `p`
+    /// do nothing until the next search. Nothing happens if no search is active.
+    ///
+    /// This is similar to [InputEvent::ClearSearch](crate::input::InputEvent::ClearSearch) except
+    /// that this is used to control it from the application's side.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::Pager;
+    ///
+    /// let pager = Pager::new();
+    /// pager.clear_search().expect("Failed to communicate with the pager");
+    /// ```
+    #[cfg(feature = "__search")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "search")))]
+    pub fn clear_search(&self) -> Result<(), MinusError> {
+        Ok(self.tx.send(Command::ClearSearch)?)
+    }
+
+    /// Set the stream to which the pager writes its output
+    ///
+    /// Writing to [`OutputTarget::Stderr`] lets programs whose standard output is piped to
+    /// another program still page text meant for the user. The user input is always read from
+    /// the terminal. This is a shorthand for setting a
+    /// [`CrosstermBackend`](crate::backend::CrosstermBackend) with the given target through
+    /// [`Pager::set_backend`], hence it replaces any backend that was set before.
+    ///
+    /// This has no effect once the pager has started.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::{OutputTarget, Pager};
+    ///
+    /// let pager = Pager::new();
+    /// pager.set_output(OutputTarget::Stderr).expect("Failed to communicate with the pager");
+    /// ```
+    pub fn set_output(&self, target: OutputTarget) -> Result<(), MinusError> {
+        Ok(self.tx.send(Command::SetBackend(Arc::new(
+            CrosstermBackend::with_target(target),
+        )))?)
+    }
+
+    /// Let the user record and replay keystrokes like in Vim
+    ///
+    /// When enabled, pressing `q` followed by a letter or digit starts recording the keys typed
+    /// into that register and pressing `q` again stops it. The keys are then replayed by pressing
+    /// `@` followed by the register, or `@@` for the register replayed last. A count like `3@a`
+    /// replays them that many times. This allows automating repetitive navigation, like jumping to
+    /// the next header and centering it.
+    ///
+    /// Since `q` is taken for recording, the pager is quit with `Q` instead while this is
+    /// enabled. The text typed at the search prompt is not recorded, hence a macro that starts a
+    /// search asks for the query when it is replayed. This is disabled by default.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::Pager;
+    ///
+    /// let pager = Pager::new();
+    /// pager.set_macros(true).expect("Failed to communicate with the pager");
+    /// ```
+    pub fn set_macros(&self, enabled: bool) -> Result<(), MinusError> {
+        Ok(self.tx.send(Command::SetMacros(enabled))?)
+    }
+
+    /// Render the pager without any colors or text attributes
+    ///
+    /// When enabled, every escape sequence that sets colors or attributes like bold is removed
+    /// from the output. This applies to the styling of the text as well as of the prompt, line
+    /// numbers and search matches. The output then only depends on the text and the size of the
+    /// terminal, which allows comparing it against a known good output in tests, for example
+    /// with a [`StreamBackend`](crate::backend::StreamBackend) or [`page_all_to`](crate::page_all_to).
+    ///
+    /// Unlike [`AnsiMode::Strip`], the text is kept as it is and the styling reappears when this
+    /// is disabled again.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::Pager;
+    ///
+    /// let pager = Pager::new();
+    /// pager.set_plain_render(true).expect("Failed to communicate with the pager");
+    /// ```
+    pub fn set_plain_render(&self, plain: bool) -> Result<(), MinusError> {
+        Ok(self.tx.send(Command::SetPlainRender(plain))?)
+    }
+
+    /// Set the title of the terminal window while paging
+    ///
+    /// With [`WindowTitle::Prompt`], the title follows the prompt as it is changed with
+    /// [`set_prompt`](Pager::set_prompt). The title is set with the OSC 0 escape sequence and the
+    /// previous one is saved on the title stack of the terminal, from where it is restored when the
+    /// pager quits or when this is set back to [`WindowTitle::Unchanged`]. Terminals without a title
+    /// stack keep the title set by minus.
+    ///
+    /// This can also be set through [`PagerConfig::window_title`](crate::config::PagerConfig::window_title).
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::{Pager, WindowTitle};
+    ///
+    /// let pager = Pager::new();
+    /// pager.set_window_title(WindowTitle::Prompt).expect("Failed to communicate with the pager");
+    /// ```
+    pub fn set_window_title(&self, title: WindowTitle) -> Result<(), MinusError> {
+        Ok(self.tx.send(Command::SetWindowTitle(title))?)
+    }
+
+    /// Set what to do with ANSI escape sequences in the text pushed into the pager
+    ///
+    /// With [`AnsiMode::Strip`], all escape sequences are removed from the text as it is pushed,
+    /// which is useful when the output of tools that force colors should be displayed in
+    /// monochrome. This also makes wrapping more accurate. Text that was already pushed is not
+    /// affected, hence this should be called before any data is pushed.
+    ///
+    /// Regardless of the mode, the overstrike sequences used by man pages for bold and underlined
+    /// text, like `X\bX` and `_\bX`, are turned into ANSI escape sequences as the text is pushed.
+    /// Hence these are removed as well with [`AnsiMode::Strip`]. Once the pushed text contains
+    /// backspaces, a sequence at its end is held back until more text arrives since the rest of
+    /// it may come with the next push.
+    ///
+    /// See [`AnsiMode`] for available options
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::{AnsiMode, Pager};
+    ///
+    /// let pager = Pager::new();
+    /// pager.set_ansi(AnsiMode::Strip).expect("Failed to communicate with the pager");
+    /// ```
+    pub fn set_ansi(&self, mode: AnsiMode) -> Result<(), MinusError> {
+        Ok(self.tx.send(Command::SetAnsiMode(mode))?)
+    }
+
+    /// Set whether a lone carriage return overwrites the line it is in
+    ///
+    /// Tools that display progress bars redraw them by writing `\r` followed by the new state of
+    /// the bar. When this is enabled, the text after the last `\r` of a line replaces everything
+    /// before it, including the text of an unterminated line that was pushed earlier, hence the
+    /// progress bar is displayed as a single line that gets updated. Otherwise lone carriage
+    /// returns are kept in the text as they are. This is disabled by default.
+    ///
+    /// `\r\n` line endings are always turned into `\n` regardless of this setting. Text that was
+    /// already pushed is not affected.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::Pager;
+    ///
+    /// let pager = Pager::new();
+    /// pager.set_carriage_return_overwrite(true).expect("Failed to communicate with the pager");
+    /// ```
+    pub fn set_carriage_return_overwrite(&self, enabled: bool) -> Result<(), MinusError> {
+        Ok(self.tx.send(Command::SetCarriageReturnOverwrite(enabled))?)
+    }
+
+    /// Set how binary data is displayed
+    ///
+    /// With [`BinaryMode::Auto`], the pager switches to a hex dump with an offset column and the
+    /// ASCII representation of each byte as soon as data containing NUL bytes or invalid UTF-8 is
+    /// pushed, like `less` does for binary files. The text pushed earlier is included in the dump.
+    ///
+    /// See [`BinaryMode`] for available options
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::{BinaryMode, Pager};
+    ///
+    /// let pager = Pager::new();
+    /// pager.set_binary_mode(BinaryMode::Auto).expect("Failed to communicate with the pager");
+    /// ```
+    pub fn set_binary_mode(&self, mode: BinaryMode) -> Result<(), MinusError> {
+        Ok(self.tx.send(Command::SetBinaryMode(mode))?)
+    }
+
+    /// Set how the user is told about actions that could not be carried out
+    ///
+    /// Feedback is given when scrolling past the top or the bottom of the text, when a search
+    /// finds no matches and when a key that isn't bound to anything is pressed. By default no
+    /// feedback is given.
+    ///
+    /// See [`FeedbackMode`] for available options
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::{FeedbackMode, Pager};
+    ///
+    /// let pager = Pager::new();
+    /// pager.set_feedback(FeedbackMode::Bell).expect("Failed to communicate with the pager");
+    /// ```
+    pub fn set_feedback(&self, mode: FeedbackMode) -> Result<(), MinusError> {
+        Ok(self.tx.send(Command::SetFeedback(mode))?)
+    }
+
+    /// Render the prompt with the given function
+    ///
+    /// `renderer` takes over the prompt completely. It is called every time the screen is
+    /// drawn with a [`PromptContext`] describing the scroll position, search state, message and
+    /// follow mode and the returned text is displayed as the prompt. This allows dynamic status
+    /// lines which can't be built with [`set_prompt`](Pager::set_prompt). The text may contain
+    /// ANSI escape sequences and should fit into [`PromptContext::cols`] columns. The input line
+    /// of the `:` commands is still displayed by minus.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::Pager;
+    ///
+    /// let pager = Pager::new();
+    /// pager.set_prompt_renderer(Box::new(|ctx| {
+    ///     let status = format!("{}/{} {}%", ctx.top_line, ctx.total_lines, ctx.percent);
+    ///     let text = ctx.message.unwrap_or(ctx.prompt);
+    ///     let padding = ctx.cols.saturating_sub(text.len() + status.len());
+    ///     format!("\x1b[7m{text}{}{status}\x1b[0m", " ".repeat(padding))
+    /// })).expect("Failed to communicate with the pager");
+    /// ```
+    pub fn set_prompt_renderer(
+        &self,
+        renderer: Box<dyn Fn(&PromptContext<'_>) -> String + Send + Sync + 'static>,
+    ) -> Result<(), MinusError> {
+        Ok(self.tx.send(Command::SetPromptRenderer(renderer))?)
+    }
+
+    /// Set the text displayed on the rows after the end of the text
+    ///
+    /// When the text is shorter than the terminal, the rows after its end are blank by default.
+    /// Setting this to `"~"` marks them like vi does. The filler may contain ANSI escape
+    /// sequences and should fit into a single row.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::Pager;
+    ///
+    /// let pager = Pager::new();
+    /// pager.set_eob_filler("~").expect("Failed to communicate with the pager");
+    /// ```
+    pub fn set_eob_filler(&self, filler: impl Into<String>) -> Result<(), MinusError> {
+        Ok(self.tx.send(Command::SetEobFiller(filler.into()))?)
+    }
+
+    /// Set the marker displayed in the prompt when the end of the text is visible
+    ///
+    /// The marker is displayed on the right side of the prompt, like `(END)` in `less`. Passing
+    /// `None` turns it off, which is also the default.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::Pager;
+    ///
+    /// let pager = Pager::new();
+    /// pager.set_eof_marker(Some("(END)")).expect("Failed to communicate with the pager");
+    /// ```
+    pub fn set_eof_marker(&self, marker: Option<&str>) -> Result<(), MinusError> {
+        Ok(self
+            .tx
+            .send(Command::SetEofMarker(marker.map(ToOwned::to_owned)))?)
+    }
+
+    /// Set line number configuration for the pager
+    ///
+    /// See [`LineNumbers`] for available options
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::{Pager, LineNumbers};
+    ///
+    /// let pager = Pager::new();
+    /// pager.set_line_numbers(LineNumbers::Enabled).expect("Failed to communicate with the pager");
+    /// ```
+    pub fn set_line_numbers(&self, l: LineNumbers) -> Result<(), MinusError> {
+        Ok(self.tx.send(Command::SetLineNumbers(l))?)
+    }
+
+    /// Set the text displayed at the bottom prompt
+    ///
+    /// # Panics
+    /// This function panics if the given text contains newline characters.
+    /// This is because, the pager reserves only one line for showing the prompt
+    /// and a newline will cause it to span multiple lines, breaking the display
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// Example
+    /// ```
+    /// use minus::Pager;
+    ///
+    /// let pager = Pager::new();
+    /// pager.set_prompt("my prompt").expect("Failed to send data to the pager");
+    /// ```
+    pub fn set_prompt(&self, text: impl Into<String>) -> Result<(), MinusError> {
+        let text: String = text.into();
+        assert!(!text.contains('\n'), "Prompt cannot contain newlines");
+        Ok(self.tx.send(Command::SetPrompt(text))?)
+    }
+
+    /// Send a message to be displayed the prompt area
+    ///
+    /// The text message is temporary and will get cleared whenever the use
+    /// rdoes a action on the terminal like pressing a key or scrolling using the mouse.
+    ///
+    /// If a message is already being displayed, the new one is queued and displayed once the
+    /// user dismisses the current one with `Enter`. The prompt tells how many messages are
+    /// waiting. Only the last few messages are kept waiting, older ones are dropped. See [`pending_messages`](Pager::pending_messages) and
+    /// [`clear_messages`](Pager::clear_messages).
+    ///
+    /// # Panics
+    /// This function panics if the given text contains newline characters.
+    /// This is because, the pager reserves only one line for showing the prompt
+    /// and a newline will cause it to span multiple lines, breaking the display
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::Pager;
+    ///
+    /// let pager = Pager::new();
+    /// pager.send_message("An error occurred").expect("Failed to send data to the pager");
+    /// ```
+    pub fn send_message(&self, text: impl Into<String>) -> Result<(), MinusError> {
+        let text: String = text.into();
+        assert!(!text.contains('\n'), "Message cannot contain newlines");
+        Ok(self.tx.send(Command::SendMessage(text))?)
+    }
+
+    /// Get the messages waiting to be displayed after the current one
+    ///
+    /// The messages are returned in the order in which they will be displayed. This returns an
+    /// empty list if the pager hasn't been started yet.
+    ///
+    /// # Example
+    /// ```
+    /// use minus::Pager;
+    ///
+    /// let pager = Pager::new();
+    /// assert!(pager.pending_messages().is_empty());
+    /// ```
+    #[must_use]
+    pub fn pending_messages(&self) -> Vec<String> {
+        let Some(state) = self.state.lock().clone() else {
+            return Vec::new();
+        };
+        let messages = state.lock().pending_messages.iter().cloned().collect();
+        messages
+    }
+
+    /// Remove the message being displayed along with all the messages waiting after it
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::Pager;
+    ///
+    /// let pager = Pager::new();
+    /// pager.send_message("An error occurred").expect("Failed to send data to the pager");
+    /// pager.clear_messages().expect("Failed to communicate with the pager");
+    /// ```
+    pub fn clear_messages(&self) -> Result<(), MinusError> {
+        Ok(self.tx.send(Command::ClearMessages)?)
+    }
+
+    /// Set the default exit strategy.
+    ///
+    /// This controls how the pager will behave when the user presses `q` or `Ctrl+C`.
+    /// See [`ExitStrategy`] for available options
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// ```
+    /// use minus::{Pager, ExitStrategy};
+    ///
+    /// let pager = Pager::new();
+    /// pager.set_exit_strategy(ExitStrategy::ProcessQuit).expect("Failed to communicate with the pager");
+    /// ```
+    pub fn set_exit_strategy(&self, es: ExitStrategy) -> Result<(), MinusError> {
+        Ok(self.tx.send(Command::SetExitStrategy(es))?)
+    }
+
+    /// Set what to do when the user presses `Ctrl+C`
+    ///
+    /// By default `Ctrl+C` quits the pager just like `q`. Applications that page the output of a
+    /// long-running child process can instead forward the interrupt to the child with
+    /// [`InterruptBehavior::Callback`] or ignore it altogether.
+    ///
+    /// See [`InterruptBehavior`] for available options
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::{InterruptBehavior, Pager};
+    ///
+    /// let pager = Pager::new();
+    /// pager.set_interrupt_behavior(InterruptBehavior::Ignore).expect("Failed to communicate with the pager");
+    /// ```
+    pub fn set_interrupt_behavior(&self, ib: InterruptBehavior) -> Result<(), MinusError> {
+        Ok(self.tx.send(Command::SetInterruptBehavior(ib))?)
+    }
+
+    /// Set whether to display pager if there's less data than
+    /// available screen height
+    ///
+    /// **WARNING: This has been deprecated in favour of [`Pager::force_pager`]. This will likely
+    /// be removed in the next major release.**
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// ```
+    /// # #![allow(deprecated)]
+    /// use minus::Pager;
+    ///
+    /// let pager = Pager::new();
+    /// pager.set_run_no_overflow(true).expect("Failed to communicate with the pager");
+    /// ```
+    #[cfg(feature = "static_output")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "static_output")))]
+    #[deprecated(note = "Use `Pager::force_pager` instead")]
+    pub fn set_run_no_overflow(&self, val: bool) -> Result<(), MinusError> {
+        self.force_pager(val)
+    }
+
+    /// Set whether to always start the pager in static output mode
+    ///
+    /// When this is set to false, the pager will simply print all the lines
+    /// to the main screen and immediately quit if the text fits within the pager threshold,
+    /// see [`Pager::set_pager_threshold`].
+    /// Setting this to true will cause a full pager to start and display the data
+    /// even if there is less number of lines to display than available rows.
+    ///
+    /// This is only available in static output mode as the size of the data is
+    /// known beforehand.
+    /// In async output the pager can receive more data anytime
+    ///
+    /// By default this is set to false
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::Pager;
+    ///
+    /// let pager = Pager::new();
+    /// pager.force_pager(true).expect("Failed to communicate with the pager");
+    /// ```
+    #[cfg(feature = "static_output")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "static_output")))]
+    pub fn force_pager(&self, force: bool) -> Result<(), MinusError> {
+        Ok(self.tx.send(Command::SetForcePager(force))?)
+    }
+
+    /// Set the number of rows that the text must exceed for the pager to start
+    ///
+    /// In static output mode, text that occupies at most these many rows is simply printed to the
+    /// main screen without starting the pager. If this is `None`, which is the default, the
+    /// number of rows in the terminal is used.
+    ///
+    /// This has no effect if the pager is forced to start with [`Pager::force_pager`].
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::Pager;
+    ///
+    /// let pager = Pager::new();
+    /// // Page the text only if it doesn't fit in 10 rows
+    /// pager.set_pager_threshold(Some(10)).expect("Failed to communicate with the pager");
+    /// ```
+    #[cfg(feature = "static_output")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "static_output")))]
+    pub fn set_pager_threshold(&self, rows: Option<usize>) -> Result<(), MinusError> {
+        Ok(self.tx.send(Command::SetPagerThreshold(rows))?)
+    }
+
+    /// Whether to allow scrolling horizontally
+    ///
+    /// Setting this to `true` implicitly disables line wrapping
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// ```
+    /// use minus::Pager;
+    ///
+    /// let pager = Pager::new();
+    /// pager.horizontal_scroll(true).expect("Failed to communicate with the pager");
+    /// ```
+    pub fn horizontal_scroll(&self, value: bool) -> Result<(), MinusError> {
+        Ok(self.tx.send(Command::LineWrapping(!value))?)
+    }
+
+    /// Set a custom input classifer type.
+    ///
+    /// An input classifier type is a type that implements the [InputClassifier]
+    /// trait. It only has one required function, [InputClassifier::classify_input]
+    /// which matches user input events and maps them to a [InputEvent]s.
+    /// When the pager encounters a user input, it calls the input classifier with
+    /// the event and [PagerState] as parameters.
+    ///
+    /// Previously, whenever any application wanted to change the default key/mouse bindings
+    /// they neededd to create a new type, implement the [InputClassifier] type by copying and
+    /// pasting the default minus's implementation of it available in the [DefaultInputClassifier]
+    /// and change the parts they wanted to change. This is not only unergonomic but also
+    /// extreemely prone to bugs. Hence a newer and much simpler method was developed.
+    /// This method is still allowed to avoid breaking backwards compatiblity but will be dropped
+    /// in the next major release.
+    ///
+    /// With the newer method, minus already provides a type called [HashedEventRegister]
+    /// which implementing the [InputClassifier] and is based on a
+    /// [HashMap] storing all the key/mouse bindings and its associated callback function.
+    /// This allows easy addition/updation/deletion of the default bindings with simple functions
+    /// like [HashedEventRegister::add_key_events] and [HashedEventRegister::add_mouse_events]
+    ///
+    /// See the [input] module for information about implementing it.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// [HashedEventRegister::add_key_events]: input::HashedEventRegister::add_key_events
+    /// [HashedEventRegister::add_mouse_events]: input::HashedEventRegister::add_mouse_events
+    /// [HashMap]: std::collections::HashMap
+    /// [PagerState]: crate::state::PagerState
+    /// [InputEvent]: input::InputEvent
+    /// [InputClassifier]: input::InputClassifier
+    /// [InputClassifier::classify_input]: input::InputClassifier
+    /// [HashedEventRegister]: input::HashedEventRegister
+    /// [DefaultInputClassifier]: input::DefaultInputClassifier
+    pub fn set_input_classifier(
+        &self,
+        handler: Box<dyn input::InputClassifier + Send + Sync>,
+    ) -> Result<(), MinusError> {
+        Ok(self.tx.send(Command::SetInputClassifier(handler))?)
+    }
+
+    /// Adds a function that will be called when the user quits the pager
+    ///
+    /// Multiple functions can be stored for calling when the user quits. These functions
+    /// run sequentially in the order they were added
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::Pager;
+    ///
+    /// fn hello() {
+    ///     println!("Hello");
+    /// }
+    ///
+    /// let pager = Pager::new();
+    /// pager.add_exit_callback(Box::new(hello)).expect("Failed to communicate with the pager");
+    /// ```
+    pub fn add_exit_callback(
+        &self,
+        cb: Box<dyn FnMut() + Send + Sync + 'static>,
+    ) -> Result<(), MinusError> {
+        Ok(self.tx.send(Command::AddExitCallback(cb))?)
+    }
+
+    /// Set the number of rows moved by the default scrolling bindings
+    ///
+    /// This controls the step of mouse wheel scrolls, half page scrolls and the overlap kept
+    /// when scrolling by a page. See [`ScrollAmounts`] for available options.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::{Pager, ScrollAmounts};
+    ///
+    /// let pager = Pager::new();
+    /// pager.set_scroll_amounts(ScrollAmounts {
+    ///     wheel: 3,
+    ///     horizontal_wheel: 8,
+    ///     half_page: Some(10),
+    ///     page_overlap: 2,
+    ///     smooth_scroll: None,
+    /// }).expect("Failed to communicate with the pager");
+    /// ```
+    pub fn set_scroll_amounts(&self, amounts: ScrollAmounts) -> Result<(), MinusError> {
+        Ok(self.tx.send(Command::SetScrollAmounts(amounts))?)
+    }
+
+    /// Set the number of rows that stay visible when scrolling by a page
+    ///
+    /// By default `Space`, `PageDown` and `PageUp` scroll by an entire screen. Setting an overlap
+    /// of `k` keeps the last `k` rows of the previous page visible after scrolling, which makes it
+    /// easier to keep track of where you are reading.
+    ///
+    /// This is a shorthand for only changing [`ScrollAmounts::page_overlap`].
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::Pager;
+    ///
+    /// let pager = Pager::new();
+    /// pager.set_page_overlap(2).expect("Failed to communicate with the pager");
+    /// ```
+    pub fn set_page_overlap(&self, overlap: usize) -> Result<(), MinusError> {
+        Ok(self.tx.send(Command::SetPageOverlap(overlap))?)
+    }
+
+    /// Set the number of rows of context kept above the target when jumping
+    ///
+    /// By default, search jumps and goto-line place the target line at the very top row of the
+    /// screen. Setting this to `n` keeps at least `n` rows above the target so that the text
+    /// surrounding it stays visible.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::Pager;
+    ///
+    /// let pager = Pager::new();
+    /// pager.set_scroll_margin(3).expect("Failed to communicate with the pager");
+    /// ```
+    pub fn set_scroll_margin(&self, margin: usize) -> Result<(), MinusError> {
+        Ok(self.tx.send(Command::SetScrollMargin(margin))?)
+    }
+
+    /// Place the target at the center of the screen when jumping
+    ///
+    /// When set to true, search jumps and goto-line place the target line in the middle of the
+    /// screen. This takes precedence over the margin set by [`Pager::set_scroll_margin`].
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::Pager;
+    ///
+    /// let pager = Pager::new();
+    /// pager.set_center_on_jump(true).expect("Failed to communicate with the pager");
+    /// ```
+    pub fn set_center_on_jump(&self, center: bool) -> Result<(), MinusError> {
+        Ok(self.tx.send(Command::SetCenterOnJump(center))?)
+    }
+
+    /// Set a function that fetches fresh content for the pager
+    ///
+    /// Whenever the user presses `R` or the reload interval set by [`Pager::set_reload_interval`]
+    /// elapses, minus calls `cb` and replaces the entire text with the returned string. minus
+    /// tries to keep the line that was at the top of the screen before the reload at the top
+    /// after it.
+    ///
+    /// This is useful for `watch`-like applications and log viewers.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::Pager;
+    ///
+    /// let pager = Pager::new();
+    /// pager.set_reload_callback(Box::new(|| {
+    ///     std::fs::read_to_string("Cargo.toml").unwrap_or_default()
+    /// })).expect("Failed to communicate with the pager");
+    /// ```
+    pub fn set_reload_callback(
+        &self,
+        cb: Box<dyn FnMut() -> String + Send + Sync + 'static>,
+    ) -> Result<(), MinusError> {
+        Ok(self.tx.send(Command::SetReloadCallback(cb))?)
+    }
+
+    /// Reload the content periodically
+    ///
+    /// When set to `Some(interval)`, minus automatically reloads the content using the callback
+    /// set by [`Pager::set_reload_callback`] after every `interval`. Passing `None` turns off
+    /// periodic reloading, which is also the default.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::Pager;
+    /// use std::time::Duration;
+    ///
+    /// let pager = Pager::new();
+    /// pager.set_reload_interval(Some(Duration::from_secs(2))).expect("Failed to communicate with the pager");
+    /// ```
+    pub fn set_reload_interval(&self, interval: Option<Duration>) -> Result<(), MinusError> {
+        Ok(self.tx.send(Command::SetReloadInterval(interval))?)
+    }
+
+    /// Set how long minus waits for an event before checking on its own state
+    ///
+    /// minus checks whether it has been closed, for example through a
+    /// [`PagerHandle`](crate::PagerHandle), every `timeout` while no input arrives. This is also
+    /// how long the pager must go without receiving any data, commands or user input before the
+    /// callback set by [`Pager::set_idle_callback`] is called. Shorter timeouts make the pager more responsive at
+    /// the cost of waking up more often. The default is 100 milliseconds.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::Pager;
+    /// use std::time::Duration;
+    ///
+    /// let pager = Pager::new();
+    /// pager.set_poll_timeout(Duration::from_millis(500)).expect("Failed to communicate with the pager");
+    /// ```
+    pub fn set_poll_timeout(&self, timeout: Duration) -> Result<(), MinusError> {
+        Ok(self.tx.send(Command::SetPollTimeout(timeout))?)
+    }
+
+    /// Set a function that is called whenever the pager is idle
+    ///
+    /// minus calls `cb` when no data, commands or user input has been received for the
+    /// [poll timeout](Pager::set_poll_timeout), and keeps calling it after every further timeout
+    /// as long as nothing arrives. This can be used to periodically refresh things like a clock
+    /// or a progress indicator without spawning an extra thread.
+    ///
+    /// The callback runs on the pager's own thread. It can send data to the pager through a clone
+    /// of the [`Pager`] but it must not block waiting on the pager, for example by sending to a
+    /// [bounded](Pager::bounded) pager whose buffer is full.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::Pager;
+    /// use std::time::Duration;
+    ///
+    /// let pager = Pager::new();
+    /// let pager2 = pager.clone();
+    /// pager.set_poll_timeout(Duration::from_secs(1)).expect("Failed to communicate with the pager");
+    /// pager.set_idle_callback(Box::new(move || {
+    ///     let _ = pager2.set_prompt(format!("{:?}", std::time::SystemTime::now()));
+    /// })).expect("Failed to communicate with the pager");
+    /// ```
+    pub fn set_idle_callback(
+        &self,
+        cb: Box<dyn FnMut() + Send + Sync + 'static>,
+    ) -> Result<(), MinusError> {
+        Ok(self.tx.send(Command::SetIdleCallback(cb))?)
+    }
+
+    /// Apply all the settings of `config` at once
+    ///
+    /// Only the settings that are set in `config` are changed. This can be called again while the
+    /// pager is running, for example when the user edits their configuration file, and the text
+    /// is only formatted and drawn again if a changed setting affects how it looks.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::{Pager, PagerConfig};
+    ///
+    /// let pager = Pager::new();
+    /// let config = PagerConfig {
+    ///     scroll_margin: Some(3),
+    ///     ..PagerConfig::default()
+    /// };
+    /// pager.apply_config(&config).expect("Failed to communicate with the pager");
+    /// ```
+    pub fn apply_config(&self, config: &PagerConfig) -> Result<(), MinusError> {
+        Ok(self
+            .tx
+            .send(Command::ApplyConfig(Box::new(config.clone())))?)
+    }
+
+    ///
+    /// This is meant for cases that the other functions don't cover. For example, an application
+    /// that changes something that the text is formatted from, like the state used by its
+    /// [line transformer](Pager::set_line_transformer), can send
+    /// [`PagerCommand::FormatRedrawDisplay`] to have the text formatted again. See
+    /// [`PagerCommand`] for the available commands.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::{Pager, PagerCommand};
+    ///
+    /// let pager = Pager::new();
+    /// pager.send_command(PagerCommand::FormatRedrawDisplay).expect("Failed to communicate with the pager");
+    /// ```
+    pub fn send_command(&self, command: PagerCommand) -> Result<(), MinusError> {
+        Ok(self.tx.send(command.into())?)
+    }
+
+    /// Limit how often the screen is updated while data is being appended
+    ///
+    /// When data arrives within `interval` of the last update, it is only stored and the screen
+    /// is updated once `interval` has passed. This keeps the CPU usage and the output sent to the
+    /// terminal low when the application writes lots of data quickly. No data is lost and user
+    /// input is still handled right away. Passing [`Duration::ZERO`], which is also the default,
+    /// draws every append as it comes.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::Pager;
+    /// use std::time::Duration;
+    ///
+    /// let pager = Pager::new();
+    /// // Update the screen at most 30 times per second
+    /// pager.set_max_refresh_rate(Duration::from_millis(33)).expect("Failed to communicate with the pager");
+    /// ```
+    pub fn set_max_refresh_rate(&self, interval: Duration) -> Result<(), MinusError> {
+        Ok(self.tx.send(Command::SetMaxRefreshRate(interval))?)
+    }
+
+    /// Set a flag which makes the pager quit once it becomes `true`
+    ///
+    /// This allows the application to close the pager from places where calling [`Pager::quit`]
+    /// isn't possible, like from a signal handler on receiving `SIGTERM`. The pager checks the
+    /// flag regularly and quits cleanly, restoring the terminal, even if the user never presses
+    /// a key. The exit reason is reported as [`ExitReason::AppQuit`](crate::ExitReason::AppQuit).
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::Pager;
+    /// use std::sync::{atomic::AtomicBool, Arc};
+    ///
+    /// let pager = Pager::new();
+    /// let cancel = Arc::new(AtomicBool::new(false));
+    /// pager.set_cancellation_flag(cancel.clone()).expect("Failed to communicate with the pager");
+    /// // Later, for example on receiving SIGTERM
+    /// cancel.store(true, std::sync::atomic::Ordering::SeqCst);
+    /// ```
+    pub fn set_cancellation_flag(&self, flag: Arc<AtomicBool>) -> Result<(), MinusError> {
+        Ok(self.tx.send(Command::SetCancellationFlag(flag))?)
+    }
+
+    /// Set a function that provides more data when the user approaches the end of the text
+    ///
+    /// Instead of pushing all the data ahead of time, the application can let minus pull it
+    /// lazily. Whenever the user scrolls close to the end of the available text, `provider` is
+    /// called with the range of line indices that minus wants next and should return the text
+    /// for those lines. Returning fewer lines than asked for is fine.
+    ///
+    /// Returning `None` signals that there is no more data, after which `provider` is never
+    /// called again. This makes it possible to page database cursors or paginated APIs.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::Pager;
+    ///
+    /// let pager = Pager::new();
+    /// pager.set_data_provider(Box::new(|range| {
+    ///     if range.start >= 1000 {
+    ///         return None;
+    ///     }
+    ///     Some(range.map(|i| format!("Line {i}\n")).collect())
+    /// })).expect("Failed to communicate with the pager");
+    /// ```
+    pub fn set_data_provider(
+        &self,
+        provider: Box<dyn FnMut(Range<usize>) -> Option<String> + Send + Sync + 'static>,
+    ) -> Result<(), MinusError> {
+        Ok(self.tx.send(Command::SetDataProvider(provider))?)
+    }
+
+    /// Set a function that transforms each line before it is displayed
+    ///
+    /// `transformer` is called with each line and its index only when the line is about to be
+    /// displayed. The returned text, which may contain ANSI escape sequences, is displayed in
+    /// place of the original line. This allows applications to add syntax highlighting without
+    /// styling the whole document ahead of time.
+    ///
+    /// Wrapping and searching are done on the original lines, so the transformer should only
+    /// style a line without changing its visible text. A transformed line never takes more rows
+    /// than the original line.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::Pager;
+    ///
+    /// let pager = Pager::new();
+    /// pager.set_line_transformer(Box::new(|line, _idx| {
+    ///     if line.starts_with('#') {
+    ///         format!("\x1b[1m{line}\x1b[0m")
+    ///     } else {
+    ///         line.to_string()
+    ///     }
+    /// })).expect("Failed to communicate with the pager");
+    /// ```
+    pub fn set_line_transformer(&self, transformer: LineTransformer) -> Result<(), MinusError> {
+        Ok(self.tx.send(Command::SetLineTransformer(transformer))?)
+    }
+
+    /// Set a function that makes the line numbers
+    ///
+    /// `format` is called with the line number, starting from 1, and the number of digits in the
+    /// largest line number. The returned text is displayed beside the first row of the line
+    /// instead of the default, which is the line number followed by a `.`. This allows
+    /// applications to show zero-padded numbers, hexadecimal offsets or numbers with thousands
+    /// separators.
+    ///
+    /// The text is aligned to the right of the line number column, which is 6 columns wider than
+    /// the number of digits. Text longer than that pushes the line to the right.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::Pager;
+    ///
+    /// let pager = Pager::new();
+    /// // Zero-pad the numbers to the width of the largest one
+    /// pager.set_line_number_format(Box::new(|number, width| {
+    ///     format!("{number:0width$}")
+    /// })).expect("Failed to communicate with the pager");
+    /// ```
+    pub fn set_line_number_format(&self, format: LineNumberFormat) -> Result<(), MinusError> {
+        Ok(self.tx.send(Command::SetLineNumberFormat(format))?)
+    }
+
+    /// Make space for line numbers with at least `digits` digits
+    ///
+    /// By default, the space before each line fits exactly the largest line number. When the
+    /// number of lines grows by a digit, like from 99 to 100, all the text has to be formatted
+    /// and drawn again, which shows up as a flash on large texts. Applications that stream lots
+    /// of output can reserve space for the number of lines they expect to avoid this.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::Pager;
+    ///
+    /// let pager = Pager::new();
+    /// // Up to a million lines without the line numbers growing
+    /// pager.set_min_line_number_digits(7).expect("Failed to communicate with the pager");
+    /// ```
+    pub fn set_min_line_number_digits(&self, digits: usize) -> Result<(), MinusError> {
+        Ok(self.tx.send(Command::SetMinLineNumberDigits(digits))?)
+    }
+
+    /// Set the styles used for the parts of the screen that minus adds to the text
+    ///
+    /// This includes the style of the line numbers and the character displayed after them. See
+    /// [`Theme`] for more info.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::{ContentStyle, Pager, Theme};
+    /// use crossterm::style::Stylize;
+    ///
+    /// let pager = Pager::new();
+    /// pager.set_theme(Theme {
+    ///     line_number: ContentStyle::new().dim(),
+    ///     ..Theme::default()
+    /// }).expect("Failed to communicate with the pager");
+    /// ```
+    pub fn set_theme(&self, theme: Theme) -> Result<(), MinusError> {
+        Ok(self.tx.send(Command::SetTheme(theme))?)
+    }
+
+    /// Set the number of columns after which lines are truncated
+    ///
+    /// Very long lines make wrapping, search highlighting and horizontal scrolling slow. Hence
+    /// lines wider than `limit` columns are cut while being formatted and a marker telling how
+    /// much was cut off is displayed at their end. Escape sequences don't count towards the limit.
+    /// The original text is kept as it is. The default is 100,000 columns which doesn't affect
+    /// normal content.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::Pager;
+    ///
+    /// let pager = Pager::new();
+    /// pager.set_line_length_limit(10_000).expect("Failed to communicate with the pager");
+    /// ```
+    pub fn set_line_length_limit(&self, limit: usize) -> Result<(), MinusError> {
+        Ok(self.tx.send(Command::SetLineLengthLimit(limit))?)
+    }
+
+    /// Remember how the first `lines` lines were wrapped
+    ///
+    /// Toggling line numbers or resizing the terminal back to a previous size then reuses the
+    /// rows into which the lines were wrapped before rather than wrapping them again. This costs
+    /// memory for each cached line as well as hashing each line to tell whether it has changed.
+    /// Lines past the first `lines` lines are always wrapped again. By default, the first 10,000
+    /// lines are cached. Setting it to 0 turns the cache off.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::Pager;
+    ///
+    /// let pager = Pager::new();
+    /// pager.set_wrap_cache(10_000).expect("Failed to communicate with the pager");
+    /// ```
+    pub fn set_wrap_cache(&self, lines: usize) -> Result<(), MinusError> {
+        Ok(self.tx.send(Command::SetWrapCache(lines))?)
+    }
+
+    /// Override the condition for running incremental search
+    ///
+    /// See [Incremental Search](../search/index.html#incremental-search) to know more on how this
+    /// works
+    ///
+    /// # Errors
+    /// This function will returns a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be send to the receiver end.
+    #[cfg(feature = "__search")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "search")))]
+    pub fn set_incremental_search_condition(
+        &self,
+        cb: Box<dyn Fn(&SearchOpts) -> bool + Send + Sync + 'static>,
+    ) -> crate::Result {
+        self.tx.send(Command::IncrementalSearchCondition(cb))?;
+        Ok(())
+    }
+
+    /// Set how long to wait after the last keystroke at the search prompt before running
+    /// incremental search
+    ///
+    /// Each run formats the complete text, so running it on every keystroke makes typing a long
+    /// query on a big document sluggish. Instead minus waits until no key has been pressed for
+    /// `interval` and a run is abandoned if a key is pressed while it is in progress. The default
+    /// is 100 milliseconds. Passing [`Duration::ZERO`] runs incremental search on every keystroke.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::Pager;
+    /// use std::time::Duration;
+    ///
+    /// let pager = Pager::new();
+    /// pager.set_incremental_search_debounce(Duration::from_millis(250))
+    ///     .expect("Failed to communicate with the pager");
+    /// ```
+    #[cfg(feature = "__search")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "search")))]
+    pub fn set_incremental_search_debounce(&self, interval: Duration) -> Result<(), MinusError> {
+        Ok(self
+            .tx
+            .send(Command::SetIncrementalSearchDebounce(interval))?)
+    }
+
+    /// Colorize every match of `pattern` in the text with `style`
+    ///
+    /// This can be used to make things like timestamps, IP addresses or error levels stand out.
+    /// Unlike searches, these highlights are permanent and apply to text appended later as well.
+    /// The pattern uses the same syntax as search queries and is matched against each line after
+    /// removing its ANSI escape sequences. The style is layered over the line's own colors and
+    /// search matches are highlighted over it just like over those colors, see
+    /// [`Pager::set_highlight_mode`]. Where the matches of several rules overlap, the rule added
+    /// last wins.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::SearchExpError)`](MinusError::SearchExpError)
+    /// if `pattern` is not a valid regex and a
+    /// [`Err(MinusError::Communication)`](MinusError::Communication) if the data could not be sent
+    /// to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::{ContentStyle, Pager};
+    /// use crossterm::style::Stylize;
+    ///
+    /// let pager = Pager::new();
+    /// pager.add_highlight_rule(r"\bERROR\b", ContentStyle::new().red().bold())
+    ///     .expect("Failed to communicate with the pager");
+    /// ```
+    #[cfg(feature = "__regex")]
+    #[cfg_attr(docsrs, doc(cfg(any(feature = "search", feature = "highlight"))))]
+    pub fn add_highlight_rule(&self, pattern: &str, style: ContentStyle) -> Result<(), MinusError> {
+        let regex = crate::pattern::Regex::new(pattern)?;
+        Ok(self.tx.send(Command::AddHighlightRule(regex, style))?)
+    }
+
+    /// Set how the escape sequences within search matches are treated when highlighting them
+    ///
+    /// Search matches are highlighted by inverting their colors. When a match contains escape
+    /// sequences like colors, they can either be kept at their place or moved to the end of the
+    /// match. The default, [`HighlightMode::Auto`], keeps them at their place for lines that
+    /// contain escape sequences. See [`HighlightMode`] for all the options.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::{search::HighlightMode, Pager};
+    ///
+    /// let pager = Pager::new();
+    /// pager.set_highlight_mode(HighlightMode::Fast).expect("Failed to communicate with the pager");
+    /// ```
+    #[cfg(feature = "__search")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "search")))]
+    pub fn set_highlight_mode(&self, mode: HighlightMode) -> Result<(), MinusError> {
+        Ok(self.tx.send(Command::SetHighlightMode(mode))?)
+    }
+
+    /// Allow search matches to span multiple lines
+    ///
+    /// By default each line is searched on its own. When this is enabled, the query is matched
+    /// against the complete text instead so queries containing `\n` or using the `s` flag like
+    /// `(?s)begin.*end` can match across line boundaries. Each row touched by such a match gets
+    /// highlighted and jumping to it moves to the row where it starts.
+    ///
+    /// As the complete text needs to be searched again whenever new data is appended, this is
+    /// slower than the default on large or frequently updated texts.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::Pager;
+    ///
+    /// let pager = Pager::new();
+    /// pager.set_multiline_search(true).expect("Failed to communicate with the pager");
+    /// ```
+    #[cfg(feature = "__search")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "search")))]
+    pub fn set_multiline_search(&self, multiline: bool) -> Result<(), MinusError> {
+        Ok(self.tx.send(Command::SetMultilineSearch(multiline))?)
+    }
+
+    /// Control whether to show the prompt
+    ///
+    /// Many applications don't want the prompt to be displayed at all. This function can be used to completely turn
+    /// off the prompt. Passing `false` to this will stops the prompt from displaying and the text will take up all
+    /// the rows of the terminal, including the last one. Users can also toggle the prompt with `Ctrl+p`.
+    ///
+    /// Note that This merely stop the prompt from being shown. Your application can still update the
+    /// prompt and send messages to the user but it won't be shown until the prompt isn't re-enabled.
+    /// The prompt section will also be used when user opens the search prompt to type a search query.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the mus's receiving end
+    ///
+    /// # Example
+    /// ```
+    /// use minus::Pager;
+    ///
+    /// let pager = Pager::new();
+    /// pager.show_prompt(false).unwrap();
+    /// ```
+    pub fn show_prompt(&self, show: bool) -> crate::Result {
+        self.tx.send(Command::ShowPrompt(show))?;
+        Ok(())
+    }
+
+    /// Configures follow output
+    ///
+    /// When set to true, minus ensures that the user's screen always follows the end part of the
+    /// output. By default it is turned off.
+    ///
+    /// If the user scrolls up while follow mode is on, it is paused so that they can read the
+    /// earlier output. It is resumed once they scroll back to the bottom, for example by pressing
+    /// `G` or `End`. A `[following]` indicator is shown at the prompt while it is active.
+    ///
+    /// This is similar to [InputEvent::FollowOutput](crate::input::InputEvent::FollowOutput) except that
+    /// this is used to control it from the application's side.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the mus's receiving end
+    ///
+    /// # Example
+    /// ```
+    /// use minus::Pager;
+    ///
+    /// let pager = Pager::new();
+    /// pager.follow_output(true).unwrap();
+    /// ```
+    pub fn follow_output(&self, follow_output: bool) -> crate::Result {
+        self.tx.send(Command::FollowOutput(follow_output))?;
+        Ok(())
+    }
+
+    /// Subscribe to events happening inside the pager
+    ///
+    /// The returned [`Receiver`] gets a [`PagerEvent`] whenever, for example, the user scrolls
+    /// the text, resizes the terminal or toggles follow mode. This can be used to react to user
+    /// actions, like loading more data lazily, without redefining the key bindings.
+    ///
+    /// Events are only emitted while the pager is running. Dropping the receiver unsubscribes it.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::{Pager, PagerEvent};
+    ///
+    /// let pager = Pager::new();
+    /// let events = pager.subscribe_events().expect("Failed to communicate with the pager");
+    ///
+    /// std::thread::spawn(move || {
+    ///     for ev in events {
+    ///         if let PagerEvent::Scroll { new, .. } = ev {
+    ///             // Load more data
+    ///         }
+    ///     }
+    /// });
+    /// ```
+    pub fn subscribe_events(&self) -> Result<Receiver<PagerEvent>, MinusError> {
+        let (tx, rx) = crossbeam_channel::unbounded();
+        self.tx.send(Command::AddEventSubscriber(tx))?;
+        Ok(rx)
+    }
+
+    /// Call a function whenever the view is scrolled
+    ///
+    /// `cb` is called with the line number, starting from 1, of the line at the top of the screen
+    /// and the percentage of text that has been read. It runs on a separate thread hence it does
+    /// not slow down drawing the screen and can be used for things like prefetching data or
+    /// saving the reading progress.
+    ///
+    /// This is a shorthand for handling [`PagerEvent::Scroll`] received from
+    /// [`Pager::subscribe_events`].
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::Pager;
+    ///
+    /// let pager = Pager::new();
+    /// pager.on_scroll(Box::new(|top_line, percent| {
+    ///     eprintln!("At line {top_line}, read {percent}%");
+    /// })).expect("Failed to communicate with the pager");
+    /// ```
+    pub fn on_scroll(
+        &self,
+        mut cb: Box<dyn FnMut(usize, usize) + Send + 'static>,
+    ) -> Result<(), MinusError> {
+        let events = self.subscribe_events()?;
+        std::thread::spawn(move || {
+            for ev in events {
+                if let PagerEvent::Scroll {
+                    top_line, percent, ..
+                } = ev
+                {
+                    cb(top_line, percent);
+                }
+            }
+        });
+        Ok(())
+    }
+
+    /// Call a function whenever the user clicks on a hyperlink
+    ///
+    /// Hyperlinks are text wrapped in OSC 8 escape sequences. `cb` is called with the URI of the
+    /// link under the mouse and can be used to open it in a browser or navigate within the
+    /// application. It runs on a separate thread hence it does not block the pager.
+    ///
+    /// This is a shorthand for handling [`PagerEvent::LinkClicked`] received from
+    /// [`Pager::subscribe_events`].
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::Pager;
+    ///
+    /// let pager = Pager::new();
+    /// pager.push_str("\x1b]8;;https://example.com\x1b\\Example\x1b]8;;\x1b\\\n")
+    ///     .expect("Failed to communicate with the pager");
+    /// pager.on_link_click(Box::new(|uri| {
+    ///     eprintln!("Clicked on {uri}");
+    /// })).expect("Failed to communicate with the pager");
+    /// ```
+    pub fn on_link_click(
+        &self,
+        mut cb: Box<dyn FnMut(String) + Send + 'static>,
+    ) -> Result<(), MinusError> {
+        let events = self.subscribe_events()?;
+        std::thread::spawn(move || {
+            for ev in events {
+                if let PagerEvent::LinkClicked(uri) = ev {
+                    cb(uri);
+                }
+            }
+        });
+        Ok(())
+    }
+
+    /// Quit the pager once the user reaches the end of the text
+    ///
+    /// When turned on, moving to the end of the text quits the pager and [`PagerHandle::wait`]
+    /// reports [`ExitReason::Eof`](crate::ExitReason::Eof). This is not done while follow mode is
+    /// active or while a [data provider](Pager::set_data_provider) can still provide more data.
+    /// By default it is turned off.
+    ///
+    /// [`PagerHandle::wait`]: crate::PagerHandle::wait
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::Pager;
+    ///
+    /// let pager = Pager::new();
+    /// pager.set_quit_on_eof(true).expect("Failed to communicate with the pager");
+    /// ```
+    pub fn set_quit_on_eof(&self, quit: bool) -> Result<(), MinusError> {
+        Ok(self.tx.send(Command::SetQuitOnEof(quit))?)
+    }
+
+    /// Get a snapshot of the current state of the pager
+    ///
+    /// This returns `None` if the pager hasn't been started yet. Once it has quit, the snapshot
+    /// describes the state in which the user left it. This can be used to persist where the user
+    /// was, for example to reopen a log at the same spot.
+    ///
+    /// # Example
+    /// ```
+    /// use minus::Pager;
+    ///
+    /// let pager = Pager::new();
+    /// assert!(pager.state_snapshot().is_none());
+    /// ```
+    #[must_use]
+    pub fn state_snapshot(&self) -> Option<StateSnapshot> {
+        let state = self.state.lock().clone()?;
+        let snapshot = state.lock().snapshot();
+        Some(snapshot)
+    }
+
+    /// Get the features of the terminal that the pager detected when it started
+    ///
+    /// Features that are missing are worked around as described in [`Capabilities`]. It returns
+    /// `None` if the pager hasn't been started yet.
+    ///
+    /// # Example
+    /// ```
+    /// use minus::Pager;
+    ///
+    /// let pager = Pager::new();
+    /// assert!(pager.capabilities().is_none());
+    /// ```
+    #[must_use]
+    pub fn capabilities(&self) -> Option<Capabilities> {
+        let state = self.state.lock().clone()?;
+        let capabilities = state.lock().capabilities;
+        Some(capabilities)
+    }
+
+    /// Get the size of the terminal that the pager is using as `(cols, rows)`
+    ///
+    /// This is the size set with [`set_term_size`](Pager::set_term_size) if any, otherwise the
+    /// size of the terminal. It returns `None` if the pager hasn't been started yet.
+    ///
+    /// # Example
+    /// ```
+    /// use minus::Pager;
+    ///
+    /// let pager = Pager::new();
+    /// assert!(pager.term_size().is_none());
+    /// ```
+    #[must_use]
+    pub fn term_size(&self) -> Option<(usize, usize)> {
+        let state = self.state.lock().clone()?;
+        let state = state.lock();
+        Some((state.cols, state.rows))
+    }
+
+    /// Get the counters describing the work done by the pager
+    ///
+    /// This tells how much data was received, how many lines were formatted and how long that
+    /// took as well as how many times the screen was drawn. It returns `None` if the pager hasn't
+    /// been started yet.
+    ///
+    /// # Example
+    /// ```
+    /// use minus::Pager;
+    ///
+    /// let pager = Pager::new();
+    /// assert!(pager.metrics().is_none());
+    /// ```
+    #[cfg(feature = "metrics")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "metrics")))]
+    #[must_use]
+    pub fn metrics(&self) -> Option<Metrics> {
+        let state = self.state.lock().clone()?;
+        let metrics = state.lock().metrics();
+        Some(metrics)
+    }
+
+    /// Get the query and the matches of the active search
+    ///
+    /// This works for searches started by the user as well as by the application. It returns
+    /// `None` if the pager hasn't been started yet or if no search is active. This can be used to
+    /// show the number of matches in the application's own UI.
+    ///
+    /// # Example
+    /// ```
+    /// use minus::Pager;
+    ///
+    /// let pager = Pager::new();
+    /// assert!(pager.search_summary().is_none());
+    /// ```
+    #[cfg(feature = "__search")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "search")))]
+    #[must_use]
+    pub fn search_summary(&self) -> Option<SearchSummary> {
+        let state = self.state.lock().clone()?;
+        let summary = state.lock().search_summary();
+        summary
+    }
+
+    /// Get the first and the last line currently displayed on the screen
+    ///
+    /// Line numbers start from 1 and both the lines are included in the region. This returns
+    /// `None` if the pager hasn't been started yet or if nothing is displayed.
+    ///
+    /// # Example
+    /// ```
+    /// use minus::Pager;
+    ///
+    /// let pager = Pager::new();
+    /// assert!(pager.visible_region().is_none());
+    /// ```
+    #[must_use]
+    pub fn visible_region(&self) -> Option<(usize, usize)> {
+        let state = self.state.lock().clone()?;
+        let lines = state.lock().visible_lines()?;
+        Some((lines.start + 1, lines.end))
+    }
+
+    /// Get the text of the lines currently displayed on the screen
+    ///
+    /// This is the original text of the lines in [`Pager::visible_region`], without any
+    /// formatting done by minus, separated by newlines. This can be used to implement actions like
+    /// copying the current screen. This returns `None` if the pager hasn't been started yet or if
+    /// nothing is displayed.
+    ///
+    /// # Example
+    /// ```
+    /// use minus::Pager;
+    ///
+    /// let pager = Pager::new();
+    /// assert!(pager.visible_text().is_none());
+    /// ```
+    #[must_use]
+    pub fn visible_text(&self) -> Option<String> {
+        let state = self.state.lock().clone()?;
+        let ps = state.lock();
+        let lines = ps.visible_lines()?;
+        Some(ps.screen.lines(lines).collect::<Vec<_>>().join("\n"))
+    }
+
+    /// Save the current position and view configuration
+    ///
+    /// This returns `None` if the pager hasn't been started yet. See [`Session`] for what gets
+    /// saved.
+    ///
+    /// # Example
+    /// ```
+    /// use minus::Pager;
+    ///
+    /// let pager = Pager::new();
+    /// assert!(pager.save_session().is_none());
+    /// ```
+    #[must_use]
+    pub fn save_session(&self) -> Option<Session> {
+        let state = self.state.lock().clone()?;
+        let session = state.lock().session();
+        Some(session)
+    }
+
+    /// Restore a [`Session`] saved with [`Pager::save_session`]
+    ///
+    /// This moves the view to the saved top line and restores the search query and toggles like
+    /// line numbers and line wrapping. The text should be set before calling this, otherwise the
+    /// saved top line can't be found and the view is moved to the bottom of the text.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::{state::Session, LineNumbers, Pager};
+    ///
+    /// let pager = Pager::new();
+    /// pager.set_text("Hello\nWorld\n").unwrap();
+    /// pager.restore_session(Session {
+    ///     top_line: 2,
+    ///     left_mark: 0,
+    ///     search_query: None,
+    ///     line_numbers: LineNumbers::Enabled,
+    ///     line_wrapping: true,
+    ///     follow_output: false,
+    /// }).expect("Failed to communicate with the pager");
+    /// ```
+    pub fn restore_session(&self, session: Session) -> Result<(), MinusError> {
+        Ok(self.tx.send(Command::RestoreSession(session))?)
+    }
+
+    /// Close the running pager
+    ///
+    /// This goes through the same teardown as the user pressing `q`, which means that the
+    /// [exit strategy](Pager::set_exit_strategy) is respected and the exit callbacks are called.
+    /// This is useful for closing the pager when, for example, an upstream process fails.
+    ///
+    /// # Errors
+    /// This function will return a [`Err(MinusError::Communication)`](MinusError::Communication) if the data
+    /// could not be sent to the receiver
+    ///
+    /// # Example
+    /// ```
+    /// use minus::Pager;
+    ///
+    /// let pager = Pager::new();
+    /// pager.quit().expect("Failed to communicate with the pager");
+    /// ```
+    pub fn quit(&self) -> Result<(), MinusError> {
+        Ok(self.tx.send(Command::Quit)?)
+    }
+
+    /// Start the pager in a separate thread and return a handle to control it
+    ///
+    /// This is similar to running [`dynamic_paging`](crate::dynamic_paging) in a new thread except
+    /// that the returned [`PagerHandle`](crate::PagerHandle) can be used to programmatically
+    /// control the pager once it has started. Data can still be fed through this [`Pager`].
+    ///
+    /// # Panics
+    /// The pager thread will panic if another instance of minus is already running.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use minus::Pager;
+    ///
+    /// let pager = Pager::new();
+    /// let handle = pager.start();
+    /// pager.push_str("Hello").unwrap();
+    /// let exit = handle.wait();
+    /// ```
+    #[cfg(feature = "dynamic_output")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "dynamic_output")))]
+    #[must_use]
+    pub fn start(&self) -> crate::PagerHandle {
+        crate::PagerHandle::new(self.clone())
+    }
+}
+
+impl Default for Pager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Appends each string as a separate line. See [`Pager::push_lines`]
+///
+/// Errors while sending the data to the pager are ignored. Use [`Pager::push_lines`] if you want
+/// to handle them.
+impl Extend<String> for Pager {
+    fn extend<T: IntoIterator<Item = String>>(&mut self, iter: T) {
+        self.push_lines(iter).ok();
     }
 }
 
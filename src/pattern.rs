@@ -0,0 +1,71 @@
+//! Regular expressions shared by searching and highlight rules
+//!
+//! Both the search queries and the patterns of
+//! [`Pager::add_highlight_rule`](crate::Pager::add_highlight_rule) are compiled with the engine
+//! selected through the crate features.
+use crate::error::RegexError;
+use std::ops::Range;
+
+// The engine used to compile expressions. `fancy-regex` takes precedence over `regex` which
+// in turn takes precedence over `regex-lite` when more than one of them is enabled.
+#[cfg(feature = "fancy-regex")]
+use fancy_regex as engine;
+#[cfg(all(feature = "regex", not(feature = "fancy-regex")))]
+use regex as engine;
+#[cfg(not(any(feature = "regex", feature = "fancy-regex")))]
+use regex_lite as engine;
+
+/// Error returned by the selected engine when an expression fails to compile
+pub type EngineError = engine::Error;
+
+/// A compiled search query
+///
+/// This wraps the regular expression engine selected through the crate features. By default
+/// this is [`regex`](https://docs.rs/regex). The `search_lite` feature swaps it for the smaller
+/// [`regex-lite`](https://docs.rs/regex-lite) while `search_fancy` uses
+/// [`fancy-regex`](https://docs.rs/fancy-regex) which adds support for look-around and
+/// backreferences. Without any of the search features, the `highlight` feature uses `regex-lite`.
+#[derive(Clone, Debug)]
+pub struct Regex(engine::Regex);
+
+impl Regex {
+    /// Compile a new search query
+    ///
+    /// # Errors
+    /// Returns a [`RegexError`] if `pattern` is not a valid expression for the selected engine.
+    pub fn new(pattern: &str) -> Result<Self, RegexError> {
+        Ok(Self(engine::Regex::new(pattern)?))
+    }
+
+    /// Returns the original pattern from which this query was compiled
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        self.0.as_str()
+    }
+
+    /// Returns true if the query matches anywhere in `text`
+    ///
+    /// With `fancy-regex`, a match that exceeds the backtracking limit is treated as no match.
+    #[must_use]
+    pub fn is_match(&self, text: &str) -> bool {
+        #[cfg(feature = "fancy-regex")]
+        return self.0.is_match(text).unwrap_or(false);
+        #[cfg(not(feature = "fancy-regex"))]
+        return self.0.is_match(text);
+    }
+
+    /// Returns the byte ranges of all non-overlapping matches in `text`
+    pub(crate) fn find_ranges<'a>(
+        &'a self,
+        text: &'a str,
+    ) -> impl Iterator<Item = Range<usize>> + 'a {
+        #[cfg(feature = "fancy-regex")]
+        return self
+            .0
+            .find_iter(text)
+            .filter_map(Result::ok)
+            .map(|m| m.range());
+        #[cfg(not(feature = "fancy-regex"))]
+        return self.0.find_iter(text).map(|m| m.range());
+    }
+}
@@ -1,17 +1,27 @@
 //! Provides functions for getting analysis of the text data inside minus.
 //!
 //! This module is still a work is progress and is subject to change.
+#[cfg(feature = "__regex")]
+use crate::pattern::Regex;
+#[cfg(feature = "__search")]
+use crate::search::{HighlightMode, SearchIndex};
 use crate::{
     minus_core::{self, utils::LinesRowMap},
-    LineNumbers,
+    LineNumbers, Theme,
 };
-#[cfg(feature = "search")]
-use regex::Regex;
 
-use std::borrow::Cow;
+use std::{
+    borrow::Cow,
+    collections::{hash_map::DefaultHasher, BTreeSet},
+    fmt::Write as _,
+    hash::{Hash, Hasher},
+    ops::Range,
+};
 
-#[cfg(feature = "search")]
-use {crate::search, std::collections::BTreeSet};
+#[cfg(feature = "__search")]
+use crate::search;
+#[cfg(feature = "__regex")]
+use crossterm::style::{ContentStyle, SetStyle};
 
 // |||||||||||||||||||||||||||||||||||||||||||||||||||||||
 //  TYPES TO BETTER DESCRIBE THE PURPOSE OF STRINGS
@@ -22,6 +32,17 @@ pub type Line<'a> = &'a str;
 pub type TextBlock<'a> = &'a str;
 pub type OwnedTextBlock = String;
 
+/// Function applied to each line when it is displayed
+///
+/// It receives the line and its index and returns the text that should be displayed in its place.
+pub type LineTransformer = Box<dyn Fn(Line, usize) -> String + Send + Sync + 'static>;
+
+/// Function that makes the line number shown beside each line
+///
+/// It receives the line number, starting from 1, and the number of digits in the largest line
+/// number and returns the text to be displayed. The text is aligned to the right of the gutter.
+pub type LineNumberFormat = Box<dyn Fn(usize, usize) -> String + Send + Sync + 'static>;
+
 // ||||||||||||||||||||||||||||||||||||||||||||||
 //  SCREEN TYPE AND ITS REKATED FUNCTIONS
 // ||||||||||||||||||||||||||||||||||||||||||||||
@@ -46,6 +67,36 @@ pub struct Screen {
     ///
     /// Its negation gives the state of whether horizontal scrolling is allowed.
     pub(crate) line_wrapping: bool,
+    /// Function to transform each line when it gets displayed
+    pub(crate) line_transformer: Option<LineTransformer>,
+    /// Function to make the line numbers
+    pub(crate) line_number_format: Option<LineNumberFormat>,
+    /// Styles used for the parts of the screen added by minus
+    pub(crate) theme: Theme,
+    /// Number of digits for which space is kept for the line numbers even if fewer are needed
+    pub(crate) min_line_number_digits: usize,
+    /// Number of columns after which lines are truncated
+    pub(crate) line_length_limit: usize,
+    /// Whether a lone `\r` in the incoming text overwrites the line it is in
+    pub(crate) carriage_return_overwrite: bool,
+    /// Row at which each line starts
+    pub(crate) lines_to_row_map: LinesRowMap,
+    /// Rows that were displayed the last time the screen was drawn
+    pub(crate) visible_rows: Range<usize>,
+    /// Data displayed as a hex dump
+    ///
+    /// When this is `Some`, [`Screen::orig_text`] holds the hex dump of these bytes.
+    pub(crate) raw_bytes: Option<Vec<u8>>,
+    /// Indices of the lines added with [`Pager::push_styled_lines`](crate::Pager::push_styled_lines)
+    ///
+    /// Their escape sequences were generated by minus, hence search matches in them are
+    /// highlighted segment by segment and highlight rules leave them alone.
+    pub(crate) styled_lines: BTreeSet<usize>,
+    /// Patterns colorized by the application
+    #[cfg(feature = "__regex")]
+    pub(crate) highlight_rules: Vec<HighlightRule>,
+    /// Rows into which the lines were wrapped for recently used widths
+    pub(crate) wrap_cache: WrapCache,
 }
 
 impl Screen {
@@ -60,6 +111,58 @@ impl Screen {
     pub const fn line_count(&self) -> usize {
         self.line_count
     }
+    /// Number of digits for which space is made for the line numbers
+    pub(crate) fn line_number_digits(&self) -> usize {
+        minus_core::utils::digits(self.line_count).max(self.min_line_number_digits)
+    }
+    /// Get the line at the given index in the original text
+    ///
+    /// Lines are indexed from 0. Returns `None` if there is no such line.
+    #[must_use]
+    pub fn get_line(&self, line: usize) -> Option<Line<'_>> {
+        self.orig_text.lines().nth(line)
+    }
+
+    /// Get an iterator over the lines within `range` in the original text
+    ///
+    /// Lines are indexed from 0. Lines past the end of the text are not included.
+    pub fn lines(&self, range: Range<usize>) -> impl Iterator<Item = Line<'_>> {
+        self.orig_text
+            .lines()
+            .skip(range.start)
+            .take(range.end.saturating_sub(range.start))
+    }
+
+    /// Get the rows that are currently displayed on the terminal
+    ///
+    /// These are the formatted rows, hence they include the line numbers and the ANSI escape
+    /// sequences. When line wrapping is turned off, the rows are not cut to the width of the
+    /// terminal.
+    ///
+    /// The [`LineTransformer`] is not applied to these rows.
+    #[must_use]
+    pub fn visible_text(&self) -> &[Row] {
+        self.get_formatted_lines_with_bounds(self.visible_rows.start, self.visible_rows.end)
+    }
+
+    /// Get the row at which the given line starts
+    ///
+    /// Both lines and rows are indexed from 0. Due to line wrapping, a line can occupy more than
+    /// one row and hence this is the first of those rows. Returns `None` if there is no such line.
+    #[must_use]
+    pub fn row_of_line(&self, line: usize) -> Option<usize> {
+        self.lines_to_row_map.get(line).copied()
+    }
+
+    /// Get the line to which the given row belongs
+    ///
+    /// Both lines and rows are indexed from 0. Rows beyond the end of the text belong to the last
+    /// line.
+    #[must_use]
+    pub fn line_of_row(&self, row: usize) -> usize {
+        self.lines_to_row_map.line_of_row(row)
+    }
+
     /// Returns all the [Rows] within the bounds
     pub(crate) fn get_formatted_lines_with_bounds(&self, start: usize, end: usize) -> &[Row] {
         if start >= self.formatted_lines_count() || start > end {
@@ -71,6 +174,79 @@ impl Screen {
         }
     }
 
+    /// Returns the rows of `rows` within the bounds as they are displayed
+    ///
+    /// `rows` are the formatted rows of the whole text, either [`Screen::formatted_lines`] or rows
+    /// formatted the same way with other search highlights. The [`LineTransformer`] is only
+    /// applied here, to the lines that are about to be displayed, rather than to the whole text
+    /// each time it is formatted. The transformed lines take the same rows as the original ones
+    /// so that wrapping and search don't depend on the transformer.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn transformed_rows<'a>(
+        &self,
+        rows: &'a [Row],
+        start: usize,
+        end: usize,
+        line_numbers: LineNumbers,
+        cols: usize,
+        #[cfg(feature = "__search")] line_matches: &SearchIndex,
+        #[cfg(feature = "__search")] search_highlight: Option<HighlightMode>,
+    ) -> Cow<'a, [Row]> {
+        let rows = if start >= rows.len() || start > end {
+            &[]
+        } else {
+            &rows[start..end.min(rows.len())]
+        };
+        let Some(transform) = self.line_transformer.as_ref() else {
+            return Cow::Borrowed(rows);
+        };
+        if rows.is_empty() {
+            return Cow::Borrowed(rows);
+        }
+        let first_line = self.line_of_row(start);
+        let last_line = self.line_of_row(start + rows.len() - 1);
+        let line_number_digits = self.line_number_digits();
+        let total_rows = self.formatted_lines_count();
+
+        let mut transformed = Vec::with_capacity(rows.len());
+        for (idx, line) in (first_line..).zip(self.lines(first_line..last_line + 1)) {
+            let line_rows = self.row_of_line(idx + 1).unwrap_or(total_rows)
+                - self.row_of_line(idx).unwrap_or_default();
+            let line = truncate_line(Cow::Owned(transform(line, idx)), self.line_length_limit);
+            #[cfg(feature = "__regex")]
+            let styled = self.styled_lines.contains(&idx);
+            #[cfg(feature = "__regex")]
+            let line = if styled {
+                line
+            } else {
+                apply_highlight_rules(line, &self.highlight_rules)
+            };
+            let mut fmt_line = formatted_line(
+                &line,
+                line_number_digits,
+                idx,
+                line_numbers,
+                self.line_number_format.as_ref(),
+                &self.theme,
+                cols,
+                self.line_wrapping,
+                None,
+                #[cfg(feature = "__search")]
+                line_matches.get(idx).unwrap_or_default(),
+                #[cfg(feature = "__search")]
+                search_highlight,
+                #[cfg(feature = "__search")]
+                styled,
+            );
+            fmt_line.resize(line_rows, Cow::Borrowed(""));
+            transformed.extend(fmt_line.into_iter().map(Cow::into_owned));
+        }
+        let skip = start - self.row_of_line(first_line).unwrap_or_default();
+        transformed.drain(..skip);
+        transformed.truncate(rows.len());
+        Cow::Owned(transformed)
+    }
+
     /// Get the length of the longest [Line] in the text.
     #[must_use]
     pub const fn get_max_line_length(&self) -> usize {
@@ -78,19 +254,36 @@ impl Screen {
     }
 
     /// Insert the text into the []
+    ///
+    /// With [`Screen::carriage_return_overwrite`] or when displaying a hex dump, a `\r` at the
+    /// start of `text` makes its first line replace the last line of the text if that line is
+    /// unterminated.
     pub(crate) fn push_screen_buf(
         &mut self,
         text: TextBlock,
         line_numbers: LineNumbers,
-        cols: u16,
-        #[cfg(feature = "search")] search_term: &Option<Regex>,
+        cols: usize,
+        #[cfg(feature = "__search")] search_term: &Option<Regex>,
+        #[cfg(feature = "__search")] search_range: Option<&Range<usize>>,
+        #[cfg(feature = "__search")] search_highlight: Option<HighlightMode>,
     ) -> FormatResult {
         // If the last line of self.screen.orig_text is not terminated by than the first line of
         // the incoming text is part of that line so we also need to take care of that.
         //
         // Appropriately in that case we set the last lne of self.screen.orig_text as attachment
         // text for the FormatOpts.
+        let (text, replace_unterminated) = match text.strip_prefix('\r') {
+            Some(rest) if self.carriage_return_overwrite || self.raw_bytes.is_some() => {
+                (rest, true)
+            }
+            _ => (text, false),
+        };
         let clean_append = self.orig_text.ends_with('\n') || self.orig_text.is_empty();
+        // The unterminated line is still treated as an attachment, only an empty one
+        if replace_unterminated && !clean_append {
+            let start = self.orig_text.rfind('\n').map_or(0, |pos| pos + 1);
+            self.orig_text.truncate(start);
+        }
         // We check if number of digits in current line count change during this text push.
         let old_lc = self.line_count();
 
@@ -103,10 +296,11 @@ impl Screen {
             .truncate(self.formatted_lines.len() - self.unterminated);
 
         let append_props = {
+            // Only look at the end of the text rather than going through all of its lines
             let attachment = if clean_append {
                 None
             } else {
-                self.orig_text.lines().last()
+                self.orig_text.rsplit('\n').next()
             };
 
             let formatted_lines_count = self.formatted_lines.len();
@@ -119,10 +313,27 @@ impl Screen {
                 formatted_lines_count,
                 lines_count: old_lc,
                 prev_unterminated: self.unterminated,
-                cols: cols.into(),
+                cols,
                 line_wrapping: self.line_wrapping,
-                #[cfg(feature = "search")]
+                line_number_format: self.line_number_format.as_ref(),
+                theme: &self.theme,
+                min_line_number_digits: self.min_line_number_digits,
+                line_length_limit: self.line_length_limit,
+                wrap_cache: Some(&mut self.wrap_cache),
+                #[cfg(feature = "__regex")]
+                styled_lines: &self.styled_lines,
+                #[cfg(feature = "__regex")]
+                highlight_rules: &self.highlight_rules,
+                #[cfg(feature = "__search")]
                 search_term,
+                #[cfg(feature = "__search")]
+                search_range,
+                #[cfg(feature = "__search")]
+                search_highlight,
+                #[cfg(feature = "__search")]
+                known_matches: None,
+                #[cfg(feature = "__search")]
+                cancel: None,
             };
             format_text_block(append_opts)
         };
@@ -153,6 +364,19 @@ impl Default for Screen {
             line_count: 0,
             max_line_length: 0,
             unterminated: 0,
+            line_transformer: None,
+            line_number_format: None,
+            theme: Theme::default(),
+            min_line_number_digits: 0,
+            line_length_limit: DEFAULT_LINE_LENGTH_LIMIT,
+            carriage_return_overwrite: false,
+            lines_to_row_map: LinesRowMap::new(),
+            visible_rows: 0..0,
+            raw_bytes: None,
+            styled_lines: BTreeSet::new(),
+            #[cfg(feature = "__regex")]
+            highlight_rules: Vec::new(),
+            wrap_cache: WrapCache::default(),
         }
     }
 }
@@ -161,6 +385,235 @@ impl Default for Screen {
 // TEXT FORMATTING FUNCTIONS
 // |||||||||||||||||||||||||||||||
 
+/// Default value of [`Screen::line_length_limit`]
+pub(crate) const DEFAULT_LINE_LENGTH_LIMIT: usize = 100_000;
+
+/// Cut `line` after `limit` display columns and mark the truncation at its end
+///
+/// Escape sequences don't take up any columns and are never split. The styles and any hyperlink
+/// left open by the kept part are closed before the marker, which is displayed in reverse video
+/// and tells how many bytes were cut off.
+fn truncate_line(line: Cow<'_, str>, limit: usize) -> Cow<'_, str> {
+    // No character is narrower than its encoding is long
+    if line.len() <= limit {
+        return line;
+    }
+    let mut end = 0;
+    let mut width = 0;
+    let mut in_hyperlink = false;
+    for token in ansi_tokens(&line) {
+        match token {
+            AnsiToken::Char(c) => {
+                width += textwrap::core::display_width(c.encode_utf8(&mut [0; 4]));
+                if width > limit {
+                    break;
+                }
+                end += c.len_utf8();
+            }
+            AnsiToken::Hyperlink(seq, uri) => {
+                in_hyperlink = !uri.is_empty();
+                end += seq.len();
+            }
+            AnsiToken::Escape(seq) => end += seq.len(),
+        }
+    }
+    if end == line.len() {
+        return line;
+    }
+    Cow::Owned(format!(
+        "{}{}\u{1b}[0m\u{1b}[7m[{} more bytes]\u{1b}[0m",
+        &line[..end],
+        if in_hyperlink { HYPERLINK_END } else { "" },
+        line.len() - end
+    ))
+}
+
+/// Turn `\r\n` line endings into `\n` and, if `overwrite` is true, apply lone carriage returns
+///
+/// With `overwrite`, only the text after the last `\r` of each line is kept. If that happens to
+/// the first line and `continues` is true, the result starts with a `\r` telling that the line
+/// replaces the unterminated line to which the text is appended, see [`Screen::push_screen_buf`].
+pub(crate) fn normalize_carriage_returns(text: String, overwrite: bool, continues: bool) -> String {
+    if !text.contains('\r') {
+        return text;
+    }
+    let text = text.replace("\r\n", "\n");
+    if !overwrite || !text.contains('\r') {
+        return text;
+    }
+    let mut normalized = String::with_capacity(text.len());
+    for (idx, line) in text.split_inclusive('\n').enumerate() {
+        match line.rfind('\r') {
+            Some(pos) => {
+                if idx == 0 && continues {
+                    normalized.push('\r');
+                }
+                normalized.push_str(&line[pos + 1..]);
+            }
+            None => normalized.push_str(line),
+        }
+    }
+    normalized
+}
+
+/// Number of widths for which a [WrapCache] keeps the wrapped rows
+const WRAP_CACHE_WIDTHS: usize = 4;
+/// Default value of [`WrapCache::limit`]
+pub(crate) const DEFAULT_WRAP_CACHE_LINES: usize = 10_000;
+
+/// Rows into which the lines were wrapped for the most recently used widths
+///
+/// Toggling line numbers or resizing the terminal back to a previous size changes the width back
+/// to one that was used recently. The lines then don't need to be wrapped again. Rows are kept as
+/// byte ranges in their line along with a hash of the line, so a line that has changed since it
+/// was wrapped is never given stale rows.
+///
+/// Only the first [`WrapCache::limit`] lines are cached, which is [`DEFAULT_WRAP_CACHE_LINES`]
+/// unless changed with [`Pager::set_wrap_cache`](crate::Pager::set_wrap_cache).
+pub(crate) struct WrapCache {
+    /// Wrapped rows of each line for a width, the most recently used width being the last
+    entries: Vec<(usize, Vec<Option<WrappedLine>>)>,
+    /// Number of lines from the start of the text whose rows are kept
+    limit: usize,
+}
+
+impl Default for WrapCache {
+    fn default() -> Self {
+        Self {
+            entries: Vec::new(),
+            limit: DEFAULT_WRAP_CACHE_LINES,
+        }
+    }
+}
+
+/// Hash of a line along with the byte ranges of its rows
+type WrappedLine = (u64, Vec<Range<usize>>);
+
+impl WrapCache {
+    /// Set the number of lines whose rows are kept, forgetting the rows of the lines past it
+    pub(crate) fn set_limit(&mut self, limit: usize) {
+        self.limit = limit;
+        if limit == 0 {
+            self.entries.clear();
+        } else {
+            self.truncate(limit);
+        }
+    }
+
+    /// Wrap `line` which is at index `idx` in the text to `width` columns
+    pub(crate) fn wrap<'a>(
+        &mut self,
+        line: &'a str,
+        idx: usize,
+        width: usize,
+    ) -> Vec<Cow<'a, str>> {
+        if idx >= self.limit {
+            return textwrap::wrap(line, width);
+        }
+        if let Some(pos) = self.entries.iter().position(|(w, _)| *w == width) {
+            let entry = self.entries.remove(pos);
+            self.entries.push(entry);
+        } else {
+            if self.entries.len() == WRAP_CACHE_WIDTHS {
+                self.entries.remove(0);
+            }
+            self.entries.push((width, Vec::new()));
+        }
+        let lines = &mut self.entries.last_mut().unwrap().1;
+        let hash = {
+            let mut hasher = DefaultHasher::new();
+            line.hash(&mut hasher);
+            hasher.finish()
+        };
+
+        if let Some(Some((cached_hash, ranges))) = lines.get(idx) {
+            if *cached_hash == hash {
+                let rows = ranges
+                    .iter()
+                    .map(|range| line.get(range.clone()).map(Cow::Borrowed))
+                    .collect::<Option<Vec<_>>>();
+                if let Some(rows) = rows {
+                    return rows;
+                }
+            }
+        }
+
+        let rows = textwrap::wrap(line, width);
+        // Only rows that are slices of the line can be stored as ranges
+        let ranges = rows
+            .iter()
+            .map(|row| match row {
+                Cow::Borrowed(row) => (row.as_ptr() as usize)
+                    .checked_sub(line.as_ptr() as usize)
+                    .filter(|start| start + row.len() <= line.len())
+                    .map(|start| start..start + row.len()),
+                Cow::Owned(_) => None,
+            })
+            .collect::<Option<Vec<_>>>();
+        if lines.len() <= idx {
+            lines.resize(idx + 1, None);
+        }
+        lines[idx] = ranges.map(|ranges| (hash, ranges));
+        rows
+    }
+
+    /// Forget the rows of the lines from index `lines` onwards
+    pub(crate) fn truncate(&mut self, lines: usize) {
+        for (_, entry) in &mut self.entries {
+            entry.truncate(lines);
+        }
+    }
+}
+
+/// Number of bytes shown on each line of a hex dump
+pub(crate) const HEX_DUMP_WIDTH: usize = 16;
+
+/// Returns true if `bytes` should be displayed as a hex dump rather than text
+///
+/// This is the case if they contain NUL bytes or aren't valid UTF-8.
+pub(crate) fn is_binary(bytes: &[u8]) -> bool {
+    bytes.contains(&0) || std::str::from_utf8(bytes).is_err()
+}
+
+/// Format `bytes` like `hexdump -C`
+///
+/// Each line has the offset of its first byte, the bytes in hex and their ASCII representation
+/// with non-printable bytes shown as `.`. The last line is padded so that all lines have the
+/// same width. If it is incomplete, it is left unterminated so that it can be replaced once more
+/// bytes arrive.
+///
+/// `offset` is the offset of the first byte of `bytes` in the data.
+pub(crate) fn hex_dump(bytes: &[u8], offset: usize) -> String {
+    let mut dump = String::with_capacity(bytes.len() / HEX_DUMP_WIDTH * 80 + 80);
+    for (idx, chunk) in bytes.chunks(HEX_DUMP_WIDTH).enumerate() {
+        write!(dump, "{:08x} ", offset + idx * HEX_DUMP_WIDTH).ok();
+        for i in 0..HEX_DUMP_WIDTH {
+            if i % 8 == 0 {
+                dump.push(' ');
+            }
+            match chunk.get(i) {
+                Some(b) => {
+                    write!(dump, "{b:02x} ").ok();
+                }
+                None => dump.push_str("   "),
+            }
+        }
+        dump.push_str(" |");
+        for i in 0..HEX_DUMP_WIDTH {
+            dump.push(match chunk.get(i) {
+                Some(&b) if b.is_ascii_graphic() || b == b' ' => b as char,
+                Some(_) => '.',
+                None => ' ',
+            });
+        }
+        dump.push('|');
+        if chunk.len() == HEX_DUMP_WIDTH {
+            dump.push('\n');
+        }
+    }
+    dump
+}
+
 // minus has a very interesting but simple text model that you must go through to understand how minus works.
 //
 // # Text Block
@@ -203,16 +656,12 @@ impl Default for Screen {
 // [`PagerState::lines`]: crate::state::PagerState::lines
 
 pub(crate) trait AppendableBuffer {
-    fn append_to_buffer(&mut self, other: &mut Rows);
     fn extend_buffer<I>(&mut self, other: I)
     where
         I: IntoIterator<Item = Row>;
 }
 
 impl AppendableBuffer for Rows {
-    fn append_to_buffer(&mut self, other: &mut Rows) {
-        self.append(other);
-    }
     fn extend_buffer<I>(&mut self, other: I)
     where
         I: IntoIterator<Item = Row>,
@@ -222,9 +671,6 @@ impl AppendableBuffer for Rows {
 }
 
 impl AppendableBuffer for &mut Rows {
-    fn append_to_buffer(&mut self, other: &mut Rows) {
-        self.append(other);
-    }
     fn extend_buffer<I>(&mut self, other: I)
     where
         I: IntoIterator<Item = Row>,
@@ -258,11 +704,40 @@ where
     /// it should be 0.
     pub prev_unterminated: usize,
     /// Search term if a search is active
-    #[cfg(feature = "search")]
-    pub search_term: &'a Option<regex::Regex>,
+    #[cfg(feature = "__search")]
+    pub search_term: &'a Option<Regex>,
+    /// Lines outside of this range are not searched
+    #[cfg(feature = "__search")]
+    pub search_range: Option<&'a Range<usize>>,
+    /// How search matches are highlighted. They aren't highlighted if this is `None`
+    #[cfg(feature = "__search")]
+    pub search_highlight: Option<HighlightMode>,
+    /// Matches already known for the text. If present, these are used instead of searching the
+    /// lines again with `search_term`
+    #[cfg(feature = "__search")]
+    pub known_matches: Option<&'a SearchIndex>,
+    /// Checked every few lines to abandon formatting whose result is no longer needed
+    #[cfg(feature = "__search")]
+    pub cancel: Option<&'a dyn Fn() -> bool>,
 
     /// Value of [PagerState::line_wrapping]
     pub line_wrapping: bool,
+    /// Function to make the line numbers
+    pub line_number_format: Option<&'a LineNumberFormat>,
+    /// Styles used for the line numbers
+    pub theme: &'a Theme,
+    /// Number of digits for which space is kept for the line numbers even if fewer are needed
+    pub min_line_number_digits: usize,
+    /// Number of characters after which lines are truncated
+    pub line_length_limit: usize,
+    /// Patterns colorized by the application
+    #[cfg(feature = "__regex")]
+    pub highlight_rules: &'a [HighlightRule],
+    /// Indices of the lines added with [`Pager::push_styled_lines`](crate::Pager::push_styled_lines)
+    #[cfg(feature = "__regex")]
+    pub styled_lines: &'a BTreeSet<usize>,
+    /// Cache to reuse the rows of lines that were wrapped before
+    pub wrap_cache: Option<&'a mut WrapCache>,
 }
 
 /// Contains the formatted rows along with some basic information about the text formatted
@@ -281,16 +756,27 @@ pub(crate) struct FormatResult {
     /// Number of rows that are unterminated
     pub num_unterminated: usize,
     /// If search is active, this contains the indices where search matches in the incoming text have been found
-    #[cfg(feature = "search")]
+    #[cfg(feature = "__search")]
     pub append_search_idx: BTreeSet<usize>,
+    /// If search is active, this contains the matches in each line of the incoming text
+    #[cfg(feature = "__search")]
+    pub append_line_matches: SearchIndex,
     /// Map of where first row of each line is placed inside in
     /// [`PagerState::formatted_lines`](crate::state::PagerState::formatted_lines)
     pub lines_to_row_map: LinesRowMap,
     /// The length of longest line encountered in the formatted text block
     pub max_line_length: usize,
     pub clean_append: bool,
+    /// Whether formatting was abandoned because [`FormatOpts::cancel`] returned `true`. The
+    /// results are incomplete in that case
+    #[cfg(feature = "__search")]
+    pub cancelled: bool,
 }
 
+/// Number of lines after which [`FormatOpts::cancel`] is checked again
+#[cfg(feature = "__search")]
+const CANCEL_CHECK_INTERVAL: usize = 256;
+
 /// Makes the text that will be displayed.
 #[allow(clippy::too_many_lines)]
 pub(crate) fn format_text_block<B>(mut opts: FormatOpts<'_, B>) -> FormatResult
@@ -335,7 +821,9 @@ where
     // * After all the formatting is done, we return the format results.
 
     // Compute the text to be format and set clean_append
-    let to_format;
+    //
+    // The text is only copied when it has to be joined with the attachment
+    let to_format: Cow<'_, str>;
     if let Some(attached_text) = opts.attachment {
         // Tweak certain parameters if we are joining the last line of already present text with the first line of
         // incoming text.
@@ -354,15 +842,26 @@ where
         s.push_str(attached_text);
         s.push_str(opts.text);
 
-        to_format = s;
+        to_format = Cow::Owned(s);
     } else {
-        to_format = opts.text.to_string();
+        to_format = Cow::Borrowed(opts.text);
     }
 
+    // Cut overly long lines before the lines get wrapped
     let lines = to_format
         .lines()
         .enumerate()
-        .collect::<Vec<(usize, &str)>>();
+        .map(|(idx, line)| {
+            let line = truncate_line(Cow::Borrowed(line), opts.line_length_limit);
+            #[cfg(feature = "__regex")]
+            let line = if opts.styled_lines.contains(&(opts.lines_count + idx)) {
+                line
+            } else {
+                apply_highlight_rules(line, opts.highlight_rules)
+            };
+            (idx, line)
+        })
+        .collect::<Vec<(usize, Cow<str>)>>();
 
     let to_format_size = lines.len();
 
@@ -370,75 +869,180 @@ where
         lines_formatted: to_format_size,
         rows_formatted: 0,
         num_unterminated: opts.prev_unterminated,
-        #[cfg(feature = "search")]
+        #[cfg(feature = "__search")]
         append_search_idx: BTreeSet::new(),
+        #[cfg(feature = "__search")]
+        append_line_matches: SearchIndex::default(),
         lines_to_row_map: LinesRowMap::new(),
         max_line_length: 0,
         clean_append: opts.attachment.is_none(),
+        #[cfg(feature = "__search")]
+        cancelled: false,
     };
 
-    let line_number_digits = minus_core::utils::digits(opts.lines_count + to_format_size);
+    let line_number_digits = minus_core::utils::digits(opts.lines_count + to_format_size)
+        .max(opts.min_line_number_digits);
 
     // Return if we have nothing to format
     if lines.is_empty() {
         return fr;
     }
 
+    // Lines outside of the search range are formatted as if no search is active
+    #[cfg(feature = "__search")]
+    let (search_term, search_range, search_highlight, known_matches) = (
+        opts.search_term,
+        opts.search_range,
+        opts.search_highlight,
+        opts.known_matches,
+    );
+    // Along with the matches, get the number of them at the start of the line which continue a
+    // match from a previous line
+    #[cfg(feature = "__search")]
+    let matches_for = |idx: usize, line: &str| -> (Vec<Range<usize>>, usize) {
+        if let Some(known) = known_matches {
+            return (
+                known.get(idx).map(<[_]>::to_vec).unwrap_or_default(),
+                known.continued_count(idx),
+            );
+        }
+        match search_term {
+            Some(query) if search_range.map_or(true, |range| range.contains(&idx)) => {
+                (search::line_matches(line, query), 0)
+            }
+            _ => (Vec::new(), 0),
+        }
+    };
+    // Record the matches of a line and the rows in which they start
+    #[cfg(feature = "__search")]
+    let text_width = text_width(opts.cols, opts.line_numbers.is_on(), line_number_digits);
+    #[cfg(feature = "__search")]
+    let line_wrapping = opts.line_wrapping;
+    #[cfg(feature = "__search")]
+    let record_matches =
+        |fr: &mut FormatResult,
+         idx: usize,
+         line: &str,
+         row: usize,
+         (matches, continued): (Vec<Range<usize>>, usize)| {
+            fr.append_search_idx.extend(
+                search::match_row_offsets(line, &matches[continued..], text_width, line_wrapping)
+                    .into_iter()
+                    .map(|offset| row + offset),
+            );
+            fr.append_line_matches.insert(idx, matches);
+        };
+
     // Number of rows that have been formatted so far
     // Whenever a line is formatted, this will be incremented to te number of rows that the formatted line has occupied
     let mut formatted_row_count = opts.formatted_lines_count;
+    let mut wrap_cache = opts.wrap_cache.take();
+    #[cfg(feature = "__search")]
+    let mut cancelled = false;
 
     {
         let line_numbers = opts.line_numbers;
+        let line_number_format = opts.line_number_format;
+        let theme = opts.theme;
         let cols = opts.cols;
         let lines_count = opts.lines_count;
         let line_wrapping = opts.line_wrapping;
-        #[cfg(feature = "search")]
-        let search_term = opts.search_term;
-
-        let rest_lines =
-            lines
-                .iter()
-                .take(lines.len().saturating_sub(1))
-                .flat_map(|(idx, line)| {
-                    let fmt_line = formatted_line(
-                        line,
-                        line_number_digits,
-                        lines_count + idx,
-                        line_numbers,
-                        cols,
-                        line_wrapping,
-                        #[cfg(feature = "search")]
-                        formatted_row_count,
-                        #[cfg(feature = "search")]
-                        &mut fr.append_search_idx,
-                        #[cfg(feature = "search")]
-                        search_term,
-                    );
-                    fr.lines_to_row_map.insert(formatted_row_count, true);
-                    formatted_row_count += fmt_line.len();
-                    if lines.len() > fr.max_line_length {
-                        fr.max_line_length = line.len();
-                    }
+        let wrap_cache = &mut wrap_cache;
+        #[cfg(feature = "__search")]
+        let styled_lines = opts.styled_lines;
+        #[cfg(feature = "__search")]
+        let cancel = opts.cancel;
+        #[cfg(feature = "__search")]
+        let cancelled = &mut cancelled;
+        #[cfg(feature = "__search")]
+        let mut keep_going = |idx: usize| {
+            if idx % CANCEL_CHECK_INTERVAL == 0 && cancel.is_some_and(|cancel| cancel()) {
+                *cancelled = true;
+                return false;
+            }
+            true
+        };
+        #[cfg(not(feature = "__search"))]
+        let keep_going = |_: usize| true;
 
-                    fmt_line
-                });
+        let rest_lines = lines
+            .iter()
+            .take(lines.len().saturating_sub(1))
+            .take_while(|(idx, _)| keep_going(*idx))
+            .flat_map(|(idx, line)| {
+                #[cfg(feature = "__search")]
+                let matches = matches_for(lines_count + idx, line);
+                let fmt_line = formatted_line(
+                    line,
+                    line_number_digits,
+                    lines_count + idx,
+                    line_numbers,
+                    line_number_format,
+                    theme,
+                    cols,
+                    line_wrapping,
+                    wrap_cache.as_deref_mut(),
+                    #[cfg(feature = "__search")]
+                    &matches.0,
+                    #[cfg(feature = "__search")]
+                    search_highlight,
+                    #[cfg(feature = "__search")]
+                    styled_lines.contains(&(lines_count + idx)),
+                );
+                #[cfg(feature = "__search")]
+                record_matches(
+                    &mut fr,
+                    lines_count + idx,
+                    line,
+                    formatted_row_count,
+                    matches,
+                );
+                fr.lines_to_row_map.insert(formatted_row_count, true);
+                formatted_row_count += fmt_line.len();
+                if line.len() > fr.max_line_length {
+                    fr.max_line_length = line.len();
+                }
+
+                fmt_line.into_iter().map(Cow::into_owned)
+            });
         opts.buffer.extend_buffer(rest_lines);
     };
+    #[cfg(feature = "__search")]
+    if cancelled {
+        fr.cancelled = true;
+        return fr;
+    }
 
-    let mut last_line = formatted_line(
-        lines.last().unwrap().1,
+    #[cfg(feature = "__search")]
+    let matches = matches_for(
+        opts.lines_count + to_format_size - 1,
+        &lines.last().unwrap().1,
+    );
+    let last_line = formatted_line(
+        &lines.last().unwrap().1,
         line_number_digits,
         opts.lines_count + to_format_size - 1,
         opts.line_numbers,
+        opts.line_number_format,
+        opts.theme,
         opts.cols,
         opts.line_wrapping,
-        #[cfg(feature = "search")]
+        wrap_cache,
+        #[cfg(feature = "__search")]
+        &matches.0,
+        #[cfg(feature = "__search")]
+        search_highlight,
+        #[cfg(feature = "__search")]
+        opts.styled_lines
+            .contains(&(opts.lines_count + to_format_size - 1)),
+    );
+    #[cfg(feature = "__search")]
+    record_matches(
+        &mut fr,
+        opts.lines_count + to_format_size - 1,
+        &lines.last().unwrap().1,
         formatted_row_count,
-        #[cfg(feature = "search")]
-        &mut fr.append_search_idx,
-        #[cfg(feature = "search")]
-        opts.search_term,
+        matches,
     );
     fr.lines_to_row_map.insert(formatted_row_count, true);
     formatted_row_count += last_line.len();
@@ -446,33 +1050,6 @@ where
         fr.max_line_length = lines.last().unwrap().1.len();
     }
 
-    #[cfg(feature = "search")]
-    {
-        // NOTE: VERY IMPORTANT BLOCK TO GET PROPER SEARCH INDEX
-        // Here is the current scenario: suppose you have text block like this (markers are present to denote where a
-        // new line begins).
-        //
-        // * This is line one row one
-        //   This is line one row two
-        //   This is line one row three
-        // * This is line two row one
-        //   This is line two row two
-        //   This is line two row three
-        //   This is line two row four
-        //
-        // and suppose a match is found at line 1 row 2 and line 2 row 4. So the index generated will be [1, 6].
-        // Let's say this text block is going to be appended to [PagerState::formatted_lines] from index 23.
-        // Now if directly append this generated index to [`PagerState::search_idx`], it will probably be wrong
-        // as these numbers are *relative to current text block*. The actual search index should have been 24, 30.
-        //
-        // To fix this we basically add the number of items in [`PagerState::formatted_lines`].
-        fr.append_search_idx = fr
-            .append_search_idx
-            .iter()
-            .map(|i| opts.formatted_lines_count + i)
-            .collect();
-    }
-
     // Calculate number of rows which are part of last line and are left unterminated  due to absence of \n
     fr.num_unterminated = if opts.text.ends_with('\n') {
         // If the last line ends with \n, then the line is complete so nothing is left as unterminated
@@ -480,12 +1057,24 @@ where
     } else {
         last_line.len()
     };
-    opts.buffer.append_to_buffer(&mut last_line);
+    opts.buffer
+        .extend_buffer(last_line.into_iter().map(Cow::into_owned));
     fr.rows_formatted = formatted_row_count - opts.formatted_lines_count;
 
     fr
 }
 
+/// Number of columns available for the text of a line
+///
+/// When line numbers are active, the space taken by them can't be used for the text.
+pub(crate) const fn text_width(cols: usize, line_numbers: bool, len_line_number: usize) -> usize {
+    if line_numbers {
+        cols.saturating_sub(len_line_number + LineNumbers::EXTRA_PADDING + 3)
+    } else {
+        cols
+    }
+}
+
 /// Formats the given `line`
 ///
 /// - `line`: The line to format
@@ -495,10 +1084,18 @@ where
 ///     number of lines in [`PagerState::lines`] is 500. This is used for calculating the padding
 ///     of each displayed line.
 /// - `idx`: is the position index where the line is placed in [`PagerState::lines`].
-/// - `formatted_idx`: is the position index where the line will be placed in the resulting
-///    [`PagerState::formatted_lines`](crate::state::PagerState::formatted_lines)
+/// - `line_number_format`: Function to make the line number. If `None`, the line number is
+///     followed by the separator of the `theme`
+/// - `theme`: Styles used for the line numbers
 /// - `cols`: Number of columns in the terminal
-/// - `search_term`: Contains the regex if a search is active
+/// - `wrap_cache`: Cache to reuse the rows of the line if it was wrapped before
+/// - `match_ranges`: Search matches in the line to highlight, as byte ranges in the line after
+///     removing ANSI escape sequences from it
+/// - `search_highlight`: How the search matches are highlighted, if they are highlighted
+/// - `styled`: Whether the line was added with [`Pager::push_styled_lines`](crate::Pager::push_styled_lines)
+///
+/// Rows that don't need any change from the line are borrowed from it. This is always the case
+/// for plain text without line numbers, so no row needs to be copied until it gets stored.
 ///
 /// [`PagerState::lines`]: crate::state::PagerState::lines
 #[allow(clippy::too_many_arguments)]
@@ -508,12 +1105,15 @@ pub(crate) fn formatted_line<'a>(
     len_line_number: usize,
     idx: usize,
     line_numbers: LineNumbers,
+    line_number_format: Option<&LineNumberFormat>,
+    theme: &Theme,
     cols: usize,
     line_wrapping: bool,
-    #[cfg(feature = "search")] formatted_idx: usize,
-    #[cfg(feature = "search")] search_idx: &mut BTreeSet<usize>,
-    #[cfg(feature = "search")] search_term: &Option<regex::Regex>,
-) -> Rows {
+    wrap_cache: Option<&mut WrapCache>,
+    #[cfg(feature = "__search")] match_ranges: &[Range<usize>],
+    #[cfg(feature = "__search")] search_highlight: Option<HighlightMode>,
+    #[cfg(feature = "__search")] styled: bool,
+) -> Vec<Cow<'a, str>> {
     assert!(
         !line.contains('\n'),
         "Newlines found in appending line {:?}",
@@ -530,103 +1130,315 @@ pub(crate) fn formatted_line<'a>(
     // actual line display when wrapping the lines
     let padding = len_line_number + LineNumbers::EXTRA_PADDING + 1;
 
-    let cols_avail = if line_numbers {
-        cols.saturating_sub(padding + 2)
-    } else {
-        cols
-    };
+    let cols_avail = text_width(cols, line_numbers, len_line_number);
 
     // Wrap the line and return an iterator over all the rows
-    let mut enumerated_rows = if line_wrapping {
-        textwrap::wrap(line, cols_avail)
+    let mut rows = if line_wrapping {
+        wrap_cache.map_or_else(
+            || textwrap::wrap(line, cols_avail),
+            |cache| cache.wrap(line, idx, cols_avail),
+        )
     } else {
         vec![Cow::from(line)]
+    };
+    // highlight the parts of each row that lie within the search matches
+    #[cfg(feature = "__search")]
+    if let Some(mode) = search_highlight.filter(|_| !match_ranges.is_empty()) {
+        let accurate = mode.is_accurate_for(line);
+        let row_ranges =
+            search::row_match_ranges(line, rows.iter().map(AsRef::as_ref), match_ranges);
+        for (row, ranges) in rows.iter_mut().zip(row_ranges) {
+            if ranges.is_empty() {
+                continue;
+            }
+            *row = Cow::Owned(if styled {
+                search::highlight_styled_ranges(row, &ranges)
+            } else {
+                search::highlight_ranges(row, &ranges, accurate)
+            });
+        }
+    }
+    if rows.len() > 1 {
+        carry_hyperlinks(&mut rows);
+    }
+
+    // If line numbers aren't active, simply return the rows with search matches highlighted if search is active
+    if !line_numbers {
+        return rows;
     }
-    .into_iter()
-    .enumerate();
-
-    // highlight the lines with matching search terms
-    // If a match is found, add this line's index to PagerState::search_idx
-    #[cfg_attr(not(feature = "search"), allow(unused_mut))]
-    #[cfg_attr(not(feature = "search"), allow(unused_variables))]
-    let mut handle_search = |row: &mut Cow<'a, str>, wrap_idx: usize| {
-        #[cfg(feature = "search")]
-        if let Some(st) = search_term.as_ref() {
-            let (highlighted_row, is_match) = search::highlight_line_matches(row, st, false);
-            if is_match {
-                *row.to_mut() = highlighted_row;
-                search_idx.insert(formatted_idx + wrap_idx);
+
+    // * If minus is run under test, ascii codes for styling the numbers are not inserted because they add
+    // extra difficulty while writing tests
+    // * Line number is added only to the first row of a line. This makes a better UI overall
+    let mut number = String::new();
+    for (row_idx, row) in rows.iter_mut().enumerate() {
+        let mut formatted = String::with_capacity(padding + 1 + row.len());
+        if row_idx == 0 {
+            match line_number_format {
+                Some(format) => number = format(idx + 1, len_line_number),
+                None => {
+                    write!(number, "{}{}", idx + 1, theme.line_number_separator).ok();
+                }
+            }
+            // Only style the number and not the space before it
+            let pad = padding.saturating_sub(textwrap::core::display_width(&number));
+            formatted.extend(std::iter::repeat(' ').take(pad));
+            if cfg!(test) {
+                formatted.push_str(&number);
+            } else {
+                write!(formatted, "{}", theme.line_number.apply(&number)).ok();
             }
+        } else {
+            formatted.extend(std::iter::repeat(' ').take(padding));
         }
+        formatted.push(' ');
+        formatted.push_str(row);
+        *row = Cow::Owned(formatted);
+    }
+    rows
+}
+
+/// Sequence that closes an OSC 8 hyperlink
+const HYPERLINK_END: &str = "\u{1b}]8;;\u{1b}\\";
+/// Sequence that resets all styles
+#[cfg(feature = "__regex")]
+const STYLE_RESET: &str = "\u{1b}[0m";
+
+/// A pattern that gets colorized wherever it appears in the text
+///
+/// See [`Pager::add_highlight_rule`](crate::Pager::add_highlight_rule)
+#[cfg(feature = "__regex")]
+#[derive(Clone, Debug)]
+pub(crate) struct HighlightRule {
+    pub(crate) regex: Regex,
+    pub(crate) style: ContentStyle,
+}
+
+/// Colorize the parts of `line` matching any of the `rules`
+///
+/// Like search queries, the rules are matched against the line after removing ANSI escape
+/// sequences from it. The style of a rule is layered over the line's own styling which gets
+/// restored after each match. Where the matches of several rules overlap, the rule added last
+/// wins.
+#[cfg(feature = "__regex")]
+pub(crate) fn apply_highlight_rules<'a>(
+    line: Cow<'a, str>,
+    rules: &[HighlightRule],
+) -> Cow<'a, str> {
+    if rules.is_empty() {
+        return line;
+    }
+    let tokens = ansi_tokens(&line).collect::<Vec<_>>();
+    let stripped = tokens
+        .iter()
+        .filter_map(|token| match token {
+            AnsiToken::Char(c) => Some(*c),
+            _ => None,
+        })
+        .collect::<String>();
+    // Rule that applies to each byte of the stripped line
+    let mut owners = vec![None; stripped.len()];
+    for (idx, rule) in rules.iter().enumerate() {
+        for m in rule.regex.find_ranges(&stripped) {
+            owners[m].fill(Some(idx));
+        }
+    }
+    if owners.iter().all(Option::is_none) {
+        return line;
+    }
+
+    let write_style = |out: &mut String, idx: usize| {
+        crossterm::Command::write_ansi(&SetStyle(rules[idx].style), out).ok();
     };
+    let mut out = String::with_capacity(line.len() + 32);
+    // SGR sequences of the line itself that are in effect, so that they can be restored after a
+    // match
+    let mut own_styles = String::new();
+    let mut current = None;
+    let mut pos = 0;
+    for token in tokens {
+        match token {
+            AnsiToken::Char(c) => {
+                let owner = owners[pos];
+                if owner != current {
+                    if current.is_some() {
+                        out.push_str(STYLE_RESET);
+                        out.push_str(&own_styles);
+                    }
+                    if let Some(idx) = owner {
+                        write_style(&mut out, idx);
+                    }
+                    current = owner;
+                }
+                out.push(c);
+                pos += c.len_utf8();
+            }
+            AnsiToken::Escape(seq) => {
+                out.push_str(seq);
+                if seq.starts_with("\u{1b}[") && seq.ends_with('m') {
+                    if matches!(seq, STYLE_RESET | "\u{1b}[m") {
+                        own_styles.clear();
+                    } else {
+                        own_styles.push_str(seq);
+                    }
+                    // Keep the rule's style on top of the line's own styling
+                    if let Some(idx) = current {
+                        write_style(&mut out, idx);
+                    }
+                }
+            }
+            AnsiToken::Hyperlink(seq, _) => out.push_str(seq),
+        }
+    }
+    if current.is_some() {
+        out.push_str(STYLE_RESET);
+        out.push_str(&own_styles);
+    }
+    Cow::Owned(out)
+}
 
-    if line_numbers {
-        let mut formatted_rows = Vec::with_capacity(256);
-
-        // Formatter for only when line numbers are active
-        // * If minus is run under test, ascii codes for making the numbers bol is not inserted because they add
-        // extra difficulty while writing tests
-        // * Line number is added only to the first row of a line. This makes a better UI overall
-        let formatter = |row: Cow<'_, str>, is_first_row: bool, idx: usize| {
-            format!(
-                "{bold}{number: >len$}{reset} {row}",
-                bold = if cfg!(not(test)) && is_first_row {
-                    crossterm::style::Attribute::Bold.to_string()
-                } else {
-                    String::new()
-                },
-                number = if is_first_row {
-                    (idx + 1).to_string() + "."
-                } else {
-                    String::new()
-                },
-                len = padding,
-                reset = if cfg!(not(test)) && is_first_row {
-                    crossterm::style::Attribute::Reset.to_string()
-                } else {
-                    String::new()
-                },
-                row = row
-            )
-        };
+/// A piece of text split at ANSI escape sequences
+pub(crate) enum AnsiToken<'a> {
+    /// A printable character
+    Char(char),
+    /// An OSC 8 hyperlink sequence with the entire sequence and the URI that it opens. The URI is
+    /// empty if the sequence closes the hyperlink.
+    Hyperlink(&'a str, &'a str),
+    /// Any other escape sequence
+    Escape(&'a str),
+}
 
-        // First format the first row separate from other rows, then the subsequent rows and finally join them
-        // This is because only the first row contains the line number and not the subsequent rows
-        let first_row = {
-            #[cfg_attr(not(feature = "search"), allow(unused_mut))]
-            let mut row = enumerated_rows.next().unwrap().1;
-            handle_search(&mut row, 0);
-            formatter(row, true, idx)
+/// Split `text` into printable characters and ANSI escape sequences
+pub(crate) fn ansi_tokens(text: &str) -> impl Iterator<Item = AnsiToken<'_>> {
+    let mut rest = text;
+    std::iter::from_fn(move || {
+        let c = rest.chars().next()?;
+        if c != '\u{1b}' {
+            rest = &rest[c.len_utf8()..];
+            return Some(AnsiToken::Char(c));
+        }
+        let (seq, token) = if rest[1..].starts_with(']') {
+            // OSC sequence: ends with BEL or ST (ESC \)
+            let body = &rest[2..];
+            let (body_len, terminator_len) = match (body.find('\u{7}'), body.find("\u{1b}\\")) {
+                (Some(bel), Some(st)) if st < bel => (st, 2),
+                (Some(bel), _) => (bel, 1),
+                (None, Some(st)) => (st, 2),
+                (None, None) => (body.len(), 0),
+            };
+            let seq = &rest[..2 + body_len + terminator_len];
+            let token = body[..body_len]
+                .strip_prefix("8;")
+                .and_then(|params| params.split_once(';'))
+                .map_or(AnsiToken::Escape(seq), |(_, uri)| {
+                    AnsiToken::Hyperlink(seq, uri)
+                });
+            (seq, token)
+        } else if rest[1..].starts_with('[') {
+            // CSI sequence: ends at the first byte in the range 0x40..=0x7E
+            let end = rest[2..]
+                .find(|c| ('\u{40}'..='\u{7e}').contains(&c))
+                .map_or(rest.len(), |i| i + 3);
+            (&rest[..end], AnsiToken::Escape(&rest[..end]))
+        } else {
+            let end = rest[1..].chars().next().map_or(1, |c| 1 + c.len_utf8());
+            (&rest[..end], AnsiToken::Escape(&rest[..end]))
         };
-        formatted_rows.push(first_row);
+        rest = &rest[seq.len()..];
+        Some(token)
+    })
+}
 
-        #[cfg_attr(not(feature = "search"), allow(unused_mut))]
-        #[cfg_attr(not(feature = "search"), allow(unused_variables))]
-        let rows_left = enumerated_rows.map(|(wrap_idx, mut row)| {
-            handle_search(&mut row, wrap_idx);
-            formatter(row, false, 0)
-        });
-        formatted_rows.extend(rows_left);
+/// Make OSC 8 hyperlinks that span multiple rows work on each of them
+///
+/// Each row is drawn independently hence a hyperlink left open at the end of a row is closed
+/// there and reopened at the start of the next row.
+fn carry_hyperlinks(rows: &mut [Cow<'_, str>]) {
+    let mut active: Option<String> = None;
+    for row in rows {
+        let reopen = active.clone();
+        for token in ansi_tokens(row) {
+            if let AnsiToken::Hyperlink(seq, uri) = token {
+                active = (!uri.is_empty()).then(|| seq.to_string());
+            }
+        }
+        if reopen.is_none() && active.is_none() {
+            continue;
+        }
+        let mut carried = reopen.unwrap_or_default();
+        carried.push_str(row);
+        if active.is_some() {
+            carried.push_str(HYPERLINK_END);
+        }
+        *row = Cow::Owned(carried);
+    }
+}
 
-        formatted_rows
-    } else {
-        // If line numbers aren't active, simply return the rows with search matches highlighted if search is active
-        #[cfg_attr(not(feature = "search"), allow(unused_variables))]
-        enumerated_rows
-            .map(|(wrap_idx, mut row)| {
-                handle_search(&mut row, wrap_idx);
-                row.to_string()
-            })
-            .collect::<Vec<String>>()
+/// Cut the columns `start..start + width` out of `row` while keeping its first `keep` columns
+///
+/// This is used to display the rows when horizontal scrolling is active. The cut is made on
+/// display columns and escape sequences are never split. Sequences lying outside the cut are
+/// still written without their text so that the styling and hyperlinks active at the left edge
+/// are the same as in `row`. A wide character cut by the left edge is replaced by spaces.
+pub(crate) fn slice_columns(row: &str, keep: usize, start: usize, width: usize) -> String {
+    let mut sliced = String::with_capacity(row.len().min(keep + width + 32));
+    let left = keep + start;
+    let right = left + width;
+    let mut col = 0;
+    for token in ansi_tokens(row) {
+        match token {
+            AnsiToken::Char(c) => {
+                let next = col + textwrap::core::display_width(c.encode_utf8(&mut [0; 4]));
+                if next <= keep || (col >= left && next <= right) {
+                    sliced.push(c);
+                } else if col < left && next > left && next <= right {
+                    sliced.push_str(&" ".repeat(next - left));
+                }
+                col = next;
+            }
+            AnsiToken::Hyperlink(seq, _) | AnsiToken::Escape(seq) => sliced.push_str(seq),
+        }
     }
+    sliced
 }
 
+/// Find the URI of the OSC 8 hyperlink under the column `col` of `row`
+pub(crate) fn hyperlink_at(row: &str, col: usize) -> Option<String> {
+    let mut active = None;
+    let mut width = 0;
+    for token in ansi_tokens(row) {
+        match token {
+            AnsiToken::Hyperlink(_, uri) => active = (!uri.is_empty()).then_some(uri),
+            AnsiToken::Char(c) => {
+                width += textwrap::core::display_width(c.encode_utf8(&mut [0; 4]));
+                if width > col {
+                    return active.map(ToString::to_string);
+                }
+            }
+            AnsiToken::Escape(_) => {}
+        }
+    }
+    None
+}
+
+#[allow(clippy::too_many_arguments)]
 pub(crate) fn make_format_lines(
     text: &String,
     line_numbers: LineNumbers,
     cols: usize,
     line_wrapping: bool,
-    #[cfg(feature = "search")] search_term: &Option<regex::Regex>,
+    line_number_format: Option<&LineNumberFormat>,
+    theme: &Theme,
+    min_line_number_digits: usize,
+    line_length_limit: usize,
+    wrap_cache: Option<&mut WrapCache>,
+    #[cfg(feature = "__regex")] styled_lines: &BTreeSet<usize>,
+    #[cfg(feature = "__regex")] highlight_rules: &[HighlightRule],
+    #[cfg(feature = "__search")] search_term: &Option<Regex>,
+    #[cfg(feature = "__search")] search_range: Option<&Range<usize>>,
+    #[cfg(feature = "__search")] search_highlight: Option<HighlightMode>,
+    #[cfg(feature = "__search")] known_matches: Option<&SearchIndex>,
+    #[cfg(feature = "__search")] cancel: Option<&dyn Fn() -> bool>,
 ) -> (Rows, FormatResult) {
     let mut buffer = Vec::with_capacity(256);
     let format_opts = FormatOpts {
@@ -638,13 +1450,123 @@ pub(crate) fn make_format_lines(
         lines_count: 0,
         prev_unterminated: 0,
         cols,
-        #[cfg(feature = "search")]
+        #[cfg(feature = "__regex")]
+        styled_lines,
+        #[cfg(feature = "__regex")]
+        highlight_rules,
+        #[cfg(feature = "__search")]
         search_term,
+        #[cfg(feature = "__search")]
+        search_range,
+        #[cfg(feature = "__search")]
+        search_highlight,
+        #[cfg(feature = "__search")]
+        known_matches,
+        #[cfg(feature = "__search")]
+        cancel,
         line_wrapping,
+        line_number_format,
+        theme,
+        min_line_number_digits,
+        line_length_limit,
+        wrap_cache,
     };
     let fr = format_text_block(format_opts);
     (buffer, fr)
 }
 
+/// Format only the lines of `text` within `lines` the same way as [make_format_lines] would
+///
+/// This gives quick search results for a part of a large text. `total_lines` is the number of
+/// lines in `text` which is needed to get the same padding for line numbers. Indices of rows
+/// with search matches are relative to the first returned row.
+#[cfg(feature = "__search")]
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn make_format_lines_window(
+    text: &str,
+    lines: Range<usize>,
+    total_lines: usize,
+    line_numbers: LineNumbers,
+    cols: usize,
+    line_wrapping: bool,
+    line_transformer: Option<&LineTransformer>,
+    line_number_format: Option<&LineNumberFormat>,
+    theme: &Theme,
+    min_line_number_digits: usize,
+    line_length_limit: usize,
+    styled_lines: &BTreeSet<usize>,
+    highlight_rules: &[HighlightRule],
+    search_term: Option<&Regex>,
+    search_range: Option<&Range<usize>>,
+    highlight_mode: HighlightMode,
+) -> (Rows, BTreeSet<usize>) {
+    let line_number_digits = minus_core::utils::digits(total_lines).max(min_line_number_digits);
+    let text_width = text_width(cols, line_numbers.is_on(), line_number_digits);
+    let mut buffer = Vec::with_capacity(256);
+    let mut search_idx = BTreeSet::new();
+
+    for (idx, line) in text
+        .lines()
+        .enumerate()
+        .skip(lines.start)
+        .take(lines.end.saturating_sub(lines.start))
+    {
+        let line = line_transformer.map_or(Cow::Borrowed(line), |transform| {
+            Cow::Owned(transform(line, idx))
+        });
+        let styled = styled_lines.contains(&idx);
+        let line = truncate_line(line, line_length_limit);
+        let line = if styled {
+            line
+        } else {
+            apply_highlight_rules(line, highlight_rules)
+        };
+        let matches = match search_term {
+            Some(query) if search_range.map_or(true, |range| range.contains(&idx)) => {
+                search::line_matches(&line, query)
+            }
+            _ => Vec::new(),
+        };
+        search_idx.extend(
+            search::match_row_offsets(&line, &matches, text_width, line_wrapping)
+                .into_iter()
+                .map(|offset| buffer.len() + offset),
+        );
+        let rows = formatted_line(
+            &line,
+            line_number_digits,
+            idx,
+            line_numbers,
+            line_number_format,
+            theme,
+            cols,
+            line_wrapping,
+            None,
+            &matches,
+            Some(highlight_mode),
+            styled,
+        );
+        buffer.extend(rows.into_iter().map(Cow::into_owned));
+    }
+    (buffer, search_idx)
+}
+
+/// Search `query` across line boundaries in `text` the same way as it would be displayed
+///
+/// See [search::multiline_matches].
+#[cfg(feature = "__search")]
+pub(crate) fn multiline_search_index(
+    text: &str,
+    query: &Regex,
+    search_range: Option<&Range<usize>>,
+    line_length_limit: usize,
+) -> SearchIndex {
+    let lines = text
+        .lines()
+        .enumerate()
+        .map(|(idx, line)| (idx, truncate_line(Cow::Borrowed(line), line_length_limit)));
+    search::multiline_matches(lines, query, search_range)
+}
+
 #[cfg(test)]
 mod tests;
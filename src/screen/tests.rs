@@ -1,19 +1,43 @@
+#[cfg(feature = "__regex")]
+static NO_STYLED_LINES: std::collections::BTreeSet<usize> = std::collections::BTreeSet::new();
+
 mod unterminated {
     use crate::screen::{format_text_block, FormatOpts, Rows};
+    use crate::Theme;
+    use once_cell::sync::Lazy;
+
+    static THEME: Lazy<Theme> = Lazy::new(Theme::default);
 
-    const fn get_append_opts_template(text: &str) -> FormatOpts<Rows> {
+    pub(super) fn get_append_opts_template(text: &str) -> FormatOpts<'_, Rows> {
         FormatOpts {
             buffer: Vec::new(),
             text,
             attachment: None,
-            #[cfg(feature = "search")]
+            #[cfg(feature = "__search")]
             search_term: &None,
+            #[cfg(feature = "__search")]
+            search_range: None,
+            #[cfg(feature = "__search")]
+            search_highlight: Some(crate::search::HighlightMode::Auto),
+            #[cfg(feature = "__search")]
+            known_matches: None,
+            #[cfg(feature = "__search")]
+            cancel: None,
+            #[cfg(feature = "__regex")]
+            highlight_rules: &[],
+            #[cfg(feature = "__regex")]
+            styled_lines: &super::NO_STYLED_LINES,
             lines_count: 0,
             formatted_lines_count: 0,
             cols: 80,
             line_numbers: crate::LineNumbers::Disabled,
             prev_unterminated: 0,
             line_wrapping: true,
+            line_number_format: None,
+            theme: &THEME,
+            min_line_number_digits: 0,
+            line_length_limit: crate::screen::DEFAULT_LINE_LENGTH_LIMIT,
+            wrap_cache: None,
         }
     }
 
@@ -190,3 +214,515 @@ third line\n",
         assert_eq!(3, append_style.num_unterminated);
     }
 }
+
+#[cfg(feature = "__search")]
+mod cancel {
+    use crate::screen::format_text_block;
+
+    #[test]
+    fn abandons_formatting() {
+        let text = "line\n".repeat(1000);
+        let mut opts = super::unterminated::get_append_opts_template(&text);
+        let checks = std::cell::Cell::new(0);
+        let cancel = || {
+            checks.set(checks.get() + 1);
+            checks.get() > 2
+        };
+        opts.cancel = Some(&cancel);
+        let fr = format_text_block(opts);
+        assert!(fr.cancelled);
+        // Checked once for the first line and after every few lines from there
+        assert_eq!(checks.get(), 3);
+    }
+}
+
+mod line_transformer {
+    use crate::PagerState;
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    #[test]
+    fn transforms_only_displayed_lines() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let mut ps = PagerState::new().unwrap();
+        let counter = calls.clone();
+        ps.screen.line_transformer = Some(Box::new(move |line, idx| {
+            counter.fetch_add(1, Ordering::Relaxed);
+            format!("{idx}: {line}")
+        }));
+        ps.append_str(&"line\n".repeat(100));
+        // Only the rows that fit on the screen are drawn after an append
+        assert!(calls.swap(0, Ordering::Relaxed) <= ps.text_rows());
+        ps.format_lines();
+        assert_eq!(calls.load(Ordering::Relaxed), 0);
+        assert_eq!(ps.screen.formatted_lines[10], "line");
+
+        assert_eq!(*ps.display_rows(10, 12), ["10: line", "11: line"]);
+        assert_eq!(calls.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn keeps_rows_of_original_lines() {
+        let mut ps = PagerState::new().unwrap();
+        ps.cols = 4;
+        ps.screen.line_transformer = Some(Box::new(|line, _| line.to_uppercase().repeat(2)));
+        ps.append_str("abcdefgh\nxy\n");
+        assert_eq!(ps.screen.formatted_lines, ["abcd", "efgh", "xy"]);
+
+        assert_eq!(*ps.display_rows(0, 3), ["ABCD", "EFGH", "XYXY"]);
+        // Rows can start in the middle of a line
+        assert_eq!(*ps.display_rows(1, 2), ["EFGH"]);
+    }
+}
+
+mod line_number_format {
+    use crate::screen::{format_text_block, FormatOpts, LineNumberFormat, Rows};
+
+    #[test]
+    fn formats_first_row_of_each_line() {
+        let format: LineNumberFormat = Box::new(|n, width| format!("{n:0width$x}:"));
+        let mut buffer: Rows = Vec::new();
+        let opts = FormatOpts {
+            buffer: &mut buffer,
+            text: "first\nsecond line\n",
+            attachment: None,
+            #[cfg(feature = "__search")]
+            search_term: &None,
+            #[cfg(feature = "__search")]
+            search_range: None,
+            #[cfg(feature = "__search")]
+            search_highlight: Some(crate::search::HighlightMode::Auto),
+            #[cfg(feature = "__search")]
+            known_matches: None,
+            #[cfg(feature = "__search")]
+            cancel: None,
+            #[cfg(feature = "__regex")]
+            highlight_rules: &[],
+            #[cfg(feature = "__regex")]
+            styled_lines: &super::NO_STYLED_LINES,
+            lines_count: 9,
+            formatted_lines_count: 0,
+            cols: 16,
+            line_numbers: crate::LineNumbers::Enabled,
+            prev_unterminated: 0,
+            line_wrapping: true,
+            line_number_format: Some(&format),
+            theme: &crate::Theme::default(),
+            min_line_number_digits: 0,
+            line_length_limit: crate::screen::DEFAULT_LINE_LENGTH_LIMIT,
+            wrap_cache: None,
+        };
+        format_text_block(opts);
+        assert_eq!(
+            buffer,
+            vec!["     0a: first", "     0b: second", "         line"]
+        );
+    }
+}
+
+mod theme {
+    use crate::screen::{format_text_block, FormatOpts, Rows};
+    use crate::{ContentStyle, Theme};
+
+    #[test]
+    fn separator() {
+        let theme = Theme {
+            line_number: ContentStyle::new(),
+            line_number_separator: '|',
+        };
+        let mut buffer: Rows = Vec::new();
+        let opts = FormatOpts {
+            buffer: &mut buffer,
+            text: "first\nsecond\n",
+            attachment: None,
+            #[cfg(feature = "__search")]
+            search_term: &None,
+            #[cfg(feature = "__search")]
+            search_range: None,
+            #[cfg(feature = "__search")]
+            search_highlight: Some(crate::search::HighlightMode::Auto),
+            #[cfg(feature = "__search")]
+            known_matches: None,
+            #[cfg(feature = "__search")]
+            cancel: None,
+            #[cfg(feature = "__regex")]
+            highlight_rules: &[],
+            #[cfg(feature = "__regex")]
+            styled_lines: &super::NO_STYLED_LINES,
+            lines_count: 0,
+            formatted_lines_count: 0,
+            cols: 80,
+            line_numbers: crate::LineNumbers::Enabled,
+            prev_unterminated: 0,
+            line_wrapping: true,
+            line_number_format: None,
+            theme: &theme,
+            min_line_number_digits: 0,
+            line_length_limit: crate::screen::DEFAULT_LINE_LENGTH_LIMIT,
+            wrap_cache: None,
+        };
+        format_text_block(opts);
+        assert_eq!(buffer, vec!["     1| first", "     2| second"]);
+    }
+}
+
+mod hyperlinks {
+    use crate::screen::{carry_hyperlinks, hyperlink_at};
+    use std::borrow::Cow;
+
+    const LINK: &str = "\u{1b}]8;;https://example.com\u{1b}\\";
+    const END: &str = "\u{1b}]8;;\u{1b}\\";
+
+    #[test]
+    fn link_is_reopened_on_each_row() {
+        let mut rows = vec![
+            Cow::Owned(format!("see {LINK}this")),
+            Cow::Borrowed("long"),
+            Cow::Owned(format!("link{END} now")),
+            Cow::Borrowed("done"),
+        ];
+        carry_hyperlinks(&mut rows);
+        assert_eq!(
+            rows,
+            [
+                format!("see {LINK}this{END}"),
+                format!("{LINK}long{END}"),
+                format!("{LINK}link{END} now"),
+                "done".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn find_link_under_column() {
+        let row = format!("\u{1b}[1mgo{LINK} to\u{1b}[0m{END} x");
+        assert_eq!(hyperlink_at(&row, 1), None);
+        assert_eq!(
+            hyperlink_at(&row, 2),
+            Some("https://example.com".to_string())
+        );
+        assert_eq!(
+            hyperlink_at(&row, 4),
+            Some("https://example.com".to_string())
+        );
+        assert_eq!(hyperlink_at(&row, 5), None);
+        assert_eq!(hyperlink_at(&row, 10), None);
+    }
+}
+
+mod line_row_map {
+    use crate::PagerState;
+
+    #[test]
+    fn maps_wrapped_lines_to_rows() {
+        let mut ps = PagerState::new().unwrap();
+        ps.cols = 20;
+        ps.screen.orig_text = format!("first\n{}\nlast\n", "A".repeat(30));
+        ps.format_lines();
+
+        assert_eq!(ps.screen.row_of_line(0), Some(0));
+        assert_eq!(ps.screen.row_of_line(1), Some(1));
+        // Second line wraps into two rows
+        assert_eq!(ps.screen.row_of_line(2), Some(3));
+        assert_eq!(ps.screen.row_of_line(3), None);
+
+        assert_eq!(ps.screen.line_of_row(0), 0);
+        assert_eq!(ps.screen.line_of_row(1), 1);
+        assert_eq!(ps.screen.line_of_row(2), 1);
+        assert_eq!(ps.screen.line_of_row(3), 2);
+        assert_eq!(ps.screen.line_of_row(100), 2);
+    }
+}
+
+mod lines {
+    use crate::screen::Screen;
+
+    #[test]
+    fn get_lines_of_original_text() {
+        let screen = Screen {
+            orig_text: "first\nsecond\nthird\n".to_string(),
+            ..Screen::default()
+        };
+
+        assert_eq!(screen.get_line(1), Some("second"));
+        assert_eq!(screen.get_line(3), None);
+        assert_eq!(
+            screen.lines(1..5).collect::<Vec<_>>(),
+            vec!["second", "third"]
+        );
+        assert_eq!(screen.lines(2..1).count(), 0);
+    }
+}
+
+mod hex_dump {
+    use crate::screen::{hex_dump, is_binary};
+
+    #[test]
+    fn detects_binary_data() {
+        assert!(!is_binary("plain text ✓\n".as_bytes()));
+        assert!(is_binary(b"with\0nul"));
+        assert!(is_binary(b"invalid \xff utf-8"));
+    }
+
+    #[test]
+    fn formats_like_hexdump() {
+        let dump = hex_dump(b"Hello, world!\n\0\x01\x02\x03", 0);
+        assert_eq!(
+            dump,
+            "00000000  48 65 6c 6c 6f 2c 20 77  6f 72 6c 64 21 0a 00 01  |Hello, world!...|\n\
+             00000010  02 03                                             |..              |"
+        );
+        assert!(hex_dump(&[], 0).is_empty());
+        assert!(hex_dump(b"\0", 0x20).starts_with("00000020  00 "));
+    }
+}
+
+#[cfg(feature = "__regex")]
+mod highlight_rules {
+    use crate::pattern::Regex;
+    use crate::screen::{apply_highlight_rules, HighlightRule};
+    use crossterm::style::{ContentStyle, Stylize};
+    use std::borrow::Cow;
+
+    fn rule(pattern: &str, style: ContentStyle) -> HighlightRule {
+        HighlightRule {
+            regex: Regex::new(pattern).unwrap(),
+            style,
+        }
+    }
+
+    #[test]
+    fn colorizes_matches() {
+        let rules = [rule("[0-9]+", ContentStyle::new().red())];
+        assert_eq!(
+            apply_highlight_rules(Cow::Borrowed("took 25 ms"), &rules),
+            "took \u{1b}[38;5;9m25\u{1b}[0m ms"
+        );
+        // Lines without matches are left untouched
+        assert!(matches!(
+            apply_highlight_rules(Cow::Borrowed("no numbers"), &rules),
+            Cow::Borrowed(_)
+        ));
+    }
+
+    #[test]
+    fn restores_styles_of_the_line() {
+        let rules = [rule("ERROR", ContentStyle::new().red())];
+        // The rule matches across the escape sequence of the line and the line's own style gets
+        // restored after the match
+        assert_eq!(
+            apply_highlight_rules(Cow::Borrowed("\u{1b}[1mERR\u{1b}[4mOR done"), &rules),
+            "\u{1b}[1m\u{1b}[38;5;9mERR\u{1b}[4m\u{1b}[38;5;9mOR\u{1b}[0m\u{1b}[1m\u{1b}[4m done"
+        );
+    }
+
+    #[test]
+    fn last_rule_wins() {
+        let rules = [
+            rule("[a-z]+", ContentStyle::new().red()),
+            rule("b", ContentStyle::new().blue()),
+        ];
+        assert_eq!(
+            apply_highlight_rules(Cow::Borrowed("abc"), &rules),
+            "\u{1b}[38;5;9ma\u{1b}[0m\u{1b}[38;5;12mb\u{1b}[0m\u{1b}[38;5;9mc\u{1b}[0m"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "__search")]
+    fn layers_with_search_highlighting() {
+        let mut ps = crate::PagerState::new().unwrap();
+        ps.screen.highlight_rules = vec![rule("[0-9]+", ContentStyle::new().red())];
+        ps.search_state.search_term = Some(Regex::new("2").unwrap());
+        ps.screen.orig_text = "took 25 ms\n".to_string();
+        ps.format_lines();
+        assert_eq!(ps.search_state.search_idx.len(), 1);
+        assert_eq!(
+            ps.screen.formatted_lines[0],
+            "took \u{1b}[7m\u{1b}[38;5;9m2\u{1b}[27m5\u{1b}[0m ms"
+        );
+
+        // Like the line's own colors, the rule's style is moved after the search match
+        ps.search_state.highlight_mode = crate::search::HighlightMode::Fast;
+        ps.format_lines();
+        assert_eq!(
+            ps.screen.formatted_lines[0],
+            "took \u{1b}[7m2\u{1b}[27m\u{1b}[38;5;9m5\u{1b}[0m ms"
+        );
+    }
+}
+
+mod line_length_limit {
+    use crate::screen::{format_text_block, FormatOpts, Rows};
+
+    #[test]
+    fn truncates_long_lines() {
+        let text = format!("short\n{}é\n", "A".repeat(20));
+        let mut buffer: Rows = Vec::new();
+        let opts = FormatOpts {
+            buffer: &mut buffer,
+            text: &text,
+            attachment: None,
+            #[cfg(feature = "__search")]
+            search_term: &None,
+            #[cfg(feature = "__search")]
+            search_range: None,
+            #[cfg(feature = "__search")]
+            search_highlight: Some(crate::search::HighlightMode::Auto),
+            #[cfg(feature = "__search")]
+            known_matches: None,
+            #[cfg(feature = "__search")]
+            cancel: None,
+            #[cfg(feature = "__regex")]
+            highlight_rules: &[],
+            #[cfg(feature = "__regex")]
+            styled_lines: &super::NO_STYLED_LINES,
+            lines_count: 0,
+            formatted_lines_count: 0,
+            cols: 80,
+            line_numbers: crate::LineNumbers::Disabled,
+            prev_unterminated: 0,
+            line_wrapping: true,
+            line_number_format: None,
+            theme: &crate::Theme::default(),
+            min_line_number_digits: 0,
+            line_length_limit: 10,
+            wrap_cache: None,
+        };
+        let fr = format_text_block(opts);
+        assert_eq!(fr.lines_formatted, 2);
+        assert_eq!(buffer[0], "short");
+        assert_eq!(
+            buffer[1],
+            format!(
+                "{}\u{1b}[0m\u{1b}[7m[12 more bytes]\u{1b}[0m",
+                "A".repeat(10)
+            )
+        );
+    }
+
+    #[test]
+    fn escape_sequences_take_no_columns() {
+        use crate::screen::truncate_line;
+        use std::borrow::Cow;
+
+        let line = "\u{1b}[31mred\u{1b}[0m and more";
+        assert_eq!(
+            truncate_line(Cow::Borrowed(line), 5),
+            "\u{1b}[31mred\u{1b}[0m a\u{1b}[0m\u{1b}[7m[7 more bytes]\u{1b}[0m"
+        );
+        // Fits once the sequences are left out
+        let line = "\u{1b}[1mbold\u{1b}[0m";
+        assert_eq!(truncate_line(Cow::Borrowed(line), 4), line);
+
+        // Wide characters take two columns and open hyperlinks get closed
+        let line = "\u{1b}]8;;https://a.b\u{1b}\\日本語\u{1b}]8;;\u{1b}\\";
+        assert_eq!(
+            truncate_line(Cow::Borrowed(line), 5),
+            "\u{1b}]8;;https://a.b\u{1b}\\日本\u{1b}]8;;\u{1b}\\\u{1b}[0m\u{1b}[7m[10 more bytes]\u{1b}[0m"
+        );
+    }
+}
+
+mod wrap_cache {
+    use crate::screen::WrapCache;
+
+    #[test]
+    fn reuses_rows_of_previous_widths() {
+        let line = "Lorem ipsum dolor sit amet, consectetur adipiscing elit";
+        let mut cache = WrapCache::default();
+        cache.set_limit(10);
+        for width in [10, 20, 10, 20] {
+            assert_eq!(cache.wrap(line, 0, width), textwrap::wrap(line, width));
+        }
+        assert_eq!(cache.entries.len(), 2);
+        assert!(cache.entries.iter().all(|(_, lines)| lines[0].is_some()));
+    }
+
+    #[test]
+    fn changed_line_is_wrapped_again() {
+        let mut cache = WrapCache::default();
+        cache.set_limit(10);
+        cache.wrap("first version of the line", 3, 10);
+        let line = "the second version";
+        assert_eq!(cache.wrap(line, 3, 10), textwrap::wrap(line, 10));
+    }
+
+    #[test]
+    fn forgets_least_recently_used_width() {
+        let mut cache = WrapCache::default();
+        cache.set_limit(10);
+        for width in [10, 20, 30, 40, 10, 50] {
+            cache.wrap("A line", 0, width);
+        }
+        let widths = cache.entries.iter().map(|(w, _)| *w).collect::<Vec<_>>();
+        assert_eq!(widths, vec![30, 40, 10, 50]);
+    }
+
+    #[test]
+    fn enabled_by_default() {
+        let mut cache = WrapCache::default();
+        cache.wrap("A line", 0, 10);
+        assert!(cache.entries[0].1[0].is_some());
+        assert!(
+            cache
+                .wrap("A line", super::super::DEFAULT_WRAP_CACHE_LINES, 10)
+                .len()
+                == 1
+        );
+        assert_eq!(cache.entries[0].1.len(), 1);
+    }
+
+    #[test]
+    fn only_keeps_lines_up_to_limit() {
+        let mut cache = WrapCache::default();
+        cache.set_limit(2);
+        for idx in 0..4 {
+            assert_eq!(cache.wrap("A line", idx, 10), textwrap::wrap("A line", 10));
+        }
+        assert_eq!(cache.entries[0].1.len(), 2);
+
+        cache.set_limit(1);
+        assert_eq!(cache.entries[0].1.len(), 1);
+        cache.set_limit(0);
+        assert!(cache.entries.is_empty());
+    }
+}
+
+mod slice_columns {
+    use crate::screen::slice_columns;
+
+    #[test]
+    fn plain_text() {
+        assert_eq!(slice_columns("0123456789", 0, 3, 4), "3456");
+        assert_eq!(slice_columns("0123456789", 2, 3, 4), "015678");
+        assert_eq!(slice_columns("0123", 0, 10, 4), "");
+    }
+
+    #[test]
+    fn escapes_are_not_split() {
+        let row = "ab\u{1b}[31mcdef\u{1b}[0mgh";
+        // The color set before the left edge is still applied to the visible text
+        assert_eq!(slice_columns(row, 0, 3, 2), "\u{1b}[31mde\u{1b}[0m");
+        assert_eq!(slice_columns(row, 0, 1, 2), "b\u{1b}[31mc\u{1b}[0m");
+        assert_eq!(slice_columns(row, 0, 7, 5), "\u{1b}[31m\u{1b}[0mh");
+    }
+
+    #[test]
+    fn hyperlinks_stay_open() {
+        let link = "\u{1b}]8;;https://example.com\u{1b}\\";
+        let end = "\u{1b}]8;;\u{1b}\\";
+        let row = format!("go {link}there{end}");
+        assert_eq!(slice_columns(&row, 0, 5, 2), format!("{link}er{end}"));
+    }
+
+    #[test]
+    fn wide_characters() {
+        // Each of these takes two columns
+        let row = "日本語";
+        assert_eq!(slice_columns(row, 0, 1, 4), " 本");
+        assert_eq!(slice_columns(row, 0, 0, 3), "日");
+    }
+}
@@ -49,11 +49,32 @@
 //! let pager = Pager::new();
 //! pager.set_incremental_search_condition(Box::new(|_| true)).unwrap();
 //! ```
+//!
+//! Incremental search doesn't run on each keystroke. It waits until no key has been pressed at the
+//! prompt for a short interval and a run that is still going on when a key is pressed gets
+//! abandoned. This keeps the prompt responsive while typing long queries on big documents. The
+//! interval can be changed with
+//! [`Pager::set_incremental_search_debounce`](crate::pager::Pager::set_incremental_search_debounce).
+//!
+//! Each run first searches only the lines needed to fill the screen from the current position. If
+//! a match shows up there, it gets displayed right away and the search is extended to the rest of
+//! the text once no key is being pressed.
+//!
+//! # Multi-line Search
+//! Each line of the text is searched on its own by default. Applications can allow matches to
+//! span multiple lines with [`Pager::set_multiline_search`](crate::pager::Pager::set_multiline_search).
+//! Queries can then use `\n` or the `s` flag to match across line boundaries, like
+//! `(?s)fn main.*?\}`. All rows of such a match get highlighted and the row where it starts is
+//! used for jumping between matches.
 
 #![allow(unused_imports)]
 use crate::minus_core::utils::{display, term};
 use crate::screen::Screen;
-use crate::{error::MinusError, input::HashedEventRegister, screen};
+use crate::{
+    error::{MinusError, RegexError},
+    input::HashedEventRegister,
+    screen,
+};
 use crate::{LineNumbers, PagerState};
 use crossterm::{
     cursor::{self, MoveTo},
@@ -62,25 +83,39 @@ use crossterm::{
     terminal::{Clear, ClearType},
 };
 use once_cell::sync::Lazy;
-use regex::Regex;
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 use std::{
+    borrow::Cow,
     convert::{TryFrom, TryInto},
     io::Write,
-    time::Duration,
+    ops::Range,
+    time::{Duration, Instant},
 };
 
 use std::collections::hash_map::RandomState;
 
+// Patterns used internally by minus don't need look-around or backreferences
+#[cfg(feature = "regex")]
+use regex::Regex as BasicRegex;
+#[cfg(not(feature = "regex"))]
+use regex_lite::Regex as BasicRegex;
+
 static INVERT: Lazy<String> = Lazy::new(|| Attribute::Reverse.to_string());
 static NORMAL: Lazy<String> = Lazy::new(|| Attribute::NoReverse.to_string());
-static ANSI_REGEX: Lazy<Regex> = Lazy::new(|| {
-    Regex::new("[\\u001b\\u009b]\\[[()#;?]*(?:[0-9]{1,4}(?:;[0-9]{0,4})*)?[0-9A-ORZcf-nqry=><]")
-        .unwrap()
+// Matches CSI sequences like colors as well as OSC sequences like hyperlinks
+static ANSI_REGEX: Lazy<BasicRegex> = Lazy::new(|| {
+    BasicRegex::new(concat!(
+        "[\\u001b\\u009b]\\[[()#;?]*(?:[0-9]{1,4}(?:;[0-9]{0,4})*)?[0-9A-ORZcf-nqry=><]",
+        "|\\u001b\\][^\\u0007\\u001b]*(?:\\u0007|\\u001b\\\\)",
+    ))
+    .unwrap()
+});
+
+static WORD: Lazy<BasicRegex> = Lazy::new(|| {
+    BasicRegex::new(r#"([\w_]+)|([-?~@#!$%^&*()-+={}\[\]:;\\|'/?<>.,"]+)|\W"#).unwrap()
 });
 
-static WORD: Lazy<Regex> =
-    Lazy::new(|| Regex::new(r#"([\w_]+)|([-?~@#!$%^&*()-+={}\[\]:;\\|'/?<>.,"]+)|\W"#).unwrap());
+pub use crate::pattern::Regex;
 
 #[derive(Clone, Copy, Debug, Eq)]
 #[cfg_attr(docsrs, doc(cfg(feature = "search")))]
@@ -107,6 +142,295 @@ impl PartialEq for SearchMode {
     }
 }
 
+/// Split the prefix restricting the search to the visible page off a search query
+///
+/// Returns whether the query had the prefix along with the actual pattern. A query starting with
+/// `\@` searches the whole text for a pattern starting with a literal `@`.
+pub(crate) fn split_scope(query: &str) -> (bool, &str) {
+    if let Some(pattern) = query
+        .strip_prefix('\\')
+        .filter(|pattern| pattern.starts_with(PAGE_SCOPE_PREFIX))
+    {
+        return (false, pattern);
+    }
+    query
+        .strip_prefix(PAGE_SCOPE_PREFIX)
+        .map_or((false, query), |pattern| (true, pattern))
+}
+
+/// Prefix of a search query which restricts the search to the lines visible on the screen
+pub(crate) const PAGE_SCOPE_PREFIX: char = '@';
+
+/// Default time to wait after the last keystroke at the prompt before running incremental search
+pub(crate) const DEFAULT_INCREMENTAL_SEARCH_DEBOUNCE: Duration = Duration::from_millis(100);
+
+/// How long to wait for an event at the prompt when no incremental search is due
+const PROMPT_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Get the lines that lie in both ranges
+///
+/// Returns the other range if one of them is `None`.
+pub(crate) fn intersect_ranges(
+    a: Option<&Range<usize>>,
+    b: Option<&Range<usize>>,
+) -> Option<Range<usize>> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.start.max(b.start)..a.end.min(b.end)),
+        (a, b) => a.or(b).cloned(),
+    }
+}
+
+/// Search matches in the text, stored per line of the original text
+///
+/// Each line with a match is mapped to the byte ranges of its matches after removing ANSI escape
+/// sequences. Unlike the indices of the rows containing matches, this doesn't depend on how the
+/// text gets wrapped, hence it stays valid when the text only needs to be reformatted due to a
+/// change in the layout like resizing the terminal or toggling line numbers.
+///
+/// With multi-line search, a match spanning several lines is split into a range for each of
+/// them. Lines whose first range continues a match from a previous line are tracked separately
+/// so that only the line where a match starts is used as a jump target.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct SearchIndex {
+    lines: BTreeMap<usize, Vec<Range<usize>>>,
+    continued: BTreeSet<usize>,
+    /// Query and lines to which the search was restricted when building the index
+    pub(crate) built_for: Option<(String, Option<Range<usize>>)>,
+}
+
+impl SearchIndex {
+    /// Get the matches in the given line
+    pub(crate) fn get(&self, line: usize) -> Option<&[Range<usize>]> {
+        self.lines.get(&line).map(Vec::as_slice)
+    }
+
+    /// Record the matches in the given line, replacing any previous ones
+    ///
+    /// Lines without any matches are not stored.
+    pub(crate) fn insert(&mut self, line: usize, matches: Vec<Range<usize>>) {
+        self.continued.remove(&line);
+        if matches.is_empty() {
+            self.lines.remove(&line);
+        } else {
+            self.lines.insert(line, matches);
+        }
+    }
+
+    /// Add a match after all the other matches of the given line
+    ///
+    /// `continued` tells whether the range is the continuation of a match that started on a
+    /// previous line. Only the first range of a line can be one.
+    pub(crate) fn push(&mut self, line: usize, range: Range<usize>, continued: bool) {
+        let matches = self.lines.entry(line).or_default();
+        if continued && matches.is_empty() {
+            self.continued.insert(line);
+        }
+        matches.push(range);
+    }
+
+    /// Get the number of ranges at the start of the given line that continue a match from a
+    /// previous line
+    pub(crate) fn continued_count(&self, line: usize) -> usize {
+        usize::from(self.continued.contains(&line))
+    }
+
+    /// Get the number of matches starting in each line that has any
+    pub(crate) fn match_counts(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.lines
+            .iter()
+            .map(move |(line, matches)| (*line, matches.len() - self.continued_count(*line)))
+            .filter(|(_, count)| *count > 0)
+    }
+
+    /// Move all matches from `other` into this index
+    pub(crate) fn append(&mut self, other: &mut Self) {
+        self.lines.append(&mut other.lines);
+        self.continued.append(&mut other.continued);
+    }
+
+    /// Returns true if the index was built for the given query and lines
+    pub(crate) fn is_built_for(&self, query: &str, range: Option<&Range<usize>>) -> bool {
+        self.built_for
+            .as_ref()
+            .is_some_and(|(q, r)| q == query && r.as_ref() == range)
+    }
+}
+
+/// Get the byte ranges of all matches of `query` in `line` after removing ANSI escape sequences
+pub(crate) fn line_matches(line: &str, query: &Regex) -> Vec<Range<usize>> {
+    let stripped = ANSI_REGEX.replace_all(line, "");
+    query.find_ranges(&stripped).collect()
+}
+
+/// Search `query` in the complete text at once so that matches can span multiple lines
+///
+/// `lines` are the lines of the text along with their indices. The lines are joined with `\n`
+/// after removing ANSI escape sequences from them and each match is split into the parts lying
+/// in each of its lines. Only matches starting in `search_range` are kept.
+pub(crate) fn multiline_matches<'a>(
+    lines: impl Iterator<Item = (usize, Cow<'a, str>)>,
+    query: &Regex,
+    search_range: Option<&Range<usize>>,
+) -> SearchIndex {
+    let mut joined = String::new();
+    let mut line_starts = Vec::new();
+    for (idx, line) in lines {
+        line_starts.push((idx, joined.len()));
+        joined.push_str(&ANSI_REGEX.replace_all(&line, ""));
+        joined.push('\n');
+    }
+    let mut index = SearchIndex::default();
+    for m in query.find_ranges(&joined) {
+        let first = line_starts
+            .partition_point(|(_, start)| *start <= m.start)
+            .saturating_sub(1);
+        if search_range.is_some_and(|range| !range.contains(&line_starts[first].0)) {
+            continue;
+        }
+        for (pos, (idx, start)) in line_starts.iter().enumerate().skip(first) {
+            // Position of the newline that ends this line
+            let end = line_starts
+                .get(pos + 1)
+                .map_or(joined.len(), |(_, next)| *next)
+                - 1;
+            index.push(
+                *idx,
+                m.start.max(*start) - start..m.end.min(end) - start,
+                pos != first,
+            );
+            if m.end <= end + 1 {
+                break;
+            }
+        }
+    }
+    index
+}
+
+/// Get the rows in which `matches` start when `line` gets wrapped to `width` columns
+///
+/// The rows are relative to the first row of the line.
+pub(crate) fn match_row_offsets(
+    line: &str,
+    matches: &[Range<usize>],
+    width: usize,
+    line_wrapping: bool,
+) -> BTreeSet<usize> {
+    if matches.is_empty() {
+        return BTreeSet::new();
+    }
+    let stripped = ANSI_REGEX.replace_all(line, "");
+    if !line_wrapping || textwrap::core::display_width(&stripped) <= width {
+        return BTreeSet::from([0]);
+    }
+    // Find where each row starts in the stripped line. Since the escape sequences don't take any
+    // space, wrapping the stripped line breaks it at the same places as the original line
+    let mut cursor = 0;
+    let row_starts = textwrap::wrap(&stripped, width)
+        .iter()
+        .map(|row| {
+            let start = stripped[cursor..]
+                .find(row.as_ref())
+                .map_or(cursor, |pos| cursor + pos);
+            cursor = start + row.len();
+            start
+        })
+        .collect::<Vec<usize>>();
+    matches
+        .iter()
+        .map(|m| {
+            row_starts
+                .partition_point(|start| *start <= m.start)
+                .saturating_sub(1)
+        })
+        .collect()
+}
+
+/// Matches of the active search
+///
+/// See [`Pager::search_summary`](crate::Pager::search_summary)
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[allow(clippy::module_name_repetitions)]
+pub struct SearchSummary {
+    /// The search query
+    pub query: String,
+    /// Total number of matches in the text
+    pub total_matches: usize,
+    /// Number of matches in each line that has any, keyed by the line number starting from 1
+    ///
+    /// A match spanning multiple lines is counted only for the line where it starts.
+    pub line_matches: BTreeMap<usize, usize>,
+}
+
+/// How ANSI escape sequences within search matches are treated when highlighting them
+///
+/// See [`Pager::set_highlight_mode`](crate::Pager::set_highlight_mode)
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum HighlightMode {
+    /// Use [`HighlightMode::Accurate`] for lines containing escape sequences like colors and
+    /// [`HighlightMode::Fast`] for the rest. This is the default.
+    #[default]
+    Auto,
+    /// Move the escape sequences within a match to its end so that all matches look the same
+    ///
+    /// This is slightly faster but the colors within a match are lost and the text after a
+    /// match may get the style that was meant for a part of the match.
+    Fast,
+    /// Keep the escape sequences at their place so that the colors within a match are kept
+    Accurate,
+}
+
+impl HighlightMode {
+    /// Returns true if the escape sequences of `line` should be placed accurately
+    pub(crate) fn is_accurate_for(self, line: &str) -> bool {
+        match self {
+            Self::Auto => line.contains('\u{1b}') || line.contains('\u{9b}'),
+            Self::Fast => false,
+            Self::Accurate => true,
+        }
+    }
+}
+
+/// Where the view is placed when jumping to a search match
+///
+/// See [`Pager::set_match_placement`](crate::Pager::set_match_placement) for more info.
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(docsrs, doc(cfg(feature = "search")))]
+pub enum MatchPlacement {
+    /// Put the match at the top of the screen, below the
+    /// [scroll margin](crate::Pager::set_scroll_margin). This is the default.
+    #[default]
+    Top,
+    /// Put the match in the middle of the screen
+    Center,
+    /// Don't move the view if the match is already visible. Otherwise scroll just enough to
+    /// bring the match into view, keeping the scroll margin around it.
+    Minimal,
+}
+
+impl MatchPlacement {
+    /// Get the upper mark that brings `row` into view
+    ///
+    /// `upper_mark` is the current upper mark, `writable_rows` the number of rows available for
+    /// text and `jump_offset` the number of rows kept around the match.
+    pub(crate) const fn upper_mark(
+        self,
+        row: usize,
+        upper_mark: usize,
+        writable_rows: usize,
+        jump_offset: usize,
+    ) -> usize {
+        match self {
+            Self::Top => row.saturating_sub(jump_offset),
+            Self::Center => row.saturating_sub(writable_rows / 2),
+            Self::Minimal if row < upper_mark => row.saturating_sub(jump_offset),
+            Self::Minimal if row < upper_mark.saturating_add(writable_rows) => upper_mark,
+            Self::Minimal => (row + 1 + jump_offset).saturating_sub(writable_rows),
+        }
+    }
+}
+
 /// Options controlling the behaviour of search overall
 ///
 /// Although it isn't much important for most use cases but it alongside [IncrementalSearchOpts] are the key components
@@ -139,6 +463,13 @@ pub struct SearchOpts<'a> {
     pub incremental_search_options: Option<IncrementalSearchOpts<'a>>,
     incremental_search_cache: Option<IncrementalSearchCache>,
     compiled_regex: Option<Regex>,
+    /// Instant after which the debounced incremental search for the current query should run
+    incremental_search_due: Option<Instant>,
+    /// Whether the screen currently shows incremental search results
+    incremental_search_drawn: bool,
+    /// Whether only the lines around the initial upper mark have been searched for the current
+    /// query and the search still needs to be extended to the complete text
+    incremental_search_extend: bool,
 }
 
 /// Options to control incremental search
@@ -154,6 +485,20 @@ pub struct IncrementalSearchOpts<'a> {
     pub screen: &'a Screen,
     /// Value of [PagerState::upper_mark] before starting of search prompt
     pub initial_left_mark: usize,
+    /// Number of rows to keep above a search match when jumping to it
+    pub jump_offset: usize,
+    /// Where the view is placed when jumping to a search match
+    pub match_placement: MatchPlacement,
+    /// Lines to which searches are restricted by the application
+    pub search_range: Option<Range<usize>>,
+    /// Indices of the lines that are at least partially on the screen
+    pub visible_lines: Range<usize>,
+    /// Time to wait after the last keystroke before running incremental search
+    pub debounce: Duration,
+    /// Whether matches can span multiple lines
+    pub multiline: bool,
+    /// How escape sequences within search matches are treated when highlighting them
+    pub highlight_mode: HighlightMode,
 }
 
 impl<'a> From<&'a PagerState> for IncrementalSearchOpts<'a> {
@@ -163,6 +508,13 @@ impl<'a> From<&'a PagerState> for IncrementalSearchOpts<'a> {
             initial_upper_mark: ps.upper_mark,
             screen: &ps.screen,
             initial_left_mark: ps.left_mark,
+            jump_offset: ps.jump_offset(),
+            match_placement: ps.search_state.match_placement,
+            search_range: ps.search_state.search_range.clone(),
+            visible_lines: ps.input_context().visible_lines,
+            debounce: ps.search_state.incremental_search_debounce,
+            multiline: ps.search_state.multiline,
+            highlight_mode: ps.search_state.highlight_mode,
         }
     }
 }
@@ -187,11 +539,15 @@ impl<'a> From<&'a PagerState> for SearchOpts<'a> {
             cursor_position: 1,
             word_index: Vec::with_capacity(200),
             search_char,
-            rows: ps.rows.try_into().unwrap(),
-            cols: ps.cols.try_into().unwrap(),
+            // Terminals can't be larger than this hence saturating is fine here
+            rows: u16::try_from(ps.rows).unwrap_or(u16::MAX),
+            cols: u16::try_from(ps.cols).unwrap_or(u16::MAX),
             incremental_search_options: Some(incremental_search_options),
             incremental_search_cache: None,
             compiled_regex: None,
+            incremental_search_due: None,
+            incremental_search_drawn: false,
+            incremental_search_extend: false,
             search_mode: ps.search_state.search_mode,
         }
     }
@@ -248,31 +604,131 @@ pub(crate) struct IncrementalSearchCache {
     pub(crate) search_mark: usize,
     /// Indices of formatted_lines where search matches have been found
     pub(crate) search_idx: BTreeSet<usize>,
+    /// Matches found in each line of the text
+    pub(crate) line_matches: SearchIndex,
     /// Index of the line from which to display the text.
     /// This will be set to the index of line which is after the current upper mark and will
     /// have a search match for sure
     pub(crate) upper_mark: usize,
 }
 
+/// Outcome of running incremental search
+enum IncrementalSearchRun {
+    /// The complete text was searched. Contains the cache if it found a match
+    Done(Option<IncrementalSearchCache>),
+    /// A match was found and drawn by searching only the lines around the initial upper mark
+    Partial,
+    /// The run was abandoned because a new event arrived while it was in progress
+    Cancelled,
+}
+
+/// Search only the lines needed to fill the screen around the initial upper mark
+///
+/// If a match is found on the page starting at the initial upper mark, the page containing it is
+/// drawn and `true` is returned. Otherwise nothing is drawn and the complete text needs to be
+/// searched to find the next match.
+fn run_window_search<O>(
+    out: &mut O,
+    so: &SearchOpts<'_>,
+    iso: &IncrementalSearchOpts<'_>,
+    search_range: Option<&Range<usize>>,
+) -> crate::Result<bool>
+where
+    O: Write,
+{
+    let writable_rows = usize::from(so.rows).saturating_sub(1);
+    let total_rows = iso.screen.formatted_lines_count();
+    if total_rows == 0 {
+        return Ok(false);
+    }
+    // Depending on the match placement, the page may start up to a screenful before the initial
+    // upper mark and end up to a screenful after the match
+    let window_start = iso.initial_upper_mark.saturating_sub(writable_rows);
+    let window_end = iso
+        .initial_upper_mark
+        .saturating_add(writable_rows.saturating_mul(2))
+        .min(total_rows);
+    let first_line = iso.screen.line_of_row(window_start);
+    let last_line = iso.screen.line_of_row(window_end.saturating_sub(1));
+    let first_row = iso.screen.row_of_line(first_line).unwrap_or(0);
+
+    let (buffer, search_idx) = screen::make_format_lines_window(
+        &iso.screen.orig_text,
+        first_line..last_line + 1,
+        iso.screen.line_count(),
+        iso.line_numbers,
+        so.cols.into(),
+        iso.screen.line_wrapping,
+        iso.screen.line_transformer.as_ref(),
+        iso.screen.line_number_format.as_ref(),
+        &iso.screen.theme,
+        iso.screen.min_line_number_digits,
+        iso.screen.line_length_limit,
+        &iso.screen.styled_lines,
+        &iso.screen.highlight_rules,
+        so.compiled_regex.as_ref(),
+        search_range,
+        iso.highlight_mode,
+    );
+    let Some(row) = search_idx
+        .iter()
+        .map(|idx| first_row + idx)
+        .find(|row| *row >= iso.initial_upper_mark)
+        .filter(|row| *row < iso.initial_upper_mark + writable_rows)
+    else {
+        return Ok(false);
+    };
+    // Never scroll past the last page, just like write_text_checked
+    let upper_mark = iso
+        .match_placement
+        .upper_mark(row, iso.initial_upper_mark, writable_rows, iso.jump_offset)
+        .min(total_rows.saturating_sub(writable_rows));
+    let lower_mark = upper_mark.saturating_add(writable_rows).min(total_rows);
+    if upper_mark < first_row || lower_mark > first_row + buffer.len() {
+        return Ok(false);
+    }
+    display::write_text_checked(
+        out,
+        &buffer[upper_mark - first_row..lower_mark - first_row],
+        0,
+        so.rows.into(),
+        so.cols.into(),
+        iso.screen.line_wrapping,
+        iso.initial_left_mark,
+        iso.line_numbers,
+        iso.screen.line_number_digits(),
+    )?;
+    Ok(true)
+}
+
 /// Runs the incremental search
 ///
-/// It will return if `Ok(SomeIncrementalSearchCache)` if there was a successful run of incremental
-/// search otherwise it will return `Ok(None)`.
+/// It will return `Ok(IncrementalSearchRun::Done(Some(IncrementalSearchCache)))` if there was a
+/// successful run of incremental search otherwise the cache will be `None`. `should_cancel` is
+/// checked every few lines while the text is searched. Once it returns `true`, the search is
+/// abandoned, nothing is drawn and `Ok(IncrementalSearchRun::Cancelled)` is returned.
+///
+/// With `window_first`, only the lines around the initial upper mark are searched first. If a
+/// match is visible among them, it is drawn right away and `Ok(IncrementalSearchRun::Partial)`
+/// is returned. The search then needs to be run again without `window_first` to build the cache.
 ///
 /// # Errors
 /// This function will returns a `Err(MinusError)` if any operation on the terminal failed to
 /// execute.
+#[allow(clippy::too_many_lines)]
 fn run_incremental_search<'a, F, O>(
     out: &mut O,
     so: &'a SearchOpts<'a>,
     incremental_search_condition: F,
-) -> crate::Result<Option<IncrementalSearchCache>>
+    should_cancel: &dyn Fn() -> bool,
+    window_first: bool,
+) -> crate::Result<IncrementalSearchRun>
 where
     O: Write,
     F: Fn(&'a SearchOpts) -> bool,
 {
     if so.incremental_search_options.is_none() {
-        return Ok(None);
+        return Ok(IncrementalSearchRun::Done(None));
     }
     let iso = so.incremental_search_options.as_ref().unwrap();
 
@@ -293,7 +749,7 @@ where
             iso.screen.line_wrapping,
             iso.initial_left_mark,
             iso.line_numbers,
-            iso.screen.line_count(),
+            iso.screen.line_number_digits(),
         )?;
         Ok(())
     };
@@ -301,9 +757,9 @@ where
     // If the query prior to the current one had a successful incremental search run and now the
     // current query isn't a valid regex or the incremental search condition has returned false
     // then
-    if so.incremental_search_cache.is_some() && !should_proceed {
+    if so.incremental_search_drawn && !should_proceed {
         reset_screen(out, so)?;
-        return Ok(None);
+        return Ok(IncrementalSearchRun::Done(None));
     }
 
     // Return immediately if search query isn't valid or incremental search condition is false
@@ -311,50 +767,169 @@ where
     // will cover all the cases of the above statement's condition and hence the terminal will ever
     // get reset
     if !should_proceed {
-        return Ok(None);
+        return Ok(IncrementalSearchRun::Done(None));
+    }
+
+    let (page_scoped, _) = split_scope(&so.string);
+    let search_range = intersect_ranges(
+        iso.search_range.as_ref(),
+        page_scoped.then_some(&iso.visible_lines),
+    );
+    // Multi-line matches may start outside of the window, so the complete text is always searched
+    if window_first && !iso.multiline && run_window_search(out, so, iso, search_range.as_ref())? {
+        return Ok(IncrementalSearchRun::Partial);
+    }
+    let multiline_index = iso.multiline.then(|| {
+        screen::multiline_search_index(
+            &iso.screen.orig_text,
+            so.compiled_regex.as_ref().unwrap(),
+            search_range.as_ref(),
+            iso.screen.line_length_limit,
+        )
+    });
+
+    if should_cancel() {
+        return Ok(IncrementalSearchRun::Cancelled);
     }
 
     // Format the text with search highlights and get the index of the element in
     // format_result.append_search_idx which is after the current upper mark
-    //
-    // PERF: Check if this can be futhur optimized
     let (buffer, format_result) = screen::make_format_lines(
         &iso.screen.orig_text,
         iso.line_numbers,
         so.cols.into(),
         iso.screen.line_wrapping,
+        iso.screen.line_number_format.as_ref(),
+        &iso.screen.theme,
+        iso.screen.min_line_number_digits,
+        iso.screen.line_length_limit,
+        None,
+        &iso.screen.styled_lines,
+        &iso.screen.highlight_rules,
         &so.compiled_regex,
+        search_range.as_ref(),
+        Some(iso.highlight_mode),
+        multiline_index.as_ref(),
+        Some(should_cancel),
     );
+    // Don't draw results for a query that the user is still typing
+    if format_result.cancelled || should_cancel() {
+        return Ok(IncrementalSearchRun::Cancelled);
+    }
     let position_of_next_match =
         next_nth_match(&format_result.append_search_idx, iso.initial_upper_mark, 0);
     // Get the upper mark. If we can't find one, reset the display
     let upper_mark;
     if let Some(pnm) = position_of_next_match {
-        upper_mark = *format_result.append_search_idx.iter().nth(pnm).unwrap();
-        // Draw the incrementally searched lines from upper mark
+        upper_mark = iso.match_placement.upper_mark(
+            *format_result.append_search_idx.iter().nth(pnm).unwrap(),
+            iso.initial_upper_mark,
+            usize::from(so.rows).saturating_sub(1),
+            iso.jump_offset,
+        );
+        // Draw the incrementally searched lines from upper mark. Only the rows that get displayed
+        // are transformed, hence the last page is picked here like write_text_checked would
+        let writable_rows = usize::from(so.rows).saturating_sub(1);
+        let first_row = upper_mark.min(buffer.len().saturating_sub(writable_rows));
+        let rows = iso.screen.transformed_rows(
+            &buffer,
+            first_row,
+            first_row.saturating_add(writable_rows),
+            iso.line_numbers,
+            so.cols.into(),
+            multiline_index
+                .as_ref()
+                .unwrap_or(&format_result.append_line_matches),
+            Some(iso.highlight_mode),
+        );
         display::write_text_checked(
             out,
-            &buffer,
-            upper_mark,
+            &rows,
+            0,
             so.rows.into(),
             so.cols.into(),
             iso.screen.line_wrapping,
             iso.initial_left_mark,
             iso.line_numbers,
-            iso.screen.line_count(),
+            iso.screen.line_number_digits(),
         )?;
     } else {
         reset_screen(out, so)?;
-        return Ok(None);
+        return Ok(IncrementalSearchRun::Done(None));
     }
     // Return the results obtained by running incremental search so that they can be stored as a
     // cache.
-    Ok(Some(IncrementalSearchCache {
+    Ok(IncrementalSearchRun::Done(Some(IncrementalSearchCache {
         formatted_lines: buffer,
         search_mark: position_of_next_match.unwrap(),
         upper_mark,
         search_idx: format_result.append_search_idx,
-    }))
+        line_matches: multiline_index.unwrap_or(format_result.append_line_matches),
+    })))
+}
+
+/// Run incremental search for the current query and store its outcome in `so`
+///
+/// The first run for a query searches only the lines around the initial upper mark. If that
+/// finds a match, the search is immediately due again so that [fetch_input] extends it to the
+/// complete text once no events are pending.
+/// Returns `false` if the run was cancelled.
+fn update_incremental_search<O, F>(
+    out: &mut O,
+    so: &mut SearchOpts<'_>,
+    incremental_search_condition: F,
+    should_cancel: &dyn Fn() -> bool,
+) -> crate::Result<bool>
+where
+    O: Write,
+    F: Fn(&SearchOpts<'_>) -> bool,
+{
+    let window_first = !so.incremental_search_extend;
+    match run_incremental_search(
+        out,
+        so,
+        incremental_search_condition,
+        should_cancel,
+        window_first,
+    )? {
+        IncrementalSearchRun::Done(cache) => {
+            so.incremental_search_drawn = cache.is_some();
+            so.incremental_search_cache = cache;
+            so.incremental_search_extend = false;
+            so.incremental_search_due = None;
+        }
+        IncrementalSearchRun::Partial => {
+            so.incremental_search_drawn = true;
+            so.incremental_search_extend = true;
+            so.incremental_search_due = Some(Instant::now());
+        }
+        IncrementalSearchRun::Cancelled => return Ok(false),
+    }
+    Ok(true)
+}
+
+/// Run the incremental search that was deferred by [handle_key_press]
+///
+/// This should be called by [fetch_input] once [SearchOpts::incremental_search_due] has passed.
+/// If the run gets cancelled, it stays due so that it is retried once the pending events have
+/// been handled.
+fn run_due_incremental_search<O, F>(
+    out: &mut O,
+    so: &mut SearchOpts<'_>,
+    incremental_search_condition: F,
+    should_cancel: &dyn Fn() -> bool,
+) -> crate::Result
+where
+    O: Write,
+    F: Fn(&SearchOpts<'_>) -> bool,
+{
+    if !update_incremental_search(out, so, incremental_search_condition, should_cancel)? {
+        return Ok(());
+    }
+    // Drawing the text moves the cursor, so bring it back to the prompt
+    term::move_cursor(out, so.cursor_position, so.rows, false)?;
+    out.flush()?;
+    Ok(())
 }
 
 /// Respond to keyboard events
@@ -372,7 +947,10 @@ where
 {
     // Bounds between which our cursor can move
     const FIRST_AVAILABLE_COLUMN: u16 = 1;
-    let last_available_column: u16 = so.string.len().saturating_add(1).try_into().unwrap();
+    // Queries longer than the terminal can't be fully displayed anyway, hence the positions are
+    // saturated rather than failing
+    let last_available_column =
+        u16::try_from(so.string.len().saturating_add(1)).unwrap_or(u16::MAX);
 
     // If no event is present, abort
     if so.ev.is_none() {
@@ -382,18 +960,28 @@ where
     let populate_word_index = |so: &mut SearchOpts<'_>| {
         so.word_index = WORD
             .find_iter(&so.string)
-            .map(|c| c.start().saturating_add(1).try_into().unwrap())
+            .map(|c| u16::try_from(c.start().saturating_add(1)).unwrap_or(u16::MAX))
             .collect::<Vec<u16>>();
     };
 
     let refresh_display = |out: &mut O, so: &mut SearchOpts<'_>| -> Result<(), MinusError> {
         // Cache the compiled regex if the regex is valid
-        so.compiled_regex = Regex::new(&so.string).ok();
+        so.compiled_regex = Regex::new(split_scope(&so.string).1).ok();
 
         // Run incremental search and update the upper mark if incremental search had a successful
-        // run otherwise set it to the initial upper mark
-        so.incremental_search_cache =
-            run_incremental_search(out, so, incremental_search_condition)?;
+        // run otherwise set it to the initial upper mark.
+        // With a debounce interval set, only schedule it. fetch_input runs it once no key
+        // has been pressed for that long
+        so.incremental_search_extend = false;
+        let debounce = so
+            .incremental_search_options
+            .as_ref()
+            .map_or(Duration::ZERO, |iso| iso.debounce);
+        if debounce.is_zero() {
+            update_incremental_search(out, so, &incremental_search_condition, &|| false)?;
+        } else {
+            so.incremental_search_due = Some(Instant::now() + debounce);
+        }
 
         // Update prompt
         term::move_cursor(out, 0, so.rows, false)?;
@@ -530,7 +1118,7 @@ where
             modifiers: KeyModifiers::NONE,
             ..
         }) => {
-            so.cursor_position = so.string.len().saturating_add(1).try_into().unwrap();
+            so.cursor_position = last_available_column;
             term::move_cursor(out, so.cursor_position, so.rows, true)?;
         }
 
@@ -560,7 +1148,7 @@ where
 /// set to either [InputStatus::Cancelled] or [InputStatus::Confirmed] by pressing `Esc` or
 /// `Enter` respectively.
 /// Finally we return
-#[cfg(feature = "search")]
+#[cfg(feature = "__search")]
 pub(crate) fn fetch_input(
     out: &mut impl std::io::Write,
     ps: &PagerState,
@@ -577,7 +1165,12 @@ pub(crate) fn fetch_input(
     // - Clear the prompt
     // - Write the search character and
     // - Show the cursor
-    term::move_cursor(out, 0, ps.rows.try_into().unwrap(), false)?;
+    term::move_cursor(
+        out,
+        0,
+        ps.rows.try_into().map_err(|_| MinusError::Conversion)?,
+        false,
+    )?;
     write!(
         out,
         "{}{}{}",
@@ -589,10 +1182,27 @@ pub(crate) fn fetch_input(
 
     let mut search_opts = SearchOpts::from(ps);
 
+    // A pending event means the user is still typing, so any in-flight incremental search
+    // should be abandoned
+    let input_pending = || ps.backend.poll_event(Duration::ZERO).unwrap_or(false);
+
     // Fetch events from the terminal and handle them
     loop {
-        if event::poll(Duration::from_millis(100)).map_err(|e| MinusError::HandleEvent(e.into()))? {
-            let ev = event::read().map_err(|e| MinusError::HandleEvent(e.into()))?;
+        let timeout = search_opts
+            .incremental_search_due
+            .map_or(PROMPT_POLL_INTERVAL, |due| {
+                due.saturating_duration_since(Instant::now())
+                    .min(PROMPT_POLL_INTERVAL)
+            });
+        if ps
+            .backend
+            .poll_event(timeout)
+            .map_err(|e| MinusError::HandleEvent(e.into()))?
+        {
+            let ev = ps
+                .backend
+                .read_event()
+                .map_err(|e| MinusError::HandleEvent(e.into()))?;
             search_opts.ev = Some(ev);
             handle_key_press(
                 out,
@@ -604,12 +1214,30 @@ pub(crate) fn fetch_input(
         if search_opts.input_status.done() {
             break;
         }
+        if search_opts
+            .incremental_search_due
+            .is_some_and(|due| due <= Instant::now())
+        {
+            run_due_incremental_search(
+                out,
+                &mut search_opts,
+                &ps.search_state.incremental_search_condition,
+                &input_pending,
+            )?;
+        }
     }
     // Teardown: almost opposite of setup
-    term::move_cursor(out, 0, ps.rows.try_into().unwrap(), false)?;
+    term::move_cursor(
+        out,
+        0,
+        ps.rows.try_into().map_err(|_| MinusError::Conversion)?,
+        false,
+    )?;
     write!(out, "{}{}", Clear(ClearType::CurrentLine), cursor::Hide)?;
     out.flush()?;
 
+    // A cache left behind by a query that was edited afterwards is stale
+    let cache_is_fresh = search_opts.incremental_search_due.is_none();
     let fetch_input_result = match search_opts.input_status {
         InputStatus::Active => unreachable!(),
         InputStatus::Cancelled => FetchInputResult::new_empty(),
@@ -617,7 +1245,9 @@ pub(crate) fn fetch_input(
         // in the cache
         InputStatus::Confirmed => FetchInputResult {
             string: search_opts.string,
-            incremental_search_result: search_opts.incremental_search_cache,
+            incremental_search_result: search_opts
+                .incremental_search_cache
+                .filter(|_| cache_is_fresh),
             compiled_regex: search_opts.compiled_regex,
         },
     };
@@ -628,11 +1258,8 @@ pub(crate) fn fetch_input(
 ///
 /// The first return value returns the line that has all the search matches highlighted
 /// The second tells whether a search match was actually found
-pub(crate) fn highlight_line_matches(
-    line: &str,
-    query: &regex::Regex,
-    accurate: bool,
-) -> (String, bool) {
+#[cfg(test)]
+pub(crate) fn highlight_line_matches(line: &str, query: &Regex, accurate: bool) -> (String, bool) {
     // Remove all ansi escapes so we can look through it as if it had none
     let stripped_str = ANSI_REGEX.replace_all(line, "");
 
@@ -641,6 +1268,44 @@ pub(crate) fn highlight_line_matches(
         return (line.to_string(), false);
     }
 
+    let match_ranges = query.find_ranges(&stripped_str).collect::<Vec<_>>();
+    (highlight_ranges(line, &match_ranges, accurate), true)
+}
+
+/// Highlights the given byte ranges of `line`
+///
+/// The ranges are positions in the line after removing all ANSI escape sequences from it, in
+/// increasing order and must not overlap.
+pub(crate) fn highlight_ranges(
+    line: &str,
+    match_ranges: &[Range<usize>],
+    accurate: bool,
+) -> String {
+    invert_ranges(line, match_ranges, accurate, false)
+}
+
+/// Highlights the given byte ranges of a line added with
+/// [`Pager::push_styled_lines`](crate::Pager::push_styled_lines)
+///
+/// Each segment of such a line resets the style at its end, which would also end the highlight
+/// of a match spanning several segments. Hence the highlight is applied again after every escape
+/// sequence within a match, keeping the colors of the segments.
+pub(crate) fn highlight_styled_ranges(line: &str, match_ranges: &[Range<usize>]) -> String {
+    invert_ranges(line, match_ranges, true, true)
+}
+
+/// Invert the colors of `match_ranges` in `line`, see [`highlight_ranges`]
+///
+/// If `reinvert` is true, the inversion is written again after each escape sequence within a
+/// match.
+fn invert_ranges(
+    line: &str,
+    match_ranges: &[Range<usize>],
+    accurate: bool,
+    reinvert: bool,
+) -> String {
+    let stripped_str = ANSI_REGEX.replace_all(line, "");
+
     // sum_width is used to calculate the total width of the ansi escapes
     // up to the point in the original string where it is being used
     let mut sum_width = 0;
@@ -649,7 +1314,7 @@ pub(crate) fn highlight_line_matches(
     // to a Vec<(usize, &str)> where
     //   .0 == the start index in the STRIPPED string
     //   .1 == the escape sequence itself
-    let escapes = ANSI_REGEX
+    let mut escapes = ANSI_REGEX
         .find_iter(line)
         .map(|escape| {
             let start = escape.start();
@@ -658,62 +1323,82 @@ pub(crate) fn highlight_line_matches(
             sum_width += as_str.len();
             ret
         })
-        .collect::<Vec<_>>();
-
-    // The matches of the term you're looking for, so that you can easily determine where
-    // the invert attributes will be placed
-    let matches = query
-        .find_iter(&stripped_str)
-        .flat_map(|c| [c.start(), c.end()])
-        .collect::<Vec<_>>();
-
-    // Highlight all the instances of the search term in the stripped string
-    // by inverting their background/foreground colors
-    let mut inverted = query
-        .replace_all(&stripped_str, |caps: &regex::Captures| {
-            format!("{}{}{}", *INVERT, &caps[0], *NORMAL)
-        })
-        .to_string();
-
-    // inserted_escs_len == the total length of the ascii escapes which have been re-inserted
-    // into the stripped string at the point where it is being checked.
-    let mut inserted_escs_len = 0;
-    for esc in escapes {
-        let match_count = matches.iter().take_while(|m| **m <= esc.0).count();
-        // Find how many invert|normal markers appear before this escape
-
-        // find the number of invert strings and number of uninvert strings that have been
-        // inserted up to this point in the string
-        let num_invert = match_count / 2;
-        let num_normal = match_count - num_invert;
-
-        // calculate the index which this escape should be re-inserted at by adding
-        // its position in the stripped string to the total length of the ansi escapes
-        // (both highlighting and the ones from the original string).
-        // TODO: Add more docs to this
-        let mut pos = if !accurate && match_count % 2 == 1 {
-            // INFO: Its safe to unwrap here
-            matches.get(match_count).unwrap()
-                + NORMAL.len()
-                + inserted_escs_len
-                + (num_invert * INVERT.len())
-                + (num_normal * NORMAL.len())
-        } else {
-            esc.0 + inserted_escs_len + (num_invert * INVERT.len()) + (num_normal * NORMAL.len())
-        };
+        .collect::<Vec<_>>()
+        .into_iter()
+        .peekable();
 
-        if match_count % 2 == 1 {
-            pos = pos.saturating_sub(1);
+    // Walk through the boundaries of the matches, i.e. the start and end of each match in order,
+    // inverting the text between them and re-inserting the escapes that lie before each
+    // boundary. An escape at the same position as a boundary is placed after it.
+    //
+    // Unless the placement is accurate, escapes within a match are moved to the end of the match
+    // so that all the matches look the same. OSC sequences like hyperlinks don't affect the
+    // colors hence they are always placed accurately so that the text they wrap stays the same
+    let mut inverted =
+        String::with_capacity(line.len() + match_ranges.len() * (INVERT.len() + NORMAL.len()));
+    let mut deferred = String::new();
+    let mut in_match = false;
+    let mut last_end = 0;
+    let boundaries = match_ranges.iter().flat_map(|m| [m.start, m.end]);
+    for boundary in boundaries {
+        while let Some((pos, esc)) = escapes.next_if(|(pos, _)| *pos < boundary) {
+            inverted.push_str(&stripped_str[last_end..pos]);
+            last_end = pos;
+            if in_match && !accurate && !esc.starts_with("\u{1b}]") {
+                deferred.push_str(esc);
+            } else {
+                inverted.push_str(esc);
+                if in_match && reinvert {
+                    inverted.push_str(&INVERT);
+                }
+            }
         }
-
-        // insert the escape back in
-        inverted.insert_str(pos, esc.1);
-
-        // increment the length of the escapes inserted back in
-        inserted_escs_len += esc.1.len();
+        inverted.push_str(&stripped_str[last_end..boundary]);
+        last_end = boundary;
+        if in_match {
+            inverted.push_str(&NORMAL);
+            inverted.push_str(&deferred);
+            deferred.clear();
+        } else {
+            inverted.push_str(&INVERT);
+        }
+        in_match = !in_match;
+    }
+    for (pos, esc) in escapes {
+        inverted.push_str(&stripped_str[last_end..pos]);
+        last_end = pos;
+        inverted.push_str(esc);
     }
+    inverted.push_str(&stripped_str[last_end..]);
+
+    inverted
+}
 
-    (inverted, true)
+/// Get the parts of `matches` that lie within each row of `line`
+///
+/// `rows` are the rows in which `line` got wrapped. The ranges for each row are relative to its
+/// start, after removing ANSI escape sequences like `matches`.
+pub(crate) fn row_match_ranges<'a>(
+    line: &str,
+    rows: impl Iterator<Item = &'a str>,
+    matches: &[Range<usize>],
+) -> Vec<Vec<Range<usize>>> {
+    let stripped = ANSI_REGEX.replace_all(line, "");
+    let mut cursor = 0;
+    rows.map(|row| {
+        let row = ANSI_REGEX.replace_all(row, "");
+        let start = stripped[cursor..]
+            .find(row.as_ref())
+            .map_or(cursor, |pos| cursor + pos);
+        let end = start + row.len();
+        cursor = end;
+        matches
+            .iter()
+            .filter(|m| m.start < end && (m.end > start || m.start == start))
+            .map(|m| m.start.max(start) - start..m.end.min(end) - start)
+            .collect()
+    })
+    .collect()
 }
 
 /// Return a index of an element from `search_idx` that will contain a search match and
@@ -807,6 +1492,9 @@ mod tests {
                 incremental_search_options: None,
                 incremental_search_cache: None,
                 compiled_regex: None,
+                incremental_search_due: None,
+                incremental_search_drawn: false,
+                incremental_search_extend: false,
                 search_mode: sm,
             }
         }
@@ -1078,13 +1766,179 @@ mod tests {
     }
 
     #[allow(clippy::trivial_regex)]
+    #[test]
+    fn test_search_scope() {
+        assert_eq!(super::split_scope("@error"), (true, "error"));
+        assert_eq!(super::split_scope("[@]error"), (false, "[@]error"));
+        assert_eq!(super::split_scope("\\@error"), (false, "@error"));
+        assert_eq!(super::split_scope("\\\\@error"), (false, "\\\\@error"));
+        assert_eq!(super::intersect_ranges(Some(&(0..10)), None), Some(0..10));
+        assert_eq!(
+            super::intersect_ranges(Some(&(0..10)), Some(&(5..20))),
+            Some(5..10)
+        );
+        assert_eq!(super::intersect_ranges(None, None), None);
+    }
+
+    #[test]
+    fn test_match_row_offsets() {
+        use std::collections::BTreeSet;
+
+        let line = "one two three four";
+        let query = super::Regex::new("t[wh]").unwrap();
+        let matches = super::line_matches(line, &query);
+        assert_eq!(matches, [4..6, 8..10]);
+
+        assert_eq!(
+            super::match_row_offsets(line, &matches, 80, true),
+            BTreeSet::from([0])
+        );
+        // Wrapped as "one", "two", "three", "four"
+        assert_eq!(
+            super::match_row_offsets(line, &matches, 5, true),
+            BTreeSet::from([1, 2])
+        );
+        assert_eq!(
+            super::match_row_offsets(line, &matches, 5, false),
+            BTreeSet::from([0])
+        );
+        // Escape sequences don't affect where the matches lie
+        let colored = "one \x1b[31mtwo\x1b[0m three four";
+        assert_eq!(super::line_matches(colored, &query), matches);
+        assert_eq!(
+            super::match_row_offsets(colored, &matches, 5, true),
+            BTreeSet::from([1, 2])
+        );
+    }
+
+    #[test]
+    fn highlight_mode() {
+        use super::HighlightMode;
+
+        assert!(!HighlightMode::Auto.is_accurate_for("plain"));
+        assert!(HighlightMode::Auto.is_accurate_for("\x1b[31mred\x1b[0m"));
+        assert!(!HighlightMode::Fast.is_accurate_for("\x1b[31mred\x1b[0m"));
+        assert!(HighlightMode::Accurate.is_accurate_for("plain"));
+    }
+
+    #[test]
+    fn test_multiline_matches() {
+        use std::borrow::Cow;
+
+        let text = "alpha\nbeta gamma\ndelta\nbeta\ngamma";
+        let lines = || text.lines().map(Cow::Borrowed).enumerate();
+        let query = super::Regex::new("gamma\ndelta|beta\ngamma").unwrap();
+        let index = super::multiline_matches(lines(), &query, None);
+        assert_eq!(index.get(1), Some(&[5..10][..]));
+        assert_eq!(index.get(2), Some(&[0..5][..]));
+        assert_eq!(index.get(3), Some(&[0..4][..]));
+        assert_eq!(index.get(4), Some(&[0..5][..]));
+        // Only the lines where the matches start are jump targets
+        assert_eq!(index.continued_count(1), 0);
+        assert_eq!(index.continued_count(2), 1);
+        assert_eq!(index.continued_count(4), 1);
+
+        // Matches are kept only if they start within the search range
+        let index = super::multiline_matches(lines(), &query, Some(&(2..4)));
+        assert_eq!(index.get(1), None);
+        assert_eq!(index.get(2), None);
+        assert_eq!(index.get(3), Some(&[0..4][..]));
+        assert_eq!(index.get(4), Some(&[0..5][..]));
+
+        // Queries without newlines behave the same as searching each line
+        let query = super::Regex::new("a$").unwrap();
+        let index = super::multiline_matches(lines(), &query, None);
+        assert_eq!(index.get(0), None);
+        let query = super::Regex::new("(?m)a$").unwrap();
+        let index = super::multiline_matches(lines(), &query, None);
+        assert_eq!(index.get(0), Some(&[4..5][..]));
+        assert_eq!(index.get(3), Some(&[3..4][..]));
+    }
+
+    #[test]
+    fn incremental_search_window_first() {
+        use crate::{search::SearchOpts, PagerState, SearchMode};
+
+        let mut ps = PagerState::new().unwrap();
+        ps.search_state.search_mode = SearchMode::Forward;
+        let text = (0..5000).map(|i| format!("line {i}\n")).collect::<String>();
+        ps.append_str(&text);
+        ps.upper_mark = 2000;
+
+        let mut so = SearchOpts::from(&ps);
+        so.string = "line 2003".to_string();
+        so.compiled_regex = super::Regex::new(&so.string).ok();
+
+        // A match on the current page is drawn from the lines around it
+        let mut out = Vec::new();
+        super::update_incremental_search(&mut out, &mut so, |_| true, &|| false).unwrap();
+        assert!(so.incremental_search_extend);
+        assert!(so.incremental_search_cache.is_none());
+        let drawn = String::from_utf8(out).unwrap();
+        assert!(drawn.contains(&format!("{}line 2003{}", *super::INVERT, *super::NORMAL)));
+        assert!(drawn.contains("line 2011"));
+        assert!(!drawn.contains("line 2012"));
+
+        // The next run completes the index
+        super::update_incremental_search(&mut Vec::new(), &mut so, |_| true, &|| false).unwrap();
+        assert!(!so.incremental_search_extend);
+        let cache = so.incremental_search_cache.unwrap();
+        assert_eq!(cache.upper_mark, 2003);
+        assert_eq!(cache.search_idx.len(), 1);
+
+        // Matches beyond the current page need the complete text to be searched
+        let mut so = SearchOpts::from(&ps);
+        so.string = "line 4000".to_string();
+        so.compiled_regex = super::Regex::new(&so.string).ok();
+        super::update_incremental_search(&mut Vec::new(), &mut so, |_| true, &|| false).unwrap();
+        assert!(!so.incremental_search_extend);
+        assert_eq!(so.incremental_search_cache.unwrap().upper_mark, 4000);
+    }
+
+    #[test]
+    fn incremental_search_debounce() {
+        use crate::{search::SearchOpts, PagerState, SearchMode};
+        use crossterm::event::{Event, KeyCode, KeyEvent};
+
+        let mut ps = PagerState::new().unwrap();
+        ps.search_state.search_mode = SearchMode::Forward;
+        let text = (0..50).map(|i| format!("line {i}\n")).collect::<String>();
+        ps.append_str(&text);
+
+        let mut so = SearchOpts::from(&ps);
+        let mut out = Vec::new();
+        for c in "line 4".chars() {
+            so.ev = Some(Event::Key(KeyEvent::from(KeyCode::Char(c))));
+            super::handle_key_press(&mut out, &mut so, |_| true).unwrap();
+        }
+        // Typing only schedules the run
+        assert!(so.incremental_search_due.is_some());
+        assert!(so.incremental_search_cache.is_none());
+
+        // The first run only searches around the current page
+        super::run_due_incremental_search(&mut out, &mut so, |_| true, &|| true).unwrap();
+        assert!(so.incremental_search_extend);
+        assert!(so.incremental_search_due.is_some());
+        assert!(so.incremental_search_cache.is_none());
+
+        // Extending it to the whole text gets abandoned on a new keystroke and stays due
+        super::run_due_incremental_search(&mut out, &mut so, |_| true, &|| true).unwrap();
+        assert!(so.incremental_search_extend);
+        assert!(so.incremental_search_due.is_some());
+        assert!(so.incremental_search_cache.is_none());
+
+        super::run_due_incremental_search(&mut out, &mut so, |_| true, &|| false).unwrap();
+        assert!(so.incremental_search_due.is_none());
+        assert_eq!(so.incremental_search_cache.unwrap().upper_mark, 4);
+    }
+
     mod highlighting {
         use std::collections::BTreeSet;
 
+        use crate::search::Regex;
         use crate::search::{highlight_line_matches, next_nth_match, INVERT, NORMAL};
         use crate::PagerState;
         use crossterm::style::Attribute;
-        use regex::Regex;
 
         // generic escape code
         const ESC: &str = "\x1b[34m";
@@ -1109,6 +1963,18 @@ eros.",
                 assert_eq!(highlight_line_matches(&line, &pat, false).0, result);
             }
 
+            #[test]
+            #[cfg(feature = "fancy-regex")]
+            fn look_around() {
+                let res = highlight_line_matches(
+                    "test tested",
+                    &Regex::new(r"test(?=ed)").unwrap(),
+                    false,
+                );
+                assert_eq!(res.0, format!("test {}test{}ed", *INVERT, *NORMAL));
+                assert!(res.1);
+            }
+
             #[test]
             fn no_match() {
                 let orig = "no match";
@@ -1181,6 +2047,16 @@ eros.",
                 );
             }
 
+            #[test]
+            fn hyperlink_after_moved_escape() {
+                // The hyperlink stays in place while the color is moved after the match. Neither
+                // of them should get inserted inside the other
+                let link = "\x1b]8;;https://example.com\x1b\\";
+                let orig = format!("a te{NONE}s{link}t");
+                let res = highlight_line_matches(&orig, &Regex::new("test").unwrap(), false);
+                assert_eq!(res.0, format!("a {}tes{link}t{}{NONE}", *INVERT, *NORMAL));
+            }
+
             #[test]
             fn esc_pair_within_match() {
                 let orig = format!("this is a t{ESC}es{NONE}t again");
@@ -1206,6 +2082,18 @@ eros.",
                     )
                 );
             }
+
+            #[test]
+            fn hyperlink_is_not_matched() {
+                let link = "\u{1b}]8;;https://test.com\u{1b}\\";
+                let end = "\u{1b}]8;;\u{7}";
+                let orig = format!("a {link}test{end} link");
+                let res = highlight_line_matches(&orig, &Regex::new("test").unwrap(), false);
+                assert_eq!(
+                    res.0,
+                    format!("a {}{link}test{}{end} link", *INVERT, *NORMAL)
+                );
+            }
         }
         mod accurate {
             use super::*;
@@ -1,37 +1,284 @@
 //! Contains types that hold run-time information of the pager.
 
-#[cfg(feature = "search")]
-use crate::search::{SearchMode, SearchOpts};
+#[cfg(feature = "__search")]
+use crate::search::{
+    self, HighlightMode, MatchPlacement, SearchIndex, SearchMode, SearchOpts, SearchSummary,
+};
 
 use crate::{
+    backend::{Backend, Capabilities, CrosstermBackend},
+    config::PagerConfig,
     error::{MinusError, TermError},
-    input::{self, HashedEventRegister},
+    input::{self, macros::Macros, HashedEventRegister},
     minus_core::{
         self,
-        utils::{display::AppendStyle, LinesRowMap},
+        utils::{
+            display::{self, AppendStyle, Region},
+            term::TermModes,
+        },
         CommandQueue,
     },
     screen::{self, Screen},
-    ExitStrategy, LineNumbers,
+    AnsiMode, BinaryMode, ExitReason, ExitStrategy, FeedbackMode, InterruptBehavior, LineNumbers,
+    PagerEvent, ScrollAmounts, WindowTitle,
+};
+use crossterm::{
+    event::{Event, KeyCode, KeyEvent, KeyboardEnhancementFlags},
+    terminal,
+    tty::IsTty,
 };
-use crossterm::{terminal, tty::IsTty};
-#[cfg(feature = "search")]
+#[cfg(feature = "__search")]
 use parking_lot::Condvar;
 use parking_lot::Mutex;
-#[cfg(feature = "search")]
-use std::collections::BTreeSet;
+#[cfg(feature = "__search")]
+use std::collections::BTreeMap;
+#[cfg(feature = "__search")]
+use std::collections::HashMap;
 use std::{
-    collections::hash_map::RandomState,
+    borrow::Cow,
+    collections::{BTreeSet, VecDeque},
     convert::TryInto,
-    io::stdout,
-    io::Stdout,
-    sync::{atomic::AtomicBool, Arc},
+    io::{self, stdout},
+    ops::Range,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
 };
 
-use crate::minus_core::{commands::Command, ev_handler::handle_event};
-use crossbeam_channel::Receiver;
+use crate::minus_core::{channel::CommandReceiver, ev_handler::handle_event};
+use crossbeam_channel::Sender;
+
+/// Function that returns the text for the given range of lines. See [`Pager::set_data_provider`]
+///
+/// [`Pager::set_data_provider`]: crate::Pager::set_data_provider
+pub(crate) type DataProvider =
+    Box<dyn FnMut(Range<usize>) -> Option<String> + Send + Sync + 'static>;
+
+/// Function that renders the prompt. See [`Pager::set_prompt_renderer`]
+///
+/// [`Pager::set_prompt_renderer`]: crate::Pager::set_prompt_renderer
+pub(crate) type PromptRenderer = Box<dyn Fn(&PromptContext<'_>) -> String + Send + Sync + 'static>;
+
+/// Time between two frames of the scroll animation
+const SCROLL_FRAME: Duration = Duration::from_millis(16);
+
+/// How long the prompt stays in reverse video with [`FeedbackMode::Flash`]
+const FLASH_DURATION: Duration = Duration::from_millis(150);
+/// Number of messages that can wait behind the displayed one before the oldest ones are dropped
+pub(crate) const MAX_PENDING_MESSAGES: usize = 8;
+
+/// A jump that is being animated
+pub(crate) struct ScrollAnimation {
+    from: usize,
+    to: usize,
+    start: Instant,
+    duration: Duration,
+}
+
+impl ScrollAnimation {
+    /// Upper mark after `elapsed` time, slowing down towards the end
+    fn position(&self, elapsed: Duration) -> usize {
+        let duration = self.duration.as_millis().max(1);
+        let t = (elapsed.as_millis() * 1000 / duration).min(1000);
+        // Ease out cubic, in thousandths
+        let eased = 1000 - (1000 - t).pow(3) / 1_000_000;
+        let distance = self.to.abs_diff(self.from) as u128 * eased / 1000;
+        let distance = distance.try_into().unwrap_or(usize::MAX);
+        if self.to > self.from {
+            self.from.saturating_add(distance)
+        } else {
+            self.from.saturating_sub(distance)
+        }
+    }
+}
+
+/// A text along with the position of the view on it and its search, while it is not displayed
+///
+/// The text and the view being displayed live in the [`PagerState`] itself. They are moved into
+/// here when something else is displayed in their place and moved back when they are displayed
+/// again.
+pub(crate) struct SavedView {
+    pub(crate) text: String,
+    raw_bytes: Option<Vec<u8>>,
+    /// See [`Screen::styled_lines`](crate::screen::Screen::styled_lines)
+    pub(crate) styled_lines: BTreeSet<usize>,
+    upper_mark: usize,
+    left_mark: usize,
+    #[cfg(feature = "__search")]
+    search_term: Option<crate::search::Regex>,
+    #[cfg(feature = "__search")]
+    search_mark: usize,
+}
+
+impl SavedView {
+    const fn new(text: String) -> Self {
+        Self {
+            text,
+            raw_bytes: None,
+            styled_lines: BTreeSet::new(),
+            upper_mark: 0,
+            left_mark: 0,
+            #[cfg(feature = "__search")]
+            search_term: None,
+            #[cfg(feature = "__search")]
+            search_mark: 0,
+        }
+    }
+}
+
+/// One of the texts held by the pager. See [`Pager::add_buffer`](crate::Pager::add_buffer)
+pub(crate) struct Buffer {
+    pub(crate) name: String,
+    pub(crate) view: SavedView,
+}
+
+impl Buffer {
+    const fn new(name: String, text: String) -> Self {
+        Self {
+            name,
+            view: SavedView::new(text),
+        }
+    }
+}
+
+/// Counters describing the work done by the pager
+///
+/// See [`Pager::metrics`](crate::Pager::metrics)
+#[cfg(feature = "metrics")]
+#[cfg_attr(docsrs, doc(cfg(feature = "metrics")))]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Metrics {
+    /// Bytes of data received from the application
+    pub bytes_received: usize,
+    /// Lines formatted, including the ones formatted again after a resize or a change in the
+    /// settings
+    pub lines_formatted: usize,
+    /// Number of times the entire screen was drawn
+    pub full_redraws: usize,
+    /// Number of times only some rows were drawn, like the rows scrolled into view or the rows of
+    /// appended text
+    pub partial_redraws: usize,
+    /// Number of times some text was formatted
+    pub formats: usize,
+    /// Total time spent formatting the text
+    pub format_time: Duration,
+}
+
+#[cfg(feature = "metrics")]
+impl Metrics {
+    /// Average time taken to format some text
+    ///
+    /// Returns `None` if nothing has been formatted yet.
+    #[must_use]
+    pub fn average_format_time(&self) -> Option<Duration> {
+        let formats = self.formats.try_into().unwrap_or(u32::MAX);
+        self.format_time.checked_div(formats)
+    }
+
+    /// Count `lines` formatted since `started`
+    pub(crate) fn record_format(&mut self, lines: usize, started: Instant) {
+        self.lines_formatted += lines;
+        self.formats += 1;
+        self.format_time += started.elapsed();
+    }
+}
+
+/// A read-only view of the [`PagerState`] at some point of time
+///
+/// See [`Pager::state_snapshot`](crate::Pager::state_snapshot)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StateSnapshot {
+    /// The row at the top of the screen
+    pub upper_mark: usize,
+    /// Line number, starting from 1, of the line at the top of the screen
+    pub top_line: usize,
+    /// Total number of lines in the text
+    pub total_lines: usize,
+    /// Number of rows of the terminal
+    pub rows: usize,
+    /// Number of columns of the terminal
+    pub cols: usize,
+    /// Whether follow mode is on
+    pub follow_output: bool,
+    /// The active search query if any
+    #[cfg(feature = "__search")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "search")))]
+    pub search_query: Option<String>,
+    /// The line number mode
+    pub line_numbers: LineNumbers,
+}
 
-#[cfg(feature = "search")]
+/// Where the user was and how the view was configured
+///
+/// This can be saved with [`Pager::save_session`] and restored in the next invocation with
+/// [`Pager::restore_session`] so that the user can continue from exactly where they left off.
+/// When the `serde` feature is enabled, this can be serialized and deserialized.
+///
+/// [`Pager::save_session`]: crate::Pager::save_session
+/// [`Pager::restore_session`]: crate::Pager::restore_session
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Session {
+    /// Line number, starting from 1, of the line at the top of the screen
+    pub top_line: usize,
+    /// Columns scrolled to the right when line wrapping is off
+    pub left_mark: usize,
+    /// The active search query if any
+    ///
+    /// This is ignored when the `search` feature is not enabled.
+    pub search_query: Option<String>,
+    /// The line number mode
+    pub line_numbers: LineNumbers,
+    /// Whether line wrapping is on
+    pub line_wrapping: bool,
+    /// Whether follow mode is on
+    pub follow_output: bool,
+}
+
+/// Information available for rendering the prompt
+///
+/// See [`Pager::set_prompt_renderer`](crate::Pager::set_prompt_renderer)
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct PromptContext<'a> {
+    /// The prompt set with [`Pager::set_prompt`](crate::Pager::set_prompt)
+    pub prompt: &'a str,
+    /// The message being displayed if any
+    pub message: Option<&'a str>,
+    /// Number of messages waiting to be displayed after the current one
+    pub pending_messages: usize,
+    /// Line number, starting from 1, of the line at the top of the screen
+    pub top_line: usize,
+    /// Total number of lines in the text
+    pub total_lines: usize,
+    /// Percentage of the text that has been scrolled through
+    pub percent: usize,
+    /// Whether the end of the text is visible
+    pub end_visible: bool,
+    /// Whether follow mode is on
+    pub follow_output: bool,
+    /// Register into which the user is recording a macro, if any. See
+    /// [`Pager::set_macros`](crate::Pager::set_macros)
+    pub recording_macro: Option<char>,
+    /// Whether the pager is waiting for more data. See [`InputEvent::WaitForData`]
+    ///
+    /// [`InputEvent::WaitForData`]: crate::input::InputEvent::WaitForData
+    pub waiting_for_data: bool,
+    /// Number of columns of the terminal
+    pub cols: usize,
+    /// The active search query if any
+    #[cfg(feature = "__search")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "search")))]
+    pub search_query: Option<&'a str>,
+    /// Number, starting from 1, of the current search match and the total number of matches
+    #[cfg(feature = "__search")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "search")))]
+    pub search_match: Option<(usize, usize)>,
+}
+
+#[cfg(feature = "__search")]
 #[cfg_attr(docsrs, doc(cfg(feature = "search")))]
 #[allow(clippy::module_name_repetitions)]
 /// Contains information about the current search
@@ -41,13 +288,33 @@ pub struct SearchState {
     /// See [`SearchMode`] for available options
     pub search_mode: SearchMode,
     /// Stores the most recent search term
-    pub(crate) search_term: Option<regex::Regex>,
-    /// Lines where searches have a match
-    /// In order to avoid duplicate entries of lines, we keep it in a [`BTreeSet`]
+    pub(crate) search_term: Option<crate::search::Regex>,
+    /// Rows where searches have a match
+    /// In order to avoid duplicate entries of rows, we keep it in a [`BTreeSet`]
+    ///
+    /// These are derived from `line_matches` each time the text gets formatted
     pub(crate) search_idx: BTreeSet<usize>,
+    /// Matches of the search in each line of the original text
+    pub(crate) line_matches: SearchIndex,
     /// Index of search item currently in focus
     /// It should be 0 even when no search is in action
     pub(crate) search_mark: usize,
+    /// Where the view is placed when jumping to a search match
+    pub(crate) match_placement: MatchPlacement,
+    /// Lines to which searches are restricted by the application
+    pub(crate) search_range: Option<Range<usize>>,
+    /// Lines that were visible when the active search was restricted to the page
+    pub(crate) page_range: Option<Range<usize>>,
+    /// Whether the search matches are highlighted
+    pub(crate) highlight: bool,
+    /// How escape sequences within search matches are treated when highlighting them
+    pub(crate) highlight_mode: HighlightMode,
+    /// Whether queries are matched against the complete text so that matches can span multiple
+    /// lines
+    pub(crate) multiline: bool,
+    /// Time to wait after the last keystroke at the search prompt before running incremental
+    /// search
+    pub(crate) incremental_search_debounce: Duration,
     /// Function to run before running an incremental search.
     ///
     /// If the function returns a `false`, the incremental search is cancelled.
@@ -55,7 +322,15 @@ pub struct SearchState {
         Box<dyn Fn(&SearchOpts) -> bool + Send + Sync + 'static>,
 }
 
-#[cfg(feature = "search")]
+#[cfg(feature = "__search")]
+impl SearchState {
+    /// Lines to which the active search is restricted, if any
+    pub(crate) fn active_range(&self) -> Option<Range<usize>> {
+        search::intersect_ranges(self.search_range.as_ref(), self.page_range.as_ref())
+    }
+}
+
+#[cfg(feature = "__search")]
 impl Default for SearchState {
     fn default() -> Self {
         let incremental_search_condition = Box::new(|so: &SearchOpts| {
@@ -72,7 +347,15 @@ impl Default for SearchState {
             search_mode: SearchMode::Unknown,
             search_term: None,
             search_idx: BTreeSet::new(),
+            line_matches: SearchIndex::default(),
             search_mark: 0,
+            match_placement: MatchPlacement::default(),
+            search_range: None,
+            page_range: None,
+            highlight: true,
+            highlight_mode: HighlightMode::default(),
+            multiline: false,
+            incremental_search_debounce: search::DEFAULT_INCREMENTAL_SEARCH_DEBOUNCE,
             incremental_search_condition,
         }
     }
@@ -88,6 +371,7 @@ impl Default for SearchState {
 /// Various fields are made public so that their values can be accessed while implementing the
 /// trait.
 #[allow(clippy::module_name_repetitions)]
+#[allow(clippy::struct_excessive_bools)]
 pub struct PagerState {
     /// Configuration for line numbers. See [`LineNumbers`]
     pub line_numbers: LineNumbers,
@@ -95,6 +379,10 @@ pub struct PagerState {
     /// The first element contains the actual message, while the second element tells
     /// whether the message has changed since the last display.
     pub message: Option<String>,
+    /// Messages waiting to be displayed after the current one
+    ///
+    /// At most [`MAX_PENDING_MESSAGES`] are kept, the oldest ones are dropped first.
+    pub(crate) pending_messages: VecDeque<String>,
     /// The upper bound of scrolling.
     ///
     /// This is useful for keeping track of the range of lines which are currently being displayed on
@@ -115,8 +403,8 @@ pub struct PagerState {
     ///
     /// **WARNING: This item has been deprecated in favour of [SearchState::search_mode] availlable
     /// by the [PagerState::search_state] field. Any new code should prefer using it instead of this one.**
-    #[cfg(feature = "search")]
-    #[cfg_attr(docsrs, cfg(feature = "search"))]
+    #[cfg(feature = "__search")]
+    #[cfg_attr(docsrs, cfg(feature = "__search"))]
     pub search_mode: SearchMode,
     /// Available rows in the terminal
     pub rows: usize,
@@ -128,12 +416,20 @@ pub struct PagerState {
     pub prefix_num: String,
     /// Describes whether minus is running and in which mode
     pub running: &'static Mutex<crate::RunMode>,
-    #[cfg(feature = "search")]
-    #[cfg_attr(docsrs, cfg(feature = "search"))]
+    #[cfg(feature = "__search")]
+    #[cfg_attr(docsrs, cfg(feature = "__search"))]
     pub search_state: SearchState,
     pub screen: Screen,
+    /// Number of rows moved by the default scrolling bindings. See [`ScrollAmounts`]
+    pub scroll_amounts: ScrollAmounts,
     /// The prompt displayed at the bottom wrapped to available terminal width
     pub(crate) prompt: String,
+    /// Function rendering the prompt in place of the default one
+    pub(crate) prompt_renderer: Option<PromptRenderer>,
+    /// Text displayed on the rows after the end of the text
+    pub(crate) eob_filler: String,
+    /// Indicator displayed in the prompt when the end of the text is visible
+    pub(crate) eof_marker: Option<String>,
     /// The input classifier to be called when a input is detected
     pub(crate) input_classifier: Box<dyn input::InputClassifier + Sync + Send>,
     /// Functions to run when the pager quits
@@ -141,22 +437,147 @@ pub struct PagerState {
     /// The behaviour to do when user quits the program using `q` or `Ctrl+C`
     /// See [`ExitStrategy`] for available options
     pub(crate) exit_strategy: ExitStrategy,
+    /// What to do when the user presses `Ctrl+C`
+    pub(crate) interrupt_behavior: InterruptBehavior,
     /// The prompt that should be displayed to the user, formatted with the
     /// current search index and number of matches (if the search feature is enabled),
     /// and the current numbers inputted to scroll
     pub(crate) displayed_prompt: String,
     /// Whether to show the prompt on the screen
     pub(crate) show_prompt: bool,
+    /// Whether the last row of the screen was given to the prompt when the text was last drawn
+    ///
+    /// See [`PagerState::prompt_shown`]
+    pub(crate) prompt_drawn: bool,
     /// Do we want to page if there is no overflow
     #[cfg(feature = "static_output")]
-    pub(crate) run_no_overflow: bool,
-    pub(crate) lines_to_row_map: LinesRowMap,
+    pub(crate) force_pager: bool,
+    /// Number of rows that the text must exceed for the pager to start. If `None`, this is the
+    /// number of rows in the terminal
+    #[cfg(feature = "static_output")]
+    pub(crate) pager_threshold: Option<usize>,
     /// Value for follow mode.
     /// See [follow_output](crate::pager::Pager::follow_output) for more info on follow mode.
     pub(crate) follow_output: bool,
+    /// Whether follow mode was paused because the user scrolled away from the bottom
+    ///
+    /// Follow mode is resumed once the user scrolls back to the bottom.
+    pub(crate) follow_paused: bool,
+    /// Whether the pager is waiting for data like `less +F`
+    ///
+    /// See [`InputEvent::WaitForData`](input::InputEvent::WaitForData)
+    pub(crate) waiting_for_data: bool,
+    /// Current frame of the spinner shown while waiting for data
+    pub(crate) wait_spinner: usize,
+    /// Minimum number of rows to keep above the target row when jumping to it
+    pub(crate) scroll_margin: usize,
+    /// Whether to place the target row at the center of the screen when jumping to it
+    pub(crate) center_on_jump: bool,
+    /// Whether the application asked to quit before the pager started
+    pub(crate) quit_requested: bool,
+    /// Whether to quit once the user reaches the end of the text
+    pub(crate) quit_on_eof: bool,
+    /// Why the pager quit, if it has
+    pub(crate) exit_reason: Option<ExitReason>,
+    /// Channels to which [`PagerEvent`]s are sent
+    pub(crate) event_subscribers: Vec<Sender<PagerEvent>>,
+    /// Function that fetches fresh content when a reload is requested
+    pub(crate) reload_callback: Option<Box<dyn FnMut() -> String + Send + Sync + 'static>>,
+    /// Interval after which the content is automatically reloaded
+    pub(crate) reload_interval: Option<Duration>,
+    /// When was the content last reloaded
+    pub(crate) last_reload: Instant,
+    /// How long the input thread waits for an event before checking whether the pager has quit
+    ///
+    /// This is also how long the pager must go without receiving anything before the
+    /// [`idle_callback`](PagerState::idle_callback) is called.
+    pub(crate) poll_timeout: Duration,
+    /// Function called when nothing was received during the poll timeout
+    pub(crate) idle_callback: Option<Box<dyn FnMut() + Send + Sync + 'static>>,
+    /// When was a command last received from the application or the input thread
+    pub(crate) last_activity: Instant,
+    /// Counters describing the work done by the pager
+    #[cfg(feature = "metrics")]
+    pub(crate) metrics: Metrics,
+    /// Minimum time between two draws of appended data
+    ///
+    /// See [`Pager::set_max_refresh_rate`](crate::Pager::set_max_refresh_rate).
+    pub(crate) refresh_interval: Duration,
+    /// When was appended data last drawn
+    pub(crate) last_append_draw: Option<Instant>,
+    /// Whether appended data is waiting to be drawn
+    pub(crate) redraw_pending: bool,
+    /// Jump being animated, see [`ScrollAmounts::smooth_scroll`]
+    pub(crate) scroll_animation: Option<ScrollAnimation>,
+    /// Flag which makes the pager quit once it is set by the application
+    pub(crate) cancellation_flag: Option<Arc<AtomicBool>>,
+    /// Function that provides more lines when the user approaches the end of the text
+    ///
+    /// This is set to `None` once the provider signals that there is no more data.
+    pub(crate) data_provider: Option<DataProvider>,
+    /// What to do with ANSI escape sequences in the incoming text
+    pub(crate) ansi_mode: AnsiMode,
+    /// Whether the text pushed last into the first buffer ended with a `\r` that was held back
+    ///
+    /// The `\r` may be the start of a `\r\n` split across two appends, hence it is only
+    /// interpreted once the next text arrives.
+    pub(crate) pending_cr: bool,
+    /// Overstrike sequence at the end of the text pushed last into the first buffer that was
+    /// held back
+    ///
+    /// Like a trailing `\r`, a sequence like `X\b` can be split across two appends. To not make
+    /// every stream lag behind by a character, this is only done if the text contains backspaces.
+    pub(crate) pending_overstrike: String,
+    /// How binary data is displayed
+    pub(crate) binary_mode: BinaryMode,
+    /// The terminal on which the pager runs
+    pub(crate) backend: Arc<dyn Backend>,
+    /// Changes made to the terminal by this pager that have to be undone when it quits
+    pub(crate) term_modes: Arc<TermModes>,
+    /// Keyboard enhancement flags requested from the terminal when the pager starts
+    pub(crate) keyboard_enhancement: KeyboardEnhancementFlags,
+    /// Whether the mouse wheel is read through alternate scroll mode rather than mouse capture
+    pub(crate) alternate_scroll: bool,
+    /// Size of the terminal as `(cols, rows)` set by the application in place of the detected one
+    pub(crate) term_size_override: Option<(usize, usize)>,
+    /// Part of the terminal in which the pager is drawn, if not the entire terminal
+    pub(crate) region: Option<Region>,
+    /// Features of the terminal, detected when the pager starts
+    pub(crate) capabilities: Capabilities,
+    /// Whether all colors and text attributes are removed from the output
+    pub(crate) plain_render: bool,
+    /// What the title of the terminal window is set to
+    pub(crate) window_title: WindowTitle,
+    /// Keystrokes recorded by the user with `q{register}`
+    pub(crate) macros: Macros,
+    /// How the user is told about actions that could not be carried out
+    pub(crate) feedback: FeedbackMode,
+    /// When the prompt flashed by [`FeedbackMode::Flash`] should be restored
+    pub(crate) flash_until: Option<Instant>,
+    /// All the buffers held by the pager
+    ///
+    /// There is always at least one buffer. The first one is unnamed unless it has been replaced
+    /// by a buffer added through [`Pager::add_buffer`](crate::Pager::add_buffer).
+    pub(crate) buffers: Vec<Buffer>,
+    /// Index of the buffer being displayed
+    pub(crate) active_buffer: usize,
+    /// The command being typed after pressing `:`, if any
+    pub(crate) command_line: Option<String>,
+    /// Pattern to which `]` and `[` jump
+    #[cfg(feature = "__search")]
+    pub(crate) jump_pattern: Option<crate::search::Regex>,
+    /// Patterns of [`InputEvent::JumpToPattern`](crate::input::InputEvent::JumpToPattern)
+    /// compiled the first time they were used. Invalid patterns are kept as `None`
+    #[cfg(feature = "__search")]
+    pub(crate) jump_regexes: HashMap<&'static str, Option<crate::search::Regex>>,
+    /// Views hidden under temporary views like the buffer list, the bottommost first
+    ///
+    /// The view being displayed is temporary whenever this is not empty.
+    pub(crate) view_stack: Vec<SavedView>,
 }
 
 impl PagerState {
+    #[allow(clippy::too_many_lines)]
     pub(crate) fn new() -> Result<Self, TermError> {
         let (rows, cols);
 
@@ -192,24 +613,83 @@ impl PagerState {
             running: &minus_core::RUNMODE,
             left_mark: 0,
             exit_strategy: ExitStrategy::ProcessQuit,
-            input_classifier: Box::<HashedEventRegister<RandomState>>::default(),
+            interrupt_behavior: InterruptBehavior::default(),
+            input_classifier: {
+                let mut register = HashedEventRegister::with_fx_hasher();
+                input::generate_default_bindings(&mut register);
+                Box::new(register)
+            },
             exit_callbacks: Vec::with_capacity(5),
             message: None,
+            pending_messages: VecDeque::new(),
             screen: Screen::default(),
+            scroll_amounts: ScrollAmounts::default(),
             displayed_prompt: String::new(),
             show_prompt: true,
+            prompt_drawn: true,
+            #[cfg(feature = "static_output")]
+            force_pager: false,
             #[cfg(feature = "static_output")]
-            run_no_overflow: false,
-            #[cfg(feature = "search")]
+            pager_threshold: None,
+            #[cfg(feature = "__search")]
             search_mode: SearchMode::default(),
-            #[cfg(feature = "search")]
+            #[cfg(feature = "__search")]
             search_state: SearchState::default(),
             // Just to be safe in tests, keep at 1x1 size
             cols,
             rows,
             prefix_num: String::new(),
-            lines_to_row_map: LinesRowMap::new(),
             follow_output: false,
+            prompt_renderer: None,
+            eob_filler: String::new(),
+            eof_marker: None,
+            follow_paused: false,
+            waiting_for_data: false,
+            wait_spinner: 0,
+            scroll_margin: 0,
+            center_on_jump: false,
+            quit_requested: false,
+            quit_on_eof: false,
+            ansi_mode: AnsiMode::default(),
+            pending_cr: false,
+            pending_overstrike: String::new(),
+            binary_mode: BinaryMode::default(),
+            backend: Arc::new(CrosstermBackend::new()),
+            term_modes: Arc::new(TermModes::default()),
+            keyboard_enhancement: KeyboardEnhancementFlags::empty(),
+            alternate_scroll: false,
+            capabilities: Capabilities::default(),
+            plain_render: false,
+            window_title: WindowTitle::default(),
+            term_size_override: None,
+            region: None,
+            macros: Macros::default(),
+            feedback: FeedbackMode::default(),
+            flash_until: None,
+            exit_reason: None,
+            event_subscribers: Vec::new(),
+            reload_callback: None,
+            data_provider: None,
+            reload_interval: None,
+            last_reload: Instant::now(),
+            poll_timeout: Duration::from_millis(100),
+            idle_callback: None,
+            last_activity: Instant::now(),
+            refresh_interval: Duration::ZERO,
+            #[cfg(feature = "metrics")]
+            metrics: Metrics::default(),
+            last_append_draw: None,
+            redraw_pending: false,
+            scroll_animation: None,
+            cancellation_flag: None,
+            buffers: vec![Buffer::new(String::new(), String::new())],
+            active_buffer: 0,
+            command_line: None,
+            #[cfg(feature = "__search")]
+            jump_pattern: None,
+            #[cfg(feature = "__search")]
+            jump_regexes: HashMap::new(),
+            view_stack: Vec::new(),
         };
 
         state.format_prompt();
@@ -227,11 +707,10 @@ impl PagerState {
     /// # Errors
     /// This function will return an error if it could not create the default [`PagerState`] or fails
     /// to process the events
-    pub(crate) fn generate_initial_state(
-        rx: &Receiver<Command>,
-        mut out: &mut Stdout,
-    ) -> Result<Self, MinusError> {
+    pub(crate) fn generate_initial_state(rx: &CommandReceiver) -> Result<Self, MinusError> {
         let mut ps = Self::new()?;
+        // Nothing gets drawn before the pager starts
+        let mut out = io::sink();
         let mut command_queue = CommandQueue::new_zero();
         rx.try_iter().try_for_each(|ev| -> Result<(), MinusError> {
             handle_event(
@@ -240,30 +719,116 @@ impl PagerState {
                 &mut ps,
                 &mut command_queue,
                 &Arc::new(AtomicBool::new(false)),
-                #[cfg(feature = "search")]
+                #[cfg(feature = "__search")]
                 &Arc::new((Mutex::new(true), Condvar::new())),
             )
         })?;
         Ok(ps)
     }
 
+    /// Format the text, searching it again if a search is active
+    ///
+    /// This must be used whenever the text or the search changes.
     pub(crate) fn format_lines(&mut self) {
+        self.make_formatted_lines(false);
+    }
+
+    /// Format the text again after a change in the layout like a resize
+    ///
+    /// If a search is active, the matches found previously are reused instead of searching the
+    /// text again as long as the search hasn't changed since then.
+    pub(crate) fn reflow_lines(&mut self) {
+        self.make_formatted_lines(true);
+    }
+
+    #[cfg_attr(not(feature = "__search"), allow(unused_variables))]
+    fn make_formatted_lines(&mut self, reuse_matches: bool) {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("minus::format_lines", reuse_matches).entered();
+        #[cfg(feature = "metrics")]
+        let started = Instant::now();
+        #[cfg(feature = "__search")]
+        let active_range = self.search_state.active_range();
+        #[cfg(feature = "__search")]
+        let query = self
+            .search_state
+            .search_term
+            .as_ref()
+            .map(|st| st.as_str().to_string());
+        #[cfg(feature = "__search")]
+        let reusable = query.as_ref().is_some_and(|query| {
+            self.search_state
+                .line_matches
+                .is_built_for(query, active_range.as_ref())
+        });
+        // Multi-line matches can't be found while formatting each line, so the text is searched
+        // as a whole beforehand
+        #[cfg(feature = "__search")]
+        let multiline = self.search_state.multiline && query.is_some();
+        #[cfg(feature = "__search")]
+        if multiline && !(reuse_matches && reusable) {
+            let mut index = screen::multiline_search_index(
+                &self.screen.orig_text,
+                self.search_state.search_term.as_ref().unwrap(),
+                active_range.as_ref(),
+                self.screen.line_length_limit,
+            );
+            index.built_for = query.clone().map(|query| (query, active_range.clone()));
+            self.search_state.line_matches = index;
+        }
+        #[cfg(feature = "__search")]
+        let reusable_or_multiline = (reuse_matches && reusable) || multiline;
+        #[cfg(feature = "__search")]
+        let known_matches = reusable_or_multiline.then_some(&self.search_state.line_matches);
         let (buffer, format_result) = screen::make_format_lines(
             &self.screen.orig_text,
             self.line_numbers,
             self.cols,
             self.screen.line_wrapping,
-            #[cfg(feature = "search")]
+            self.screen.line_number_format.as_ref(),
+            &self.screen.theme,
+            self.screen.min_line_number_digits,
+            self.screen.line_length_limit,
+            Some(&mut self.screen.wrap_cache),
+            #[cfg(feature = "__regex")]
+            &self.screen.styled_lines,
+            #[cfg(feature = "__regex")]
+            &self.screen.highlight_rules,
+            #[cfg(feature = "__search")]
             &self.search_state.search_term,
+            #[cfg(feature = "__search")]
+            active_range.as_ref(),
+            #[cfg(feature = "__search")]
+            self.search_state
+                .highlight
+                .then_some(self.search_state.highlight_mode),
+            #[cfg(feature = "__search")]
+            known_matches,
+            #[cfg(feature = "__search")]
+            None,
         );
+        #[cfg(feature = "metrics")]
+        self.metrics
+            .record_format(format_result.lines_formatted, started);
 
-        #[cfg(feature = "search")]
+        #[cfg(feature = "__search")]
         {
             self.search_state.search_idx = format_result.append_search_idx;
+            // A reused index is already complete
+            if !reusable_or_multiline {
+                self.search_state.line_matches = format_result.append_line_matches;
+                self.search_state.line_matches.built_for = query.map(|query| (query, active_range));
+            }
         }
+        // Lines beyond the end of the text aren't going to be needed anymore
+        self.screen
+            .wrap_cache
+            .truncate(format_result.lines_formatted);
         self.screen.formatted_lines = buffer;
-        self.lines_to_row_map = format_result.lines_to_row_map;
+        self.screen.lines_to_row_map = format_result.lines_to_row_map;
         self.screen.max_line_length = format_result.max_line_length;
+        // The longest line may have become shorter
+        self.left_mark = self.left_mark.min(self.max_left_mark());
 
         self.screen.unterminated = format_result.num_unterminated;
         self.format_prompt();
@@ -271,6 +836,16 @@ impl PagerState {
 
     /// Reformat the inputted prompt to how it should be displayed
     pub(crate) fn format_prompt(&mut self) {
+        // The command line is always displayed by minus
+        if let (Some(renderer), None) = (&self.prompt_renderer, &self.command_line) {
+            self.displayed_prompt = renderer(&self.prompt_context());
+        } else {
+            self.format_default_prompt();
+        }
+    }
+
+    /// Format the prompt displayed when the application hasn't set a prompt renderer
+    fn format_default_prompt(&mut self) {
         const PROMPT_SPEC: &str = "\x1b[2;40;37m";
         const SEARCH_SPEC: &str = "\x1b[30;44m";
         const INPUT_SPEC: &str = "\x1b[30;43m";
@@ -283,9 +858,9 @@ impl PagerState {
         let mut format_string = String::with_capacity(self.cols + (SEARCH_SPEC.len() * 5) + 4);
 
         // Get the string that will contain the search index/match indicator
-        #[cfg(feature = "search")]
+        #[cfg(feature = "__search")]
         let mut search_str = String::new();
-        #[cfg(feature = "search")]
+        #[cfg(feature = "__search")]
         if !self.search_state.search_idx.is_empty() {
             search_str.push(' ');
             search_str.push_str(&(self.search_state.search_mark + 1).to_string());
@@ -294,38 +869,60 @@ impl PagerState {
             search_str.push(' ');
         }
 
-        // And get the string that will contain the prefix_num
-        let mut prefix_str = String::new();
-        if !self.prefix_num.is_empty() {
-            prefix_str.push(' ');
-            prefix_str.push_str(&self.prefix_num);
-            prefix_str.push(' ');
+        // The string showing the visible columns when the view is scrolled horizontally
+        let mut cols_str = String::new();
+        if !self.screen.line_wrapping && self.left_mark > 0 {
+            cols_str.push_str(" cols ");
+            cols_str.push_str(&(self.left_mark + 1).to_string());
+            cols_str.push('-');
+            cols_str.push_str(&(self.left_mark + self.cols).to_string());
+            cols_str.push(' ');
         }
 
         // And lastly, the string that contains the prompt or msg
-        let prompt_str = self.message.as_ref().unwrap_or(&self.prompt);
+        let prompt_str = self.prompt_text();
+        let prompt_str = prompt_str.as_ref();
 
-        #[cfg(feature = "search")]
+        #[cfg(feature = "__search")]
         let search_len = search_str.len();
-        #[cfg(not(feature = "search"))]
+        #[cfg(not(feature = "__search"))]
         let search_len = 0;
 
-        let follow_mode_str: &str = if self.follow_output { "[F]" } else { "" };
+        let follow_mode_str: &str = if self.follow_output {
+            "[following]"
+        } else {
+            ""
+        };
+
+        let recording_str = self
+            .macros
+            .recording()
+            .map_or_else(String::new, |register| format!("recording @{register} "));
+
+        let eof_str = match &self.eof_marker {
+            Some(marker) if self.is_end_visible() => marker.as_str(),
+            _ => "",
+        };
 
         // Calculate how much extra padding in the middle we need between
         // the prompt/message and the indicators on the right
-        let prefix_len = prefix_str.len();
-        let extra_space = self
-            .cols
-            .saturating_sub(search_len + prefix_len + follow_mode_str.len() + prompt_str.len());
+        let indicators_len = search_len
+            + cols_str.len()
+            + recording_str.len()
+            + follow_mode_str.len()
+            + eof_str.len();
+        let extra_space = self.cols.saturating_sub(indicators_len + prompt_str.len());
         let dsp_prompt: &str = if extra_space == 0 {
-            &prompt_str[..self.cols - search_len - prefix_len - follow_mode_str.len()]
+            let available = self.cols.saturating_sub(indicators_len);
+            prompt_str.get(..available).unwrap_or_default()
         } else {
             prompt_str
         };
 
         // push the prompt/msg
-        if self.message.is_some() {
+        if !self.prefix_num.is_empty() {
+            format_string.push_str(INPUT_SPEC);
+        } else if self.message.is_some() {
             format_string.push_str(MSG_SPEC);
         } else {
             format_string.push_str(PROMPT_SPEC);
@@ -333,30 +930,979 @@ impl PagerState {
         format_string.push_str(dsp_prompt);
         format_string.push_str(&" ".repeat(extra_space));
 
-        // add the prefix_num if it exists
-        if prefix_len > 0 {
-            format_string.push_str(INPUT_SPEC);
-            format_string.push_str(&prefix_str);
-        }
-
         // and add the search indicator stuff if it exists
-        #[cfg(feature = "search")]
+        #[cfg(feature = "__search")]
         if search_len > 0 {
             format_string.push_str(SEARCH_SPEC);
             format_string.push_str(&search_str);
         }
 
+        // add the horizontal position indicator
+        if !cols_str.is_empty() {
+            format_string.push_str(PROMPT_SPEC);
+            format_string.push_str(&cols_str);
+        }
+
+        // add the macro recording indicator
+        if !recording_str.is_empty() {
+            format_string.push_str(PROMPT_SPEC);
+            format_string.push_str(&recording_str);
+        }
+
         // add follow-mode indicator
         if !follow_mode_str.is_empty() {
             format_string.push_str(FOLLOW_MODE_SPEC);
             format_string.push_str(follow_mode_str);
         }
 
+        // add the end of text marker
+        if !eof_str.is_empty() {
+            format_string.push_str(PROMPT_SPEC);
+            format_string.push_str(eof_str);
+        }
+
         format_string.push_str(RESET);
 
         self.displayed_prompt = format_string;
     }
 
+    /// Edit the count typed before a command with `Esc` or `Backspace`
+    ///
+    /// `Esc` cancels the count and `Backspace` removes its last digit. Returns `true` if `ev`
+    /// edited the count.
+    pub(crate) fn edit_prefix_num(&mut self, ev: &Event) -> bool {
+        if self.prefix_num.is_empty() {
+            return false;
+        }
+        let Event::Key(KeyEvent { code, .. }) = ev else {
+            return false;
+        };
+        match code {
+            KeyCode::Esc => self.prefix_num.clear(),
+            KeyCode::Backspace => {
+                self.prefix_num.pop();
+            }
+            _ => return false,
+        }
+        self.format_prompt();
+        true
+    }
+
+    /// Display `message`, or queue it if another message is being displayed
+    pub(crate) fn push_message(&mut self, message: String) {
+        if self.message.is_some() {
+            if self.pending_messages.len() == MAX_PENDING_MESSAGES {
+                self.pending_messages.pop_front();
+            }
+            self.pending_messages.push_back(message);
+        } else {
+            self.message = Some(message);
+        }
+    }
+
+    /// Create the [`PromptContext`] for rendering the prompt
+    pub(crate) fn prompt_context(&self) -> PromptContext<'_> {
+        PromptContext {
+            prompt: &self.prompt,
+            message: self.message.as_deref(),
+            pending_messages: self.pending_messages.len(),
+            top_line: self.top_line(),
+            total_lines: self.screen.line_count(),
+            percent: self.scroll_percent(),
+            end_visible: self.is_end_visible(),
+            follow_output: self.follow_output,
+            recording_macro: self.macros.recording(),
+            waiting_for_data: self.waiting_for_data,
+            cols: self.cols,
+            #[cfg(feature = "__search")]
+            search_query: self
+                .search_state
+                .search_term
+                .as_ref()
+                .map(crate::search::Regex::as_str),
+            #[cfg(feature = "__search")]
+            search_match: (!self.search_state.search_idx.is_empty()).then(|| {
+                (
+                    self.search_state.search_mark + 1,
+                    self.search_state.search_idx.len(),
+                )
+            }),
+        }
+    }
+
+    /// The text displayed on the left side of the prompt
+    fn prompt_text(&self) -> Cow<'_, str> {
+        const SPINNER: [char; 4] = ['|', '/', '-', '\\'];
+
+        match (&self.command_line, &self.message) {
+            (Some(command), _) => Cow::Owned(format!(":{command}")),
+            // Echo the count typed before a command
+            _ if !self.prefix_num.is_empty() => Cow::Owned(format!(":{}", self.prefix_num)),
+            _ if self.waiting_for_data => Cow::Owned(format!(
+                "Waiting for data... {} (press any key to stop)",
+                SPINNER[self.wait_spinner % SPINNER.len()]
+            )),
+            (None, Some(message)) if !self.pending_messages.is_empty() => Cow::Owned(format!(
+                "{message} (+{} more, press Enter)",
+                self.pending_messages.len()
+            )),
+            (None, message) => Cow::Borrowed(message.as_ref().unwrap_or(&self.prompt)),
+        }
+    }
+
+    /// Whether the last row of the text is on the screen
+    pub(crate) fn is_end_visible(&self) -> bool {
+        self.upper_mark.saturating_add(self.text_rows()) >= self.screen.formatted_lines_count()
+    }
+
+    /// Number of rows available for the text
+    ///
+    /// This excludes the last row of the terminal which is reserved for the prompt, unless the
+    /// prompt is hidden.
+    pub(crate) const fn text_rows(&self) -> usize {
+        if self.prompt_shown() {
+            self.rows.saturating_sub(1)
+        } else {
+            self.rows
+        }
+    }
+
+    /// Whether the last row of the terminal is used for the prompt
+    ///
+    /// While the prompt is hidden, the row is still taken back temporarily to show a message or
+    /// the command being typed.
+    pub(crate) const fn prompt_shown(&self) -> bool {
+        self.show_prompt || self.message.is_some() || self.command_line.is_some()
+    }
+
+    /// Whether the terminal is too small to display the text
+    ///
+    /// While this is `true`, a placeholder is drawn in place of the text and the prompt.
+    pub(crate) const fn is_term_too_small(&self) -> bool {
+        self.rows < display::MIN_ROWS || self.cols < display::MIN_COLS
+    }
+
+    /// Information about the text and the viewport for use in input callbacks
+    ///
+    /// See [`InputContext`](input::InputContext)
+    #[must_use]
+    pub fn input_context(&self) -> input::InputContext<'_> {
+        let total_rows = self.screen.formatted_lines_count();
+        let text_rows = self.text_rows();
+        let last_row = self.upper_mark.saturating_add(text_rows).min(total_rows);
+        let visible_lines = if self.upper_mark < last_row {
+            self.screen.line_of_row(self.upper_mark)..self.screen.line_of_row(last_row - 1) + 1
+        } else {
+            0..0
+        };
+        input::InputContext {
+            screen: &self.screen,
+            upper_mark: self.upper_mark,
+            left_mark: self.left_mark,
+            text_rows,
+            cols: self.cols,
+            visible_lines,
+            total_lines: self.screen.line_count(),
+            total_rows,
+            max_line_length: self.screen.max_line_length,
+            line_wrapping: self.screen.line_wrapping,
+            #[cfg(feature = "__search")]
+            search_query: self
+                .search_state
+                .search_term
+                .as_ref()
+                .map(crate::search::Regex::as_str),
+            #[cfg(feature = "__search")]
+            search_matches: &self.search_state.search_idx,
+            #[cfg(feature = "__search")]
+            current_match: self
+                .search_state
+                .search_idx
+                .iter()
+                .nth(self.search_state.search_mark)
+                .copied(),
+        }
+    }
+
+    /// Number of rows moved by a full page scroll
+    ///
+    /// This is the number of writable rows minus [`ScrollAmounts::page_overlap`] and is always
+    /// at least 1.
+    #[must_use]
+    pub fn page_scroll_rows(&self) -> usize {
+        self.rows
+            .saturating_sub(1)
+            .saturating_sub(self.scroll_amounts.page_overlap)
+            .max(1)
+    }
+
+    /// Number of rows moved by a half page scroll
+    ///
+    /// This is [`ScrollAmounts::half_page`] if set, otherwise half of the terminal rows.
+    #[must_use]
+    pub fn half_page_rows(&self) -> usize {
+        self.scroll_amounts.half_page.unwrap_or(self.rows / 2)
+    }
+
+    /// Largest left mark at which the end of the longest line is still visible
+    ///
+    /// This is used to clamp horizontal scrolling when line wrapping is turned off.
+    #[must_use]
+    pub const fn max_left_mark(&self) -> usize {
+        self.screen.get_max_line_length().saturating_sub(self.cols)
+    }
+
+    /// Turn line wrapping on or off
+    ///
+    /// Horizontal scrolling is reset when wrapping is turned on. The lines must be reformatted
+    /// afterwards.
+    pub(crate) fn set_line_wrapping(&mut self, line_wrapping: bool) {
+        self.screen.line_wrapping = line_wrapping;
+        if line_wrapping {
+            self.left_mark = 0;
+        }
+    }
+
+    /// Apply the settings of `config` that are set
+    ///
+    /// Returns `true` if the text needs to be formatted again for the new settings to show up.
+    /// Settings that are the same as the current ones don't cause the text to be formatted again.
+    pub(crate) fn apply_config(&mut self, config: PagerConfig) -> bool {
+        let mut reformat = false;
+        if let Some(ln) = config.line_numbers {
+            reformat |= self.line_numbers != ln;
+            self.line_numbers = ln;
+        }
+        if let Some(digits) = config.min_line_number_digits {
+            reformat |= self.line_numbers.is_on() && self.screen.min_line_number_digits != digits;
+            self.screen.min_line_number_digits = digits;
+        }
+        if let Some(theme) = config.theme {
+            reformat |= self.line_numbers.is_on() && self.screen.theme != theme;
+            self.screen.theme = theme;
+        }
+        if let Some(lw) = config.line_wrapping {
+            reformat |= self.screen.line_wrapping != lw;
+            self.set_line_wrapping(lw);
+        }
+        if let Some(amounts) = config.scroll_amounts {
+            self.scroll_amounts = amounts;
+        }
+        if let Some(margin) = config.scroll_margin {
+            self.scroll_margin = margin;
+        }
+        if let Some(center) = config.center_on_jump {
+            self.center_on_jump = center;
+        }
+        if let Some(quit) = config.quit_on_eof {
+            self.quit_on_eof = quit;
+        }
+        if let Some(mode) = config.feedback {
+            self.feedback = mode;
+        }
+        if let Some(es) = config.exit_strategy {
+            self.exit_strategy = es;
+        }
+        if let Some(enabled) = config.macros {
+            self.macros.enabled = enabled;
+        }
+        if let Some(enabled) = config.alternate_scroll {
+            self.alternate_scroll = enabled;
+        }
+        if let Some(enabled) = config.carriage_return_overwrite {
+            self.screen.carriage_return_overwrite = enabled;
+        }
+        if let Some(title) = &config.window_title {
+            self.window_title = title.clone();
+        }
+        #[cfg(feature = "__search")]
+        {
+            let searching = self.search_state.search_term.is_some();
+            if let Some(placement) = config.match_placement {
+                self.search_state.match_placement = placement;
+            }
+            if let Some(mode) = config.highlight_mode {
+                reformat |= searching && self.search_state.highlight_mode != mode;
+                self.search_state.highlight_mode = mode;
+            }
+            if let Some(multiline) = config.multiline_search {
+                if self.search_state.multiline != multiline {
+                    // The matches of the active search need to be found again
+                    self.search_state.line_matches.built_for = None;
+                    reformat |= searching;
+                }
+                self.search_state.multiline = multiline;
+            }
+            if let Some(interval) = config.incremental_search_debounce {
+                self.search_state.incremental_search_debounce = interval;
+            }
+        }
+        reformat
+    }
+
+    /// Number of rows kept above the target row when jumping to it
+    ///
+    /// This is used by search jumps and goto-line. See [`Pager::set_scroll_margin`] and
+    /// [`Pager::set_center_on_jump`].
+    ///
+    /// [`Pager::set_scroll_margin`]: crate::Pager::set_scroll_margin
+    /// [`Pager::set_center_on_jump`]: crate::Pager::set_center_on_jump
+    #[must_use]
+    pub fn jump_offset(&self) -> usize {
+        let writable_rows = self.text_rows();
+        if self.center_on_jump {
+            writable_rows / 2
+        } else {
+            self.scroll_margin.min(writable_rows.saturating_sub(1))
+        }
+    }
+
+    /// Get the upper mark that brings `row` into view after a jump
+    ///
+    /// This keeps [`jump_offset`](PagerState::jump_offset) rows of context above `row`.
+    #[must_use]
+    pub fn jump_target(&self, row: usize) -> usize {
+        row.saturating_sub(self.jump_offset())
+    }
+
+    /// Get the upper mark that brings the search match at `row` into view
+    ///
+    /// See [`MatchPlacement`] for how the view is placed.
+    #[cfg(feature = "__search")]
+    #[must_use]
+    pub fn search_jump_target(&self, row: usize) -> usize {
+        self.search_state.match_placement.upper_mark(
+            row,
+            self.upper_mark,
+            self.text_rows(),
+            self.jump_offset(),
+        )
+    }
+
+    /// Time remaining until the next automatic reload
+    ///
+    /// Returns `None` if automatic reloading is turned off or there is no reload callback.
+    pub(crate) fn time_until_reload(&self) -> Option<Duration> {
+        self.reload_callback.as_ref()?;
+        self.reload_interval
+            .map(|interval| interval.saturating_sub(self.last_reload.elapsed()))
+    }
+
+    /// Time remaining until the idle callback is called
+    ///
+    /// Returns `None` if there is no idle callback.
+    pub(crate) fn time_until_idle(&self) -> Option<Duration> {
+        self.idle_callback.as_ref()?;
+        Some(
+            self.poll_timeout
+                .saturating_sub(self.last_activity.elapsed()),
+        )
+    }
+
+    /// Whether appended data should be drawn now rather than held back
+    ///
+    /// Data is held back if it arrives within [`refresh_interval`](PagerState::refresh_interval)
+    /// of the last time appended data was drawn. A redraw is then scheduled.
+    pub(crate) fn should_draw_append(&mut self) -> bool {
+        let throttled = self
+            .last_append_draw
+            .is_some_and(|last| last.elapsed() < self.refresh_interval);
+        if throttled {
+            self.redraw_pending = true;
+        } else {
+            self.last_append_draw = Some(Instant::now());
+        }
+        !throttled
+    }
+
+    /// Time remaining until the held back data is drawn
+    ///
+    /// Returns `None` if no data is waiting to be drawn.
+    pub(crate) fn time_until_redraw(&self) -> Option<Duration> {
+        if !self.redraw_pending {
+            return None;
+        }
+        Some(self.last_append_draw.map_or(Duration::ZERO, |last| {
+            self.refresh_interval.saturating_sub(last.elapsed())
+        }))
+    }
+
+    /// Upper mark at which the end of the text is at the bottom of the screen
+    pub(crate) fn bottom_upper_mark(&self) -> usize {
+        self.screen
+            .formatted_lines_count()
+            .saturating_sub(self.text_rows())
+    }
+
+    /// Start animating a jump to `upper_mark` if smooth scrolling is on and the jump is large
+    ///
+    /// Returns the upper mark to be drawn right away. This is `upper_mark` itself when the jump
+    /// isn't animated. Any animation in progress is stopped.
+    pub(crate) fn start_scroll_animation(&mut self, upper_mark: usize) -> usize {
+        self.scroll_animation = None;
+        let Some(duration) = self.scroll_amounts.smooth_scroll else {
+            return upper_mark;
+        };
+        let writable_rows = self.text_rows();
+        let target = upper_mark.min(
+            self.screen
+                .formatted_lines_count()
+                .saturating_sub(writable_rows),
+        );
+        if target.abs_diff(self.upper_mark) < (writable_rows / 2).max(2) {
+            return upper_mark;
+        }
+        self.scroll_animation = Some(ScrollAnimation {
+            from: self.upper_mark,
+            to: target,
+            start: Instant::now(),
+            duration,
+        });
+        self.next_scroll_frame().unwrap_or(upper_mark)
+    }
+
+    /// Upper mark for the next frame of the scroll animation
+    ///
+    /// Returns `None` if nothing is being animated. The animation ends once the last frame is
+    /// returned.
+    pub(crate) fn next_scroll_frame(&mut self) -> Option<usize> {
+        let anim = self.scroll_animation.as_ref()?;
+        // Draw one frame ahead so that the first frame already moves
+        let elapsed = anim.start.elapsed() + SCROLL_FRAME;
+        if elapsed >= anim.duration {
+            return self.scroll_animation.take().map(|anim| anim.to);
+        }
+        Some(anim.position(elapsed))
+    }
+
+    /// Time remaining until the next frame of the scroll animation
+    pub(crate) fn time_until_scroll_frame(&self) -> Option<Duration> {
+        self.scroll_animation.as_ref().map(|_| SCROLL_FRAME)
+    }
+
+    /// Start flashing the prompt
+    pub(crate) fn start_flash(&mut self) {
+        self.flash_until = Some(Instant::now() + FLASH_DURATION);
+    }
+
+    /// Time remaining until the flashed prompt is restored
+    ///
+    /// Returns `None` if the prompt isn't flashing.
+    pub(crate) fn time_until_flash_end(&self) -> Option<Duration> {
+        self.flash_until
+            .map(|until| until.saturating_duration_since(Instant::now()))
+    }
+
+    /// Whether scrolling to `upper_mark` would leave the view where it is
+    ///
+    /// This is the case when the user tries to scroll past the top or the bottom of the text.
+    pub(crate) fn is_stuck_scroll(&self, upper_mark: usize) -> bool {
+        let max_upper_mark = self
+            .screen
+            .formatted_lines_count()
+            .saturating_sub(self.text_rows());
+        upper_mark.min(max_upper_mark) == self.upper_mark
+    }
+
+    /// Whether the application has asked to quit through the cancellation flag
+    pub(crate) fn is_cancelled(&self) -> bool {
+        self.cancellation_flag
+            .as_ref()
+            .is_some_and(|flag| flag.load(Ordering::SeqCst))
+    }
+
+    /// Add a buffer named `name` holding `text`
+    ///
+    /// If a buffer with the same name exists, its text is replaced. If the pager doesn't hold any
+    /// text yet, the new buffer takes the place of the first, unnamed buffer. Returns `true` if
+    /// the text on the screen has changed.
+    pub(crate) fn add_buffer(&mut self, name: String, text: String) -> bool {
+        let existing = self
+            .buffers
+            .iter()
+            .position(|b| b.name == name)
+            .or_else(|| {
+                (self.buffers.len() == 1
+                    && self.buffers[0].name.is_empty()
+                    && self.screen.orig_text.is_empty()
+                    && self.view_stack.is_empty())
+                .then_some(0)
+            });
+        let Some(idx) = existing else {
+            self.buffers.push(Buffer::new(name, text));
+            return false;
+        };
+        self.buffers[idx].name = name;
+        if let Some(hidden) = self.hidden_view_mut(idx) {
+            hidden.text = text;
+            hidden.styled_lines.clear();
+            return false;
+        }
+        self.screen.orig_text = text;
+        self.screen.styled_lines.clear();
+        self.screen.line_count = self.screen.orig_text.lines().count();
+        self.format_lines();
+        true
+    }
+
+    /// Text of the buffer at `idx` if it is not being displayed
+    ///
+    /// This is `None` for the active buffer unless it is covered by a temporary view.
+    pub(crate) fn hidden_text_mut(&mut self, idx: usize) -> Option<&mut String> {
+        self.hidden_view_mut(idx).map(|view| &mut view.text)
+    }
+
+    /// View of the buffer at `idx` if it is not being displayed, see
+    /// [`PagerState::hidden_text_mut`]
+    pub(crate) fn hidden_view_mut(&mut self, idx: usize) -> Option<&mut SavedView> {
+        if idx != self.active_buffer {
+            return Some(&mut self.buffers[idx].view);
+        }
+        self.view_stack.first_mut()
+    }
+
+    /// Remember that the lines of `text`, which is about to be appended to the first buffer, were
+    /// added with [`Pager::push_styled_lines`](crate::Pager::push_styled_lines)
+    ///
+    /// If the text of the buffer ends with an unterminated line, the first line of `text` gets
+    /// joined with it and isn't marked.
+    pub(crate) fn mark_styled_lines(&mut self, text: &str) {
+        let (existing, line_count, styled_lines) = match self.hidden_view_mut(0) {
+            Some(view) => (
+                &view.text,
+                view.text.lines().count(),
+                &mut view.styled_lines,
+            ),
+            None => (
+                &self.screen.orig_text,
+                self.screen.line_count,
+                &mut self.screen.styled_lines,
+            ),
+        };
+        let joined = usize::from(!existing.is_empty() && !existing.ends_with('\n'));
+        styled_lines.extend(line_count..line_count - joined + text.lines().count());
+    }
+
+    /// Move the text and the view being displayed out into a [`SavedView`]
+    fn save_view(&mut self) -> SavedView {
+        SavedView {
+            text: std::mem::take(&mut self.screen.orig_text),
+            raw_bytes: self.screen.raw_bytes.take(),
+            styled_lines: std::mem::take(&mut self.screen.styled_lines),
+            upper_mark: self.upper_mark,
+            left_mark: self.left_mark,
+            #[cfg(feature = "__search")]
+            search_term: self.search_state.search_term.take(),
+            #[cfg(feature = "__search")]
+            search_mark: self.search_state.search_mark,
+        }
+    }
+
+    /// Display the text of `view` exactly as it was when it was saved
+    fn restore_view(&mut self, view: SavedView) {
+        self.screen.orig_text = view.text;
+        self.screen.raw_bytes = view.raw_bytes;
+        self.screen.styled_lines = view.styled_lines;
+        self.upper_mark = view.upper_mark;
+        self.left_mark = view.left_mark;
+        #[cfg(feature = "__search")]
+        {
+            self.search_state.search_term = view.search_term;
+            self.search_state.search_mark = view.search_mark;
+        }
+        self.screen.line_count = self.screen.orig_text.lines().count();
+        self.format_lines();
+    }
+
+    /// Display the buffer at `idx`, keeping the view of the current buffer for when the user
+    /// comes back to it
+    ///
+    /// Any temporary views are closed first. Returns `false` if `idx` is already being displayed
+    /// or doesn't exist.
+    pub(crate) fn switch_buffer(&mut self, idx: usize) -> bool {
+        if idx == self.active_buffer || idx >= self.buffers.len() {
+            return false;
+        }
+        while self.pop_view() {}
+        self.buffers[self.active_buffer].view = self.save_view();
+        let next = std::mem::replace(&mut self.buffers[idx].view, SavedView::new(String::new()));
+        self.restore_view(next);
+        self.active_buffer = idx;
+        true
+    }
+
+    /// Row of the next line matching `regex` in the given `direction` from the top of the screen
+    #[cfg(feature = "__search")]
+    pub(crate) fn find_pattern(
+        &self,
+        regex: &crate::search::Regex,
+        direction: SearchMode,
+    ) -> Option<usize> {
+        let current = self.screen.line_of_row(self.upper_mark);
+        let is_match = |line: &&str| regex.is_match(&minus_core::utils::strip_ansi(line));
+        let lines = self.screen.orig_text.lines().enumerate();
+        let line = if direction == SearchMode::Reverse {
+            lines.take(current).filter(|(_, l)| is_match(l)).last()
+        } else {
+            lines.skip(current + 1).find(|(_, l)| is_match(l))
+        };
+        line.and_then(|(idx, _)| self.screen.row_of_line(idx))
+    }
+
+    /// Display `text` temporarily on top of the current view
+    ///
+    /// The current view, along with its position and search, is restored exactly as it was
+    /// when the temporary view is closed with [`PagerState::pop_view`].
+    pub(crate) fn push_view(&mut self, text: String) {
+        let saved = self.save_view();
+        self.view_stack.push(saved);
+        self.restore_view(SavedView::new(text));
+    }
+
+    /// Close the topmost temporary view, returning `false` if there is none
+    pub(crate) fn pop_view(&mut self) -> bool {
+        let Some(view) = self.view_stack.pop() else {
+            return false;
+        };
+        self.restore_view(view);
+        true
+    }
+
+    /// Text to show when the user lists the buffers, with the active one marked with a `*`
+    pub(crate) fn buffer_list(&self) -> Vec<String> {
+        self.buffers
+            .iter()
+            .enumerate()
+            .map(|(i, b)| {
+                let marker = if i == self.active_buffer { "*" } else { "" };
+                let name = if b.name.is_empty() {
+                    "[unnamed]"
+                } else {
+                    &b.name
+                };
+                format!("{marker}{}:{name}", i + 1)
+            })
+            .collect()
+    }
+
+    /// Replace the text with the content returned by the reload callback
+    ///
+    /// The line at the top of the screen is kept at the top after the reload if it still exists.
+    pub(crate) fn reload(&mut self) -> bool {
+        // Don't replace a temporary view with the content
+        if !self.view_stack.is_empty() {
+            return false;
+        }
+        let Some(cb) = self.reload_callback.as_mut() else {
+            return false;
+        };
+        let text = cb();
+        self.last_reload = Instant::now();
+
+        let top_line = self.screen.line_of_row(self.upper_mark);
+        let line_offset = self
+            .upper_mark
+            .saturating_sub(self.screen.row_of_line(top_line).unwrap_or_default());
+        self.screen.orig_text = text;
+        self.screen.styled_lines.clear();
+        self.format_lines();
+        self.screen.line_count = self.screen.orig_text.lines().count();
+        self.upper_mark = self
+            .screen
+            .row_of_line(top_line)
+            .map_or(usize::MAX - 1, |row| row + line_offset);
+        true
+    }
+
+    /// Fetch more lines from the data provider if the view is close to the end of the text
+    ///
+    /// Enough lines are requested to keep at least a page of text below the bottom of the
+    /// screen. If the provider signals the end of data by returning `None`, it is removed.
+    pub(crate) fn request_data(&mut self) -> Option<String> {
+        let writable_rows = self.text_rows().max(1);
+        let provider = self.data_provider.as_mut()?;
+        let wanted_rows = self
+            .upper_mark
+            .saturating_add(writable_rows.saturating_mul(2));
+        let available_rows = self.screen.formatted_lines_count();
+        if wanted_rows <= available_rows {
+            return None;
+        }
+        let start = self.screen.line_count();
+        let count = (wanted_rows - available_rows).max(writable_rows);
+        let text = provider(start..start.saturating_add(count));
+        if text.is_none() {
+            self.data_provider = None;
+        }
+        text.filter(|text| !text.is_empty())
+    }
+
+    /// Create a [`PagerEvent::Scroll`] for a scroll from the `old` upper mark to the current one
+    pub(crate) fn scroll_event(&self, old: usize) -> PagerEvent {
+        PagerEvent::Scroll {
+            old,
+            new: self.upper_mark,
+            top_line: self.top_line(),
+            percent: self.scroll_percent(),
+        }
+    }
+
+    /// Percentage of the text up to the bottom of the screen
+    fn scroll_percent(&self) -> usize {
+        let line_count = self.screen.formatted_lines_count();
+        let bottom = self
+            .upper_mark
+            .saturating_add(self.text_rows())
+            .min(line_count);
+        (bottom * 100).checked_div(line_count).unwrap_or(100)
+    }
+
+    /// Line number, starting from 1, of the line at the top of the screen
+    pub(crate) fn top_line(&self) -> usize {
+        self.screen.line_of_row(self.upper_mark) + 1
+    }
+
+    /// Rows of the text within the bounds as they are displayed
+    ///
+    /// See [`Screen::transformed_rows`].
+    pub(crate) fn display_rows(&self, start: usize, end: usize) -> Cow<'_, [screen::Row]> {
+        self.screen.transformed_rows(
+            &self.screen.formatted_lines,
+            start,
+            end,
+            self.line_numbers,
+            self.cols,
+            #[cfg(feature = "__search")]
+            &self.search_state.line_matches,
+            #[cfg(feature = "__search")]
+            self.search_state
+                .highlight
+                .then_some(self.search_state.highlight_mode),
+        )
+    }
+
+    /// Indices of the lines that were displayed the last time the screen was drawn
+    ///
+    /// Returns `None` if nothing was displayed.
+    pub(crate) fn visible_lines(&self) -> Option<Range<usize>> {
+        let rows = &self.screen.visible_rows;
+        let last_row = rows.end.min(self.screen.formatted_lines_count());
+        if rows.start >= last_row {
+            return None;
+        }
+        Some(self.screen.line_of_row(rows.start)..self.screen.line_of_row(last_row - 1) + 1)
+    }
+
+    /// Create a [`Session`] from the current state
+    #[must_use]
+    pub fn session(&self) -> Session {
+        #[cfg(feature = "__search")]
+        let search_query = self
+            .search_state
+            .search_term
+            .as_ref()
+            .map(|re| re.as_str().to_string());
+        #[cfg(not(feature = "__search"))]
+        let search_query = None;
+
+        Session {
+            top_line: self.top_line(),
+            left_mark: self.left_mark,
+            search_query,
+            line_numbers: self.line_numbers,
+            line_wrapping: self.screen.line_wrapping,
+            follow_output: self.follow_output,
+        }
+    }
+
+    /// Restore the view from the given [`Session`]
+    ///
+    /// The text must already be present for the top line to be restored.
+    pub(crate) fn restore_session(&mut self, session: Session) {
+        self.line_numbers = session.line_numbers;
+        self.screen.line_wrapping = session.line_wrapping;
+        self.left_mark = session.left_mark;
+        self.follow_output = session.follow_output;
+        self.follow_paused = false;
+        #[cfg(feature = "__search")]
+        {
+            self.search_state.search_term = session
+                .search_query
+                .and_then(|query| crate::search::Regex::new(&query).ok());
+        }
+        self.format_lines();
+        self.upper_mark = if self.follow_output {
+            usize::MAX - 1
+        } else {
+            self.screen
+                .row_of_line(session.top_line.saturating_sub(1))
+                .unwrap_or(usize::MAX - 1)
+        };
+    }
+
+    /// Take a [`StateSnapshot`] of the current state
+    #[must_use]
+    pub fn snapshot(&self) -> StateSnapshot {
+        StateSnapshot {
+            upper_mark: self.upper_mark,
+            top_line: self.top_line(),
+            total_lines: self.screen.line_count(),
+            rows: self.rows,
+            cols: self.cols,
+            follow_output: self.follow_output,
+            #[cfg(feature = "__search")]
+            search_query: self
+                .search_state
+                .search_term
+                .as_ref()
+                .map(|re| re.as_str().to_string()),
+            line_numbers: self.line_numbers,
+        }
+    }
+
+    /// Get the [`Metrics`] collected so far
+    #[cfg(feature = "metrics")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "metrics")))]
+    #[must_use]
+    pub const fn metrics(&self) -> Metrics {
+        self.metrics
+    }
+
+    /// Get a [`SearchSummary`] of the active search
+    ///
+    /// This returns `None` if no search is active.
+    #[cfg(feature = "__search")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "search")))]
+    #[must_use]
+    pub fn search_summary(&self) -> Option<SearchSummary> {
+        let query = self.search_state.search_term.as_ref()?.as_str().to_string();
+        let line_matches = self
+            .search_state
+            .line_matches
+            .match_counts()
+            .map(|(line, count)| (line + 1, count))
+            .collect::<BTreeMap<_, _>>();
+        Some(SearchSummary {
+            query,
+            total_matches: line_matches.values().sum(),
+            line_matches,
+        })
+    }
+
+    /// Send the event to all subscribers
+    ///
+    /// Subscribers whose receiving end has been dropped are removed.
+    pub(crate) fn emit_event(&mut self, ev: &PagerEvent) {
+        #[cfg(feature = "tracing")]
+        tracing::debug!(event = ?ev, "pager event");
+        self.event_subscribers.retain(|tx| {
+            let sent = tx.send(ev.clone()).is_ok();
+            #[cfg(feature = "tracing")]
+            if !sent {
+                tracing::debug!("dropping a disconnected event subscriber");
+            }
+            sent
+        });
+    }
+
+    /// Prepare the text pushed by the application for being stored according to [`AnsiMode`]
+    ///
+    /// `\r\n` line endings are turned into `\n` and lone carriage returns are applied if
+    /// [`Screen::carriage_return_overwrite`] is set. Overstrike sequences are turned into ANSI
+    /// escape sequences.
+    pub(crate) fn process_incoming(&self, text: String) -> String {
+        let text =
+            screen::normalize_carriage_returns(text, self.screen.carriage_return_overwrite, false);
+        self.filter_text(text)
+    }
+
+    /// Prepare text pushed into the first buffer for being stored
+    ///
+    /// `replace` tells whether the text replaces the text of the buffer rather than being
+    /// appended to it. Unlike [`PagerState::process_incoming`], a trailing `\r` or overstrike
+    /// sequence is held back until the next text arrives and appended text may start with a `\r`
+    /// if its first line replaces the unterminated line that it continues.
+    pub(crate) fn process_streamed(&mut self, mut text: String, replace: bool) -> String {
+        let pending_overstrike = std::mem::take(&mut self.pending_overstrike);
+        if !replace {
+            text.insert_str(0, &pending_overstrike);
+        }
+        if std::mem::take(&mut self.pending_cr) && !replace {
+            text.insert(0, '\r');
+        }
+        if text.ends_with('\r') {
+            text.pop();
+            self.pending_cr = true;
+        } else if text.contains('\u{8}') {
+            let tail = minus_core::utils::overstrike_tail(&text);
+            self.pending_overstrike = text.split_off(tail);
+        }
+        let text = screen::normalize_carriage_returns(
+            text,
+            self.screen.carriage_return_overwrite,
+            !replace,
+        );
+        self.filter_text(text)
+    }
+
+    /// Turn overstrike sequences into ANSI escape sequences and apply [`AnsiMode`]
+    fn filter_text(&self, text: String) -> String {
+        let text = match minus_core::utils::overstrike_to_ansi(&text) {
+            Cow::Borrowed(_) => text,
+            Cow::Owned(converted) => converted,
+        };
+        match self.ansi_mode {
+            AnsiMode::Passthrough => text,
+            AnsiMode::Strip => match minus_core::utils::strip_ansi(&text) {
+                Cow::Borrowed(_) => text,
+                Cow::Owned(stripped) => stripped,
+            },
+        }
+    }
+
+    /// Add `bytes` to the hex dump, switching to it if needed according to [`BinaryMode`]
+    ///
+    /// If `replace` is true, `bytes` replace the current data. Returns `false` if the data should
+    /// be displayed as text instead.
+    pub(crate) fn push_binary(&mut self, bytes: &[u8], replace: bool) -> bool {
+        if replace {
+            self.screen.raw_bytes = None;
+        }
+        if self.screen.raw_bytes.is_none() {
+            match self.binary_mode {
+                BinaryMode::Text => return false,
+                BinaryMode::Auto if !screen::is_binary(bytes) => return false,
+                BinaryMode::Auto => {
+                    self.push_message("Binary content, showing hex dump".to_string());
+                }
+                BinaryMode::HexDump => {}
+            }
+            let mut raw_bytes = if replace {
+                Vec::new()
+            } else {
+                std::mem::take(&mut self.screen.orig_text).into_bytes()
+            };
+            raw_bytes.extend_from_slice(bytes);
+            self.screen.orig_text = screen::hex_dump(&raw_bytes, 0);
+            self.screen.raw_bytes = Some(raw_bytes);
+            self.screen.styled_lines.clear();
+            self.screen.line_count = self.screen.orig_text.lines().count();
+            self.format_lines();
+            return true;
+        }
+        // Only the last row, which may be incomplete, is dumped again along with the new bytes
+        let raw_bytes = self.screen.raw_bytes.as_mut().unwrap();
+        let start = raw_bytes.len() / screen::HEX_DUMP_WIDTH * screen::HEX_DUMP_WIDTH;
+        let incomplete = start < raw_bytes.len();
+        raw_bytes.extend_from_slice(bytes);
+        let mut dump = screen::hex_dump(&raw_bytes[start..], start);
+        if incomplete {
+            dump.insert(0, '\r');
+        }
+        self.append_str(&dump);
+        true
+    }
+
     /// Runs the exit callbacks
     pub(crate) fn exit(&mut self) {
         for func in &mut self.exit_callbacks {
@@ -365,36 +1911,64 @@ impl PagerState {
     }
 
     pub(crate) fn append_str(&mut self, text: &str) -> AppendStyle {
-        let old_lc = self.screen.line_count();
-        let old_lc_dgts = minus_core::utils::digits(old_lc);
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("minus::append_str", bytes = text.len()).entered();
+        #[cfg(feature = "metrics")]
+        let started = Instant::now();
+        let old_lc_dgts = self.screen.line_number_digits();
+        let old_rows = self.screen.formatted_lines_count();
         let mut append_result = self.screen.push_screen_buf(
             text,
             self.line_numbers,
-            self.cols.try_into().unwrap(),
-            #[cfg(feature = "search")]
+            self.cols,
+            #[cfg(feature = "__search")]
             &self.search_state.search_term,
+            #[cfg(feature = "__search")]
+            self.search_state.active_range().as_ref(),
+            #[cfg(feature = "__search")]
+            self.search_state
+                .highlight
+                .then_some(self.search_state.highlight_mode),
         );
-        let new_lc = self.screen.line_count();
-        let new_lc_dgts = minus_core::utils::digits(new_lc);
-        #[cfg(feature = "search")]
+        #[cfg(feature = "metrics")]
+        self.metrics
+            .record_format(append_result.lines_formatted, started);
+        let new_lc_dgts = self.screen.line_number_digits();
+        #[cfg(feature = "__search")]
         {
             let mut append_search_idx = append_result.append_search_idx;
             self.search_state.search_idx.append(&mut append_search_idx);
+            self.search_state
+                .line_matches
+                .append(&mut append_result.append_line_matches);
         }
-        self.lines_to_row_map.append(
+        self.screen.lines_to_row_map.append(
             &mut append_result.lines_to_row_map,
             append_result.clean_append,
         );
 
-        if self.line_numbers.is_on() && (new_lc_dgts != old_lc_dgts && old_lc_dgts != 0) {
+        // The incoming text can complete a match that started in the text already present
+        #[cfg(feature = "__search")]
+        if self.search_state.multiline && self.search_state.search_term.is_some() {
             self.format_lines();
             return AppendStyle::FullRedraw;
         }
 
+        if self.line_numbers.is_on() && (new_lc_dgts != old_lc_dgts && old_lc_dgts != 0) {
+            self.reflow_lines();
+            return AppendStyle::FullRedraw;
+        }
+
         let total_rows = self.screen.formatted_lines_count();
-        let fmt_lines = &self
-            .screen
-            .get_formatted_lines_with_bounds(total_rows - append_result.rows_formatted, total_rows);
-        AppendStyle::PartialUpdate(fmt_lines)
+        // The unterminated line was overwritten by a line spanning fewer rows, whose rows must be
+        // cleared
+        if total_rows < old_rows {
+            return AppendStyle::FullRedraw;
+        }
+        // No more rows than the ones that fit on the screen can be drawn
+        let start = total_rows - append_result.rows_formatted;
+        AppendStyle::PartialUpdate(
+            self.display_rows(start, total_rows.min(start + self.text_rows())),
+        )
     }
 }
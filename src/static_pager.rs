@@ -1,8 +1,14 @@
 //! Contains function for displaying static data
 //!
 //! This module provides provides the [`page_all`] function to display static output via minus
+use crate::backend::CrosstermBackend;
 use crate::minus_core::init;
 use crate::{error::MinusError, Pager};
+use crossterm::tty::IsTty;
+use std::{
+    fmt::{Debug, Display},
+    io::Write,
+};
 
 /// Display static information to the screen
 ///
@@ -10,8 +16,8 @@ use crate::{error::MinusError, Pager};
 /// * If stdout is not a tty, minus not start a pager. It will simply print all the data and quit
 /// * If there are more rows in the terminal than the number of lines of data to display
 /// minus will not start a pager and simply display all data on the main stdout screen.
-/// This behaviour can be turned off if
-/// [`Pager::set_run_no_overflow(true)`](Pager::set_run_no_overflow) has been
+/// A different number of rows can be set with [`Pager::set_pager_threshold`] and this
+/// behaviour can be turned off if [`Pager::force_pager(true)`](Pager::force_pager) has been
 /// called before starting
 /// * Since any other event except user inputs will not occur, we can do some optimizations on
 /// matching events.
@@ -26,5 +32,88 @@ use crate::{error::MinusError, Pager};
 #[cfg_attr(docsrs, doc(cfg(feature = "static_output")))]
 #[allow(clippy::needless_pass_by_value)]
 pub fn page_all(pager: Pager) -> Result<(), MinusError> {
-    init::init_core(&pager, crate::RunMode::Static)
+    init::init_core(&pager, crate::RunMode::Static).map(|_| ())
+}
+
+/// Display static information on `out`
+///
+/// This is the same as [`page_all`] except that the pager is displayed on `out` instead of the
+/// standard output. Like `less`, this allows paging on the terminal even if the standard output
+/// of the program is redirected, by passing `/dev/tty` as `out`. If `out` is not a terminal, all
+/// the data is simply written to it.
+///
+/// # Panics
+/// This function will panic if another instance of minus is already running.
+///
+/// # Errors
+/// The function will return with an error if it encounters a error during paging.
+///
+/// # Example
+/// ```
+/// use minus::{page_all_to, Pager};
+/// use std::fs::File;
+///
+/// let pager = Pager::new();
+/// pager.push_str("Hello world").unwrap();
+///
+/// // A file isn't a terminal hence the text is simply written to it. Pass `/dev/tty` instead to
+/// // page on the terminal
+/// let path = std::env::temp_dir().join("minus-page-all-to.txt");
+/// page_all_to(pager, File::create(&path).unwrap()).unwrap();
+/// assert_eq!(std::fs::read_to_string(&path).unwrap(), "Hello world\n");
+/// # std::fs::remove_file(&path).unwrap();
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "static_output")))]
+pub fn page_all_to(
+    pager: Pager,
+    out: impl Write + IsTty + Send + 'static,
+) -> Result<(), MinusError> {
+    pager.set_backend(Box::new(CrosstermBackend::with_writer(out)))?;
+    page_all(pager)
+}
+
+/// Display a value in the pager using its [`Display`] implementation
+///
+/// This is a shortcut for creating a [`Pager`], pushing the text of `value` into it and calling
+/// [`page_all`]. It is handy for quickly looking at large values like configurations or reports.
+///
+/// # Panics
+/// This function will panic if another instance of minus is already running.
+///
+/// # Errors
+/// The function will return with an error if it encounters a error during paging.
+///
+/// # Example
+/// ```no_run
+/// let report = (1..=100).map(|i| format!("Item {i}\n")).collect::<String>();
+/// minus::page_display(report).unwrap();
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "static_output")))]
+pub fn page_display(value: impl Display) -> Result<(), MinusError> {
+    let pager = Pager::new();
+    pager.push_str(value.to_string())?;
+    page_all(pager)
+}
+
+/// Display a value in the pager using its [`Debug`] implementation
+///
+/// The value is pretty-printed, i.e. formatted with `{:#?}`, so that large nested values like
+/// syntax trees are spread over multiple lines. Otherwise this is the same as [`page_display`].
+///
+/// # Panics
+/// This function will panic if another instance of minus is already running.
+///
+/// # Errors
+/// The function will return with an error if it encounters a error during paging.
+///
+/// # Example
+/// ```no_run
+/// let map = (1..=100).map(|i| (i, i * i)).collect::<std::collections::BTreeMap<_, _>>();
+/// minus::page_debug(map).unwrap();
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "static_output")))]
+pub fn page_debug(value: impl Debug) -> Result<(), MinusError> {
+    let pager = Pager::new();
+    pager.push_str(format!("{value:#?}"))?;
+    page_all(pager)
 }
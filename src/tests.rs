@@ -54,6 +54,27 @@ mod pager_append_str {
         );
     }
 
+    #[test]
+    fn reserved_line_number_digits_avoid_full_redraw() {
+        use crate::{minus_core::utils::display::AppendStyle, LineNumbers};
+
+        let mut ps = PagerState::new().unwrap();
+        ps.line_numbers = LineNumbers::Enabled;
+        ps.append_str(&"line\n".repeat(9));
+        assert_eq!(ps.append_str("line\n"), AppendStyle::FullRedraw);
+
+        let mut ps = PagerState::new().unwrap();
+        ps.line_numbers = LineNumbers::Enabled;
+        ps.screen.min_line_number_digits = 3;
+        ps.append_str(&"line\n".repeat(9));
+        assert!(matches!(
+            ps.append_str("line\n"),
+            AppendStyle::PartialUpdate(_)
+        ));
+        assert_eq!(ps.screen.formatted_lines[0], "       1. line");
+        assert_eq!(ps.screen.formatted_lines[9], "      10. line");
+    }
+
     #[test]
     fn crlf_write() {
         const LINES: [&str; 4] = [
@@ -242,9 +263,42 @@ fn exit_callback() {
     assert!(exited.load(Ordering::Relaxed));
 }
 
+#[test]
+fn terminal_gone() {
+    use crate::error::MinusError;
+    use std::io;
+
+    let gone = MinusError::Draw(io::Error::from(io::ErrorKind::BrokenPipe)).or_terminal_gone();
+    assert!(matches!(gone, MinusError::TerminalGone));
+    #[cfg(unix)]
+    {
+        // ENXIO
+        let gone = MinusError::Draw(io::Error::from_raw_os_error(6)).or_terminal_gone();
+        assert!(matches!(gone, MinusError::TerminalGone));
+    }
+
+    let other = MinusError::Draw(io::Error::from(io::ErrorKind::Interrupted)).or_terminal_gone();
+    assert!(matches!(other, MinusError::Draw(_)));
+    assert!(matches!(
+        MinusError::Conversion.or_terminal_gone(),
+        MinusError::Conversion
+    ));
+}
+
 mod emit_events {
     // Check functions emit correct events on function calls
-    use crate::{minus_core::commands::Command, ExitStrategy, LineNumbers, Pager};
+    use crate::{
+        input::InputEvent,
+        minus_core::{commands::Command, utils::display::Region},
+        AnsiMode, BinaryMode, ExitStrategy, FeedbackMode, InterruptBehavior, LineNumbers,
+        OutputTarget, Pager, PagerCommand, PagerConfig, PagerEvent, ScrollAmounts, Theme,
+        WindowTitle,
+    };
+    use crossterm::event::KeyboardEnhancementFlags;
+    use std::{
+        sync::{atomic::AtomicBool, Arc},
+        time::Duration,
+    };
 
     const TEST_STR: &str = "This is sample text";
     #[test]
@@ -289,11 +343,28 @@ mod emit_events {
 
     #[test]
     #[cfg(feature = "static_output")]
+    #[allow(deprecated)]
     fn set_run_no_overflow() {
         let pager = Pager::new();
         pager.set_run_no_overflow(false).unwrap();
+        assert_eq!(Command::SetForcePager(false), pager.rx.try_recv().unwrap());
+    }
+
+    #[test]
+    #[cfg(feature = "static_output")]
+    fn force_pager() {
+        let pager = Pager::new();
+        pager.force_pager(true).unwrap();
+        assert_eq!(Command::SetForcePager(true), pager.rx.try_recv().unwrap());
+    }
+
+    #[test]
+    #[cfg(feature = "static_output")]
+    fn set_pager_threshold() {
+        let pager = Pager::new();
+        pager.set_pager_threshold(Some(10)).unwrap();
         assert_eq!(
-            Command::SetRunNoOverflow(false),
+            Command::SetPagerThreshold(Some(10)),
             pager.rx.try_recv().unwrap()
         );
     }
@@ -318,6 +389,823 @@ mod emit_events {
         );
     }
 
+    #[test]
+    fn subscribe_events() {
+        let pager = Pager::new();
+        let _events = pager.subscribe_events().unwrap();
+        assert!(matches!(
+            pager.rx.try_recv().unwrap(),
+            Command::AddEventSubscriber(_)
+        ));
+    }
+
+    #[test]
+    fn set_data_provider() {
+        let pager = Pager::new();
+        pager
+            .set_data_provider(Box::new(|_| Some(String::new())))
+            .unwrap();
+        assert!(matches!(
+            pager.rx.try_recv().unwrap(),
+            Command::SetDataProvider(_)
+        ));
+    }
+
+    #[test]
+    fn set_backend() {
+        let pager = Pager::new();
+        pager
+            .set_backend(Box::new(crate::backend::CrosstermBackend::new()))
+            .unwrap();
+        assert!(matches!(
+            pager.rx.try_recv().unwrap(),
+            Command::SetBackend(_)
+        ));
+    }
+
+    #[test]
+    fn set_keyboard_enhancement() {
+        let pager = Pager::new();
+        let flags = KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES;
+        pager.set_keyboard_enhancement(flags).unwrap();
+        assert_eq!(
+            Command::SetKeyboardEnhancement(flags),
+            pager.rx.try_recv().unwrap()
+        );
+    }
+
+    #[test]
+    fn set_output() {
+        let pager = Pager::new();
+        pager.set_output(OutputTarget::Stderr).unwrap();
+        assert!(matches!(
+            pager.rx.try_recv().unwrap(),
+            Command::SetBackend(_)
+        ));
+    }
+
+    #[test]
+    fn set_line_length_limit() {
+        let pager = Pager::new();
+        pager.set_line_length_limit(100).unwrap();
+        assert_eq!(
+            Command::SetLineLengthLimit(100),
+            pager.rx.try_recv().unwrap()
+        );
+    }
+
+    #[test]
+    fn set_wrap_cache() {
+        let pager = Pager::new();
+        pager.set_wrap_cache(100).unwrap();
+        assert_eq!(Command::SetWrapCache(100), pager.rx.try_recv().unwrap());
+    }
+
+    #[test]
+    fn clear_messages() {
+        let pager = Pager::new();
+        pager.clear_messages().unwrap();
+        assert!(matches!(
+            pager.rx.try_recv().unwrap(),
+            Command::ClearMessages
+        ));
+    }
+
+    #[test]
+    fn pending_messages() {
+        let pager = Pager::new();
+        assert!(pager.pending_messages().is_empty());
+
+        let mut ps = crate::PagerState::new().unwrap();
+        ps.push_message("first".to_string());
+        ps.push_message("second".to_string());
+        *pager.state.lock() = Some(Arc::new(parking_lot::Mutex::new(ps)));
+        assert_eq!(pager.pending_messages(), ["second"]);
+    }
+
+    #[test]
+    fn set_eob_filler() {
+        let pager = Pager::new();
+        pager.set_eob_filler("~").unwrap();
+        assert_eq!(
+            Command::SetEobFiller("~".to_string()),
+            pager.rx.try_recv().unwrap()
+        );
+    }
+
+    #[test]
+    fn set_eof_marker() {
+        let pager = Pager::new();
+        pager.set_eof_marker(Some("(END)")).unwrap();
+        assert_eq!(
+            Command::SetEofMarker(Some("(END)".to_string())),
+            pager.rx.try_recv().unwrap()
+        );
+    }
+
+    #[test]
+    fn push_bytes() {
+        let pager = Pager::new();
+        pager.push_bytes(b"\xff\0".as_slice()).unwrap();
+        assert_eq!(
+            Command::AppendBytes(vec![0xff, 0]),
+            pager.rx.try_recv().unwrap()
+        );
+    }
+
+    #[test]
+    fn set_binary_mode() {
+        let pager = Pager::new();
+        pager.set_binary_mode(BinaryMode::Auto).unwrap();
+        assert_eq!(
+            Command::SetBinaryMode(BinaryMode::Auto),
+            pager.rx.try_recv().unwrap()
+        );
+    }
+
+    #[test]
+    fn set_feedback() {
+        let pager = Pager::new();
+        pager.set_feedback(FeedbackMode::Flash).unwrap();
+        assert_eq!(
+            Command::SetFeedback(FeedbackMode::Flash),
+            pager.rx.try_recv().unwrap()
+        );
+    }
+
+    #[test]
+    fn set_ansi() {
+        let pager = Pager::new();
+        pager.set_ansi(AnsiMode::Strip).unwrap();
+        assert_eq!(
+            Command::SetAnsiMode(AnsiMode::Strip),
+            pager.rx.try_recv().unwrap()
+        );
+    }
+
+    #[test]
+    fn set_carriage_return_overwrite() {
+        let pager = Pager::new();
+        pager.set_carriage_return_overwrite(true).unwrap();
+        assert_eq!(
+            Command::SetCarriageReturnOverwrite(true),
+            pager.rx.try_recv().unwrap()
+        );
+    }
+
+    #[test]
+    fn set_macros() {
+        let pager = Pager::new();
+        pager.set_macros(true).unwrap();
+        assert_eq!(Command::SetMacros(true), pager.rx.try_recv().unwrap());
+    }
+
+    #[test]
+    fn set_plain_render() {
+        let pager = Pager::new();
+        pager.set_plain_render(true).unwrap();
+        assert_eq!(Command::SetPlainRender(true), pager.rx.try_recv().unwrap());
+    }
+
+    #[test]
+    fn set_term_size() {
+        let pager = Pager::new();
+        pager.set_term_size(80, 24).unwrap();
+        assert_eq!(
+            Command::SetTermSize(Some((80, 24))),
+            pager.rx.try_recv().unwrap()
+        );
+        pager.reset_term_size().unwrap();
+        assert_eq!(Command::SetTermSize(None), pager.rx.try_recv().unwrap());
+    }
+
+    #[test]
+    fn set_alternate_scroll() {
+        let pager = Pager::new();
+        pager.set_alternate_scroll(true).unwrap();
+        assert_eq!(
+            Command::SetAlternateScroll(true),
+            pager.rx.try_recv().unwrap()
+        );
+    }
+
+    #[test]
+    fn set_region() {
+        let pager = Pager::new();
+        pager.set_region(20, 2, 60, 22).unwrap();
+        assert_eq!(
+            Command::SetRegion(Some(Region {
+                x: 20,
+                y: 2,
+                cols: 60,
+                rows: 22
+            })),
+            pager.rx.try_recv().unwrap()
+        );
+        pager.reset_region().unwrap();
+        assert_eq!(Command::SetRegion(None), pager.rx.try_recv().unwrap());
+    }
+
+    #[test]
+    fn set_window_title() {
+        let pager = Pager::new();
+        pager.set_window_title(WindowTitle::Prompt).unwrap();
+        assert_eq!(
+            Command::SetWindowTitle(WindowTitle::Prompt),
+            pager.rx.try_recv().unwrap()
+        );
+    }
+
+    #[test]
+    fn set_prompt_renderer() {
+        let pager = Pager::new();
+        pager
+            .set_prompt_renderer(Box::new(|ctx| ctx.prompt.to_string()))
+            .unwrap();
+        assert!(matches!(
+            pager.rx.try_recv().unwrap(),
+            Command::SetPromptRenderer(_)
+        ));
+    }
+
+    #[test]
+    fn set_min_line_number_digits() {
+        let pager = Pager::new();
+        pager.set_min_line_number_digits(5).unwrap();
+        assert_eq!(
+            Command::SetMinLineNumberDigits(5),
+            pager.rx.try_recv().unwrap()
+        );
+    }
+
+    #[test]
+    fn set_theme() {
+        let pager = Pager::new();
+        let theme = Theme {
+            line_number_separator: ':',
+            ..Theme::default()
+        };
+        pager.set_theme(theme).unwrap();
+        assert_eq!(Command::SetTheme(theme), pager.rx.try_recv().unwrap());
+    }
+
+    #[test]
+    fn set_line_number_format() {
+        let pager = Pager::new();
+        pager
+            .set_line_number_format(Box::new(|number, _| format!("{number:x}")))
+            .unwrap();
+        assert!(matches!(
+            pager.rx.try_recv().unwrap(),
+            Command::SetLineNumberFormat(_)
+        ));
+    }
+
+    #[test]
+    fn set_line_transformer() {
+        let pager = Pager::new();
+        pager
+            .set_line_transformer(Box::new(|line, _| line.to_uppercase()))
+            .unwrap();
+        assert!(matches!(
+            pager.rx.try_recv().unwrap(),
+            Command::SetLineTransformer(_)
+        ));
+    }
+
+    #[test]
+    fn on_scroll() {
+        let pager = Pager::new();
+        let (tx, rx) = crossbeam_channel::unbounded();
+        pager
+            .on_scroll(Box::new(move |top_line, percent| {
+                tx.send((top_line, percent)).unwrap();
+            }))
+            .unwrap();
+        let Command::AddEventSubscriber(events) = pager.rx.try_recv().unwrap() else {
+            panic!("on_scroll should subscribe to pager events");
+        };
+        events.send(PagerEvent::FollowToggled(true)).unwrap();
+        events
+            .send(PagerEvent::Scroll {
+                old: 0,
+                new: 10,
+                top_line: 11,
+                percent: 40,
+            })
+            .unwrap();
+        assert_eq!(rx.recv().unwrap(), (11, 40));
+    }
+
+    #[test]
+    fn on_link_click() {
+        let pager = Pager::new();
+        let (tx, rx) = crossbeam_channel::unbounded();
+        pager
+            .on_link_click(Box::new(move |uri| {
+                tx.send(uri).unwrap();
+            }))
+            .unwrap();
+        let Command::AddEventSubscriber(events) = pager.rx.try_recv().unwrap() else {
+            panic!("on_link_click should subscribe to pager events");
+        };
+        events
+            .send(PagerEvent::LinkClicked("https://example.com".to_string()))
+            .unwrap();
+        assert_eq!(rx.recv().unwrap(), "https://example.com");
+    }
+
+    #[test]
+    fn set_quit_on_eof() {
+        let pager = Pager::new();
+        pager.set_quit_on_eof(true).unwrap();
+        assert_eq!(Command::SetQuitOnEof(true), pager.rx.try_recv().unwrap());
+    }
+
+    #[test]
+    fn state_snapshot() {
+        let pager = Pager::new();
+        assert!(pager.state_snapshot().is_none());
+
+        let mut ps = crate::PagerState::new().unwrap();
+        ps.screen.orig_text = "A line\n".repeat(30);
+        ps.format_lines();
+        ps.screen.line_count = 30;
+        ps.upper_mark = 4;
+        ps.follow_output = true;
+        *pager.state.lock() = Some(std::sync::Arc::new(parking_lot::Mutex::new(ps)));
+
+        let snapshot = pager.state_snapshot().unwrap();
+        assert_eq!(snapshot.upper_mark, 4);
+        assert_eq!(snapshot.top_line, 5);
+        assert_eq!(snapshot.total_lines, 30);
+        assert!(snapshot.follow_output);
+        assert_eq!(snapshot.line_numbers, LineNumbers::Disabled);
+    }
+
+    #[test]
+    #[cfg(feature = "__search")]
+    fn search_summary() {
+        let pager = Pager::new();
+        assert!(pager.search_summary().is_none());
+
+        let mut ps = crate::PagerState::new().unwrap();
+        ps.screen.orig_text = "foo bar foo bar\nbar\nfoo\n".to_string();
+        ps.format_lines();
+        let ps = std::sync::Arc::new(parking_lot::Mutex::new(ps));
+        *pager.state.lock() = Some(ps.clone());
+        // No search is active
+        assert!(pager.search_summary().is_none());
+
+        ps.lock().search_state.search_term = Some(crate::search::Regex::new("foo").unwrap());
+        ps.lock().format_lines();
+        let summary = pager.search_summary().unwrap();
+        assert_eq!(summary.query, "foo");
+        assert_eq!(summary.total_matches, 3);
+        assert_eq!(
+            summary.line_matches,
+            std::collections::BTreeMap::from([(1, 2), (3, 1)])
+        );
+
+        // Multi-line matches are counted once
+        ps.lock().search_state.multiline = true;
+        ps.lock().search_state.search_term = Some(crate::search::Regex::new("bar\nbar").unwrap());
+        ps.lock().format_lines();
+        let summary = pager.search_summary().unwrap();
+        assert_eq!(summary.total_matches, 1);
+        assert_eq!(
+            summary.line_matches,
+            std::collections::BTreeMap::from([(1, 1)])
+        );
+    }
+
+    #[test]
+    fn visible_region() {
+        let pager = Pager::new();
+        assert!(pager.visible_region().is_none());
+
+        let mut ps = crate::PagerState::new().unwrap();
+        ps.cols = 20;
+        ps.screen.orig_text = (1..=30).map(|i| format!("line {i}\n")).collect();
+        ps.format_lines();
+        // Nothing has been drawn yet
+        assert!(ps.visible_lines().is_none());
+
+        ps.screen.visible_rows = 4..13;
+        *pager.state.lock() = Some(std::sync::Arc::new(parking_lot::Mutex::new(ps)));
+        assert_eq!(pager.visible_region(), Some((5, 13)));
+        assert_eq!(
+            pager.visible_text().unwrap(),
+            "line 5\nline 6\nline 7\nline 8\nline 9\nline 10\nline 11\nline 12\nline 13"
+        );
+    }
+
+    #[test]
+    fn save_and_restore_session() {
+        let pager = Pager::new();
+        assert!(pager.save_session().is_none());
+
+        let mut ps = crate::PagerState::new().unwrap();
+        ps.screen.orig_text = "A line\n".repeat(30);
+        ps.format_lines();
+        ps.upper_mark = 7;
+        ps.line_numbers = LineNumbers::Enabled;
+        ps.format_lines();
+        *pager.state.lock() = Some(std::sync::Arc::new(parking_lot::Mutex::new(ps)));
+
+        let session = pager.save_session().unwrap();
+        assert_eq!(session.top_line, 8);
+        assert_eq!(session.line_numbers, LineNumbers::Enabled);
+
+        let mut ps = crate::PagerState::new().unwrap();
+        ps.screen.orig_text = "A line\n".repeat(30);
+        ps.restore_session(session.clone());
+        assert_eq!(ps.upper_mark, 7);
+        assert_eq!(ps.session(), session);
+
+        pager.restore_session(session.clone()).unwrap();
+        assert_eq!(
+            Command::RestoreSession(session),
+            pager.rx.try_recv().unwrap()
+        );
+    }
+
+    #[test]
+    fn with_text() {
+        let pager = Pager::with_text("Hello\nWorld\n");
+        assert_eq!(
+            Command::SetData("Hello\nWorld\n".to_string()),
+            pager.rx.try_recv().unwrap()
+        );
+    }
+
+    #[test]
+    fn push_lines() {
+        let mut pager = Pager::new();
+        pager.push_lines(["Hello", "World"]).unwrap();
+        assert_eq!(
+            Command::AppendData("Hello\nWorld\n".to_string()),
+            pager.rx.try_recv().unwrap()
+        );
+
+        pager.extend(vec!["A".to_string(), "B".to_string()]);
+        assert_eq!(
+            Command::AppendData("A\nB\n".to_string()),
+            pager.rx.try_recv().unwrap()
+        );
+        assert!(pager.rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn push_styled_lines() {
+        use crossterm::style::{ContentStyle, Stylize};
+
+        let pager = Pager::new();
+        pager
+            .push_styled_lines([
+                vec![
+                    (ContentStyle::new().bold(), "error"),
+                    (ContentStyle::new(), ": not found"),
+                ],
+                vec![(ContentStyle::new(), "plain")],
+            ])
+            .unwrap();
+        let Command::AppendStyledLines(text) = pager.rx.try_recv().unwrap() else {
+            panic!("push_styled_lines should append styled lines");
+        };
+        assert_eq!(text, "\x1b[1merror\x1b[0m: not found\nplain\n");
+    }
+
+    #[test]
+    fn quit() {
+        let pager = Pager::new();
+        pager.quit().unwrap();
+        assert_eq!(Command::Quit, pager.rx.try_recv().unwrap());
+    }
+
+    #[test]
+    fn set_exit_strategy_with_code_and_callback() {
+        let pager = Pager::new();
+        pager
+            .set_exit_strategy(ExitStrategy::ProcessQuitWithCode(2))
+            .unwrap();
+        assert_eq!(
+            Command::SetExitStrategy(ExitStrategy::ProcessQuitWithCode(2)),
+            pager.rx.try_recv().unwrap()
+        );
+
+        let cb: std::sync::Arc<dyn Fn() -> i32 + Send + Sync> = std::sync::Arc::new(|| 3);
+        pager
+            .set_exit_strategy(ExitStrategy::ProcessQuitWithCallback(cb.clone()))
+            .unwrap();
+        assert_eq!(
+            Command::SetExitStrategy(ExitStrategy::ProcessQuitWithCallback(cb)),
+            pager.rx.try_recv().unwrap()
+        );
+        assert_ne!(
+            ExitStrategy::ProcessQuitWithCallback(std::sync::Arc::new(|| 3)),
+            ExitStrategy::ProcessQuitWithCode(3)
+        );
+    }
+
+    #[test]
+    fn set_page_overlap() {
+        let pager = Pager::new();
+        pager.set_page_overlap(2).unwrap();
+        assert_eq!(Command::SetPageOverlap(2), pager.rx.try_recv().unwrap());
+    }
+
+    #[test]
+    fn set_scroll_amounts() {
+        let pager = Pager::new();
+        let amounts = ScrollAmounts {
+            wheel: 3,
+            horizontal_wheel: 2,
+            half_page: Some(4),
+            page_overlap: 1,
+            smooth_scroll: Some(Duration::from_millis(150)),
+        };
+        pager.set_scroll_amounts(amounts).unwrap();
+        assert_eq!(
+            Command::SetScrollAmounts(amounts),
+            pager.rx.try_recv().unwrap()
+        );
+    }
+
+    #[test]
+    fn set_scroll_margin() {
+        let pager = Pager::new();
+        pager.set_scroll_margin(4).unwrap();
+        assert_eq!(Command::SetScrollMargin(4), pager.rx.try_recv().unwrap());
+    }
+
+    #[test]
+    fn set_center_on_jump() {
+        let pager = Pager::new();
+        pager.set_center_on_jump(true).unwrap();
+        assert_eq!(Command::SetCenterOnJump(true), pager.rx.try_recv().unwrap());
+    }
+
+    #[test]
+    fn set_reload_interval() {
+        let pager = Pager::new();
+        pager
+            .set_reload_interval(Some(Duration::from_secs(1)))
+            .unwrap();
+        assert_eq!(
+            Command::SetReloadInterval(Some(Duration::from_secs(1))),
+            pager.rx.try_recv().unwrap()
+        );
+    }
+
+    #[test]
+    fn set_poll_timeout() {
+        let pager = Pager::new();
+        pager.set_poll_timeout(Duration::from_secs(1)).unwrap();
+        assert_eq!(
+            Command::SetPollTimeout(Duration::from_secs(1)),
+            pager.rx.try_recv().unwrap()
+        );
+    }
+
+    #[test]
+    fn apply_config() {
+        let pager = Pager::new();
+        let config = PagerConfig {
+            line_wrapping: Some(false),
+            ..PagerConfig::default()
+        };
+        pager.apply_config(&config).unwrap();
+        assert_eq!(
+            Command::ApplyConfig(Box::new(config)),
+            pager.rx.try_recv().unwrap()
+        );
+    }
+
+    #[test]
+    fn send_command() {
+        let pager = Pager::new();
+        pager
+            .send_command(PagerCommand::UserInput(InputEvent::Reload))
+            .unwrap();
+        assert_eq!(
+            Command::UserInput(InputEvent::Reload),
+            pager.rx.try_recv().unwrap()
+        );
+        pager
+            .send_command(PagerCommand::FormatRedrawDisplay)
+            .unwrap();
+        assert_eq!(Command::FormatRedrawDisplay, pager.rx.try_recv().unwrap());
+    }
+
+    #[test]
+    fn add_buffer() {
+        let pager = Pager::new();
+        pager.add_buffer("name", TEST_STR).unwrap();
+        assert_eq!(
+            Command::AddBuffer("name".to_string(), TEST_STR.to_string()),
+            pager.rx.try_recv().unwrap()
+        );
+    }
+
+    #[test]
+    fn switch_to() {
+        let pager = Pager::new();
+        pager.switch_to("name").unwrap();
+        assert_eq!(
+            Command::SwitchToBuffer("name".to_string()),
+            pager.rx.try_recv().unwrap()
+        );
+    }
+
+    #[test]
+    fn bounded_pager() {
+        let pager = Pager::bounded(2);
+        pager.push_str("a").unwrap();
+        pager.try_push_str("b").unwrap();
+        assert!(matches!(
+            pager.try_push_str("c"),
+            Err(crate::MinusError::WouldBlock)
+        ));
+
+        // There is room again once the pager catches up
+        assert_eq!(
+            Command::AppendData("a".to_string()),
+            pager.rx.try_recv().unwrap()
+        );
+        pager.try_push_str("c").unwrap();
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn push_str_async() {
+        let pager = Pager::bounded(1);
+        pager.push_str_async("a").await.unwrap();
+
+        // Waits without blocking until the pager catches up
+        let rx = pager.rx.clone();
+        let reader = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(2));
+            rx.try_recv().unwrap()
+        });
+        pager.push_str_async("b").await.unwrap();
+        assert_eq!(Command::AppendData("a".to_string()), reader.join().unwrap());
+        assert_eq!(
+            Command::AppendData("b".to_string()),
+            pager.rx.try_recv().unwrap()
+        );
+    }
+
+    #[test]
+    fn set_max_refresh_rate() {
+        let pager = Pager::new();
+        pager
+            .set_max_refresh_rate(Duration::from_millis(50))
+            .unwrap();
+        assert_eq!(
+            Command::SetMaxRefreshRate(Duration::from_millis(50)),
+            pager.rx.try_recv().unwrap()
+        );
+    }
+
+    #[test]
+    fn scroll_to_line() {
+        let pager = Pager::new();
+        pager.scroll_to_line(42).unwrap();
+        assert_eq!(Command::ScrollToLine(42), pager.rx.try_recv().unwrap());
+    }
+
+    #[test]
+    #[cfg(feature = "__search")]
+    fn set_jump_pattern() {
+        let pager = Pager::new();
+        pager.set_jump_pattern(Some("^@@")).unwrap();
+        assert_eq!(
+            Command::SetJumpPattern(Some(crate::search::Regex::new("^@@").unwrap())),
+            pager.rx.try_recv().unwrap()
+        );
+        pager.set_jump_pattern(None).unwrap();
+        assert_eq!(Command::SetJumpPattern(None), pager.rx.try_recv().unwrap());
+        assert!(matches!(
+            pager.set_jump_pattern(Some("(")),
+            Err(crate::error::MinusError::SearchExpError(_))
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "__search")]
+    fn set_match_placement() {
+        use crate::search::MatchPlacement;
+
+        let pager = Pager::new();
+        pager.set_match_placement(MatchPlacement::Center).unwrap();
+        assert_eq!(
+            Command::SetMatchPlacement(MatchPlacement::Center),
+            pager.rx.try_recv().unwrap()
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "__search")]
+    fn set_incremental_search_debounce() {
+        let pager = Pager::new();
+        pager
+            .set_incremental_search_debounce(Duration::from_millis(250))
+            .unwrap();
+        assert_eq!(
+            Command::SetIncrementalSearchDebounce(Duration::from_millis(250)),
+            pager.rx.try_recv().unwrap()
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "__regex")]
+    fn add_highlight_rule() {
+        use crossterm::style::{ContentStyle, Stylize};
+
+        let pager = Pager::new();
+        pager
+            .add_highlight_rule("[0-9]+", ContentStyle::new().red())
+            .unwrap();
+        assert_eq!(
+            Command::AddHighlightRule(
+                crate::pattern::Regex::new("[0-9]+").unwrap(),
+                ContentStyle::new().red()
+            ),
+            pager.rx.try_recv().unwrap()
+        );
+        assert!(matches!(
+            pager.add_highlight_rule("(", ContentStyle::new()),
+            Err(crate::MinusError::SearchExpError(_))
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "__search")]
+    fn set_highlight_mode() {
+        use crate::search::HighlightMode;
+
+        let pager = Pager::new();
+        pager.set_highlight_mode(HighlightMode::Accurate).unwrap();
+        assert_eq!(
+            Command::SetHighlightMode(HighlightMode::Accurate),
+            pager.rx.try_recv().unwrap()
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "__search")]
+    fn set_multiline_search() {
+        let pager = Pager::new();
+        pager.set_multiline_search(true).unwrap();
+        assert_eq!(
+            Command::SetMultilineSearch(true),
+            pager.rx.try_recv().unwrap()
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "__search")]
+    fn clear_search() {
+        let pager = Pager::new();
+        pager.clear_search().unwrap();
+        assert_eq!(Command::ClearSearch, pager.rx.try_recv().unwrap());
+    }
+
+    #[test]
+    #[cfg(feature = "__search")]
+    fn set_search_range() {
+        let pager = Pager::new();
+        pager.set_search_range(Some(10..20)).unwrap();
+        assert_eq!(
+            Command::SetSearchRange(Some(10..20)),
+            pager.rx.try_recv().unwrap()
+        );
+    }
+
+    #[test]
+    fn set_interrupt_behavior() {
+        let pager = Pager::new();
+        pager
+            .set_interrupt_behavior(InterruptBehavior::Ignore)
+            .unwrap();
+        assert_eq!(
+            Command::SetInterruptBehavior(InterruptBehavior::Ignore),
+            pager.rx.try_recv().unwrap()
+        );
+    }
+
+    #[test]
+    fn set_cancellation_flag() {
+        let pager = Pager::new();
+        let flag = Arc::new(AtomicBool::new(false));
+        pager.set_cancellation_flag(flag.clone()).unwrap();
+        assert_eq!(
+            Command::SetCancellationFlag(flag),
+            pager.rx.try_recv().unwrap()
+        );
+    }
+
     #[test]
     fn add_exit_callback() {
         let func = Box::new(|| println!("Hello"));
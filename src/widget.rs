@@ -0,0 +1,419 @@
+//! Provides the [`PagerView`] widget for embedding minus inside a [`ratatui`] application
+//!
+//! Instead of taking over the entire terminal, a [`PagerView`] renders the text into any region
+//! of a ratatui [`Buffer`] while keeping minus's formatting, scrolling, key bindings and search.
+//! The application stays in charge of the terminal and the event loop and forwards the events
+//! meant for the pager to [`PagerView::handle_input`].
+
+use crate::{
+    error::MinusError,
+    input::InputEvent,
+    minus_core::{commands::Command, ev_handler::handle_event, CommandQueue},
+    screen::{self, AnsiToken},
+    PagerState,
+};
+use crossterm::event::Event;
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    widgets::Widget,
+};
+use std::{
+    io,
+    sync::{atomic::AtomicBool, Arc},
+};
+
+#[cfg(feature = "__search")]
+use parking_lot::{Condvar, Mutex};
+
+/// A pager that renders into a region of a ratatui [`Buffer`]
+///
+/// The last row of the region is used for the prompt, just like in the standalone pager.
+///
+/// # Example
+/// ```
+/// use minus::widget::PagerView;
+/// use ratatui::{buffer::Buffer, layout::Rect, widgets::Widget};
+///
+/// let mut view = PagerView::new().unwrap();
+/// view.push_str("Hello\nWorld\n").unwrap();
+///
+/// let area = Rect::new(0, 0, 20, 5);
+/// let mut buffer = Buffer::empty(area);
+/// view.render(area, &mut buffer);
+/// ```
+pub struct PagerView {
+    state: PagerState,
+}
+
+impl PagerView {
+    /// Create a new empty view
+    ///
+    /// # Errors
+    /// This function will return an error if it could not get the size of the terminal
+    pub fn new() -> Result<Self, MinusError> {
+        Ok(Self {
+            state: PagerState::new()?,
+        })
+    }
+
+    /// Appends text to the view
+    ///
+    /// # Errors
+    /// This function will return an error if the text could not be formatted
+    pub fn push_str(&mut self, text: impl Into<String>) -> Result<(), MinusError> {
+        self.run(Command::AppendData(text.into()))
+    }
+
+    /// Replaces all the text in the view with `text`
+    ///
+    /// # Errors
+    /// This function will return an error if the text could not be formatted
+    pub fn set_text(&mut self, text: impl Into<String>) -> Result<(), MinusError> {
+        self.run(Command::SetData(text.into()))
+    }
+
+    /// React to a terminal event
+    ///
+    /// The event is classified using the same key and mouse bindings as the standalone pager.
+    /// Returns `false` if the event was not handled, like when the user presses `q` or `/`.
+    /// Quitting and entering a search query is left to the application; see
+    /// [`PagerView::search`].
+    ///
+    /// # Errors
+    /// This function will return an error if handling the event failed
+    pub fn handle_input(&mut self, ev: Event) -> Result<bool, MinusError> {
+        let Some(ev) = crate::input::normalize_event(ev) else {
+            return Ok(false);
+        };
+        if self.state.edit_prefix_num(&ev) {
+            return Ok(true);
+        }
+        let input = self.state.input_classifier.classify_input(ev, &self.state);
+        input.map_or(Ok(false), |input| self.apply(input))
+    }
+
+    /// Apply an [`InputEvent`] to the view
+    ///
+    /// Returns `false` if the event is not supported by the view. See
+    /// [`PagerView::handle_input`].
+    ///
+    /// # Errors
+    /// This function will return an error if handling the event failed
+    pub fn apply(&mut self, input: InputEvent) -> Result<bool, MinusError> {
+        if let InputEvent::Number(n) = input {
+            self.state.prefix_num.push(n);
+            self.state.format_prompt();
+            return Ok(true);
+        }
+        if !self.state.prefix_num.is_empty() {
+            self.state.prefix_num.clear();
+            self.state.format_prompt();
+        }
+
+        match input {
+            #[cfg(feature = "__search")]
+            InputEvent::Search(_) => Ok(false),
+            InputEvent::Exit
+            | InputEvent::Interrupt
+            | InputEvent::EnterCommand
+            | InputEvent::UpdateTermArea(..)
+            | InputEvent::WaitForData(_)
+            | InputEvent::Reload => Ok(false),
+            InputEvent::UpdateLineNumber(l) => {
+                self.state.line_numbers = l;
+                self.state.format_lines();
+                Ok(true)
+            }
+            InputEvent::HorizontalScroll(val) => {
+                self.state.set_line_wrapping(val);
+                self.state.format_lines();
+                Ok(true)
+            }
+            input => {
+                self.run(Command::UserInput(input))?;
+                Ok(true)
+            }
+        }
+    }
+
+    /// Search for `query` and move to the first match after the top of the view
+    ///
+    /// Returns the number of rows that matched.
+    ///
+    /// # Errors
+    /// This function will return an error if `query` is not a valid regular expression
+    #[cfg(feature = "__search")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "search")))]
+    pub fn search(&mut self, query: &str) -> Result<usize, MinusError> {
+        self.state.search_state.search_term = Some(crate::search::Regex::new(query)?);
+        self.state.search_state.highlight = true;
+        self.state.search_state.search_mark = 0;
+        self.state.format_lines();
+        self.run(Command::UserInput(InputEvent::MoveToNextMatch(1)))?;
+        Ok(self.state.search_state.search_idx.len())
+    }
+
+    /// The state of the view
+    #[must_use]
+    pub const fn state(&self) -> &PagerState {
+        &self.state
+    }
+
+    /// Handle `command` and all the commands that follow from it
+    ///
+    /// Handling stops at the first command that fails.
+    fn run(&mut self, command: Command) -> Result<(), MinusError> {
+        let mut command_queue = CommandQueue::new_zero();
+        let mut command = Some(command);
+        while let Some(cmd) = command {
+            // Never touch the terminal, it belongs to the application
+            if !cmd.is_exit_event() {
+                handle_event(
+                    cmd,
+                    &mut io::sink(),
+                    &mut self.state,
+                    &mut command_queue,
+                    &Arc::new(AtomicBool::new(false)),
+                    #[cfg(feature = "__search")]
+                    &Arc::new((Mutex::new(true), Condvar::new())),
+                )?;
+            }
+            command = command_queue.pop_front();
+        }
+        Ok(())
+    }
+
+    /// Adjust the state for rendering into an area of `cols` columns and `rows` rows
+    fn resize(&mut self, cols: usize, rows: usize) {
+        if (cols, rows) == (self.state.cols, self.state.rows) {
+            return;
+        }
+        self.state.cols = cols;
+        self.state.rows = rows;
+        self.state.format_lines();
+    }
+}
+
+impl Widget for &mut PagerView {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        self.resize(area.width.into(), area.height.into());
+        let ps = &mut self.state;
+        let writable_rows = ps.text_rows();
+
+        // Never scroll past the last page
+        let line_count = ps.screen.formatted_lines_count();
+        if ps.upper_mark.saturating_add(writable_rows) > line_count {
+            ps.upper_mark = line_count.saturating_sub(writable_rows);
+        }
+        let left_mark = if ps.screen.line_wrapping {
+            0
+        } else {
+            ps.left_mark
+        };
+
+        ps.screen.visible_rows = ps.upper_mark..ps.upper_mark + writable_rows;
+        let rows = ps.screen.visible_text();
+        for (y, row) in (area.y..).zip(rows) {
+            render_ansi(buf, area.x, y, area.width, row, left_mark);
+        }
+        if !ps.eob_filler.is_empty() {
+            for y in (area.y..).take(writable_rows).skip(rows.len()) {
+                render_ansi(buf, area.x, y, area.width, &ps.eob_filler, 0);
+            }
+        }
+        if ps.prompt_renderer.is_some() {
+            ps.format_prompt();
+        }
+        if ps.prompt_shown() && area.height > 0 {
+            let y = area.y + area.height - 1;
+            render_ansi(buf, area.x, y, area.width, &ps.displayed_prompt, 0);
+        }
+    }
+}
+
+/// Write `text` at `x`, `y` in `buf` with its ANSI escape sequences converted to [`Style`]s
+///
+/// The first `skip` columns are not rendered and the text is truncated to `width` columns.
+fn render_ansi(buf: &mut Buffer, x: u16, y: u16, width: u16, text: &str, mut skip: usize) {
+    let mut style = Style::default();
+    let mut pos = x;
+    let end = x.saturating_add(width);
+    let mut chunk = String::new();
+
+    for token in screen::ansi_tokens(text) {
+        match token {
+            AnsiToken::Char(c) => {
+                if skip > 0 {
+                    skip -= 1;
+                } else {
+                    chunk.push(c);
+                }
+            }
+            AnsiToken::Escape(seq) => {
+                if let Some(params) = seq
+                    .strip_prefix("\u{1b}[")
+                    .and_then(|seq| seq.strip_suffix('m'))
+                {
+                    pos = buf.set_stringn(pos, y, &chunk, (end - pos).into(), style).0;
+                    chunk.clear();
+                    style = apply_sgr(style, params);
+                }
+            }
+            AnsiToken::Hyperlink(..) => {}
+        }
+    }
+    buf.set_stringn(pos, y, &chunk, (end - pos).into(), style);
+}
+
+/// Apply the parameters of a SGR escape sequence to `style`
+fn apply_sgr(mut style: Style, params: &str) -> Style {
+    let mut params = params.split(';').map(|p| p.parse::<u8>().unwrap_or(0));
+    while let Some(param) = params.next() {
+        style = match param {
+            0 => Style::default(),
+            1 => style.add_modifier(Modifier::BOLD),
+            2 => style.add_modifier(Modifier::DIM),
+            3 => style.add_modifier(Modifier::ITALIC),
+            4 => style.add_modifier(Modifier::UNDERLINED),
+            5 => style.add_modifier(Modifier::SLOW_BLINK),
+            7 => style.add_modifier(Modifier::REVERSED),
+            8 => style.add_modifier(Modifier::HIDDEN),
+            9 => style.add_modifier(Modifier::CROSSED_OUT),
+            22 => style.remove_modifier(Modifier::BOLD | Modifier::DIM),
+            23 => style.remove_modifier(Modifier::ITALIC),
+            24 => style.remove_modifier(Modifier::UNDERLINED),
+            25 => style.remove_modifier(Modifier::SLOW_BLINK),
+            27 => style.remove_modifier(Modifier::REVERSED),
+            28 => style.remove_modifier(Modifier::HIDDEN),
+            29 => style.remove_modifier(Modifier::CROSSED_OUT),
+            30..=37 => style.fg(base_color(param - 30)),
+            38 => style.fg(extended_color(&mut params)),
+            39 => style.fg(Color::Reset),
+            40..=47 => style.bg(base_color(param - 40)),
+            48 => style.bg(extended_color(&mut params)),
+            49 => style.bg(Color::Reset),
+            90..=97 => style.fg(bright_color(param - 90)),
+            100..=107 => style.bg(bright_color(param - 100)),
+            _ => style,
+        };
+    }
+    style
+}
+
+const fn base_color(n: u8) -> Color {
+    match n {
+        0 => Color::Black,
+        1 => Color::Red,
+        2 => Color::Green,
+        3 => Color::Yellow,
+        4 => Color::Blue,
+        5 => Color::Magenta,
+        6 => Color::Cyan,
+        _ => Color::Gray,
+    }
+}
+
+const fn bright_color(n: u8) -> Color {
+    match n {
+        0 => Color::DarkGray,
+        1 => Color::LightRed,
+        2 => Color::LightGreen,
+        3 => Color::LightYellow,
+        4 => Color::LightBlue,
+        5 => Color::LightMagenta,
+        6 => Color::LightCyan,
+        _ => Color::White,
+    }
+}
+
+/// Parse the 256 color or true color parameters following a `38` or `48`
+fn extended_color(params: &mut impl Iterator<Item = u8>) -> Color {
+    match params.next() {
+        Some(5) => Color::Indexed(params.next().unwrap_or(0)),
+        Some(2) => {
+            let mut next = || params.next().unwrap_or(0);
+            Color::Rgb(next(), next(), next())
+        }
+        _ => Color::Reset,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PagerView;
+    use crate::input::InputEvent;
+    use ratatui::{
+        buffer::Buffer,
+        layout::Rect,
+        style::{Color, Modifier},
+        widgets::Widget,
+    };
+
+    fn render(view: &mut PagerView, area: Rect) -> Buffer {
+        let mut buffer = Buffer::empty(area);
+        view.render(area, &mut buffer);
+        buffer
+    }
+
+    fn row_text(buffer: &Buffer, y: u16) -> String {
+        (buffer.area.x..buffer.area.right())
+            .map(|x| buffer.get(x, y).symbol())
+            .collect::<String>()
+            .trim_end()
+            .to_string()
+    }
+
+    #[test]
+    fn renders_into_area() {
+        let mut view = PagerView::new().unwrap();
+        view.push_str((1..=20).map(|i| format!("Line {i}\n")).collect::<String>())
+            .unwrap();
+        view.state.show_prompt = false;
+
+        let area = Rect::new(5, 2, 20, 4);
+        let buffer = render(&mut view, area);
+        assert_eq!(row_text(&buffer, 2), "Line 1");
+        assert_eq!(row_text(&buffer, 4), "Line 3");
+
+        assert!(view.apply(InputEvent::UpdateUpperMark(10)).unwrap());
+        assert!(!view.apply(InputEvent::Exit).unwrap());
+        let buffer = render(&mut view, area);
+        assert_eq!(row_text(&buffer, 2), "Line 11");
+
+        // Scrolling past the end shows the last page, using the row of the hidden prompt
+        view.apply(InputEvent::UpdateUpperMark(100)).unwrap();
+        let buffer = render(&mut view, area);
+        assert_eq!(row_text(&buffer, 2), "Line 17");
+        assert_eq!(row_text(&buffer, 5), "Line 20");
+    }
+
+    #[test]
+    fn converts_ansi_styles() {
+        let mut view = PagerView::new().unwrap();
+        view.push_str("a \u{1b}[1;31mbold\u{1b}[0m \u{1b}[38;5;42mx\u{1b}[39m\n")
+            .unwrap();
+
+        let area = Rect::new(0, 0, 20, 3);
+        let buffer = render(&mut view, area);
+        assert_eq!(row_text(&buffer, 0), "a bold x");
+        assert_eq!(buffer.get(0, 0).fg, Color::Reset);
+        assert_eq!(buffer.get(2, 0).fg, Color::Red);
+        assert_eq!(buffer.get(2, 0).modifier, Modifier::BOLD);
+        assert_eq!(buffer.get(6, 0).modifier, Modifier::empty());
+        assert_eq!(buffer.get(7, 0).fg, Color::Indexed(42));
+    }
+
+    #[cfg(feature = "__search")]
+    #[test]
+    fn search_moves_to_match() {
+        let mut view = PagerView::new().unwrap();
+        view.push_str((1..=30).map(|i| format!("Line {i}\n")).collect::<String>())
+            .unwrap();
+        assert_eq!(view.search("Line 2[0-5]").unwrap(), 6);
+
+        let buffer = render(&mut view, Rect::new(0, 0, 20, 5));
+        assert!(row_text(&buffer, 0).ends_with("Line 20"));
+    }
+}